@@ -171,6 +171,12 @@ fn extract_value(value: &OriginalValue) -> Value {
         OriginalValue::Text(value) => Value::Text {
             value: value.to_string(),
         },
+        // there is no tolerance value here, use the range it covers
+        OriginalValue::Tolerant { .. } => extract_value(&value.tolerance_bounds()),
+        // unbound variables are shown as written, like `$amount`
+        OriginalValue::Variable(_) => Value::Text {
+            value: value.to_string(),
+        },
     }
 }
 
@@ -1,17 +1,21 @@
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use regex::Regex;
 
-use crate::convert::{Converter, PhysicalQuantity};
-use crate::error::{label, CowStr, PassResult, SourceDiag, SourceReport};
+use crate::convert::{convert_f64, Converter, PhysicalQuantity, Unit, UnitAliases};
+use crate::error::{label, CowStr, Label, PassResult, SourceDiag, SourceReport};
 use crate::located::Located;
-use crate::metadata::SpecialKey;
+use crate::metadata::{Metadata, SpecialKey};
 use crate::parser::{
     self, BlockKind, Event, IntermediateData, IntermediateRefMode, IntermediateTargetKind,
     Modifiers,
 };
-use crate::quantity::{Quantity, QuantityValue, ScalableValue, UnitInfo, Value};
+use crate::quantity::{
+    Number, PerUnitQuantity, Quantity, QuantityUnit, QuantityValue, ScalableQuantity,
+    ScalableValue, UnitInfo, Value,
+};
 use crate::span::Span;
 use crate::text::Text;
 use crate::{model::*, Extensions, ParseOptions};
@@ -42,6 +46,33 @@ pub fn parse_events<'i, 'c>(
     extensions: Extensions,
     converter: &Converter,
     parse_options: ParseOptions,
+) -> AnalysisResult {
+    parse_events_impl(events, input, extensions, converter, parse_options, false)
+}
+
+/// Same as [`parse_events`] but the output is always [`Some`], even with
+/// parser errors
+///
+/// The parser recovers from errors in each block (metadata line, section
+/// header or step), so the output contains all the blocks. Invalid
+/// components are kept as text in their step.
+pub(crate) fn parse_events_partial<'i>(
+    events: impl Iterator<Item = Event<'i>>,
+    input: &'i str,
+    extensions: Extensions,
+    converter: &Converter,
+    parse_options: ParseOptions,
+) -> AnalysisResult {
+    parse_events_impl(events, input, extensions, converter, parse_options, true)
+}
+
+fn parse_events_impl<'i>(
+    events: impl Iterator<Item = Event<'i>>,
+    input: &'i str,
+    extensions: Extensions,
+    converter: &Converter,
+    parse_options: ParseOptions,
+    partial: bool,
 ) -> AnalysisResult {
     let mut ctx = SourceReport::empty();
     let temperature_regex = extensions
@@ -67,7 +98,9 @@ pub fn parse_events<'i, 'c>(
         extensions,
         temperature_regex,
         converter,
+        unit_aliases: None,
         parse_options,
+        partial,
 
         content: ScalableRecipe {
             metadata: Default::default(),
@@ -79,6 +112,7 @@ pub fn parse_events<'i, 'c>(
             data: (),
         },
         current_section: Section::default(),
+        in_section: false,
 
         define_mode: DefineMode::All,
         duplicate_mode: DuplicateMode::New,
@@ -96,10 +130,16 @@ struct RecipeCollector<'i, 'c> {
     extensions: Extensions,
     temperature_regex: Option<&'c Regex>,
     converter: &'c Converter,
+    /// Unit aliases declared in the recipe
+    unit_aliases: Option<UnitAliases<'c>>,
     parse_options: ParseOptions<'c>,
+    /// Keep going after parser errors
+    partial: bool,
 
     content: ScalableRecipe,
     current_section: Section,
+    /// A section line was found
+    in_section: bool,
 
     define_mode: DefineMode,
     duplicate_mode: DuplicateMode,
@@ -115,6 +155,9 @@ struct Locations<'i> {
     ingredients: Vec<Located<parser::Ingredient<'i>>>,
     cookware: Vec<Located<parser::Cookware<'i>>>,
     metadata: HashMap<SpecialKey, (Text<'i>, Text<'i>)>,
+    metadata_keys: HashMap<String, Span>,
+    /// Same as `metadata_keys` for the current section
+    section_metadata_keys: HashMap<String, Span>,
 }
 
 const IMPLICIT_REF_WARN: &str = "The reference (&) is implicit";
@@ -126,18 +169,33 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
             Text(String),
         }
         let mut current_block = None;
+        #[cfg(feature = "spans")]
+        let mut block_span: Option<Span> = None;
 
         let events = events.by_ref();
         while let Some(event) = events.next() {
             match event {
-                Event::Metadata { key, value } => self.metadata(key, value),
-                Event::Section { name } => {
+                Event::Metadata {
+                    key,
+                    value,
+                    comment,
+                } => self.metadata(key, value, comment),
+                Event::Section { name, optional } => {
                     self.step_counter = 1;
                     if !self.current_section.is_empty() {
                         self.content.sections.push(self.current_section);
                     }
                     self.current_section =
-                        Section::new(name.map(|t| t.text_trimmed().into_owned()));
+                        Section::new(name.map(|t| t.text_trimmed().into_owned()), optional);
+                    self.in_section = true;
+                    self.locations.section_metadata_keys.clear();
+                }
+                Event::StepGroup { title } => {
+                    let start = self.current_section.content.len();
+                    self.current_section.step_groups.push(StepGroup {
+                        title: title.text_trimmed().into_owned(),
+                        steps: start..start,
+                    });
                 }
                 Event::Start(kind) => {
                     let buffer = if self.define_mode == DefineMode::Text {
@@ -148,7 +206,11 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                             BlockKind::Text => BlockBuffer::Text(String::new()),
                         }
                     };
-                    current_block = Some(buffer)
+                    current_block = Some(buffer);
+                    #[cfg(feature = "spans")]
+                    {
+                        block_span = None;
+                    }
                 }
                 Event::End(kind) => {
                     let new_content = match current_block {
@@ -157,6 +219,8 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                             Content::Step(Step {
                                 items,
                                 number: self.step_counter,
+                                #[cfg(feature = "spans")]
+                                span: block_span.unwrap_or(Span::pos(0)),
                             })
                         }
                         Some(BlockBuffer::Text(text)) => {
@@ -170,12 +234,22 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
 
                     // If define mode is ingredients, don't add the
                     // step to the section. The components should have been
-                    // added to their lists
-                    if self.define_mode != DefineMode::Components || new_content.is_text() {
+                    // added to their lists. Empty steps are also skipped if
+                    // the option is set, so they don't take a step number.
+                    let skip_empty = self.parse_options.skip_empty_steps
+                        && matches!(&new_content, Content::Step(step) if step.is_empty());
+                    if !skip_empty
+                        && (self.define_mode != DefineMode::Components || new_content.is_text())
+                    {
                         if new_content.is_step() {
                             self.step_counter += 1;
                         }
                         self.current_section.content.push(new_content);
+                        // a group lasts until the next one or the end of the section
+                        let end = self.current_section.content.len();
+                        if let Some(group) = self.current_section.step_groups.last_mut() {
+                            group.steps.end = end;
+                        }
                     }
 
                     current_block = None;
@@ -183,12 +257,31 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                 item @ (Event::Text(_)
                 | Event::Ingredient(_)
                 | Event::Cookware(_)
-                | Event::Timer(_)) => match &mut current_block {
-                    Some(BlockBuffer::Step(items)) => self.in_step(item, items),
-                    Some(BlockBuffer::Text(text)) => self.in_text(item, text),
-                    None => panic!("Content outside block"),
-                },
+                | Event::Timer(_)) => {
+                    #[cfg(feature = "spans")]
+                    {
+                        let span = match &item {
+                            Event::Text(t) => t.span(),
+                            Event::Ingredient(c) => c.span(),
+                            Event::Cookware(c) => c.span(),
+                            Event::Timer(c) => c.span(),
+                            _ => unreachable!(),
+                        };
+                        block_span = Some(match block_span {
+                            Some(s) => {
+                                Span::new(s.start().min(span.start()), s.end().max(span.end()))
+                            }
+                            None => span,
+                        });
+                    }
+                    match &mut current_block {
+                        Some(BlockBuffer::Step(items)) => self.in_step(item, items),
+                        Some(BlockBuffer::Text(text)) => self.in_text(item, text),
+                        None => panic!("Content outside block"),
+                    }
+                }
 
+                Event::Error(e) if self.partial => self.ctx.error(e),
                 Event::Error(e) => {
                     // on a parser error, collect all other parser errors and
                     // warnings
@@ -211,7 +304,7 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         PassResult::new(Some(self.content), self.ctx)
     }
 
-    fn metadata(&mut self, key: Text<'i>, value: Text<'i>) {
+    fn metadata(&mut self, key: Text<'i>, value: Text<'i>, comment: Option<Text<'i>>) {
         let key_t = key.text_trimmed();
         let value_t = value.text_outer_trimmed();
         let invalid_value = |possible| {
@@ -258,8 +351,7 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                             "Possible config keys are '[mode]', '[duplicate]' and '[auto scale]'",
                         ),
                     );
-                    self.content
-                        .metadata
+                    self.metadata_target()
                         .map
                         .insert(key_t.into_owned(), value_t.into_owned());
                 }
@@ -280,11 +372,39 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
             }
         }
 
+        let in_section = self.parse_options.section_metadata && self.in_section;
+
+        // repeated keys keep all their values, but special keys are parsed
+        // from the last one. tags are accumulated, so they can be repeated
+        let keys = if in_section {
+            &mut self.locations.section_metadata_keys
+        } else {
+            &mut self.locations.metadata_keys
+        };
+        let prev_key = keys.insert(key_t.to_string(), key.span());
+        if let Some(prev_key) = prev_key {
+            let special = SpecialKey::from_str(&key_t);
+            if special.is_ok_and(|k| k != SpecialKey::Tags) {
+                self.ctx.warn(
+                    warning!(
+                        format!("Duplicate metadata key: '{key_t}'"),
+                        label!(key.span(), "this entry overrides the previous one")
+                    )
+                    .related(prev_key, "previous entry here")
+                    .hint("Only the last value is used"),
+                );
+            }
+        }
+
         // insert the value into the map
-        self.content
-            .metadata
-            .map
-            .insert(key_t.to_string(), value_t.to_string());
+        self.metadata_target()
+            .insert_raw(key_t.to_string(), value_t.to_string());
+        if self.parse_options.metadata_comments {
+            let comment = comment
+                .map(|c| c.text_trimmed().into_owned())
+                .filter(|c| !c.is_empty());
+            self.metadata_target().set_comment(&key_t, comment);
+        }
 
         // check if it's a special key
         if let Ok(sp_key) = SpecialKey::from_str(&key_t) {
@@ -296,10 +416,10 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
             }
 
             // try to insert it
-            let res =
-                self.content
-                    .metadata
-                    .insert_special(sp_key, value_t.to_string(), self.converter);
+            let converter = self.converter;
+            let res = self
+                .metadata_target()
+                .insert_special(sp_key, value_t.to_string(), converter);
             if let Err(err) = res {
                 self.ctx.warn(
                     warning!(
@@ -315,6 +435,13 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                 );
                 return;
             }
+            if sp_key == SpecialKey::Units {
+                self.define_unit_aliases();
+            }
+            // the locations are only needed for the recipe metadata
+            if in_section {
+                return;
+            }
             // store it's location if it was inserted
             self.locations
                 .metadata
@@ -329,6 +456,34 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         }
     }
 
+    /// Parses the next quantities with the unit aliases of the last `units`
+    /// metadata entry
+    fn define_unit_aliases(&mut self) {
+        let Some(aliases) = self.metadata_target().unit_aliases().map(<[_]>::to_vec) else {
+            return;
+        };
+        let aliases = aliases.iter().map(|(a, u)| (a.as_str(), u.as_str()));
+        // the units were checked when the entry was inserted
+        self.unit_aliases = self.converter.with_unit_aliases(aliases).ok();
+    }
+
+    /// Finds a unit, with the unit aliases of the recipe
+    fn find_unit(&self, unit: &str) -> Option<Arc<Unit>> {
+        match &self.unit_aliases {
+            Some(aliases) => aliases.find_unit(unit),
+            None => self.converter.find_unit(unit),
+        }
+    }
+
+    /// Metadata where the entries go, the recipe one or the section one
+    fn metadata_target(&mut self) -> &mut Metadata {
+        if self.parse_options.section_metadata && self.in_section {
+            &mut self.current_section.metadata
+        } else {
+            &mut self.content.metadata
+        }
+    }
+
     fn time_override_check(&mut self, new: SpecialKey) {
         let locs = |keys: &[SpecialKey]| {
             assert!(!keys.is_empty());
@@ -370,7 +525,7 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         for e in overriden {
             warn.add_label(label!(e, OVERRIDEN));
         }
-        warn.add_label(label!(overrides, OVERRIDES));
+        warn.add_related(overrides, OVERRIDES);
         warn.add_hint("Prep time and/or cook time overrides total time and vice versa");
         self.ctx.warn(warn);
     }
@@ -424,7 +579,7 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
             }
 
             Event::Ingredient(i) => items.push(Item::Ingredient {
-                index: self.ingredient(i),
+                index: self.ingredient(*i),
             }),
             Event::Cookware(i) => items.push(Item::Cookware {
                 index: self.cookware(i),
@@ -468,18 +623,63 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
 
         let name = ingredient.name.text_trimmed();
 
+        let mut quantity = ingredient.quantity.clone().map(|q| self.quantity(q, true));
+        let mut alternative_quantities = ingredient
+            .alternative_quantities
+            .into_iter()
+            .map(|q| self.quantity(q, true))
+            .collect::<Vec<_>>();
+        let per_unit = match (ingredient.count, &mut quantity) {
+            (Some(count), Some(quantity)) => {
+                self.per_unit_quantity(count, quantity, &mut alternative_quantities)
+            }
+            _ => None,
+        };
+        let purchase_quantity = ingredient.purchase_quantity.map(|q| self.quantity(q, true));
+
         let mut new_igr = Ingredient {
             name: name.into_owned(),
             alias: ingredient.alias.map(|t| t.text_trimmed().into_owned()),
-            quantity: ingredient.quantity.clone().map(|q| self.quantity(q, true)),
+            variant: ingredient.variant.map(|t| t.text_trimmed().into_owned()),
+            quantity,
+            alternative_quantities,
+            per_unit,
+            purchase_quantity,
+            quantity_inherited: false,
             note: ingredient.note.map(|n| n.text_trimmed().into_owned()),
+            substitutes: ingredient
+                .substitutes
+                .into_iter()
+                .map(Located::into_inner)
+                .collect(),
+            category: ingredient.category.map(|c| c.text_trimmed().into_owned()),
+            original_name: None,
+            reference_fragment: ingredient
+                .reference_fragment
+                .map(|f| f.text_trimmed().into_owned()),
+            preparation: ingredient
+                .preparation
+                .map(|p| p.text_trimmed().into_owned()),
+            title_reference: ingredient.title_reference,
+            nutrition: ingredient.nutrition.map(|facts| {
+                facts
+                    .into_iter()
+                    .map(|f| (f.key.text_trimmed().into_owned(), f.value.into_inner()))
+                    .collect()
+            }),
             modifiers: ingredient.modifiers.into_inner(),
             relation: IngredientRelation::definition(
                 Vec::new(),
                 self.define_mode != DefineMode::Components,
             ),
+            #[cfg(feature = "spans")]
+            span: location,
         };
 
+        if self.extensions.contains(Extensions::ADVANCED_UNITS) {
+            self.alternative_quantities_check(&new_igr, &located_ingredient);
+        }
+
         if let Some(inter_data) = ingredient.intermediate_data {
             assert!(new_igr.modifiers().contains(Modifiers::REF));
             let invalid_modifiers = Modifiers::RECIPE | Modifiers::HIDDEN | Modifiers::NEW;
@@ -508,6 +708,11 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
             let definition_location = &self.locations.ingredients[references_to];
             assert!(definition.relation.is_definition());
 
+            // references are part of the same variant as the definition
+            if new_igr.variant.is_none() {
+                new_igr.variant = definition.variant.clone();
+            }
+
             if self.extensions.contains(Extensions::ADVANCED_UNITS) {
                 if let Some(new_quantity) = &new_igr.quantity {
                     let all_quantities = std::iter::once(references_to)
@@ -534,7 +739,7 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                                 .map(|l| l.span())
                                 .unwrap_or(new_q_loc.span());
 
-                            let (new_label, old_label) = match &e {
+                            let (new_label, (old_span, old_message)): (Label, Label) = match &e {
                                 crate::quantity::IncompatibleUnits::MissingUnit { found } => {
                                     let m = "this is missing a unit";
                                     let f = "matching this one";
@@ -552,6 +757,13 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                                 crate::quantity::IncompatibleUnits::UnknownDifferentUnits { .. } => {
                                     (label!(new, "this unit"), label!(old, "differs from this"))
                                 }
+                                crate::quantity::IncompatibleUnits::DifferentPerUnit { a, b } => {
+                                    let per = |p: &Option<String>| match p {
+                                        Some(p) => format!("per {p}"),
+                                        None => "not per unit".to_string(),
+                                    };
+                                    (label!(new, per(b)), label!(old, per(a)))
+                                }
                             };
 
                             self.ctx.warn(
@@ -559,7 +771,7 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                                     "Incompatible units prevent calculating total amount",
                                     new_label
                                 )
-                                .label(old_label)
+                                .related(old_span, old_message.unwrap_or_default())
                                 .set_source(e),
                             )
                         }
@@ -575,6 +787,26 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                 ));
             }
 
+            if let (Some(first), Some(last)) = (
+                located_ingredient.substitutes.first(),
+                located_ingredient.substitutes.last(),
+            ) {
+                let span = Span::new(first.span().start(), last.span().end());
+                let mut e = error!(
+                    "Substitutes not allowed in reference",
+                    label!(span, "remove this")
+                )
+                .hint("Add the substitutes in the definition of the ingredient")
+                .label(label!(
+                    Span::pos(definition_location.span().end()),
+                    "add the substitutes here"
+                ));
+                if implicit {
+                    e.add_hint(IMPLICIT_REF_WARN);
+                }
+                self.ctx.error(e);
+            }
+
             // When the ingredient is not defined in a step, only the definition
             // or the references can have quantities.
             // This is to avoid confusion when calculating the total amount.
@@ -624,6 +856,13 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                 }
             }
 
+            if self.parse_options.inherit_reference_quantities && new_igr.quantity.is_none() {
+                if let Some(q) = &definition.quantity {
+                    new_igr.quantity = Some(q.clone());
+                    new_igr.quantity_inherited = true;
+                }
+            }
+
             Ingredient::set_referenced_from(&mut self.content.ingredients, references_to);
         }
 
@@ -646,6 +885,54 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         self.content.ingredients.len() - 1
     }
 
+    /// Checks that the alternative quantities are not the same physical
+    /// quantity as the main one or each other
+    fn alternative_quantities_check(
+        &mut self,
+        new_igr: &Ingredient<ScalableValue>,
+        located: &Located<parser::Ingredient<'i>>,
+    ) {
+        let Some(main) = &new_igr.quantity else {
+            return;
+        };
+        let quantities = std::iter::once(main)
+            .chain(&new_igr.alternative_quantities)
+            .zip(
+                located
+                    .quantity
+                    .iter()
+                    .chain(&located.alternative_quantities),
+            )
+            .collect::<Vec<_>>();
+
+        let physical_quantity = |q: &Quantity<ScalableValue>| match q
+            .unit()
+            .map(|u| u.unit_info_or_parse(self.converter))
+        {
+            Some(UnitInfo::Known(unit)) => Some(unit.physical_quantity),
+            _ => None,
+        };
+
+        for (i, (q, q_loc)) in quantities.iter().enumerate().skip(1) {
+            let Some(pq) = physical_quantity(q) else {
+                continue;
+            };
+            let same = quantities[..i]
+                .iter()
+                .find(|(other, _)| physical_quantity(other) == Some(pq));
+            if let Some((_, other_loc)) = same {
+                self.ctx.warn(
+                    warning!(
+                        "Alternative quantity with the same physical quantity",
+                        label!(q_loc.span(), "this is a {}", pq)
+                    )
+                    .label(label!(other_loc.span(), "same as this one"))
+                    .hint("Alternative quantities should be in different physical quantities, like volume and mass"),
+                );
+            }
+        }
+    }
+
     fn resolve_intermediate_ref(
         &mut self,
         inter_data: Located<IntermediateData>,
@@ -658,7 +945,7 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         const INVALID: &str = "Invalid intermediate preparation reference";
 
         if val == 0 {
-            match inter_data.ref_mode {
+            match &inter_data.ref_mode {
                 Mode::Number => {
                     return Err(error!(
                         format!("{INVALID}: number is 0"),
@@ -673,6 +960,7 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                     )
                     .hint("Relative reference value has to be greater than 0"));
                 }
+                Mode::Name(_) => {}
             }
         }
 
@@ -684,7 +972,7 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
             .hint(help))
         };
 
-        let relation = match (inter_data.target_kind, inter_data.ref_mode) {
+        let relation = match (inter_data.target_kind, &inter_data.ref_mode) {
             (Kind::Step, Mode::Number) => {
                 let index = self
                     .current_section
@@ -764,10 +1052,55 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                 let index = self.content.sections.len().saturating_sub(val);
                 IngredientRelation::reference(index, IngredientReferenceTarget::Section)
             }
+            (_, Mode::Name(name)) => {
+                return self.resolve_section_name_ref(name, inter_data.span());
+            }
         };
         Ok(relation)
     }
 
+    fn resolve_section_name_ref(
+        &self,
+        name: &str,
+        span: Span,
+    ) -> Result<IngredientRelation, SourceDiag> {
+        let is_named = |s: Option<&str>| s.is_some_and(|s| s.trim() == name);
+
+        if let Some(index) = self
+            .content
+            .sections
+            .iter()
+            .position(|s| is_named(s.name.as_deref()))
+        {
+            return Ok(IngredientRelation::reference(
+                index,
+                IngredientReferenceTarget::Section,
+            ));
+        }
+
+        let mut e = error!(
+            format!("Invalid intermediate preparation reference: unknown section '{name}'"),
+            label!(span)
+        );
+        if is_named(self.current_section.name.as_deref()) {
+            e.add_hint("Only sections before this one can be referenced");
+        } else {
+            let names = self
+                .content
+                .sections
+                .iter()
+                .filter_map(|s| s.name.as_deref())
+                .map(|n| format!("`{n}`"))
+                .collect::<Vec<_>>();
+            if names.is_empty() {
+                e.add_hint("There are no named sections before this one");
+            } else {
+                e.add_hint(format!("Previous sections are: {}", names.join(", ")));
+            }
+        }
+        Err(e)
+    }
+
     fn cookware(&mut self, cookware: Located<parser::Cookware<'i>>) -> usize {
         let located_cookware = cookware.clone();
         let (cookware, location) = cookware.take_pair();
@@ -782,6 +1115,8 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                 referenced_from: Vec::new(),
                 defined_in_step: self.define_mode != DefineMode::Components,
             },
+            #[cfg(feature = "spans")]
+            span: location,
         };
 
         if let Some((references_to, implicit)) =
@@ -842,6 +1177,26 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
             Cookware::set_referenced_from(&mut self.content.cookware, references_to);
         }
 
+        // the amount is fixed unless marked to scale, after the modifiers are
+        // inherited by the references
+        if new_cw.modifiers.contains(Modifiers::SCALE) {
+            new_cw.quantity = new_cw.quantity.take().map(|q| match q {
+                ScalableValue::Fixed(value) if !value.is_text() => ScalableValue::Linear(value),
+                q => {
+                    if let (ScalableValue::Fixed(_), Some(loc)) = (&q, &located_cookware.quantity) {
+                        self.ctx.error(
+                            error!(
+                                "Text value with scale modifier",
+                                label!(loc.span(), "this can't be scaled")
+                            )
+                            .hint("Text cannot be scaled, remove the scale modifier (*)"),
+                        );
+                    }
+                    q
+                }
+            });
+        }
+
         self.locations.cookware.push(located_cookware);
         self.content.cookware.push(new_cw);
         self.content.cookware.len() - 1
@@ -875,10 +1230,16 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                                 ));
                             }
                         }
-                        UnitInfo::Unknown => self.ctx.error(error!(
-                            format!("Unknown timer unit: {unit}"),
-                            label!(unit_span, "expected time unit")
-                        )),
+                        UnitInfo::Unknown => {
+                            let mut e = error!(
+                                format!("Unknown timer unit: {unit}"),
+                                label!(unit_span, "expected time unit")
+                            );
+                            if let Some(suggestion) = self.converter.suggest_unit(unit.text()) {
+                                e.add_hint(format!("did you mean {suggestion}?"));
+                            }
+                            self.ctx.error(e)
+                        }
                     }
                 }
             }
@@ -888,6 +1249,8 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         let new_timer = Timer {
             name: timer.name.map(|t| t.text_trimmed().into_owned()),
             quantity,
+            #[cfg(feature = "spans")]
+            span: located_timer.span(),
         };
 
         self.content.timers.push(new_timer);
@@ -899,11 +1262,179 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         quantity: Located<parser::Quantity<'i>>,
         is_ingredient: bool,
     ) -> Quantity<ScalableValue> {
-        let parser::Quantity { value, unit, .. } = quantity.into_inner();
-        Quantity::new(
-            self.value(value, is_ingredient),
-            unit.map(|t| t.text_trimmed().into_owned()),
-        )
+        let span = quantity.span();
+        let parser::Quantity {
+            mut value,
+            unit,
+            start_unit,
+            per,
+        } = quantity.into_inner();
+        if is_ingredient && unit.is_none() && self.parse_options.require_units {
+            self.missing_unit_check(&value, span);
+        }
+        if let Some(start_unit) = start_unit {
+            let end_unit = unit.as_ref().expect("range start unit without end unit");
+            self.range_units(&mut value, &start_unit, end_unit);
+        }
+        if self.parse_options.swap_inverted_ranges {
+            swap_inverted_ranges(&mut value);
+        }
+        let decimals = self
+            .parse_options
+            .keep_decimals
+            .then(|| written_decimals(&value, self.input))
+            .flatten();
+        let value = self.value(value, is_ingredient);
+        let unit_text = unit.as_ref().map(|t| t.text_trimmed().into_owned());
+        let alias = unit_text
+            .as_deref()
+            .and_then(|text| self.unit_aliases.as_ref()?.alias(text))
+            .cloned();
+        let mut q = match (unit_text, alias) {
+            // the local aliases are not known after parsing, so set the unit now
+            (Some(text), Some(unit)) => Quantity::with_aliased_unit(value, text, unit),
+            (unit_text, _) => Quantity::new(value, unit_text),
+        };
+        if self.parse_options.canonical_units {
+            q.canonicalize_unit(self.converter);
+        }
+        q.per = per.map(|t| t.text_trimmed().into_owned());
+        q.decimals = decimals;
+
+        // timers have their own unit checks
+        if is_ingredient && self.extensions.contains(Extensions::ADVANCED_UNITS) {
+            if let Some((unit, unit_text)) = q.unit().zip(unit) {
+                self.unknown_unit_check(unit, unit_text.span());
+            }
+        }
+
+        q
+    }
+
+    /// Replaces the quantities of each unit in `2 x 50%g` with the totals
+    ///
+    /// The count scales like the quantity and each unit is fixed.
+    fn per_unit_quantity(
+        &mut self,
+        count: Located<Value>,
+        quantity: &mut ScalableQuantity,
+        alternatives: &mut [ScalableQuantity],
+    ) -> Option<PerUnitQuantity<ScalableValue>> {
+        let (count, span) = count.take_pair();
+        let totals = std::iter::once(&*quantity)
+            .chain(alternatives.iter())
+            .map(|q| q.value.try_mul(&count))
+            .collect::<Result<Vec<_>, _>>();
+        let totals = match totals {
+            Ok(totals) => totals,
+            Err(err) => {
+                self.ctx.error(
+                    error!(
+                        "Quantity of each unit is not a number",
+                        label!(span, "this is a count of units")
+                    )
+                    .hint("Write a number for each unit, like `{2 x 50%g}`")
+                    .set_source(err),
+                );
+                return None;
+            }
+        };
+
+        let (count, per_unit_value) = match &quantity.value {
+            ScalableValue::Linear(value) => (
+                ScalableValue::Linear(count),
+                ScalableValue::Fixed(value.clone()),
+            ),
+            other => (ScalableValue::Fixed(count), other.clone()),
+        };
+        let per_unit = PerUnitQuantity {
+            count,
+            per_unit: Quantity {
+                value: per_unit_value,
+                ..quantity.clone()
+            },
+        };
+        for (q, total) in std::iter::once(quantity)
+            .chain(alternatives.iter_mut())
+            .zip(totals)
+        {
+            q.value = total;
+        }
+        Some(per_unit)
+    }
+
+    fn missing_unit_check(&mut self, value: &parser::QuantityValue, span: Span) {
+        // an empty unit, like `{2%}`, marks a count
+        let is_count = self.input[span.range()].trim_end().ends_with('%');
+        if !is_count && value.values().any(|v| !v.is_text()) {
+            self.ctx.error(
+                error!(
+                    "Missing unit in ingredient quantity",
+                    label!(span, "add a unit")
+                )
+                .hint("Units are required, write the quantity like `{2%kg}`")
+                .hint("If it's a count, leave the unit empty like `{2%}`"),
+            );
+        }
+    }
+
+    /// Converts the start of a range with a different unit to the end unit
+    fn range_units(
+        &mut self,
+        value: &mut parser::QuantityValue,
+        start_unit: &Text<'i>,
+        end_unit: &Text<'i>,
+    ) {
+        let parser::QuantityValue::Single { value, .. } = value else {
+            return;
+        };
+        // on error, the value may be recovered as something else
+        let Value::Range { start, .. } = &mut **value else {
+            return;
+        };
+
+        let start_text = start_unit.text_trimmed();
+        let end_text = end_unit.text_trimmed();
+        match (self.find_unit(&start_text), self.find_unit(&end_text)) {
+            (Some(from), Some(to)) if from.physical_quantity == to.physical_quantity => {
+                *start = convert_f64(start.value(), &from, &to).into();
+            }
+            (Some(from), Some(to)) => self.ctx.error(
+                error!(
+                    "Incompatible units in range",
+                    label!(start_unit.span(), from.physical_quantity.to_string())
+                )
+                .label(label!(end_unit.span(), to.physical_quantity.to_string()))
+                .hint("Both ends of the range must be the same physical quantity"),
+            ),
+            // nothing to convert
+            _ if start_text == end_text => {}
+            (from, _) => {
+                let unknown = if from.is_none() { start_unit } else { end_unit };
+                self.ctx.error(
+                    error!(
+                        "Unknown unit in range",
+                        label!(unknown.span(), "this unit is not known")
+                    )
+                    .hint("Different units in a range must be known to convert them"),
+                );
+            }
+        }
+    }
+
+    fn unknown_unit_check(&mut self, unit: &QuantityUnit, span: Span) {
+        if !matches!(unit.unit_info_or_parse(self.converter), UnitInfo::Unknown) {
+            return;
+        }
+        if let Some(suggestion) = self.converter.suggest_unit(unit.text()) {
+            self.ctx.warn(
+                warning!(
+                    format!("Unknown unit: '{}'", unit.text()),
+                    label!(span, "this unit is not known")
+                )
+                .hint(format!("did you mean {suggestion}?")),
+            );
+        }
     }
 
     fn value(&mut self, value: parser::QuantityValue, is_ingredient: bool) -> ScalableValue {
@@ -1219,7 +1750,7 @@ impl RefComponent for Cookware<ScalableValue> {
 
     #[inline]
     fn inherit_modifiers() -> Modifiers {
-        Modifiers::HIDDEN | Modifiers::OPT
+        Modifiers::HIDDEN | Modifiers::OPT | Modifiers::SCALE
     }
 
     #[inline]
@@ -1264,6 +1795,42 @@ fn find_temperature<'a>(text: &'a str, re: &Regex) -> Option<(&'a str, Quantity<
     Some((before, temperature, after))
 }
 
+fn values_mut(value: &mut parser::QuantityValue) -> &mut [Located<Value>] {
+    match value {
+        parser::QuantityValue::Single { value, .. } => std::slice::from_mut(value),
+        parser::QuantityValue::Many(values) => values.as_mut_slice(),
+    }
+}
+
+fn swap_inverted_ranges(value: &mut parser::QuantityValue) {
+    for value in values_mut(value) {
+        if let Value::Range { start, end } = &mut **value {
+            if start.value() > end.value() {
+                std::mem::swap(start, end);
+            }
+        }
+    }
+}
+
+/// Max decimal places the numbers of the value were written with
+fn written_decimals(value: &parser::QuantityValue, input: &str) -> Option<u8> {
+    let mut decimals = None;
+    for value in value.values() {
+        let text = &input[value.span().range()];
+        let parts = match &**value {
+            Value::Number(_) => Some((text, "")),
+            Value::Range { .. } => text.split_once('-'),
+            Value::Tolerant { .. } => text.split_once('±').or_else(|| text.split_once("+-")),
+            Value::Text(_) | Value::Variable(_) => None,
+        };
+        if let Some((a, b)) = parts {
+            let places = Number::decimal_places(a).max(Number::decimal_places(b));
+            decimals = decimals.max(places);
+        }
+    }
+    decimals
+}
+
 fn note_reference_error(span: Span, implicit: bool, def_span: Span) -> SourceDiag {
     let span = Span::new(span.start().saturating_sub(1), span.end() + 1);
 
@@ -1285,10 +1852,7 @@ fn conflicting_reference_quantity_error(
         "Conflicting component reference quantities",
         label!(ref_quantity_span, "reference with quantity here")
     )
-    .label(label!(
-        def_span,
-        "definition with quantity outside a step here"
-    ))
+    .related(def_span, "definition with quantity outside a step here")
     .hint("If the component is not defined in a step and has a quantity, its references cannot have a quantity");
     if implicit {
         e.add_hint(IMPLICIT_REF_WARN);
@@ -7,8 +7,11 @@ use crate::error::{CowStr, PassResult, SourceDiag};
 use crate::ScalableRecipe;
 
 mod event_consumer;
+mod validate;
 
 pub use event_consumer::parse_events;
+pub(crate) use event_consumer::parse_events_partial;
+pub(crate) use validate::{reference_cycles, span_of, validate};
 
 pub type AnalysisResult = PassResult<ScalableRecipe>;
 
@@ -39,6 +42,80 @@ pub struct ParseOptions<'a> {
     /// The boolean returned indicates if the value should be included in the
     /// recipe.
     pub metadata_validator: Option<MetadataValidator<'a>>,
+    /// Replace known units with their canonical form
+    ///
+    /// The unit of quantities will be the symbol of the unit in the
+    /// [`Converter`](crate::Converter), and the text the author wrote is kept
+    /// in [`Quantity::unit_raw`](crate::quantity::Quantity::unit_raw). Known
+    /// units are also matched ignoring case, so `Gram` will be `g`.
+    ///
+    /// Use the raw unit to display the recipe as written and the canonical
+    /// one to operate with it.
+    pub canonical_units: bool,
+    /// Ingredient quantities with a numeric value must have a unit
+    ///
+    /// A quantity like `@eggs{2}` will be an error. Text values, like
+    /// `@salt{a pinch}`, are not checked. Counts are written with an empty
+    /// unit, like `@eggs{2%}`, and are allowed without the usual warning.
+    pub require_units: bool,
+    /// Swap the ends of ranges that start with the greater value
+    ///
+    /// A range like `5-2` always has a warning. With this, it will also be
+    /// stored as `2-5`.
+    pub swap_inverted_ranges: bool,
+    /// Keep the decimal places numbers were written with
+    ///
+    /// Numbers like `10.00` will be displayed as written instead of `10`. See
+    /// [`Quantity::decimals`](crate::quantity::Quantity::decimals).
+    pub keep_decimals: bool,
+    /// Keep the trailing comments of metadata lines
+    ///
+    /// They are available with [`Metadata::entry`](crate::metadata::Metadata::entry).
+    /// By default comments are discarded like in the rest of the recipe.
+    pub metadata_comments: bool,
+    /// Metadata lines after a section line belong to that section
+    ///
+    /// They are stored in [`Section::metadata`](crate::model::Section::metadata)
+    /// instead of the recipe metadata. Config keys, like `[mode]`, are not
+    /// affected. See [`Recipe::section_metadata`](crate::model::Recipe::section_metadata).
+    pub section_metadata: bool,
+    /// Remove the steps without content
+    ///
+    /// An [empty](crate::model::Step::is_empty) step is not added to its
+    /// section, so it does not take a step number and the next one continues
+    /// the count.
+    pub skip_empty_steps: bool,
+    /// References without a quantity show the quantity of the definition
+    ///
+    /// A reference like `@&flour{}` gets a copy of the definition quantity
+    /// and [`Ingredient::quantity_inherited`](crate::model::Ingredient::quantity_inherited)
+    /// is set. The copy is only for display, so it's not added again to the
+    /// total. By default, the reference has no quantity and doesn't add
+    /// anything to the definition.
+    pub inherit_reference_quantities: bool,
+    /// Keep the leading and trailing whitespace of text values
+    ///
+    /// Text values in quantities, like ` a pinch ` in `@salt{ a pinch }`, are
+    /// trimmed by default.
+    pub keep_text_whitespace: bool,
+    /// Replace every run of whitespace in text values with a single space
+    pub collapse_text_whitespace: bool,
+    /// Keep the lines with only comments as text blocks
+    ///
+    /// By default they are discarded. With this, each of them ends up in the
+    /// recipe as a [`Content::Text`](crate::Content::Text) with the comment
+    /// text.
+    pub comment_notes: bool,
+    /// Warn about quantities with a numeric value of zero, like `@salt{0%g}`
+    ///
+    /// They are almost always a mistake.
+    pub warn_zero_quantities: bool,
+    /// Negative quantity values are expected
+    ///
+    /// Values like `@salt{-5%g}` always have a warning unless this is
+    /// enabled, for recipes that write the changes to the amounts of another
+    /// recipe.
+    pub delta_quantities: bool,
 }
 
 /// Return type for check functions in [`ParseOptions`]
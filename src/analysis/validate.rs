@@ -0,0 +1,417 @@
+//! Semantic checks of an already built recipe
+
+use crate::{
+    convert::Converter,
+    error::{label, CowStr, Severity, SourceDiag, SourceReport, Stage},
+    model::{Content, IngredientReferenceTarget, Item, Recipe},
+    quantity::{QuantityUnit, QuantityValue, UnitInfo, Value},
+    span::Span,
+};
+
+#[cfg(feature = "spans")]
+macro_rules! span_of {
+    ($component:expr) => {
+        Some($component.span)
+    };
+}
+
+#[cfg(not(feature = "spans"))]
+macro_rules! span_of {
+    ($component:expr) => {{
+        let _ = &$component;
+        None
+    }};
+}
+pub(crate) use span_of;
+
+/// Checks a built recipe for semantic problems
+///
+/// See [`Recipe::validate`].
+pub(crate) fn validate<D, V: QuantityValue>(
+    recipe: &Recipe<D, V>,
+    converter: &Converter,
+) -> SourceReport {
+    let mut report = SourceReport::empty();
+
+    // references
+    for igr in &recipe.ingredients {
+        if let Some((index, IngredientReferenceTarget::Ingredient)) = igr.relation.references_to() {
+            let defined = recipe
+                .ingredients
+                .get(index)
+                .is_some_and(|d| d.relation.is_definition());
+            if !defined {
+                report.push(diag(
+                    Severity::Error,
+                    format!("Reference to an undefined ingredient: '{}'", igr.name),
+                    span_of!(igr),
+                ));
+            }
+        }
+    }
+    for cw in &recipe.cookware {
+        if let Some(index) = cw.relation.references_to() {
+            let defined = recipe
+                .cookware
+                .get(index)
+                .is_some_and(|d| d.relation.is_definition());
+            if !defined {
+                report.push(diag(
+                    Severity::Error,
+                    format!("Reference to an undefined cookware: '{}'", cw.name),
+                    span_of!(cw),
+                ));
+            }
+        }
+    }
+    for step in recipe
+        .sections
+        .iter()
+        .flat_map(|s| &s.content)
+        .filter_map(|c| match c {
+            Content::Step(step) => Some(step),
+            Content::Text(_) => None,
+        })
+    {
+        for item in &step.items {
+            let (exists, what) = match *item {
+                Item::Text { .. } => continue,
+                Item::Ingredient { index } => (index < recipe.ingredients.len(), "ingredient"),
+                Item::Cookware { index } => (index < recipe.cookware.len(), "cookware"),
+                Item::Timer { index } => (index < recipe.timers.len(), "timer"),
+                Item::InlineQuantity { index } => {
+                    (index < recipe.inline_quantities.len(), "inline quantity")
+                }
+            };
+            if !exists {
+                report.push(diag(
+                    Severity::Error,
+                    format!("Step contains an undefined {what}"),
+                    span_of!(step),
+                ));
+            }
+        }
+    }
+
+    // intermediate references
+    for diag in reference_cycles(recipe) {
+        report.push(diag);
+    }
+
+    // timers
+    for timer in &recipe.timers {
+        let Some(q) = &timer.quantity else { continue };
+        if q.value.inner_values().into_iter().any(Value::is_zero) {
+            report.push(
+                diag(
+                    Severity::Warning,
+                    "Timer with zero duration",
+                    span_of!(timer),
+                )
+                .hint("Remove the timer or give it a duration"),
+            );
+        }
+    }
+
+    // units
+    let mut unknown_unit = |unit: Option<&QuantityUnit>, span: Option<Span>| {
+        if let Some(unit) = unit {
+            if let UnitInfo::Unknown = unit.unit_info_or_parse(converter) {
+                report.push(diag(
+                    Severity::Warning,
+                    format!("Unknown unit: '{}'", unit.text()),
+                    span,
+                ));
+            }
+        }
+    };
+    for igr in &recipe.ingredients {
+        // inherited quantities are checked in the definition
+        let quantities = igr
+            .quantity
+            .iter()
+            .filter(|_| !igr.quantity_inherited)
+            .chain(&igr.alternative_quantities)
+            .chain(&igr.purchase_quantity);
+        for q in quantities {
+            unknown_unit(q.unit(), span_of!(igr));
+        }
+    }
+    for timer in &recipe.timers {
+        if let Some(q) = &timer.quantity {
+            unknown_unit(q.unit(), span_of!(timer));
+        }
+    }
+    for q in &recipe.inline_quantities {
+        unknown_unit(q.unit(), None);
+    }
+
+    // zero ranges
+    let zero_range_warning = |name: &str, span: Option<Span>| {
+        diag(
+            Severity::Warning,
+            format!("Range with a zero end in '{name}'"),
+            span,
+        )
+        .hint("A range that starts or ends in zero may be optional instead")
+    };
+    for igr in &recipe.ingredients {
+        let values = igr
+            .quantity
+            .iter()
+            .filter(|_| !igr.quantity_inherited)
+            .chain(&igr.alternative_quantities)
+            .chain(&igr.purchase_quantity)
+            .flat_map(|q| q.value.inner_values());
+        if values.into_iter().any(is_zero_range) {
+            report.push(zero_range_warning(&igr.name, span_of!(igr)));
+        }
+    }
+    for cw in &recipe.cookware {
+        let values = cw.quantity.iter().flat_map(|v| v.inner_values());
+        if values.into_iter().any(is_zero_range) {
+            report.push(zero_range_warning(&cw.name, span_of!(cw)));
+        }
+    }
+
+    report
+}
+
+/// Finds intermediate references that form a cycle
+///
+/// See [`Recipe::check_reference_cycles`].
+pub(crate) fn reference_cycles<D, V: QuantityValue>(recipe: &Recipe<D, V>) -> Vec<SourceDiag> {
+    // one node per step, a reference to a section depends on all its steps
+    let mut node_of = Vec::with_capacity(recipe.sections.len());
+    let mut steps = Vec::new();
+    for section in &recipe.sections {
+        let nodes = section
+            .content
+            .iter()
+            .map(|c| match c {
+                Content::Step(step) => {
+                    steps.push(step);
+                    Some(steps.len() - 1)
+                }
+                Content::Text(_) => None,
+            })
+            .collect::<Vec<_>>();
+        node_of.push(nodes);
+    }
+
+    // edges are (target step, ingredient making the reference)
+    let mut edges: Vec<Vec<(usize, usize)>> = vec![Vec::new(); steps.len()];
+    for nodes in &node_of {
+        for &node in nodes.iter().flatten() {
+            for item in &steps[node].items {
+                let Item::Ingredient { index } = *item else {
+                    continue;
+                };
+                let Some(igr) = recipe.ingredients.get(index) else {
+                    continue;
+                };
+                match igr.relation.references_to() {
+                    Some((target, IngredientReferenceTarget::Step)) => {
+                        if let Some(&Some(to)) = nodes.get(target) {
+                            edges[node].push((to, index));
+                        }
+                    }
+                    Some((target, IngredientReferenceTarget::Section)) => {
+                        if let Some(targets) = node_of.get(target) {
+                            edges[node].extend(targets.iter().flatten().map(|&to| (to, index)));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        Visiting,
+        Done,
+    }
+
+    fn visit(
+        node: usize,
+        edges: &[Vec<(usize, usize)>],
+        state: &mut [State],
+        found: &mut Vec<usize>,
+    ) {
+        if state[node] != State::New {
+            return;
+        }
+        state[node] = State::Visiting;
+        for &(to, igr) in &edges[node] {
+            match state[to] {
+                State::Visiting => found.push(igr),
+                State::New => visit(to, edges, state, found),
+                State::Done => {}
+            }
+        }
+        state[node] = State::Done;
+    }
+
+    let mut state = vec![State::New; steps.len()];
+    let mut found = Vec::new();
+    for node in 0..steps.len() {
+        visit(node, &edges, &mut state, &mut found);
+    }
+    found.sort_unstable();
+    found.dedup();
+
+    found
+        .into_iter()
+        .map(|index| {
+            let igr = &recipe.ingredients[index];
+            diag(
+                Severity::Error,
+                format!("Circular intermediate reference: '{}'", igr.name),
+                span_of!(igr),
+            )
+            .hint("A step can only use the result of steps or sections before it")
+        })
+        .collect()
+}
+
+fn diag(severity: Severity, message: impl Into<CowStr>, span: Option<Span>) -> SourceDiag {
+    match (severity, span) {
+        (Severity::Error, Some(span)) => SourceDiag::error(message, label!(span), Stage::Analysis),
+        (Severity::Warning, Some(span)) => {
+            SourceDiag::warning(message, label!(span), Stage::Analysis)
+        }
+        (severity, None) => SourceDiag::unlabeled(message, severity, Stage::Analysis),
+    }
+}
+
+fn is_zero_range(value: &Value) -> bool {
+    match value {
+        Value::Range { start, end } => start.value() == 0.0 || end.value() == 0.0,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        model::{ComponentRelation, IngredientRelation},
+        CooklangParser, Extensions, ScalableRecipe,
+    };
+
+    fn parse(input: &str) -> ScalableRecipe {
+        CooklangParser::new(Extensions::all(), Converter::bundled())
+            .parse(input)
+            .unwrap_output()
+    }
+
+    fn messages(report: &SourceReport) -> Vec<String> {
+        messages_of(report.iter())
+    }
+
+    fn messages_of<'a>(diags: impl IntoIterator<Item = &'a SourceDiag>) -> Vec<String> {
+        diags.into_iter().map(|d| d.message.to_string()).collect()
+    }
+
+    #[test]
+    fn valid() {
+        let r = parse("Mix @flour{200%g} in a #bowl{2} and wait ~{10%min}. Add @&flour{10%g}.");
+        assert!(r.validate(&Converter::bundled()).is_empty());
+    }
+
+    #[test]
+    fn undefined_references() {
+        let mut r = parse("Mix @flour{200%g} in a #bowl. Add @&flour{10%g} to the #&bowl.");
+        r.ingredients[1].relation =
+            IngredientRelation::reference(5, IngredientReferenceTarget::Ingredient);
+        r.cookware[1].relation = ComponentRelation::Reference { references_to: 5 };
+        let report = r.validate(&Converter::bundled());
+        assert_eq!(report.errors().count(), 2);
+        assert_eq!(
+            messages(&report),
+            [
+                "Reference to an undefined ingredient: 'flour'",
+                "Reference to an undefined cookware: 'bowl'"
+            ]
+        );
+    }
+
+    #[test]
+    fn undefined_step_items() {
+        let mut r = parse("Mix @flour{200%g} for ~{10%min}.");
+        r.timers.clear();
+        let report = r.validate(&Converter::bundled());
+        assert_eq!(messages(&report), ["Step contains an undefined timer"]);
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn zero_timer() {
+        let r = parse("Wait ~{0%min}.");
+        let report = r.validate(&Converter::bundled());
+        assert_eq!(messages(&report), ["Timer with zero duration"]);
+        assert!(!report.has_errors());
+    }
+
+    #[test]
+    fn unknown_units() {
+        let r = parse("Add @salt{1%pinch} and @flour{1%g}.");
+        let report = r.validate(&Converter::bundled());
+        assert_eq!(messages(&report), ["Unknown unit: 'pinch'"]);
+        assert!(!report.has_errors());
+    }
+
+    #[test]
+    fn reference_cycles() {
+        let input = indoc::indoc! {"
+            = Dough
+            Mix @flour{200%g} and @water{100%ml}.
+
+            = Sauce
+            Cook @tomatoes{2}.
+
+            Add the @&(=~1)dough{}.
+        "};
+        let mut r = parse(input);
+        assert!(r.check_reference_cycles().is_empty());
+        assert!(r.validate(&Converter::bundled()).is_empty());
+
+        // make the dough use the sauce, which uses the dough
+        let sauce = r
+            .sections
+            .iter()
+            .position(|s| s.name.as_deref() == Some("Sauce"))
+            .unwrap();
+        r.ingredients[0].relation =
+            IngredientRelation::reference(sauce, IngredientReferenceTarget::Section);
+        let cycles = r.check_reference_cycles();
+        assert_eq!(
+            messages_of(&cycles),
+            ["Circular intermediate reference: 'dough'"]
+        );
+        assert!(r.validate(&Converter::bundled()).has_errors());
+
+        // a step referencing itself
+        let mut r = parse("Mix @flour{200%g}.\n\nAdd the @&(~1)dough{}.");
+        assert!(r.check_reference_cycles().is_empty());
+        let dough = r.ingredients.len() - 1;
+        r.ingredients[dough].relation =
+            IngredientRelation::reference(1, IngredientReferenceTarget::Step);
+        assert_eq!(r.check_reference_cycles().len(), 1);
+    }
+
+    #[test]
+    fn zero_ranges() {
+        let r = parse("Add @salt{0-1%tsp}, @pepper{1-2%tsp} and #pan{0-1}.");
+        let report = r.validate(&Converter::bundled());
+        assert_eq!(
+            messages(&report),
+            [
+                "Range with a zero end in 'salt'",
+                "Range with a zero end in 'pan'"
+            ]
+        );
+    }
+}
@@ -32,8 +32,9 @@ pub fn build_ast<'i>(events: impl Iterator<Item = Event<'i>>) -> PassResult<Ast<
     let mut ctx = SourceReport::empty();
     for event in events {
         match event {
-            Event::Metadata { key, value } => blocks.push(Block::Metadata { key, value }),
-            Event::Section { name } => blocks.push(Block::Section { name }),
+            Event::Metadata { key, value, .. } => blocks.push(Block::Metadata { key, value }),
+            Event::Section { name, optional } => blocks.push(Block::Section { name, optional }),
+            Event::StepGroup { title } => blocks.push(Block::StepGroup { title }),
             Event::Start(_kind) => items.clear(),
             Event::End(kind) => {
                 match kind {
@@ -60,7 +61,7 @@ pub fn build_ast<'i>(events: impl Iterator<Item = Event<'i>>) -> PassResult<Ast<
                 };
             }
             Event::Text(t) => items.push(Item::Text(t)),
-            Event::Ingredient(c) => items.push(Item::Ingredient(Box::new(c))),
+            Event::Ingredient(c) => items.push(Item::Ingredient(c)),
             Event::Cookware(c) => items.push(Item::Cookware(Box::new(c))),
             Event::Timer(c) => items.push(Item::Timer(Box::new(c))),
             Event::Error(e) => ctx.push(e),
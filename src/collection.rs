@@ -0,0 +1,180 @@
+//! Resolve references between the recipes of a collection
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{metadata::Metadata, model::Recipe, quantity::QuantityValue};
+
+/// Finds the recipes of a collection by their `title` metadata
+///
+/// This resolves the references by title of the
+/// [`TITLE_REFERENCES`](crate::Extensions::TITLE_REFERENCES) extension, like
+/// `@{{Tomato Sauce}}`. Titles are compared ignoring case and extra
+/// whitespace.
+///
+/// `K` is whatever identifies a recipe in the collection, like its path.
+///
+/// ```
+/// # use cooklang::collection::TitleIndex;
+/// let sauce = cooklang::parse(">> title: Tomato Sauce\nCook @tomatoes{}.").unwrap_output();
+/// let pasta = cooklang::parse("Mix @pasta{} and @{{tomato sauce}}{200%g}.").unwrap_output();
+///
+/// let index: TitleIndex<&str> = [("sauce.cook", &sauce.metadata)].into_iter().collect();
+/// let resolved = index.resolve_recipe(&pasta);
+/// assert_eq!(resolved.len(), 1);
+/// assert_eq!(resolved[0].0, 1); // index of the ingredient
+/// assert_eq!(resolved[0].1, Ok(&"sauce.cook"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TitleIndex<K> {
+    titles: HashMap<String, Vec<K>>,
+}
+
+impl<K> Default for TitleIndex<K> {
+    fn default() -> Self {
+        Self {
+            titles: HashMap::new(),
+        }
+    }
+}
+
+impl<K> TitleIndex<K> {
+    /// Creates an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a recipe to the index
+    ///
+    /// Recipes without a title are skipped.
+    pub fn insert(&mut self, key: K, metadata: &Metadata) {
+        if let Some(title) = metadata.get("title") {
+            self.titles
+                .entry(normalize_title(title))
+                .or_default()
+                .push(key);
+        }
+    }
+
+    /// Finds the recipe with a title
+    ///
+    /// It's an error if there is no recipe or more than one with the title.
+    pub fn resolve(&self, title: &str) -> Result<&K, TitleRefError> {
+        match self.titles.get(&normalize_title(title)).map(Vec::as_slice) {
+            Some([key]) => Ok(key),
+            Some(keys) if !keys.is_empty() => Err(TitleRefError::Ambiguous {
+                title: title.to_string(),
+                count: keys.len(),
+            }),
+            _ => Err(TitleRefError::NotFound(title.to_string())),
+        }
+    }
+
+    /// Resolves all the references by title of a recipe
+    ///
+    /// Returns the index in [`Recipe::ingredients`] of every ingredient
+    /// definition that references a title, with the result of
+    /// [`Self::resolve`].
+    pub fn resolve_recipe<D, V: QuantityValue>(
+        &self,
+        recipe: &Recipe<D, V>,
+    ) -> Vec<(usize, Result<&K, TitleRefError>)> {
+        recipe
+            .ingredients
+            .iter()
+            .enumerate()
+            .filter(|(_, igr)| igr.title_reference && igr.relation.is_definition())
+            .map(|(index, igr)| (index, self.resolve(&igr.name)))
+            .collect()
+    }
+}
+
+impl<'a, K> FromIterator<(K, &'a Metadata)> for TitleIndex<K> {
+    fn from_iter<T: IntoIterator<Item = (K, &'a Metadata)>>(iter: T) -> Self {
+        let mut index = Self::new();
+        for (key, metadata) in iter {
+            index.insert(key, metadata);
+        }
+        index
+    }
+}
+
+/// Error from [`TitleIndex::resolve`]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TitleRefError {
+    #[error("No recipe with title: {0}")]
+    NotFound(String),
+    #[error("Found {count} recipes with title: {title}")]
+    Ambiguous { title: String, count: usize },
+}
+
+fn normalize_title(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CooklangParser;
+
+    fn parse(input: &str) -> crate::ScalableRecipe {
+        CooklangParser::default().parse(input).unwrap_output()
+    }
+
+    #[test]
+    fn resolve_by_title() {
+        let sauce = parse(">> title: Tomato Sauce\nCook @tomatoes{}.");
+        let dough = parse(">> title: Pizza   Dough\nKnead @flour{}.");
+        let untitled = parse("Just @water{}.");
+        let index: TitleIndex<usize> = [
+            (0, &sauce.metadata),
+            (1, &dough.metadata),
+            (2, &untitled.metadata),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(index.resolve("Tomato Sauce"), Ok(&0));
+        assert_eq!(index.resolve(" pizza dough "), Ok(&1));
+        assert_eq!(
+            index.resolve("Pesto"),
+            Err(TitleRefError::NotFound("Pesto".into()))
+        );
+
+        let pizza = parse("Spread @{{Pizza Dough}}, add @{{Tomato Sauce}}{100%g} and @{{Pesto}}.");
+        let resolved = index.resolve_recipe(&pizza);
+        assert_eq!(
+            resolved,
+            [
+                (0, Ok(&1)),
+                (1, Ok(&0)),
+                (2, Err(TitleRefError::NotFound("Pesto".into())))
+            ]
+        );
+        assert!(pizza.ingredients[1].quantity.is_some());
+    }
+
+    #[test]
+    fn ambiguous_title() {
+        let a = parse(">> title: Tomato Sauce\nCook @tomatoes{}.");
+        let b = parse(">> title: tomato sauce\nCook @canned tomatoes{}.");
+        let mut index = TitleIndex::new();
+        index.insert("a.cook", &a.metadata);
+        index.insert("b.cook", &b.metadata);
+
+        let err = index.resolve("Tomato Sauce").unwrap_err();
+        assert_eq!(
+            err,
+            TitleRefError::Ambiguous {
+                title: "Tomato Sauce".into(),
+                count: 2
+            }
+        );
+        assert_eq!(err.to_string(), "Found 2 recipes with title: Tomato Sauce");
+    }
+}
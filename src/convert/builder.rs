@@ -5,7 +5,10 @@ use thiserror::Error;
 
 use super::{
     convert_f64,
-    units_file::{self, BestUnits, Extend, Precedence, SIPrefix, UnitEntry, Units, UnitsFile, SI},
+    units_file::{
+        self, BestUnits, Extend, NamedAmount, Precedence, SIPrefix, UnitEntry, UnitPoints, Units,
+        UnitsFile, SI,
+    },
     BestConversions, BestConversionsStore, Converter, Fractions, PhysicalQuantity, System, Unit,
     UnitIndex, UnknownUnit,
 };
@@ -24,6 +27,7 @@ pub struct ConverterBuilder {
     fractions: Vec<units_file::Fractions>,
     best_units: EnumMap<PhysicalQuantity, Option<BestUnits>>,
     default_system: System,
+    named_amounts: HashMap<String, NamedAmount>,
 }
 
 #[derive(Debug)]
@@ -32,6 +36,8 @@ struct UnitBuilder {
     is_expanded: bool,
     expand_si: bool,
     expanded_units: Option<EnumMap<SIPrefix, usize>>,
+    /// Resolved to [`Unit::points`] when finishing
+    pending_points: Option<UnitPoints>,
 }
 
 impl std::ops::Deref for UnitBuilder {
@@ -85,21 +91,7 @@ impl ConverterBuilder {
             let mut add_units =
                 |units: Vec<UnitEntry>, system| -> Result<(), ConverterBuilderError> {
                     for entry in units {
-                        let unit = Unit {
-                            names: entry.names,
-                            symbols: entry.symbols,
-                            aliases: entry.aliases,
-                            ratio: entry.ratio,
-                            difference: entry.difference,
-                            physical_quantity: group.quantity,
-                            system,
-                        };
-                        let _id = self.add_unit(UnitBuilder {
-                            unit,
-                            is_expanded: false,
-                            expand_si: entry.expand_si,
-                            expanded_units: None,
-                        })?;
+                        self.add_entry(entry, group.quantity, system)?;
                     }
                     Ok(())
                 };
@@ -159,9 +151,90 @@ impl ConverterBuilder {
             self.fractions.push(fractions);
         }
 
+        self.named_amounts.extend(units.named_amounts);
+
+        Ok(self)
+    }
+
+    /// Add a temperature scale to the builder
+    ///
+    /// This is the same as adding the unit to the temperature quantity of a
+    /// [`UnitsFile`]. A scale with an offset, like kelvin, only needs a
+    /// [`UnitEntry::difference`] and scales that are not linear, like gas
+    /// marks, can be given with [`UnitEntry::points`].
+    ///
+    /// ```
+    /// # use cooklang::convert::{units_file::UnitEntry, ConverterBuilder, System};
+    /// let kelvin = UnitEntry {
+    ///     names: vec!["kelvin".into()],
+    ///     symbols: vec!["K".into()],
+    ///     aliases: vec![],
+    ///     ratio: 1.0,
+    ///     difference: 0.0,
+    ///     points: None,
+    ///     expand_si: false,
+    /// };
+    /// let converter = ConverterBuilder::new()
+    ///     .with_bundled_units()?
+    ///     .with_temperature_scale(kelvin, Some(System::Metric))?
+    ///     .finish()?;
+    /// assert!(converter.find_unit("K").is_some());
+    /// # Ok::<(), cooklang::convert::ConverterBuilderError>(())
+    /// ```
+    pub fn add_temperature_scale(
+        &mut self,
+        entry: UnitEntry,
+        system: Option<System>,
+    ) -> Result<&mut Self, ConverterBuilderError> {
+        self.add_entry(entry, PhysicalQuantity::Temperature, system)?;
         Ok(self)
     }
 
+    /// Add a temperature scale to the builder
+    ///
+    /// See [`Self::add_temperature_scale`].
+    pub fn with_temperature_scale(
+        mut self,
+        entry: UnitEntry,
+        system: Option<System>,
+    ) -> Result<Self, ConverterBuilderError> {
+        self.add_temperature_scale(entry, system)?;
+        Ok(self)
+    }
+
+    fn add_entry(
+        &mut self,
+        entry: UnitEntry,
+        physical_quantity: PhysicalQuantity,
+        system: Option<System>,
+    ) -> Result<usize, ConverterBuilderError> {
+        let unit = Unit {
+            names: entry.names,
+            symbols: entry.symbols,
+            aliases: entry.aliases,
+            ratio: entry.ratio,
+            difference: entry.difference,
+            points: Vec::new(),
+            physical_quantity,
+            system,
+        };
+        let expand_si = entry.expand_si && entry.points.is_none();
+        let id = self.add_unit(UnitBuilder {
+            unit,
+            is_expanded: false,
+            expand_si,
+            expanded_units: None,
+            pending_points: entry.points,
+        })?;
+        if expand_si != entry.expand_si {
+            return Err(ConverterBuilderError::InvalidPoints {
+                unit: self.all_units[id].to_string(),
+                reason: "a unit with points can't expand SI prefixes",
+            });
+        }
+        Ok(id)
+    }
+
     /// Consume the builder and return the new [`Converter`]
     pub fn finish(mut self) -> Result<Converter, ConverterBuilderError> {
         // expand the stored units
@@ -184,10 +257,16 @@ impl ConverterBuilder {
             &self.si,
         )?;
 
+        resolve_points(&mut self.all_units, &self.unit_index)?;
+
         let best = enum_map! {
             q =>  {
                 if let Some(best_units) = &self.best_units[q] {
                     BestConversionsStore::new(best_units, &self.unit_index, &self.all_units)?
+                } else if q == PhysicalQuantity::Area
+                    || !self.all_units.iter().any(|u| u.physical_quantity == q)
+                {
+                    BestConversionsStore::default()
                 } else {
                     return Err(ConverterBuilderError::EmptyBest { reason: "no best units given", quantity: q })
                 }
@@ -204,6 +283,15 @@ impl ConverterBuilder {
 
         let fractions = build_fractions_config(&self.fractions, &self.unit_index, &self.all_units)?;
 
+        let named_amounts = self
+            .named_amounts
+            .into_iter()
+            .map(|(name, amount)| {
+                let unit_id = self.unit_index.get_unit_id(&amount.unit)?;
+                Ok((name.to_lowercase(), (amount.value, unit_id)))
+            })
+            .collect::<Result<_, ConverterBuilderError>>()?;
+
         Ok(Converter {
             all_units: self
                 .all_units
@@ -215,6 +303,7 @@ impl ConverterBuilder {
             best,
             fractions,
             default_system: self.default_system,
+            named_amounts,
             temperature_regex: Default::default(),
         })
     }
@@ -485,11 +574,13 @@ fn expand_si(
                 aliases: Vec::new(),
                 ratio: unit.ratio * prefix.ratio(),
                 difference: unit.difference,
+                points: Vec::new(),
                 physical_quantity: unit.physical_quantity,
                 system: unit.system,
             },                expand_si: false,
             expanded_units: None,
-            is_expanded: true
+            is_expanded: true,
+            pending_points: None,
         }
         }
     };
@@ -497,6 +588,49 @@ fn expand_si(
     Ok(map)
 }
 
+/// Converts the [`UnitPoints`] of the units to the common scale of their
+/// physical quantity
+fn resolve_points(
+    all_units: &mut [UnitBuilder],
+    unit_index: &UnitIndex,
+) -> Result<(), ConverterBuilderError> {
+    for id in 0..all_units.len() {
+        let Some(points) = all_units[id].pending_points.take() else {
+            continue;
+        };
+        let invalid = |reason| ConverterBuilderError::InvalidPoints {
+            unit: all_units[id].to_string(),
+            reason,
+        };
+        let reference = &all_units[unit_index.get_unit_id(&points.unit)?];
+        if reference.physical_quantity != all_units[id].physical_quantity {
+            return Err(invalid(
+                "the reference unit is of a different physical quantity",
+            ));
+        }
+        if !reference.points.is_empty() || reference.pending_points.is_some() {
+            return Err(invalid("the reference unit also has points"));
+        }
+        if points.values.len() < 2 {
+            return Err(invalid("at least 2 points are needed"));
+        }
+        if points
+            .values
+            .windows(2)
+            .any(|w| w[0].0 >= w[1].0 || w[0].1 >= w[1].1)
+        {
+            return Err(invalid("the values of the points are not increasing"));
+        }
+        let values = points
+            .values
+            .iter()
+            .map(|&(value, in_reference)| (value, reference.value_to_base(in_reference)))
+            .collect();
+        all_units[id].unit.points = values;
+    }
+    Ok(())
+}
+
 impl UnitIndex {
     fn remove_unit(&mut self, unit: &Unit) {
         for key in unit.all_keys() {
@@ -567,4 +701,7 @@ pub enum ConverterBuilderError {
 
     #[error("No SI prefixes found when expandind SI on a unit")]
     EmptySIPrefixes,
+
+    #[error("Invalid points in unit '{unit}': {reason}")]
+    InvalidPoints { unit: String, reason: &'static str },
 }
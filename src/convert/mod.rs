@@ -15,7 +15,10 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
+    analysis::span_of,
+    error::{label, Severity, SourceDiag, Stage},
     quantity::{Number, Quantity, ScaledQuantity, Value},
+    span::Span,
     ScaledRecipe, UnitInfo,
 };
 
@@ -42,6 +45,8 @@ pub struct Converter {
     best: EnumMap<PhysicalQuantity, BestConversionsStore>,
     fractions: Fractions,
     default_system: System,
+    /// Lowercase name to value and unit id
+    named_amounts: HashMap<String, (f64, usize)>,
 
     temperature_regex: OnceCell<Regex>,
 }
@@ -69,6 +74,7 @@ impl Converter {
             default_system: Default::default(),
             temperature_regex: Default::default(),
             fractions: Default::default(),
+            named_amounts: Default::default(),
         }
     }
 
@@ -90,6 +96,36 @@ impl Converter {
             .unwrap()
     }
 
+    /// Converter with only a few metric units
+    ///
+    /// See [`UnitsFile::minimal`]. This is meant for when the full units are
+    /// not needed and memory or startup time matter. Unlike
+    /// [`Converter::bundled`], this is always available.
+    pub fn minimal() -> Self {
+        ConverterBuilder::new()
+            .with_units_file(UnitsFile::minimal())
+            .unwrap()
+            .finish()
+            .unwrap()
+    }
+
+    /// Converter with the bundled units of some physical quantities
+    ///
+    /// Like [`Converter::bundled`], but only with the units of the given
+    /// quantities. Any other unit will be unknown.
+    ///
+    /// This is only available when the `bundled_units` feature is enabled.
+    #[cfg(feature = "bundled_units")]
+    pub fn bundled_subset(quantities: &[PhysicalQuantity]) -> Self {
+        let mut file = UnitsFile::bundled();
+        file.retain_quantities(quantities);
+        ConverterBuilder::new()
+            .with_units_file(file)
+            .unwrap()
+            .finish()
+            .unwrap()
+    }
+
     /// Get the default unit [System]
     pub fn default_system(&self) -> System {
         self.default_system
@@ -115,10 +151,10 @@ impl Converter {
     /// # Panics
     /// If the unit is not known.
     pub fn is_best_unit(&self, unit: &Unit) -> bool {
-        let unit_id = self
-            .unit_index
-            .get_unit_id(unit.symbol())
-            .expect("unit not found");
+        // units with exponents are never in the index
+        let Ok(unit_id) = self.unit_index.get_unit_id(unit.symbol()) else {
+            return false;
+        };
         let Some(system) = unit.system else {
             return false;
         };
@@ -126,6 +162,16 @@ impl Converter {
         conversions.0.iter().any(|&(_, id)| id == unit_id)
     }
 
+    /// Checks if there are best units for a quantity in any system
+    fn has_best_units(&self, quantity: PhysicalQuantity) -> bool {
+        match &self.best[quantity] {
+            BestConversionsStore::Unified(u) => !u.0.is_empty(),
+            BestConversionsStore::BySystem { metric, imperial } => {
+                !metric.0.is_empty() || !imperial.0.is_empty()
+            }
+        }
+    }
+
     /// Get the (marked) best units for a quantity and a system.
     ///
     /// If system is None, returns for all the systems.
@@ -144,10 +190,314 @@ impl Converter {
         }
     }
 
+    /// Get the symbols of all the known units of a system and physical quantity
+    ///
+    /// Units without a system, like the time units in the bundled file, are
+    /// included for every system. The units are sorted from small to large.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, convert::{PhysicalQuantity, System}};
+    /// let converter = Converter::bundled();
+    /// let units = converter.units_in_system(System::Metric, PhysicalQuantity::Volume);
+    /// assert_eq!(units.first(), Some(&"ml"));
+    /// assert_eq!(units.last(), Some(&"kl"));
+    /// assert!(units.contains(&"l"));
+    /// assert!(!units.contains(&"cup"));
+    /// ```
+    pub fn units_in_system(&self, system: System, quantity: PhysicalQuantity) -> Vec<&str> {
+        let mut units = self
+            .quantity_units(quantity)
+            .filter(|u| u.system.is_none_or(|s| s == system))
+            .collect::<Vec<_>>();
+        units.sort_by(|a, b| a.ratio.total_cmp(&b.ratio));
+        units.into_iter().map(Unit::symbol).collect()
+    }
+
+    /// Get the symbols of all the units a unit can be converted to
+    ///
+    /// These are the known units of the same physical quantity in every
+    /// system, without the unit itself, sorted from small to large. Empty if
+    /// the unit is not known.
+    ///
+    /// ```
+    /// # use cooklang::Converter;
+    /// let converter = Converter::bundled();
+    /// let units = converter.compatible_units("ml");
+    /// assert!(units.contains(&"l"));
+    /// assert!(units.contains(&"c"));
+    /// assert!(!units.contains(&"ml"));
+    /// assert!(!units.contains(&"g"));
+    /// ```
+    pub fn compatible_units(&self, unit: &str) -> Vec<&str> {
+        let Some(unit) = self.find_unit(unit) else {
+            return Vec::new();
+        };
+        let mut units = self
+            .quantity_units(unit.physical_quantity)
+            .filter(|u| u.symbol() != unit.symbol())
+            .collect::<Vec<_>>();
+        units.sort_by(|a, b| a.ratio.total_cmp(&b.ratio));
+        units.into_iter().map(Unit::symbol).collect()
+    }
+
     /// Find a unit by any of it's names, symbols or aliases
+    ///
+    /// A length unit with an exponent, like `cm2` or `m^3`, and a metric
+    /// unit with any SI prefix, like `µg`, are also found. See
+    /// [`Self::unit_with_exponent`] and [`Self::scale_unit_prefix`].
     pub fn find_unit(&self, unit: &str) -> Option<Arc<Unit>> {
-        let uid = self.unit_index.get_unit_id(unit).ok()?;
-        Some(self.all_units[uid].clone())
+        match self.unit_index.get_unit_id(unit) {
+            Ok(uid) => Some(self.all_units[uid].clone()),
+            Err(_) => self
+                .unit_with_exponent(unit)
+                .or_else(|| self.scale_unit_prefix(unit)),
+        }
+    }
+
+    /// Overlay of the converter with more aliases for known units
+    ///
+    /// Every pair is the new alias and a unit found with [`Self::find_unit`].
+    /// The aliases are checked first, so an alias that is already a key of
+    /// another unit replaces it. The converter is not copied.
+    ///
+    /// ```
+    /// # use cooklang::Converter;
+    /// let converter = Converter::bundled();
+    /// let local = converter.with_unit_aliases([("T", "tbsp")]).unwrap();
+    /// assert_eq!(local.find_unit("T").unwrap().symbol(), "tbsp");
+    /// assert_eq!(local.find_unit("g").unwrap().symbol(), "g");
+    /// assert!(converter.with_unit_aliases([("x", "unknown")]).is_err());
+    /// ```
+    pub fn with_unit_aliases<'a>(
+        &self,
+        aliases: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<UnitAliases<'_>, UnknownUnit> {
+        let aliases = aliases
+            .into_iter()
+            .map(|(alias, unit)| {
+                let unit = self
+                    .find_unit(unit)
+                    .ok_or_else(|| UnknownUnit(unit.to_string()))?;
+                Ok((alias.to_string(), unit))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(UnitAliases {
+            converter: self,
+            aliases,
+        })
+    }
+
+    /// Approximate quantity of a named amount, like `pinch` or `dash`
+    ///
+    /// The amounts are configured in the [`UnitsFile::named_amounts`]. The
+    /// name is matched ignoring case, surrounding whitespace and a leading
+    /// `a` or `an`, so the text value of `@salt{a pinch}` is found. This is
+    /// only an approximation to use along with the text, like when adding up
+    /// a shopping list.
+    ///
+    /// ```
+    /// # use cooklang::Converter;
+    /// let converter = Converter::bundled();
+    /// let pinch = converter.named_amount("a pinch").unwrap();
+    /// assert_eq!(pinch.to_string(), "0.5 g");
+    /// assert!(converter.named_amount("to taste").is_none());
+    /// ```
+    pub fn named_amount(&self, name: &str) -> Option<Quantity<Value>> {
+        let name = name.trim().to_lowercase();
+        let name = ["a ", "an "]
+            .iter()
+            .find_map(|article| name.strip_prefix(article))
+            .unwrap_or(&name)
+            .trim_start();
+        let &(value, unit_id) = self.named_amounts.get(name)?;
+        Some(Quantity::with_known_unit(
+            Value::from(value),
+            Arc::clone(&self.all_units[unit_id]),
+        ))
+    }
+
+    /// Squared or cubed length unit, like `cm2`, `m^2`, `cm³` or `m^3`
+    ///
+    /// Squared units are an [`Area`](PhysicalQuantity::Area) and cubed units
+    /// a [`Volume`](PhysicalQuantity::Volume). The ratio is the one of the
+    /// length unit raised to the exponent. For volumes, it's changed to the
+    /// volume ratios with the `m` and `l` units of the converter, so cubed
+    /// units need both of them.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, convert::PhysicalQuantity};
+    /// let converter = Converter::bundled();
+    /// let cm2 = converter.unit_with_exponent("cm2").unwrap();
+    /// assert_eq!(cm2.physical_quantity, PhysicalQuantity::Area);
+    /// let cm3 = converter.unit_with_exponent("cm^3").unwrap();
+    /// assert_eq!(cm3.physical_quantity, PhysicalQuantity::Volume);
+    /// assert!(converter.unit_with_exponent("g2").is_none());
+    /// ```
+    pub fn unit_with_exponent(&self, unit: &str) -> Option<Arc<Unit>> {
+        const LITRES_IN_CUBIC_METRE: f64 = 1000.0;
+
+        let (base, exponent) = ["^2", "²", "2", "^3", "³", "3"]
+            .iter()
+            .find_map(|suffix| Some((unit.strip_suffix(suffix)?, suffix)))?;
+        let exponent = if exponent.ends_with(['2', '²']) {
+            2
+        } else {
+            3
+        };
+        let base = &self.all_units[self.unit_index.get_unit_id(base.trim_end()).ok()?];
+        if base.physical_quantity != PhysicalQuantity::Length
+            || base.difference != 0.0
+            || !base.points.is_empty()
+        {
+            return None;
+        }
+
+        let (physical_quantity, ratio, prefix) = match exponent {
+            2 => (PhysicalQuantity::Area, base.ratio.powi(2), "square"),
+            _ => {
+                let find = |key, quantity| {
+                    self.find_unit(key)
+                        .filter(|u: &Arc<Unit>| u.physical_quantity == quantity)
+                };
+                let metre = find("m", PhysicalQuantity::Length)?;
+                let litre = find("l", PhysicalQuantity::Volume)?;
+                let volume_ratio = LITRES_IN_CUBIC_METRE * litre.ratio / metre.ratio.powi(3);
+                (
+                    PhysicalQuantity::Volume,
+                    base.ratio.powi(3) * volume_ratio,
+                    "cubic",
+                )
+            }
+        };
+        let suffixed = |keys: &[Arc<str>]| {
+            keys.iter()
+                .map(|k| Arc::from(format!("{k}{exponent}")))
+                .collect()
+        };
+        Some(Arc::new(Unit {
+            names: base
+                .names
+                .iter()
+                .map(|n| Arc::from(format!("{prefix} {n}")))
+                .collect(),
+            symbols: suffixed(&base.symbols),
+            aliases: suffixed(&base.aliases),
+            ratio,
+            difference: 0.0,
+            points: Vec::new(),
+            physical_quantity,
+            system: base.system,
+        }))
+    }
+
+    /// Metric unit with an SI prefix, like `µg`, `Mg` or `micrograms`
+    ///
+    /// The unit is made from a known metric unit without an offset or points
+    /// and the factor of the prefix, so the units file doesn't need every
+    /// prefixed unit. A symbol prefix goes with a symbol of the unit and a
+    /// name prefix with a name. For micro, `µ`, `μ` and `u` are all accepted.
+    ///
+    /// ```
+    /// # use cooklang::Converter;
+    /// let converter = Converter::bundled();
+    /// let ug = converter.scale_unit_prefix("µg").unwrap();
+    /// assert_eq!(ug.symbol(), "µg");
+    /// assert_eq!(ug.ratio, 1e-6);
+    /// assert!(converter.scale_unit_prefix("microgram").is_some());
+    /// assert!(converter.scale_unit_prefix("mcup").is_none());
+    /// ```
+    pub fn scale_unit_prefix(&self, unit: &str) -> Option<Arc<Unit>> {
+        // symbols, name and factor. The first symbol is the canonical one
+        const PREFIXES: &[(&[&str], &str, f64)] = &[
+            (&["G"], "giga", 1e9),
+            (&["M"], "mega", 1e6),
+            (&["k"], "kilo", 1e3),
+            (&["h"], "hecto", 1e2),
+            (&["da"], "deca", 1e1),
+            (&["d"], "deci", 1e-1),
+            (&["c"], "centi", 1e-2),
+            (&["m"], "milli", 1e-3),
+            (&["µ", "μ", "u"], "micro", 1e-6),
+            (&["n"], "nano", 1e-9),
+        ];
+
+        let prefixed = |keys: &[Arc<str>], prefix: &str| {
+            keys.iter()
+                .map(|k| Arc::from(format!("{prefix}{k}")))
+                .collect()
+        };
+
+        PREFIXES.iter().find_map(|&(symbols, name, factor)| {
+            let mut candidates = symbols.iter().map(|&s| (s, true)).chain([(name, false)]);
+            candidates.find_map(|(prefix, is_symbol)| {
+                let rest = unit.strip_prefix(prefix)?;
+                let base = &self.all_units[self.unit_index.get_unit_id(rest).ok()?];
+                let keys = if is_symbol {
+                    &base.symbols
+                } else {
+                    &base.names
+                };
+                if base.system != Some(System::Metric)
+                    || base.difference != 0.0
+                    || !base.points.is_empty()
+                    || !keys.iter().any(|k| k.as_ref() == rest)
+                {
+                    return None;
+                }
+
+                Some(Arc::new(Unit {
+                    names: prefixed(&base.names, name),
+                    symbols: prefixed(&base.symbols, symbols[0]),
+                    aliases: Vec::new(),
+                    ratio: base.ratio * factor,
+                    difference: 0.0,
+                    points: Vec::new(),
+                    physical_quantity: base.physical_quantity,
+                    system: base.system,
+                }))
+            })
+        })
+    }
+
+    /// Suggest a known unit for a possibly misspelled one
+    ///
+    /// Compares the text with all the known names, symbols and aliases using
+    /// the edit distance and returns the closest one if it's close enough.
+    /// Returns [`None`] if the unit is already known or nothing is similar.
+    ///
+    /// ```
+    /// # use cooklang::Converter;
+    /// let converter = Converter::bundled();
+    /// assert_eq!(converter.suggest_unit("mll").as_deref(), Some("ml"));
+    /// assert_eq!(converter.suggest_unit("ml"), None);
+    /// ```
+    pub fn suggest_unit(&self, unit: &str) -> Option<String> {
+        if self.unit_index.get_unit_id(unit).is_ok() {
+            return None;
+        }
+        let lowercase = unit.to_lowercase();
+        let len = unit.chars().count();
+        if len == 0 {
+            return None;
+        }
+        // short units are very easy to confuse, so be stricter with them
+        let max_distance = if len <= 3 { 1 } else { 2 };
+
+        self.unit_index
+            .0
+            .keys()
+            .filter_map(|key| {
+                let d = edit_distance(&lowercase, &key.to_lowercase());
+                (d <= max_distance && d < len).then(|| (d, edit_distance(unit, key), key))
+            })
+            // ties are resolved by the case sensitive distance and then the
+            // key itself to get a deterministic result
+            .min_by(|a, b| {
+                a.0.cmp(&b.0)
+                    .then_with(|| a.1.cmp(&b.1))
+                    .then_with(|| a.2.cmp(b.2))
+            })
+            .map(|(_, _, key)| key.to_string())
     }
 
     /// Gets the fractions configuration for the given unit
@@ -156,10 +506,10 @@ impl Converter {
     /// If the unit is not known.
     #[tracing::instrument(level = "trace", skip_all, fields(unit = %unit), ret)]
     pub(crate) fn fractions_config(&self, unit: &Unit) -> FractionsConfig {
-        let unit_id = self
-            .unit_index
-            .get_unit_id(unit.symbol())
-            .expect("unit not found");
+        // units with exponents are never in the index
+        let Ok(unit_id) = self.unit_index.get_unit_id(unit.symbol()) else {
+            return FractionsConfig::default();
+        };
         self.fractions
             .config(unit.system, unit.physical_quantity, unit_id)
     }
@@ -194,6 +544,7 @@ impl PartialEq for Converter {
             && self.quantity_index == other.quantity_index
             && self.best == other.best
             && self.default_system == other.default_system
+            && self.named_amounts == other.named_amounts
         // temperature_regex ignored, it should be the same if the rest is the
         // the same
     }
@@ -261,11 +612,35 @@ impl UnitIndex {
     }
 }
 
+/// A [`Converter`] with more aliases for known units
+///
+/// Created with [`Converter::with_unit_aliases`].
+#[derive(Debug, Clone)]
+pub struct UnitAliases<'c> {
+    converter: &'c Converter,
+    aliases: HashMap<String, Arc<Unit>>,
+}
+
+impl UnitAliases<'_> {
+    /// Same as [`Converter::find_unit`] but checking the aliases first
+    pub fn find_unit(&self, unit: &str) -> Option<Arc<Unit>> {
+        self.alias(unit)
+            .cloned()
+            .or_else(|| self.converter.find_unit(unit))
+    }
+
+    /// Unit of an alias, if `alias` is one
+    pub fn alias(&self, alias: &str) -> Option<&Arc<Unit>> {
+        self.aliases.get(alias)
+    }
+}
+
 pub(crate) type UnitQuantityIndex = EnumMap<PhysicalQuantity, Vec<usize>>;
 
 /// A unit
 ///
-/// Conversion will be `val * [Self::ratio] + [Self::difference]`
+/// Conversion will be `(val + [Self::difference]) * [Self::ratio]` or, if the
+/// unit has [`Self::points`], an interpolation between them.
 ///
 /// It implements [Display](std::fmt::Display). It will use [`Self::symbol`] or,
 /// if alternate (`#`) is given, it will try the first name.
@@ -281,6 +656,16 @@ pub struct Unit {
     pub ratio: f64,
     /// Difference offset to the conversion ratio
     pub difference: f64,
+    /// Pairs of a value in this unit and the same value converted with the
+    /// ratio of the physical quantity
+    ///
+    /// When not empty, [`Self::ratio`] and [`Self::difference`] are ignored
+    /// and values are converted interpolating linearly between the points,
+    /// and extending the first and last segments. This is for scales that
+    /// are not linear, like gas marks. They are sorted and increasing in
+    /// both values.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub points: Vec<(f64, f64)>,
     /// The [PhysicalQuantity] this unit belongs to
     pub physical_quantity: PhysicalQuantity,
     /// The unit [System] this unit belongs to, if any
@@ -304,6 +689,43 @@ impl Unit {
             .or_else(|| self.aliases.first())
             .expect("symbol, name or alias in unit")
     }
+
+    /// Converts a value in this unit to the common scale of its physical
+    /// quantity
+    pub(crate) fn value_to_base(&self, value: f64) -> f64 {
+        if self.points.is_empty() {
+            (value + self.difference) * self.ratio
+        } else {
+            interpolate(&self.points, value, |p| p.0, |p| p.1)
+        }
+    }
+
+    /// Inverse of [`Self::value_to_base`]
+    pub(crate) fn value_from_base(&self, value: f64) -> f64 {
+        if self.points.is_empty() {
+            (value / self.ratio) - self.difference
+        } else {
+            interpolate(&self.points, value, |p| p.1, |p| p.0)
+        }
+    }
+}
+
+/// Linear interpolation of `x` between the points, from one coordinate to the
+/// other
+fn interpolate(
+    points: &[(f64, f64)],
+    x: f64,
+    from: impl Fn(&(f64, f64)) -> f64,
+    to: impl Fn(&(f64, f64)) -> f64,
+) -> f64 {
+    debug_assert!(points.len() >= 2);
+    // outside of the points, extend the first or last segment
+    let i = points
+        .partition_point(|p| from(p) < x)
+        .clamp(1, points.len() - 1);
+    let (a, b) = (&points[i - 1], &points[i]);
+    let t = (x - from(a)) / (from(b) - from(a));
+    to(a) + t * (to(b) - to(a))
 }
 
 impl PartialEq for Unit {
@@ -313,6 +735,7 @@ impl PartialEq for Unit {
             && self.aliases == other.aliases
             && self.ratio == other.ratio
             && self.difference == other.difference
+            && self.points == other.points
             && self.physical_quantity == other.physical_quantity
             && self.system == other.system
         // expand_si and expanded_units ignored
@@ -373,6 +796,7 @@ impl BestConversions {
         let value = match value {
             ConvertValue::Number(n) => n.abs(),
             ConvertValue::Range(r) => r.start().abs(),
+            ConvertValue::Tolerant { center, .. } => center.abs(),
         };
         let base_unit_id = self.base()?;
         let base_unit = &converter.all_units[base_unit_id];
@@ -416,45 +840,92 @@ pub enum PhysicalQuantity {
     Length,
     Temperature,
     Time,
+    /// Only squared length units, like `cm2`. Best units are optional.
+    Area,
 }
 
 impl ScaledRecipe {
-    /// Convert a [`ScaledRecipe`] to another [`System`] in place.
+    /// Convert a [`ScaledRecipe`] to another [`System`]
+    ///
+    /// With [`None`], the [system](crate::metadata::Metadata::system) in the
+    /// recipe metadata is used, or the converter
+    /// [default](Converter::default_system) if it doesn't have one.
     ///
-    /// When an error occurs, it is stored and the quantity stays the same.
+    /// Each quantity is converted on its own. When one fails, it stays the
+    /// same and a warning is returned for it, so the rest are still
+    /// converted. These usually are missing units, unknown units or text
+    /// values. The warnings have a location only with the `spans` feature.
     ///
-    /// Returns all the errors while converting. These usually are missing units,
-    /// unknown units or text values.
-    pub fn convert(&mut self, to: System, converter: &Converter) -> Vec<ConvertError> {
-        let mut errors = Vec::new();
+    /// ```
+    /// # use cooklang::{convert::System, CooklangParser};
+    /// let parser = CooklangParser::default();
+    /// let recipe = parser
+    ///     .parse("Add @flour{1%lb} and @salt{1%pinch}.")
+    ///     .unwrap_output()
+    ///     .default_scale();
+    /// let (recipe, warnings) = recipe.convert(System::Metric, parser.converter());
+    /// let flour = recipe.ingredients[0].quantity.as_ref().unwrap();
+    /// assert_eq!(flour.unit_text(), Some("g"));
+    /// assert_eq!(warnings.len(), 1);
+    /// assert_eq!(
+    ///     warnings[0].message,
+    ///     "Cannot convert ingredient 'salt': Unknown unit: 'pinch'"
+    /// );
+    /// ```
+    pub fn convert(
+        mut self,
+        to: impl Into<Option<System>>,
+        converter: &Converter,
+    ) -> (Self, Vec<SourceDiag>) {
+        let mut warnings = Vec::new();
 
+        let to = to
+            .into()
+            .or_else(|| self.metadata.system())
+            .unwrap_or_else(|| converter.default_system());
         let to = ConvertTo::from(to);
 
-        let mut conv = |q: &mut ScaledQuantity| {
+        let mut conv = |q: &mut ScaledQuantity, what: &str, span: Option<Span>| {
             if let Err(e) = q.convert(to, converter) {
-                errors.push(e)
+                let message = format!("Cannot convert {what}: {e}");
+                warnings.push(match span {
+                    Some(span) => SourceDiag::warning(message, label!(span), Stage::Analysis),
+                    None => SourceDiag::unlabeled(message, Severity::Warning, Stage::Analysis),
+                });
             }
         };
 
         for igr in &mut self.ingredients {
-            if let Some(q) = &mut igr.quantity {
-                conv(q);
+            let what = format!("ingredient '{}'", igr.name);
+            let span = span_of!(igr);
+            for q in igr
+                .quantity
+                .iter_mut()
+                .chain(&mut igr.alternative_quantities)
+                .chain(&mut igr.purchase_quantity)
+            {
+                conv(q, &what, span);
             }
         }
 
         // cookware can't have units
 
         for timer in &mut self.timers {
+            let what = match &timer.name {
+                Some(name) => format!("timer '{name}'"),
+                None => "timer".to_string(),
+            };
+            let span = span_of!(timer);
             if let Some(q) = &mut timer.quantity {
-                conv(q);
+                conv(q, &what, span);
             }
         }
 
         for q in &mut self.inline_quantities {
-            conv(q);
+            conv(q, "inline quantity", None);
         }
 
-        errors
+        (self, warnings)
     }
 }
 
@@ -481,12 +952,12 @@ impl ScaledQuantity {
                     self.unit_text().unwrap().to_string(),
                 )))
             }
-            None => return Err(ConvertError::NoUnit(self.clone())),
+            None => return Err(ConvertError::NoUnit(Box::new(self.clone()))),
         };
         let value = ConvertValue::try_from(&self.value)?;
 
         let (new_value, new_unit) = converter.convert(value, unit, to)?;
-        *self = Quantity::with_known_unit(new_value.into(), Arc::clone(&new_unit));
+        self.set_known_unit(new_value.into(), Arc::clone(&new_unit));
         match to {
             ConvertTo::Unit(_) => {
                 self.try_fraction(converter);
@@ -512,6 +983,11 @@ impl ScaledQuantity {
             return Ok(());
         };
 
+        // without best units, like areas, there is nothing to fit to
+        if !converter.has_best_units(unit.physical_quantity) {
+            return Ok(());
+        }
+
         // If configured, try fitting as a fraction
         if converter.should_fit_fraction(&unit)
             && self.fit_fraction(&unit, unit.system, converter)?
@@ -549,7 +1025,9 @@ impl ScaledQuantity {
         let value = match self.value {
             Value::Number(n) => n.value(),
             Value::Range { start, .. } => start.value(),
+            Value::Tolerant { center, .. } => center.value(),
             Value::Text(ref t) => return Err(ConvertError::TextValue(t.clone())),
+            Value::Variable(ref name) => return Err(ConvertError::Variable(name.clone())),
         };
 
         let possible_conversions = converter.best[unit.physical_quantity]
@@ -598,9 +1076,21 @@ impl ScaledQuantity {
                     end: end_frac,
                 }
             }
-            Value::Text(_) => unreachable!(),
+            Value::Tolerant { center, tolerance } => {
+                let tolerance =
+                    converter.convert_f64(center.value() + tolerance.value(), unit, new_unit)
+                        - converter.convert_f64(center.value(), unit, new_unit);
+                let tolerance = tolerance.abs();
+                let tolerance = approx(tolerance, converter.fractions_config(new_unit))
+                    .unwrap_or(Number::Regular(tolerance));
+                Value::Tolerant {
+                    center: new_value,
+                    tolerance,
+                }
+            }
+            Value::Text(_) | Value::Variable(_) => unreachable!(),
         };
-        *self = Quantity::with_known_unit(new_value, Arc::clone(new_unit));
+        self.set_known_unit(new_value, Arc::clone(new_unit));
         Ok(true)
     }
 
@@ -626,7 +1116,11 @@ impl ScaledQuantity {
                 start.try_approx(cfg.accuracy, cfg.max_denominator, cfg.max_whole)
                     || end.try_approx(cfg.accuracy, cfg.max_denominator, cfg.max_whole)
             }
-            Value::Text(_) => false,
+            Value::Tolerant { center, tolerance } => {
+                center.try_approx(cfg.accuracy, cfg.max_denominator, cfg.max_whole)
+                    || tolerance.try_approx(cfg.accuracy, cfg.max_denominator, cfg.max_whole)
+            }
+            Value::Text(_) | Value::Variable(_) => false,
         }
     }
 }
@@ -644,12 +1138,12 @@ impl Converter {
         let (value, unit) = match to {
             ConvertTo::Unit(target_unit) => {
                 let to = self.get_unit(&target_unit)?;
-                let val = self.convert_to_unit(value, unit, to.as_ref())?;
-                (val, Arc::clone(to))
+                let val = self.convert_to_unit(value, &unit, &to)?;
+                (val, to)
             }
-            ConvertTo::Best(system) => self.convert_to_best(value, unit, system)?,
+            ConvertTo::Best(system) => self.convert_to_best(value, &unit, system)?,
             ConvertTo::SameSystem => {
-                self.convert_to_best(value, unit, unit.system.unwrap_or(self.default_system))?
+                self.convert_to_best(value, &unit, unit.system.unwrap_or(self.default_system))?
             }
         };
         Ok((value, unit))
@@ -697,6 +1191,14 @@ impl Converter {
                 let e = self.convert_f64(*r.end(), from, to);
                 ConvertValue::Range(s..=e)
             }
+            ConvertValue::Tolerant { center, tolerance } => {
+                let c = self.convert_f64(center, from, to);
+                let t = self.convert_f64(center + tolerance, from, to) - c;
+                ConvertValue::Tolerant {
+                    center: c,
+                    tolerance: t.abs(),
+                }
+            }
         }
     }
 
@@ -707,26 +1209,157 @@ impl Converter {
         convert_f64(value, from, to)
     }
 
-    pub(crate) fn get_unit<'a>(
-        &'a self,
-        unit: &'a ConvertUnit,
-    ) -> Result<&'a Arc<Unit>, UnknownUnit> {
+    pub(crate) fn get_unit(&self, unit: &ConvertUnit) -> Result<Arc<Unit>, UnknownUnit> {
         let unit = match unit {
-            ConvertUnit::Unit(u) => u,
-            ConvertUnit::Key(key) => {
-                let id = self.unit_index.get_unit_id(key)?;
-                &self.all_units[id]
-            }
+            ConvertUnit::Unit(u) => Arc::clone(u),
+            ConvertUnit::Key(key) => match self.unit_index.get_unit_id(key) {
+                Ok(id) => Arc::clone(&self.all_units[id]),
+                Err(err) => self
+                    .unit_with_exponent(key)
+                    .or_else(|| self.scale_unit_prefix(key))
+                    .ok_or(err)?,
+            },
         };
         Ok(unit)
     }
 }
 
+/// Memoizes unit lookups and conversion factors between units
+///
+/// Adding many quantities, like when grouping the ingredients of a big
+/// recipe, looks up the same units and converts between the same pairs of
+/// units again and again. Pass the same cache to the `_cached` methods, like
+/// [`GroupedQuantity::add_cached`](crate::GroupedQuantity::add_cached), to
+/// do it only once. The results are the same as without the cache.
+///
+/// A cache must only be used with one [`Converter`].
+#[derive(Debug, Default, Clone)]
+pub struct ConversionCache {
+    units: HashMap<String, UnitInfo>,
+    factors: HashMap<String, HashMap<String, Option<Factor>>>,
+}
+
+/// The parts of [`convert_f64`] for a pair of units
+///
+/// Units with [`Unit::points`] don't have one.
+#[derive(Debug, Clone, Copy)]
+struct Factor {
+    from_ratio: f64,
+    from_difference: f64,
+    to_ratio: f64,
+    to_difference: f64,
+}
+
+impl Factor {
+    fn new(from: &Unit, to: &Unit) -> Option<Self> {
+        assert_eq!(from.physical_quantity, to.physical_quantity);
+        if !from.points.is_empty() || !to.points.is_empty() {
+            return None;
+        }
+        Some(Self {
+            from_ratio: from.ratio,
+            from_difference: from.difference,
+            to_ratio: to.ratio,
+            to_difference: to.difference,
+        })
+    }
+
+    fn apply(&self, value: f64) -> f64 {
+        let norm = (value + self.from_difference) * self.from_ratio;
+        (norm / self.to_ratio) - self.to_difference
+    }
+}
+
+impl ConversionCache {
+    /// Creates an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same as [`UnitInfo::new`] but only parses each unit text once
+    pub(crate) fn unit_info(&mut self, text: &str, converter: &Converter) -> UnitInfo {
+        if let Some(info) = self.units.get(text) {
+            return info.clone();
+        }
+        let info = UnitInfo::new(text, converter);
+        self.units.insert(text.to_string(), info.clone());
+        info
+    }
+
+    /// Converts a value between two units of the same physical quantity
+    pub(crate) fn convert_value(
+        &mut self,
+        value: &Value,
+        from: &Unit,
+        to: &Unit,
+    ) -> Result<Value, ConvertError> {
+        let value = ConvertValue::try_from(value)?;
+        if from.physical_quantity != to.physical_quantity {
+            return Err(ConvertError::MixedQuantities {
+                from: from.physical_quantity,
+                to: to.physical_quantity,
+            });
+        }
+        let factor = self.factor(from, to);
+        let convert = |n: f64| {
+            if std::ptr::eq(from, to) {
+                n
+            } else if let Some(factor) = factor {
+                factor.apply(n)
+            } else {
+                convert_f64(n, from, to)
+            }
+        };
+        let converted = match value {
+            ConvertValue::Number(n) => ConvertValue::Number(convert(n)),
+            ConvertValue::Range(r) => ConvertValue::Range(convert(*r.start())..=convert(*r.end())),
+            ConvertValue::Tolerant { center, tolerance } => {
+                let c = convert(center);
+                ConvertValue::Tolerant {
+                    center: c,
+                    tolerance: (convert(center + tolerance) - c).abs(),
+                }
+            }
+        };
+        Ok(converted.into())
+    }
+
+    fn factor(&mut self, from: &Unit, to: &Unit) -> Option<Factor> {
+        if let Some(factor) = self
+            .factors
+            .get(from.symbol())
+            .and_then(|m| m.get(to.symbol()))
+        {
+            return *factor;
+        }
+        let factor = Factor::new(from, to);
+        self.factors
+            .entry(from.symbol().to_string())
+            .or_default()
+            .insert(to.symbol().to_string(), factor);
+        factor
+    }
+}
+
 pub(crate) fn convert_f64(value: f64, from: &Unit, to: &Unit) -> f64 {
     assert_eq!(from.physical_quantity, to.physical_quantity);
+    to.value_from_base(from.value_to_base(value))
+}
 
-    let norm = (value + from.difference) * from.ratio;
-    (norm / to.ratio) - to.difference
+/// Levenshtein distance between two strings, by chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
 }
 
 /// Error when try to convert an unknown unit
@@ -741,6 +1374,14 @@ pub enum ConvertValue {
     /// It will convert the range as if start and end were 2 calls to convert as
     /// a number
     Range(RangeInclusive<f64>),
+    /// Number with a tolerance
+    ///
+    /// The tolerance is converted as a difference, so offsets, like the one
+    /// between celsius and fahrenheit, don't change it.
+    Tolerant {
+        center: f64,
+        tolerance: f64,
+    },
 }
 
 /// Input unit for [`Converter::convert`]
@@ -825,6 +1466,10 @@ impl From<ConvertValue> for Value {
                 start: (*r.start()).into(),
                 end: (*r.end()).into(),
             },
+            ConvertValue::Tolerant { center, tolerance } => Self::Tolerant {
+                center: center.into(),
+                tolerance: tolerance.into(),
+            },
         }
     }
 }
@@ -835,7 +1480,12 @@ impl TryFrom<&Value> for ConvertValue {
         let value = match value {
             Value::Number(n) => ConvertValue::Number(n.value()),
             Value::Range { start, end } => ConvertValue::Range(start.value()..=end.value()),
+            Value::Tolerant { center, tolerance } => ConvertValue::Tolerant {
+                center: center.value(),
+                tolerance: tolerance.value(),
+            },
             Value::Text(t) => return Err(ConvertError::TextValue(t.clone())),
+            Value::Variable(name) => return Err(ConvertError::Variable(name.clone())),
         };
         Ok(value)
     }
@@ -859,6 +1509,7 @@ impl PartialOrd<Self> for ConvertValue {
             match v {
                 ConvertValue::Number(n) => *n,
                 ConvertValue::Range(r) => *r.start(),
+                ConvertValue::Tolerant { center, .. } => *center,
             }
         }
         let this = extract(self);
@@ -871,11 +1522,14 @@ impl PartialOrd<Self> for ConvertValue {
 #[derive(Debug, Error)]
 pub enum ConvertError {
     #[error("Tried to convert a value with no unit")]
-    NoUnit(ScaledQuantity),
+    NoUnit(Box<ScaledQuantity>),
 
     #[error("Tried to convert a text value: {0}")]
     TextValue(String),
 
+    #[error("Tried to convert an unbound variable: ${0}")]
+    Variable(String),
+
     #[error("Mixed physical quantities: {from} {to}")]
     MixedQuantities {
         from: PhysicalQuantity,
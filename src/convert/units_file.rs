@@ -32,11 +32,27 @@ pub struct UnitsFile {
     pub fractions: Option<Fractions>,
     /// Extend and/or edit units from other layers before
     pub extend: Option<Extend>,
+    /// Approximate quantities for text values, like `pinch`
+    ///
+    /// Layers after can overwrite the amounts of the ones before. See
+    /// [`Converter::named_amount`](super::Converter::named_amount).
+    #[serde(default)]
+    pub named_amounts: HashMap<String, NamedAmount>,
     /// Declare new units
     #[serde(default)]
     pub quantity: Vec<QuantityGroup>,
 }
 
+/// Approximate quantity of a named amount used in [`UnitsFile`]
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct NamedAmount {
+    /// Numeric value
+    pub value: f64,
+    /// Any name, symbol or alias of a known unit
+    pub unit: String,
+}
+
 /// [SI] configuration used in [`UnitsFile`]
 ///
 /// [SI]: https://en.wikipedia.org/wiki/International_System_of_Units
@@ -236,7 +252,8 @@ pub struct QuantityGroup {
     /// List of best units
     ///
     /// This is optional by at least one [`QuantityGroup`] of the quantity in
-    /// any [`UnitsFile`] in a converter has to define it and not be empty.
+    /// any [`UnitsFile`] in a converter has to define it and not be empty,
+    /// unless the quantity has no units at all.
     ///
     /// **This will always replace the configuration from [`UnitsFile`] before**
     #[serde(default)]
@@ -328,6 +345,16 @@ pub struct UnitEntry {
     /// (namely celsius to fahrenheit).
     #[serde(default)]
     pub difference: f64,
+    /// Conversion points for a unit that is not linear with the others
+    ///
+    /// When given, [`Self::ratio`] and [`Self::difference`] are ignored. For
+    /// example, gas marks:
+    ///
+    /// ```toml
+    /// { names = ["gas mark"], symbols = ["gas"], ratio = 1, points = { unit = "C", values = [[1, 140], [2, 150], [3, 170]] } }
+    /// ```
+    #[serde(default)]
+    pub points: Option<UnitPoints>,
     /// Mark this unit to expand with [`SI`] configuration.
     ///
     /// For example, if this unit is `gram` and is marked with `expand_si`, it
@@ -337,6 +364,23 @@ pub struct UnitEntry {
     pub expand_si: bool,
 }
 
+/// Values of a unit matched to the values of another unit
+///
+/// Values between the points are interpolated linearly. See
+/// [`UnitEntry::points`].
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct UnitPoints {
+    /// Unit of the second value of each pair
+    ///
+    /// It must be of the same physical quantity and without points itself.
+    pub unit: String,
+    /// Pairs of a value in the new unit and the same value in [`Self::unit`]
+    ///
+    /// At least 2 are needed and both values have to be increasing.
+    pub values: Vec<(f64, f64)>,
+}
+
 #[cfg(feature = "bundled_units")]
 impl UnitsFile {
     /// Get the bundled units file
@@ -347,4 +391,158 @@ impl UnitsFile {
         static FILE: Lazy<UnitsFile> = Lazy::new(|| toml::from_str(TEXT).unwrap());
         FILE.clone()
     }
+
+    /// Keep only the units of the given quantities
+    ///
+    /// Named amounts, fractions and extensions that refer to a removed unit
+    /// are removed too.
+    pub(crate) fn retain_quantities(&mut self, quantities: &[PhysicalQuantity]) {
+        self.quantity.retain(|g| quantities.contains(&g.quantity));
+
+        let mut known = std::collections::HashSet::new();
+        for group in &self.quantity {
+            let entries: Vec<&UnitEntry> = match &group.units {
+                None => vec![],
+                Some(Units::Unified(units)) => units.iter().collect(),
+                Some(Units::BySystem {
+                    metric,
+                    imperial,
+                    unspecified,
+                }) => metric.iter().chain(imperial).chain(unspecified).collect(),
+            };
+            for entry in entries {
+                known.extend(
+                    entry
+                        .names
+                        .iter()
+                        .chain(&entry.symbols)
+                        .chain(&entry.aliases)
+                        .map(|n| n.to_string()),
+                );
+                if !entry.expand_si {
+                    continue;
+                }
+                let Some(si) = &self.si else { continue };
+                for (prefixes, base) in [
+                    (&si.prefixes, &entry.names),
+                    (&si.symbol_prefixes, &entry.symbols),
+                ] {
+                    for prefix in prefixes.iter().flat_map(|p| p.values().flatten()) {
+                        known.extend(base.iter().map(|n| format!("{prefix}{n}")));
+                    }
+                }
+            }
+        }
+
+        self.named_amounts.retain(|_, a| known.contains(&a.unit));
+        if let Some(fractions) = &mut self.fractions {
+            fractions.quantity.retain(|q, _| quantities.contains(q));
+            fractions.unit.retain(|u, _| known.contains(u));
+        }
+        if let Some(extend) = &mut self.extend {
+            extend.units.retain(|u, _| known.contains(u));
+        }
+    }
+}
+
+impl UnitsFile {
+    /// Get a small units file with only metric units
+    ///
+    /// It has grams, liters and meters, with SI prefixes, seconds, minutes,
+    /// hours and celsius. Unlike [`UnitsFile::bundled`], this is always
+    /// available.
+    pub fn minimal() -> Self {
+        fn entry(names: &[&str], symbols: &[&str], ratio: f64, expand_si: bool) -> UnitEntry {
+            UnitEntry {
+                names: names.iter().map(|&n| n.into()).collect(),
+                symbols: symbols.iter().map(|&s| s.into()).collect(),
+                aliases: vec![],
+                ratio,
+                difference: 0.0,
+                points: None,
+                expand_si,
+            }
+        }
+        fn group(
+            quantity: PhysicalQuantity,
+            best: &[&str],
+            metric: Vec<UnitEntry>,
+            unspecified: Vec<UnitEntry>,
+        ) -> QuantityGroup {
+            QuantityGroup {
+                quantity,
+                best: Some(BestUnits::Unified(
+                    best.iter().map(|&b| b.to_string()).collect(),
+                )),
+                units: Some(Units::BySystem {
+                    metric,
+                    imperial: vec![],
+                    unspecified,
+                }),
+            }
+        }
+        fn prefixes(list: [&str; 6]) -> EnumMap<SIPrefix, Vec<String>> {
+            EnumMap::from_array(list.map(|p| vec![p.to_string()]))
+        }
+
+        let celsius = UnitEntry {
+            difference: 273.15,
+            ..entry(&["celsius"], &["°C", "ºC", "℃", "C"], 1.0, false)
+        };
+
+        Self {
+            default_system: Some(System::Metric),
+            si: Some(SI {
+                prefixes: Some(prefixes([
+                    "kilo", "hecto", "deca", "deci", "centi", "milli",
+                ])),
+                symbol_prefixes: Some(prefixes(["k", "h", "da", "d", "c", "m"])),
+                precedence: Precedence::default(),
+            }),
+            fractions: None,
+            extend: None,
+            named_amounts: HashMap::new(),
+            quantity: vec![
+                group(
+                    PhysicalQuantity::Volume,
+                    &["ml", "l"],
+                    vec![entry(
+                        &["liter", "liters", "litre", "litres"],
+                        &["l", "L"],
+                        1.0,
+                        true,
+                    )],
+                    vec![],
+                ),
+                group(
+                    PhysicalQuantity::Length,
+                    &["cm", "mm", "m"],
+                    vec![entry(
+                        &["meter", "meters", "metre", "metres"],
+                        &["m"],
+                        1.0,
+                        true,
+                    )],
+                    vec![],
+                ),
+                group(
+                    PhysicalQuantity::Mass,
+                    &["mg", "g", "kg"],
+                    vec![entry(&["gram", "grams"], &["g"], 1.0, true)],
+                    vec![],
+                ),
+                group(
+                    PhysicalQuantity::Time,
+                    &["s", "h", "min"],
+                    vec![],
+                    vec![
+                        entry(&["second", "seconds"], &["s", "sec"], 1.0, false),
+                        entry(&["minute", "minutes"], &["min"], 60.0, false),
+                        entry(&["hour", "hours"], &["h"], 3600.0, false),
+                    ],
+                ),
+                group(PhysicalQuantity::Temperature, &["C"], vec![celsius], vec![]),
+            ],
+        }
+    }
 }
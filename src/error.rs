@@ -1,6 +1,8 @@
 //! Error type, formatting and utilities.
 
-use std::{borrow::Cow, panic::RefUnwindSafe};
+use std::{borrow::Cow, collections::BTreeMap, panic::RefUnwindSafe};
+
+use serde::Serialize;
 
 use crate::Span;
 
@@ -23,8 +25,12 @@ pub type CowStr = Cow<'static, str>;
 /// A label is a pair of a code location and an optional hint at that location
 pub type Label = (Span, Option<CowStr>);
 
+/// Related information is a secondary location, usually somewhere else in
+/// the recipe, with a message explaining its relation with the diagnostic
+pub type RelatedInfo = (Span, CowStr);
+
 /// A diagnostic of source code
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 #[non_exhaustive]
 pub struct SourceDiag {
     /// If the diagnostic is an error or warning
@@ -34,6 +40,7 @@ pub struct SourceDiag {
     /// Report message describing the problem
     pub message: CowStr,
     /// Lower level error that produced the problem, if any
+    #[serde(skip)]
     source: Option<std::sync::Arc<dyn std::error::Error + Send + Sync + RefUnwindSafe + 'static>>,
     /// Spans of the code that helps the user find the error
     ///
@@ -44,6 +51,21 @@ pub struct SourceDiag {
     ///
     /// It should be ordered from high to low importance.
     pub hints: Vec<CowStr>,
+    /// Related information and suggested fix
+    ///
+    /// Most diagnostics have neither, so it's boxed to keep the results with
+    /// a diagnostic as the error small.
+    #[serde(flatten)]
+    extra: Option<Box<DiagExtra>>,
+}
+
+/// Less common parts of a [`SourceDiag`]
+#[derive(Debug, Clone, Default, Serialize)]
+struct DiagExtra {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related: Vec<RelatedInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suggestion: Option<(Span, String)>,
 }
 
 impl std::fmt::Display for SourceDiag {
@@ -61,6 +83,10 @@ impl RichError for SourceDiag {
         self.hints.as_slice().into()
     }
 
+    fn related(&self) -> Cow<'_, [RelatedInfo]> {
+        self.related_info().into()
+    }
+
     fn severity(&self) -> Severity {
         self.severity
     }
@@ -90,6 +116,7 @@ impl SourceDiag {
             message: message.into(),
             labels: vec![label],
             hints: vec![],
+            extra: None,
             source: None,
             stage,
         }
@@ -102,6 +129,7 @@ impl SourceDiag {
             message: message.into(),
             labels: vec![label],
             hints: vec![],
+            extra: None,
             source: None,
             stage,
         }
@@ -118,6 +146,7 @@ impl SourceDiag {
             source: None,
             labels: vec![],
             hints: vec![],
+            extra: None,
         }
     }
 
@@ -152,6 +181,65 @@ impl SourceDiag {
         self.hints.push(hint.into());
         self
     }
+
+    /// Adds new related information
+    pub(crate) fn related(mut self, span: Span, message: impl Into<CowStr>) -> Self {
+        self.add_related(span, message);
+        self
+    }
+    /// Adds new related information
+    pub(crate) fn add_related(&mut self, span: Span, message: impl Into<CowStr>) -> &mut Self {
+        self.extra
+            .get_or_insert_with(Default::default)
+            .related
+            .push((span, message.into()));
+        self
+    }
+    /// Other locations involved in the problem
+    ///
+    /// Unlike [`Self::labels`], these are not part of the problem itself, like
+    /// the previous definition of a duplicate entry.
+    pub fn related_info(&self) -> &[RelatedInfo] {
+        self.extra.as_ref().map_or(&[], |e| e.related.as_slice())
+    }
+    /// Sets the suggested fix
+    ///
+    /// Replacing the text at `span` with `replacement` should fix the problem.
+    /// It replaces the previous suggestion, if any, so tools can offer their
+    /// own fix for a diagnostic.
+    ///
+    /// ```
+    /// # use cooklang::CooklangParser;
+    /// let input = "Add @salt{1%}.";
+    /// let report = CooklangParser::default().parse(input).into_report();
+    /// let warning = report
+    ///     .into_vec()
+    ///     .into_iter()
+    ///     .find(|w| w.suggestion().is_some())
+    ///     .unwrap();
+    /// let (span, text) = warning.suggestion().unwrap();
+    /// assert_eq!(text, "");
+    /// // suggest a unit instead of removing the separator
+    /// let warning = warning.with_suggestion(span, "%g");
+    /// assert_eq!(warning.suggestion(), Some((span, "%g")));
+    /// ```
+    pub fn with_suggestion(mut self, span: Span, replacement: impl Into<String>) -> Self {
+        self.extra.get_or_insert_with(Default::default).suggestion =
+            Some((span, replacement.into()));
+        self
+    }
+    /// Suggested fix for the problem, if any
+    ///
+    /// It's a location and the text to replace it with, so editors can offer
+    /// it as a quick fix. An empty text means removing the location.
+    pub fn suggestion(&self) -> Option<(Span, &str)> {
+        self.extra
+            .as_ref()?
+            .suggestion
+            .as_ref()
+            .map(|(span, text)| (*span, text.as_str()))
+    }
+
     /// Sets the error source
     ///
     /// This is where [`std::error::Error::source`] get's the information
@@ -165,7 +253,8 @@ impl SourceDiag {
 }
 
 /// Diagnostic severity
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub enum Severity {
     /// Fatal error
     Error,
@@ -174,7 +263,8 @@ pub enum Severity {
 }
 
 /// Stage where the diagnostic origined
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub enum Stage {
     /// Parse stage
     Parse,
@@ -227,6 +317,25 @@ impl SourceReport {
         self.severity = severity;
     }
 
+    /// Moves all the locations `offset` bytes forward
+    ///
+    /// Used when a part of a larger input is parsed on its own.
+    pub(crate) fn shift_spans(&mut self, offset: usize) {
+        for diag in &mut self.buf {
+            for (span, _) in &mut diag.labels {
+                *span = span.shift(offset);
+            }
+            if let Some(extra) = &mut diag.extra {
+                for (span, _) in &mut extra.related {
+                    *span = span.shift(offset);
+                }
+                if let Some((span, _)) = &mut extra.suggestion {
+                    *span = span.shift(offset);
+                }
+            }
+        }
+    }
+
     /// Returns the severity of this report.
     ///
     /// - `None` means any severity.
@@ -326,6 +435,182 @@ impl SourceReport {
     pub fn eprint(&self, file_name: &str, source_code: &str, color: bool) -> std::io::Result<()> {
         self.write(file_name, source_code, color, &mut std::io::stderr().lock())
     }
+
+    /// Groups the diagnostics by line
+    ///
+    /// `source_code` has to be the input the report comes from. Lines start
+    /// at 1 and the line of a diagnostic is the start of its first label.
+    /// Diagnostics without a location are in line 0.
+    ///
+    /// ```
+    /// let input = "Add @salt{1/0%g}.\n\nAdd @pepper{2/0}.";
+    /// let report = cooklang::parse(input).into_report();
+    /// let lines = report.by_line(input);
+    /// assert!(lines[&1][0].message.starts_with("Division by zero"));
+    /// assert!(lines[&3][0].message.starts_with("Division by zero"));
+    /// ```
+    pub fn by_line(&self, source_code: &str) -> BTreeMap<usize, Vec<&SourceDiag>> {
+        let lines = LineIndex::new(source_code);
+        let mut grouped: BTreeMap<usize, Vec<&SourceDiag>> = BTreeMap::new();
+        for diag in self.iter() {
+            let line = diag
+                .labels
+                .first()
+                .map(|(span, _)| lines.line(span.start()) + 1)
+                .unwrap_or(0);
+            grouped.entry(line).or_default().push(diag);
+        }
+        grouped
+    }
+
+    /// Convert the report to Language Server Protocol diagnostics
+    ///
+    /// `source_code` has to be the input the report comes from. The location
+    /// of each diagnostic is its first label. Hints are added to the message.
+    ///
+    /// ```
+    /// let input = "Add @salt{1/0%g}.";
+    /// let report = cooklang::parse(input).into_report();
+    /// let diags = report.to_lsp_diagnostics(input);
+    /// let diag = diags.iter().find(|d| d.message.starts_with("Division by zero")).unwrap();
+    /// assert_eq!(diag.range.start.character, 10);
+    /// assert_eq!(diag.range.end.character, 13);
+    /// ```
+    #[cfg(feature = "lsp")]
+    pub fn to_lsp_diagnostics(&self, source_code: &str) -> Vec<LspDiagnostic> {
+        let lines = LineIndex::new(source_code);
+        self.iter()
+            .map(|diag| {
+                let range = diag
+                    .labels
+                    .first()
+                    .map(|(span, _)| lines.range(*span))
+                    .unwrap_or_default();
+                let mut message = diag.message.to_string();
+                for hint in &diag.hints {
+                    message.push_str("\nhint: ");
+                    message.push_str(hint);
+                }
+                LspDiagnostic {
+                    range,
+                    severity: diag.severity.into(),
+                    source: "cooklang",
+                    message,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Diagnostic in the shape of the Language Server Protocol `Diagnostic`
+///
+/// Created with [`SourceReport::to_lsp_diagnostics`].
+#[cfg(feature = "lsp")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LspDiagnostic {
+    /// Location of the problem
+    pub range: LspRange,
+    /// Error or warning
+    pub severity: LspSeverity,
+    /// Always `cooklang`
+    pub source: &'static str,
+    /// Message, with the hints in new lines
+    pub message: String,
+}
+
+/// Start and end position in a document
+#[cfg(feature = "lsp")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LspRange {
+    pub start: LspPosition,
+    /// Exclusive
+    pub end: LspPosition,
+}
+
+/// Position in a document
+///
+/// Both are 0 based and the character is in UTF-16 code units, the LSP
+/// default.
+#[cfg(feature = "lsp")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LspPosition {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// LSP diagnostic severity, serialized as its number
+#[cfg(feature = "lsp")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LspSeverity {
+    Error = 1,
+    Warning = 2,
+}
+
+#[cfg(feature = "lsp")]
+impl From<Severity> for LspSeverity {
+    fn from(value: Severity) -> Self {
+        match value {
+            Severity::Error => Self::Error,
+            Severity::Warning => Self::Warning,
+        }
+    }
+}
+
+#[cfg(feature = "lsp")]
+impl Serialize for LspSeverity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+/// Maps byte offsets of a source to lines and positions
+struct LineIndex<'a> {
+    #[cfg_attr(not(feature = "lsp"), allow(dead_code))]
+    source: &'a str,
+    /// Byte offset where each line starts
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(source: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { source, starts }
+    }
+
+    /// 0 based line of an offset, out of bounds is the last line
+    fn line(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&s| s <= offset) - 1
+    }
+
+    #[cfg(feature = "lsp")]
+    fn position(&self, offset: usize) -> LspPosition {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.line(offset);
+        let character = self.source[self.starts[line]..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum::<usize>();
+        LspPosition {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+
+    #[cfg(feature = "lsp")]
+    fn range(&self, span: Span) -> LspRange {
+        LspRange {
+            start: self.position(span.start()),
+            end: self.position(span.end()),
+        }
+    }
 }
 
 impl std::fmt::Display for SourceReport {
@@ -438,6 +723,9 @@ pub trait RichError: std::error::Error {
     fn hints(&self) -> Cow<[CowStr]> {
         Cow::Borrowed(&[])
     }
+    fn related(&self) -> Cow<'_, [RelatedInfo]> {
+        Cow::Borrowed(&[])
+    }
     fn severity(&self) -> Severity {
         Severity::Error
     }
@@ -502,6 +790,7 @@ fn build_report<'a>(
     }
 
     let mut c = ColorGenerator::new();
+    let n_labels = labels.len();
     r.add_labels(labels.into_iter().enumerate().map(|(order, (span, text))| {
         let mut l = Label::new(span)
             .with_order(order as i32)
@@ -512,6 +801,15 @@ fn build_report<'a>(
         l
     }));
 
+    // related information is shown as labels after the regular ones
+    let related = err.related();
+    r.add_labels(related.iter().enumerate().map(|(order, (span, text))| {
+        Label::new(span.to_chars_span(src_code, file_name).range())
+            .with_order((n_labels + order) as i32)
+            .with_color(c.next())
+            .with_message(text)
+    }));
+
     let hints = err.hints();
     let mut hints = hints.iter();
 
@@ -571,3 +869,83 @@ where
         Self::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn by_line() {
+        let input = "Add @salt{1/0%g}.\n\nFirst @pepper{2/0} then @oil{3/0}.\r\nLast.";
+        let report = crate::parse(input).into_report();
+        let lines = report.by_line(input);
+        let errors = |line| {
+            lines[&line]
+                .iter()
+                .filter(|d| d.message.starts_with("Division by zero"))
+                .count()
+        };
+        assert_eq!(errors(1), 1);
+        assert_eq!(errors(3), 2);
+        assert!(!lines.contains_key(&2));
+        assert!(!lines.contains_key(&4));
+
+        let mut report = SourceReport::empty();
+        report.push(SourceDiag::unlabeled(
+            "no location",
+            Severity::Warning,
+            Stage::Analysis,
+        ));
+        assert_eq!(report.by_line(input)[&0].len(), 1);
+    }
+
+    #[cfg(feature = "lsp")]
+    #[test]
+    fn lsp_positions() {
+        let lines = LineIndex::new("first\nsécond 🧂\r\nthird");
+        let pos = |line, character| LspPosition { line, character };
+        assert_eq!(lines.position(0), pos(0, 0));
+        assert_eq!(lines.position(5), pos(0, 5));
+        assert_eq!(lines.position(6), pos(1, 0));
+        // é is 2 bytes but 1 UTF-16 unit
+        assert_eq!(lines.position(9), pos(1, 2));
+        // 🧂 is 4 bytes and 2 UTF-16 units
+        assert_eq!(lines.position(18), pos(1, 9));
+        assert_eq!(lines.position(20), pos(2, 0));
+        // out of bounds is the end
+        assert_eq!(lines.position(100), pos(2, 5));
+    }
+
+    #[cfg(feature = "lsp")]
+    #[test]
+    fn lsp_diagnostics() {
+        let input = "Some text.\n\nAdd @salt{1/0%g}.\n";
+        let report = crate::parse(input).into_report();
+        let diags = report.to_lsp_diagnostics(input);
+        let diag = diags
+            .iter()
+            .find(|d| d.message.starts_with("Division by zero"))
+            .unwrap();
+        assert_eq!(diag.severity, LspSeverity::Error);
+        assert_eq!(
+            diag.range.start,
+            LspPosition {
+                line: 2,
+                character: 10
+            }
+        );
+        assert_eq!(
+            diag.range.end,
+            LspPosition {
+                line: 2,
+                character: 13
+            }
+        );
+        assert!(diag.message.contains("\nhint: "));
+
+        let json = serde_json::to_value(diag).unwrap();
+        assert_eq!(json["severity"], 1);
+        assert_eq!(json["source"], "cooklang");
+        assert_eq!(json["range"]["start"]["line"], 2);
+    }
+}
@@ -0,0 +1,302 @@
+//! YAML front matter support
+//!
+//! A recipe can start with a YAML block between `---` lines with metadata
+//! instead of (or in addition to) `>>` lines:
+//!
+//! ```cooklang
+//! ---
+//! servings: 4
+//! tags: [vegan, quick]
+//! ---
+//! Mix @flour{200%g} with @water{100%ml}.
+//! ```
+
+use std::str::FromStr;
+
+use crate::{
+    error::{label, PassResult, SourceDiag, SourceReport, Stage},
+    metadata::{IndexMap, Metadata, SpecialKey},
+    span::Span,
+    Converter, Extensions,
+};
+
+/// Location of the front matter in the input
+struct FrontMatter<'i> {
+    /// The whole block, including the delimiters
+    span: Span,
+    /// YAML source between the delimiters
+    yaml: &'i str,
+    /// Offset of the YAML source in the input
+    yaml_offset: usize,
+}
+
+/// Runs `parse` without the front matter of `input`, if any, and merges it
+/// into the metadata of the output
+///
+/// The front matter is replaced with whitespace before parsing, so all the
+/// locations still refer to the original input.
+pub(crate) fn parse_with_front_matter<T>(
+    input: &str,
+    extensions: Extensions,
+    converter: &Converter,
+    parse: impl FnOnce(&str) -> PassResult<T>,
+    metadata: impl FnOnce(&mut T) -> &mut Metadata,
+) -> PassResult<T> {
+    let Some(front_matter) = FrontMatter::find(input) else {
+        return parse(input);
+    };
+
+    let blanked = front_matter.blank(input);
+    let (mut output, mut report) = parse(&blanked).into_tuple();
+    let entries = front_matter.entries(&mut report);
+    if let Some(output) = &mut output {
+        front_matter.merge(
+            entries,
+            metadata(output),
+            extensions,
+            converter,
+            &mut report,
+        );
+    }
+    PassResult::new(output, report)
+}
+
+impl<'i> FrontMatter<'i> {
+    fn find(input: &'i str) -> Option<Self> {
+        let first_line_end = input.find('\n')?;
+        if input[..first_line_end].trim_end() != "---" {
+            return None;
+        }
+        let yaml_offset = first_line_end + 1;
+        let mut offset = yaml_offset;
+        for line in input[yaml_offset..].split_inclusive('\n') {
+            if line.trim_end() == "---" {
+                return Some(Self {
+                    span: Span::new(0, offset + line.len()),
+                    yaml: &input[yaml_offset..offset],
+                    yaml_offset,
+                });
+            }
+            offset += line.len();
+        }
+        // not closed, so it's not front matter
+        None
+    }
+
+    /// Replaces the front matter with spaces, keeping the newlines so line
+    /// numbers don't change
+    fn blank(&self, input: &str) -> String {
+        let end = self.span.end();
+        let mut blanked = String::with_capacity(input.len());
+        blanked.extend(
+            input[..end]
+                .bytes()
+                .map(|b| if b == b'\n' { '\n' } else { ' ' }),
+        );
+        blanked.push_str(&input[end..]);
+        blanked
+    }
+
+    /// Parses the YAML into key/value pairs
+    ///
+    /// Lists are joined with commas, so `tags: [a, b]` is the same as
+    /// `>> tags: a, b`.
+    fn entries(&self, report: &mut SourceReport) -> Vec<(String, String)> {
+        if self.yaml.trim().is_empty() {
+            return Vec::new();
+        }
+        let mapping: serde_yaml::Mapping = match serde_yaml::from_str(self.yaml) {
+            Ok(mapping) => mapping,
+            Err(err) => {
+                report.error(
+                    SourceDiag::error(
+                        "Invalid YAML front matter",
+                        label!(self.span, "expected key/value pairs"),
+                        Stage::Parse,
+                    )
+                    .hint(err.to_string()),
+                );
+                return Vec::new();
+            }
+        };
+
+        let mut entries = Vec::with_capacity(mapping.len());
+        for (key, value) in mapping {
+            let Some(key) = scalar_to_string(key) else {
+                report.warn(
+                    SourceDiag::warning(
+                        "Unsupported front matter key",
+                        label!(self.span),
+                        Stage::Parse,
+                    )
+                    .hint("Keys must be text"),
+                );
+                continue;
+            };
+            match value_to_string(value) {
+                Some(value) => entries.push((key, value)),
+                None => report.warn(
+                    SourceDiag::warning(
+                        format!("Unsupported front matter value for key '{key}'"),
+                        label!(self.key_span(&key)),
+                        Stage::Parse,
+                    )
+                    .hint("Only text, numbers and lists are supported"),
+                ),
+            }
+        }
+        entries
+    }
+
+    /// Inserts the entries before the ones from the recipe
+    ///
+    /// If a key is also in a metadata line of the recipe, that value is kept.
+    fn merge(
+        &self,
+        entries: Vec<(String, String)>,
+        metadata: &mut Metadata,
+        extensions: Extensions,
+        converter: &Converter,
+        report: &mut SourceReport,
+    ) {
+        let mut map = IndexMap::with_capacity(entries.len() + metadata.map.len());
+        for (key, value) in entries {
+            if metadata.map.contains_key(&key) {
+                report.warn(
+                    SourceDiag::warning(
+                        format!("Metadata key '{key}' is in the front matter and in the recipe"),
+                        label!(self.key_span(&key), "ignored value"),
+                        Stage::Analysis,
+                    )
+                    .hint("The value from the metadata line (`>>`) is used"),
+                );
+                continue;
+            }
+
+            if let Ok(sp_key) = SpecialKey::from_str(&key) {
+                // always parse servings
+                if sp_key == SpecialKey::Servings
+                    || extensions.contains(Extensions::SPECIAL_METADATA)
+                {
+                    if let Err(err) = metadata.insert_special(sp_key, value.clone(), converter) {
+                        report.warn(
+                            SourceDiag::warning(
+                                format!("Unsupported value for special key: '{key}'"),
+                                label!(self.key_span(&key)),
+                                Stage::Analysis,
+                            )
+                            .hint("It will be a regular metadata entry")
+                            .set_source(err),
+                        );
+                    }
+                }
+            }
+            map.insert(key, value);
+        }
+        for (key, value) in std::mem::take(&mut metadata.map) {
+            map.insert(key, value);
+        }
+        metadata.map = map;
+    }
+
+    /// Location of a top level key in the YAML source, or the whole block if
+    /// not found
+    fn key_span(&self, key: &str) -> Span {
+        let mut offset = self.yaml_offset;
+        for line in self.yaml.split_inclusive('\n') {
+            if line
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with(':'))
+            {
+                return Span::new(offset, offset + key.len());
+            }
+            offset += line.len();
+        }
+        self.span
+    }
+}
+
+fn scalar_to_string(value: serde_yaml::Value) -> Option<String> {
+    use serde_yaml::Value;
+    match value {
+        Value::Null => Some(String::new()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s),
+        Value::Sequence(_) | Value::Mapping(_) | Value::Tagged(_) => None,
+    }
+}
+
+fn value_to_string(value: serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::Sequence(items) => items
+            .into_iter()
+            .map(scalar_to_string)
+            .collect::<Option<Vec<_>>>()
+            .map(|items| items.join(", ")),
+        other => scalar_to_string(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::CooklangParser;
+
+    fn parser() -> CooklangParser {
+        CooklangParser::extended()
+    }
+
+    #[test]
+    fn front_matter() {
+        let input = "---\ntitle: Bread\nservings: 4\ntags: [easy, vegan]\n---\n>> source: me\nMix @flour{500%g}.\n";
+        let r = parser().parse(input);
+        assert!(r.report().is_empty());
+        let r = r.unwrap_output();
+        let keys = r.metadata.map.keys().collect::<Vec<_>>();
+        assert_eq!(keys, ["title", "servings", "tags", "source"]);
+        assert_eq!(r.metadata.map["tags"], "easy, vegan");
+        assert_eq!(r.metadata.servings(), Some([4].as_slice()));
+        assert_eq!(r.metadata.tags().unwrap(), ["easy", "vegan"]);
+        assert_eq!(r.ingredients.len(), 1);
+    }
+
+    #[test]
+    fn without_front_matter() {
+        let input = ">> servings: 4\nMix @flour{500%g}.\n";
+        let r = parser().parse(input);
+        assert!(r.report().is_empty());
+        let r = r.unwrap_output();
+        assert_eq!(r.metadata.map.len(), 1);
+        assert_eq!(r.ingredients.len(), 1);
+
+        // not closed
+        let input = "---\nservings: 4\nMix @flour{500%g}.\n";
+        let r = parser().parse(input).unwrap_output();
+        assert!(r.metadata.map.is_empty());
+        assert_eq!(r.ingredients.len(), 1);
+    }
+
+    #[test]
+    fn conflict() {
+        let input = "---\nservings: 4\n---\n>> servings: 2\nMix @flour{500%g}.\n";
+        let r = parser().parse(input);
+        let warnings = r.report().warnings().collect::<Vec<_>>();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].labels[0].0, crate::Span::new(4, 12));
+        let r = r.unwrap_output();
+        assert_eq!(r.metadata.servings(), Some([2].as_slice()));
+    }
+
+    #[test]
+    fn invalid_yaml() {
+        let input = "---\n[not, a, map]\n---\nMix @flour{500%g}.\n";
+        let r = parser().parse(input);
+        assert_eq!(r.report().errors().count(), 1);
+        assert_eq!(r.output().unwrap().ingredients.len(), 1);
+
+        let input = "---\ntitle: Bread\nnested:\n  a: 1\n---\nMix.\n";
+        let r = parser().parse_metadata(input);
+        assert_eq!(r.report().warnings().count(), 1);
+        assert_eq!(r.unwrap_output().map["title"], "Bread");
+    }
+}
@@ -6,7 +6,7 @@ use serde::Serialize;
 
 use crate::{
     aisle::AisleConf,
-    convert::Converter,
+    convert::{ConversionCache, Converter},
     model::Ingredient,
     quantity::{GroupedQuantity, GroupedValue},
     scale::ScaleOutcome,
@@ -77,11 +77,13 @@ impl ScaledRecipe {
     pub fn group_ingredients<'a>(&'a self, converter: &Converter) -> Vec<GroupedIngredient<'a>> {
         let mut list = Vec::new();
         let data = self.scaled_data();
+        let mut cache = ConversionCache::new();
         for (index, ingredient) in self.ingredients.iter().enumerate() {
             if !ingredient.relation.is_definition() {
                 continue;
             }
-            let grouped = ingredient.group_quantities(&self.ingredients, converter);
+            let grouped =
+                ingredient.group_quantities_cached(&self.ingredients, converter, &mut cache);
             let outcome: Option<ScaleOutcome> = data
                 .as_ref()
                 .map(|data| {
@@ -156,25 +158,55 @@ impl IngredientList {
     /// for each one.
     ///
     /// Only ingredients for which [`should_be_listed`](crate::Modifiers::should_be_listed)
-    /// is true are added.
+    /// is true are added. The quantities from [optional](crate::model::Section::optional)
+    /// sections are skipped, so an ingredient only used in them is not added.
+    ///
+    /// The quantities are the [`shopping_quantity`](crate::model::Ingredient::shopping_quantity)
+    /// of the ingredients, so a purchase quantity is used instead of the recipe
+    /// one.
     ///
     /// Scaling outcomes are ignored, but logged with [tracing] if they are an
     /// error.
     ///
     /// Ingredients are listed based on their [`display_name`](crate::model::Ingredient::display_name).
     pub fn add_recipe(&mut self, recipe: &ScaledRecipe, converter: &Converter) {
+        let optional = recipe.optional_ingredients();
         for entry in recipe.group_ingredients(converter) {
             let GroupedIngredient {
+                index,
                 ingredient,
-                quantity,
+                mut quantity,
                 outcome,
-                ..
             } = entry;
 
             if !ingredient.modifiers().should_be_listed() {
                 continue;
             }
 
+            let all_indices = std::iter::once(index)
+                .chain(ingredient.relation.referenced_from().iter().copied())
+                .collect::<Vec<_>>();
+            if all_indices.iter().all(|&i| optional[i]) {
+                continue;
+            }
+            let purchase = all_indices
+                .iter()
+                .any(|&i| recipe.ingredients[i].purchase_quantity.is_some());
+            if purchase || all_indices.iter().any(|&i| optional[i]) {
+                // group again only the quantities to buy outside optional sections
+                quantity = GroupedQuantity::default();
+                for &i in all_indices.iter().filter(|&&i| !optional[i]) {
+                    let igr = &recipe.ingredients[i];
+                    if igr.quantity_inherited {
+                        continue;
+                    }
+                    if let Some(q) = igr.shopping_quantity() {
+                        quantity.add(q, converter);
+                    }
+                }
+                let _ = quantity.fit(converter);
+            }
+
             if let Some(ScaleOutcome::Error(err)) = outcome {
                 tracing::error!("Error scaling ingredient: {err}");
             }
@@ -238,6 +270,50 @@ impl IntoIterator for IngredientList {
     }
 }
 
+/// Total quantities of the ingredients of many recipes
+///
+/// Ingredients with the same name are merged, and their quantities added
+/// like in [`IngredientList::add_recipe`]. If `case_sensitive` is false,
+/// `Flour` and `flour` are the same ingredient, named as it first appears.
+///
+/// The result is sorted by name.
+///
+/// ```
+/// # use cooklang::{CooklangParser, ingredient_list::aggregate_ingredients};
+/// let parser = CooklangParser::default();
+/// let bread = parser.parse("@Flour{500%g} @water{300%ml}").unwrap_output().default_scale();
+/// let pizza = parser.parse("@flour{1%kg}").unwrap_output().default_scale();
+/// let total = aggregate_ingredients(&[&bread, &pizza], parser.converter(), false);
+/// assert_eq!(total[0].0, "Flour");
+/// assert_eq!(total[0].1.to_string(), "1.5 kg");
+/// ```
+pub fn aggregate_ingredients(
+    recipes: &[&ScaledRecipe],
+    converter: &Converter,
+    case_sensitive: bool,
+) -> Vec<(String, GroupedQuantity)> {
+    let mut all: BTreeMap<String, (String, GroupedQuantity)> = BTreeMap::new();
+    for recipe in recipes {
+        for (name, quantity) in IngredientList::from_recipe(recipe, converter) {
+            let key = if case_sensitive {
+                name.clone()
+            } else {
+                name.to_lowercase()
+            };
+            all.entry(key)
+                .or_insert_with(|| (name, GroupedQuantity::empty()))
+                .1
+                .merge(&quantity, converter);
+        }
+    }
+    all.into_values()
+        .map(|(name, mut quantity)| {
+            let _ = quantity.fit(converter);
+            (name, quantity)
+        })
+        .collect()
+}
+
 /// Ingredient list split into categories.
 ///
 /// Obtained from [`IngredientList::categorize`].
@@ -41,6 +41,13 @@ impl<'a> Cursor<'a> {
         self.chars.clone().next().unwrap_or(EOF_CHAR)
     }
 
+    /// Peeks the second next char. If none, [`EOF_CHAR`] is returned.
+    pub(crate) fn second(&self) -> char {
+        let mut chars = self.chars.clone();
+        chars.next();
+        chars.next().unwrap_or(EOF_CHAR)
+    }
+
     /// Checks if there is more input to consume.
     pub(crate) fn is_eof(&self) -> bool {
         self.chars.as_str().is_empty()
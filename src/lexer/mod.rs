@@ -16,7 +16,12 @@ impl Token {
     }
 }
 
+/// Kind of a [`Token`](crate::Token)
+///
+/// Tokens are the same with every [`Extensions`](crate::Extensions), it's the
+/// parser who gives them a meaning.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum TokenKind {
     /// ">>"
     MetadataStart,
@@ -93,7 +98,7 @@ fn is_word_char(c: char) -> bool {
         c if c.is_alphabetic() => true, // quick return true
         ' ' | '\n' | '\r' | '\t' | '0'..='9' | '.' => false, // common chars that break a word
         '>' | ':' | '@' | '#' | '~' | '?' | '+' | '-' | '/' | '*' | '&' | '|' | '=' | '%' | '{'
-        | '}' | '(' | ')' => false,
+        | '}' | '(' | ')' | '⁄' => false,
         c if c.is_separator_space() || c.is_punctuation() => false, // '\' (escape) is punctuation and not common, so I will leave it here
         _ => true,
     }
@@ -108,6 +113,10 @@ impl Cursor<'_> {
 
         let token_kind = match current {
             '\\' => {
+                // a line continuation escapes the whole line break
+                if self.first() == '\r' && self.second() == '\n' {
+                    self.bump(); // '\r'
+                }
                 self.bump(); // any
                 TokenKind::Escaped
             }
@@ -149,6 +158,8 @@ impl Cursor<'_> {
             '(' => TokenKind::OpenParen,
             ')' => TokenKind::CloseParen,
             '.' => TokenKind::Dot,
+            // unicode fraction slash, it's only a `/` in numbers
+            '⁄' => TokenKind::Punctuation,
 
             c if is_whitespace(c) => self.whitespace(),
             c if c.is_punctuation() => TokenKind::Punctuation,
@@ -348,6 +359,8 @@ mod tests {
         t!("two words", vec![Word, Whitespace, Word]);
         t!("two words", vec![Word, Whitespace, Word]); // unicode whitespace U+2009
         t!("word\nanother", vec![Word, Newline, Word]);
+        t!("word \\\nanother", vec![Word, Whitespace, Escaped, Word]);
+        t!("word \\\r\nanother", vec![Word, Whitespace, Escaped, Word]);
 
         // composed emojis more than one char
         t!("👩🏿‍🔬", vec![Word]);
@@ -368,6 +381,8 @@ mod tests {
         t!("0.03", vec![Int, Dot, ZeroInt]);
         t!("{.3}", vec![OpenBrace, Dot, Int, CloseBrace]);
         t!("14.", vec![Int, Dot]);
+        t!("1⁄2", vec![Int, Punctuation, Int]);
+        t!("a⁄b", vec![Word, Punctuation, Word]);
     }
 
     #[test]
@@ -24,7 +24,7 @@
 //! # use cooklang::{CooklangParser, Converter, Extensions};
 //! // Create a parser
 //! // (this is the default configuration)
-//! let parser = CooklangParser::new(Extensions::all(), Converter::default());
+//! let parser = CooklangParser::new(Extensions::default(), Converter::default());
 //! # assert_eq!(parser, CooklangParser::default());
 //! ```
 //!
@@ -56,7 +56,7 @@ pub mod _extensions {
 
 #[cfg(doc)]
 pub mod _features {
-    //! This lib has 2 features, both enabled by default:
+    //! This lib has 2 features enabled by default:
     //! - `bundled_units`. Includes a units file with the most common units for
     //!   recipes in English. These units are available to load when you want
     //!   without the need to read a file. The default
@@ -64,16 +64,27 @@ pub mod _features {
     //!   enabled. [This is the bundled file](https://github.com/cooklang/cooklang-rs/blob/main/units.toml)
     //!
     //! - `aisle`. Enables the [`aisle`](crate::aisle) module.
+    //!
+    //! And some optional features:
+    //! - `spans`. Adds the source location to ingredients, cookware, timers
+    //!   and steps in the final [`Recipe`](crate::model::Recipe). Useful to
+    //!   map them back to the source, for example, in editors.
+    //! - `front_matter`. Recipes can start with a YAML block between `---`
+    //!   lines with metadata. It's merged with the `>>` metadata lines.
+    //! - `lsp`. Converts reports to diagnostics of the Language Server
+    //!   Protocol with `SourceReport::to_lsp_diagnostics`.
 }
 
 #[cfg(feature = "aisle")]
 pub mod aisle;
 pub mod analysis;
 pub mod ast;
+pub mod collection;
 pub mod convert;
 pub mod error;
 pub mod ingredient_list;
 pub mod located;
+pub mod markdown;
 pub mod metadata;
 pub mod model;
 pub mod parser;
@@ -82,19 +93,21 @@ pub mod scale;
 pub mod span;
 pub mod text;
 
+#[cfg(feature = "front_matter")]
+mod front_matter;
 mod lexer;
 
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 
-use error::PassResult;
+use error::{PassResult, SourceReport};
 
 pub use analysis::ParseOptions;
 pub use convert::Converter;
 pub use located::Located;
 pub use metadata::Metadata;
 pub use model::*;
-pub use parser::Modifiers;
+pub use parser::{Modifiers, ParserConfig, Token, TokenKind};
 pub use quantity::{
     GroupedQuantity, Quantity, QuantityUnit, ScalableQuantity, ScalableValue, ScaledQuantity,
     UnitInfo, Value,
@@ -108,7 +121,10 @@ bitflags! {
     /// This allows to enable or disable the extensions. See [extensions](_extensions)
     /// for a detailed explanation of all of them.
     ///
-    /// [`Extensions::default`] enables all extensions.
+    /// [`Extensions::default`] enables all extensions except the ones that
+    /// change how regular text is parsed, [`Extensions::STEP_GROUPS`],
+    /// [`Extensions::SUBSTITUTES`], [`Extensions::PREPARATIONS`],
+    /// [`Extensions::CATEGORIES`] and [`Extensions::LEADING_UNITS`].
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
     pub struct Extensions: u32 {
         /// Steps separation is a blank line, not a line break. This may break
@@ -140,12 +156,80 @@ bitflags! {
         const INTERMEDIATE_PREPARATIONS = 1 << 11 | Self::COMPONENT_MODIFIERS.bits();
         /// Enables special metadata key parsing
         const SPECIAL_METADATA = 1 << 12;
+        /// Allow each end of a range to have its own unit `@igr{1 cup - 250 ml}`
+        ///
+        /// This only works when [`Self::ADVANCED_UNITS`] and
+        /// [`Self::RANGE_VALUES`] are also enabled.
+        const RANGE_UNITS = 1 << 13;
+        /// Ingredients can have alternative quantities `@igr{1 cup / 120 g}`
+        const ALTERNATIVE_QUANTITIES = 1 << 14;
+        /// Ingredients can be tagged with a variant `@[vegan]butter{}`. See
+        /// [`Recipe::select_variant`].
+        const VARIANTS = 1 << 15;
+        /// Ingredients can have a category `@flour{}[baking]`. See
+        /// [`Recipe::group_by_category`].
+        ///
+        /// This is not enabled by [`Extensions::default`].
+        const CATEGORIES = 1 << 16;
+        /// A note starting with `or` is a list of substitutes,
+        /// `@butter{1%cup}(or margarine)`.
+        ///
+        /// This is not enabled by [`Extensions::default`].
+        const SUBSTITUTES = 1 << 17;
+        /// Currency symbols can be the unit before or right after the value,
+        /// like `@bread{$5}` or `@bread{5€}`. See [`LEADING_UNIT_SYMBOLS`](crate::parser::LEADING_UNIT_SYMBOLS).
+        ///
+        /// This is not enabled by [`Extensions::default`].
+        const LEADING_UNITS = 1 << 18;
+        /// Ingredient quantities can be a count of units of the same size,
+        /// like `@eggs{2 x 50%g}`
+        const PER_UNIT_QUANTITIES = 1 << 19;
+        /// Numeric quantity values can be added, like `@flour{1+1/2%cups}`
+        const VALUE_SUMS = 1 << 20;
+        /// Timers can be written as an ISO 8601 duration, like `~{PT1H30M}`
+        const ISO_DURATIONS = 1 << 21;
+        /// Ingredients can have nutrition facts after the quantity,
+        /// `@sugar{10%g}{cal=40}`
+        const NUTRITION = 1 << 22;
+        /// A single line ending with `:`, like `Make the sauce:`, groups the
+        /// steps after it. See [`Section::step_groups`](crate::model::Section::step_groups).
+        ///
+        /// This is not enabled by [`Extensions::default`].
+        const STEP_GROUPS = 1 << 23;
+        /// Quantity values can have a tolerance, like `@flour{100±5%g}` or
+        /// `@flour{100+-5%g}`
+        const TOLERANCES = 1 << 24;
+        /// Recipe references can point to one of the recipe ingredients, like
+        /// `@@./sauce#tomato{}`
+        const REFERENCE_FRAGMENTS = 1 << 25;
+        /// Ingredient names can end with a preparation after a comma, like
+        /// `@onion, chopped{1}`
+        ///
+        /// This is not enabled by [`Extensions::default`].
+        const PREPARATIONS = 1 << 26;
+        /// Quantity values can be a named variable, like `@flour{$amount%g}`.
+        /// See [`Recipe::bind`](crate::Recipe::bind).
+        const VARIABLES = 1 << 27;
+        /// Ingredients can reference a recipe of a collection by its title,
+        /// like `@{{Tomato Sauce}}{200%g}`. See [`TitleIndex`](crate::collection::TitleIndex).
+        const TITLE_REFERENCES = 1 << 28;
+        /// Ingredients can have a quantity to buy after a `;`, like
+        /// `@milk{2%cups; 1%l}`. See [`Ingredient::purchase_quantity`](crate::model::Ingredient::purchase_quantity).
+        const PURCHASE_QUANTITIES = 1 << 29;
+        /// Numeric quantity values can be expressions with parentheses and
+        /// `*`, like `@flour{(1+1/2)*2%cups}`
+        ///
+        /// This extension also enables [`Self::VALUE_SUMS`].
+        const VALUE_EXPRESSIONS = 1 << 30 | Self::VALUE_SUMS.bits();
 
         /// Enables a subset of extensions to maximize compatibility with other
         /// cooklang parsers.
         ///
-        /// Currently it enables all the extensions except
-        /// [`Self::MULTILINE_STEPS`] and [`Self::TIMER_REQUIRES_TIME`].
+        /// Currently it enables [`Self::COMPONENT_MODIFIERS`],
+        /// [`Self::COMPONENT_NOTE`], [`Self::COMPONENT_ALIAS`],
+        /// [`Self::SECTIONS`], [`Self::ADVANCED_UNITS`], [`Self::MODES`],
+        /// [`Self::TEMPERATURE`], [`Self::TEXT_STEPS`], [`Self::RANGE_VALUES`],
+        /// [`Self::INTERMEDIATE_PREPARATIONS`] and [`Self::SPECIAL_METADATA`].
         ///
         /// **ADDITIONS TO THE EXTENSIONS THIS ENABLES WILL NOT BE CONSIDERED A BREAKING CHANGE**
         const COMPAT = Self::COMPONENT_MODIFIERS.bits()
@@ -163,9 +247,17 @@ bitflags! {
 }
 
 impl Default for Extensions {
-    /// Enables all extensions
+    /// Enables all extensions except [`Self::STEP_GROUPS`],
+    /// [`Self::SUBSTITUTES`], [`Self::PREPARATIONS`], [`Self::CATEGORIES`] and
+    /// [`Self::LEADING_UNITS`]
     fn default() -> Self {
-        Self::all()
+        Self::all().difference(
+            Self::STEP_GROUPS
+                | Self::SUBSTITUTES
+                | Self::PREPARATIONS
+                | Self::CATEGORIES
+                | Self::LEADING_UNITS,
+        )
     }
 }
 
@@ -174,7 +266,7 @@ impl Default for Extensions {
 /// Instantiating this takes time and the first parse may take longer. So
 /// you may want to create only one and reuse it.
 ///
-/// The default parser enables all extensions.
+/// The default parser enables the [`Extensions::default`] extensions.
 ///
 /// The 2 main methods are [`CooklangParser::parse`] and [`CooklangParser::parse_metadata`].
 ///
@@ -183,6 +275,7 @@ impl Default for Extensions {
 pub struct CooklangParser {
     extensions: Extensions,
     converter: Converter,
+    config: ParserConfig,
 }
 
 pub type RecipeResult = PassResult<ScalableRecipe>;
@@ -196,9 +289,25 @@ impl CooklangParser {
         Self {
             extensions,
             converter,
+            config: ParserConfig::default(),
         }
     }
 
+    /// Sets the runtime configuration
+    ///
+    /// The flags named like an extension change the enabled extensions. See
+    /// [`ParserConfig`].
+    pub fn with_config(mut self, config: ParserConfig) -> Self {
+        self.extensions = config.apply(self.extensions);
+        self.config = config;
+        self
+    }
+
+    /// Get the parser runtime configuration
+    pub fn config(&self) -> &ParserConfig {
+        &self.config
+    }
+
     /// Creates a new extended parser
     ///
     /// This enables all extensions and uses the bunlded units.
@@ -234,14 +343,150 @@ impl CooklangParser {
     /// Same as [`Self::parse`] but with aditional options
     #[tracing::instrument(level = "debug", name = "parse", skip_all, fields(len = input.len()))]
     pub fn parse_with_options(&self, input: &str, options: ParseOptions) -> RecipeResult {
-        let mut parser = parser::PullParser::new(input, self.extensions);
-        analysis::parse_events(
-            &mut parser,
-            input,
-            self.extensions,
-            &self.converter,
-            options,
-        )
+        let parse = |input: &str| {
+            let mut parser = parser::PullParser::new(input, self.extensions)
+                .with_config(&self.config)
+                .with_options(&options);
+            analysis::parse_events(
+                &mut parser,
+                input,
+                self.extensions,
+                &self.converter,
+                options,
+            )
+        };
+        #[cfg(feature = "front_matter")]
+        let parse = |input: &str| {
+            front_matter::parse_with_front_matter(
+                input,
+                self.extensions,
+                &self.converter,
+                parse,
+                |r| &mut r.metadata,
+            )
+        };
+        parse(input)
+    }
+
+    /// Parse many recipes from the same input
+    ///
+    /// The recipes are separated by lines with only the `delimiter`, like
+    /// `===`. Every recipe is parsed on its own, with its own diagnostics,
+    /// when the iterator reaches it, so the whole file is never parsed at
+    /// once. Parts with only whitespace are skipped.
+    ///
+    /// The locations in the reports, and the spans of the recipes with the
+    /// `spans` feature, refer to the whole `input`.
+    ///
+    /// ```
+    /// # use cooklang::CooklangParser;
+    /// let parser = CooklangParser::default();
+    /// let input = "Boil @water{}.\n===\nToast @bread{}.\n";
+    /// let recipes = parser
+    ///     .parse_many(input, "===")
+    ///     .map(|r| r.into_result().unwrap().0)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(recipes.len(), 2);
+    /// assert_eq!(recipes[1].ingredients[0].name, "bread");
+    /// ```
+    pub fn parse_many<'a>(
+        &'a self,
+        input: &'a str,
+        delimiter: &'a str,
+    ) -> impl Iterator<Item = RecipeResult> + 'a {
+        split_recipes(input, delimiter).map(move |(offset, recipe)| {
+            let (output, mut report) = self.parse(recipe).into_tuple();
+            report.shift_spans(offset);
+            #[cfg(feature = "spans")]
+            let output = output.map(|mut r| {
+                r.shift_spans(offset);
+                r
+            });
+            PassResult::new(output, report)
+        })
+    }
+
+    /// Parse a recipe and always get a recipe, even with errors
+    ///
+    /// [`Self::parse`] does not return a recipe if there is any error in the
+    /// syntax. This is useful for editors, to show a preview while the
+    /// recipe is being written.
+    ///
+    /// The parser recovers from errors in each block: a metadata line, a
+    /// section header or a step. Invalid components are kept as text in
+    /// their step and the rest of the recipe is parsed as usual. The errors
+    /// are in the [`SourceReport`].
+    ///
+    /// ```
+    /// # use cooklang::CooklangParser;
+    /// let parser = CooklangParser::default();
+    /// let input = "Mix @flour{200%g} and @&(?)water{}.\n\nBake.";
+    /// assert!(parser.parse(input).output().is_none());
+    /// let (recipe, report) = parser.parse_partial(input);
+    /// assert!(report.has_errors());
+    /// assert_eq!(recipe.sections[0].content.len(), 2);
+    /// ```
+    pub fn parse_partial(&self, input: &str) -> (ScalableRecipe, SourceReport) {
+        self.parse_partial_with_options(input, ParseOptions::default())
+    }
+
+    /// Same as [`Self::parse_partial`] but with aditional options
+    #[tracing::instrument(level = "debug", name = "partial", skip_all, fields(len = input.len()))]
+    pub fn parse_partial_with_options(
+        &self,
+        input: &str,
+        options: ParseOptions,
+    ) -> (ScalableRecipe, SourceReport) {
+        let parse = |input: &str| {
+            let mut parser = parser::PullParser::new(input, self.extensions)
+                .with_config(&self.config)
+                .with_options(&options);
+            analysis::parse_events_partial(
+                &mut parser,
+                input,
+                self.extensions,
+                &self.converter,
+                options,
+            )
+        };
+        #[cfg(feature = "front_matter")]
+        let parse = |input: &str| {
+            front_matter::parse_with_front_matter(
+                input,
+                self.extensions,
+                &self.converter,
+                parse,
+                |r| &mut r.metadata,
+            )
+        };
+        let (recipe, report) = parse(input).into_tuple();
+        (recipe.expect("partial parse always has output"), report)
+    }
+
+    /// Parse a recipe and get only the section with the given name
+    ///
+    /// The full recipe is parsed, so all components and references are
+    /// resolved in the context of the whole recipe. The indices in the
+    /// section [`Item`]s point to the components of the full recipe, so they
+    /// may refer to components defined in other sections.
+    ///
+    /// The output is [`None`] if no section has that name. If more than one
+    /// section has the same name, the first one is returned.
+    ///
+    /// ```
+    /// # use cooklang::CooklangParser;
+    /// let parser = CooklangParser::default();
+    /// let input = "= Dough\n@flour\n= Sauce\n@tomato";
+    /// let section = parser.parse_section(input, "Sauce").unwrap_output();
+    /// assert_eq!(section.unwrap().name.as_deref(), Some("Sauce"));
+    /// ```
+    pub fn parse_section(&self, input: &str, section_name: &str) -> PassResult<Option<Section>> {
+        self.parse(input).map(|recipe| {
+            recipe
+                .sections
+                .into_iter()
+                .find(|s| s.name.as_deref() == Some(section_name))
+        })
     }
 
     /// Parse only the metadata of a recipe
@@ -258,22 +503,37 @@ impl CooklangParser {
         input: &str,
         options: ParseOptions,
     ) -> MetadataResult {
-        let parser = parser::PullParser::new(input, self.extensions);
-        let meta_events = parser.into_meta_iter();
-        analysis::parse_events(
-            meta_events,
-            input,
-            Extensions::SPECIAL_METADATA & self.extensions,
-            &self.converter,
-            options,
-        )
-        .map(|c| c.metadata)
+        let parse = |input: &str| {
+            let parser = parser::PullParser::new(input, self.extensions)
+                .with_config(&self.config)
+                .with_options(&options);
+            let meta_events = parser.into_meta_iter();
+            analysis::parse_events(
+                meta_events,
+                input,
+                Extensions::SPECIAL_METADATA & self.extensions,
+                &self.converter,
+                options,
+            )
+            .map(|c| c.metadata)
+        };
+        #[cfg(feature = "front_matter")]
+        let parse = |input: &str| {
+            front_matter::parse_with_front_matter(
+                input,
+                self.extensions,
+                &self.converter,
+                parse,
+                |m| m,
+            )
+        };
+        parse(input)
     }
 }
 
 /// Parse a recipe with a default [`CooklangParser`]. Avoid calling this in a loop.
 ///
-/// The default parser enables all extensions.
+/// The default parser enables the [`Extensions::default`] extensions.
 ///
 /// **IMPORTANT:** If you are going to parse more than one recipe you may want
 /// to only create one [`CooklangParser`] and reuse it. Every time this function
@@ -283,3 +543,57 @@ impl CooklangParser {
 pub fn parse(input: &str) -> RecipeResult {
     CooklangParser::default().parse(input)
 }
+
+/// Splits `input` in the lines with only `delimiter`
+///
+/// Yields the offset of every part in the input and the part itself.
+fn split_recipes<'a>(input: &'a str, delimiter: &'a str) -> impl Iterator<Item = (usize, &'a str)> {
+    let mut start = 0;
+    let mut lines = input.split_inclusive('\n');
+    let mut offset = 0;
+    std::iter::from_fn(move || loop {
+        let Some(line) = lines.next() else {
+            if start >= input.len() {
+                return None;
+            }
+            let part = (start, &input[start..]);
+            start = input.len();
+            return Some(part);
+        };
+        let line_start = offset;
+        offset += line.len();
+        if line.trim() == delimiter {
+            let part = (start, &input[start..line_start]);
+            start = offset;
+            return Some(part);
+        }
+    })
+    .filter(|(_, part)| !part.trim().is_empty())
+}
+
+/// Split a recipe into tokens without parsing it
+///
+/// This is fast and never fails, so it's useful for syntax highlighting.
+/// Every byte of the input is in exactly one token and the spans are byte
+/// offsets. The tokens don't depend on the [`Extensions`], for example, `@`
+/// is always [`TokenKind::At`] even if it's escaped later by the parser.
+///
+/// ```
+/// use cooklang::{tokens, TokenKind};
+/// let input = "Add @salt{}";
+/// let kinds = tokens(input).map(|t| t.kind).collect::<Vec<_>>();
+/// assert_eq!(
+///     kinds,
+///     [
+///         TokenKind::Word,
+///         TokenKind::Whitespace,
+///         TokenKind::At,
+///         TokenKind::Word,
+///         TokenKind::OpenBrace,
+///         TokenKind::CloseBrace,
+///     ]
+/// );
+/// ```
+pub fn tokens(input: &str) -> impl Iterator<Item = Token> + '_ {
+    parser::TokenStream::new(input)
+}
@@ -0,0 +1,276 @@
+//! Render a recipe as Markdown
+
+use crate::{
+    convert::Converter,
+    metadata::{NameAndUrl, RecipeTime},
+    model::{Content, Item, Section},
+    ScaledRecipe,
+};
+
+impl ScaledRecipe {
+    /// Renders the recipe as a conventional Markdown recipe
+    ///
+    /// The output has:
+    /// - A header with the title, description and some metadata.
+    /// - An ingredients list with the total quantities, grouped with
+    ///   [`ScaledRecipe::group_ingredients`].
+    /// - A cookware list.
+    /// - Every section as a `##` heading with a numbered list of steps. The
+    ///   components in the steps are rendered with their quantities.
+    ///
+    /// Text from the recipe is not escaped.
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, Converter};
+    /// let parser = CooklangParser::default();
+    /// let recipe = parser
+    ///     .parse(">> title: Toast\nToast @bread{2%slices}.")
+    ///     .unwrap_output()
+    ///     .default_scale();
+    /// let md = recipe.to_markdown(parser.converter());
+    /// assert!(md.starts_with("# Toast\n"));
+    /// assert!(md.contains("- bread: 2 slices\n"));
+    /// assert!(md.contains("1. Toast bread (2 slices).\n"));
+    /// ```
+    pub fn to_markdown(&self, converter: &Converter) -> String {
+        let mut blocks = Vec::new();
+        header(self, &mut blocks);
+        ingredients(self, converter, &mut blocks);
+        cookware(self, &mut blocks);
+        for section in &self.sections {
+            section_blocks(self, section, &mut blocks);
+        }
+
+        let mut out = blocks.join("\n\n");
+        out.push('\n');
+        out
+    }
+}
+
+fn header(recipe: &ScaledRecipe, blocks: &mut Vec<String>) {
+    let meta = &recipe.metadata;
+    if let Some(title) = meta.get("title") {
+        blocks.push(format!("# {title}"));
+    }
+    if let Some(description) = meta.description() {
+        blocks.push(description.to_string());
+    }
+
+    let mut list = Vec::new();
+    if let Some(servings) = meta.servings() {
+        let servings = servings
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        list.push(format!("- Servings: {servings}"));
+    }
+    match meta.time() {
+        Some(RecipeTime::Total(total)) => list.push(format!("- Time: {total} min")),
+        Some(RecipeTime::Composed {
+            prep_time,
+            cook_time,
+        }) => {
+            if let Some(prep) = prep_time {
+                list.push(format!("- Prep time: {prep} min"));
+            }
+            if let Some(cook) = cook_time {
+                list.push(format!("- Cook time: {cook} min"));
+            }
+        }
+        None => {}
+    }
+    if let Some(author) = meta.author().and_then(name_and_url) {
+        list.push(format!("- Author: {author}"));
+    }
+    if let Some(source) = meta.source().and_then(name_and_url) {
+        list.push(format!("- Source: {source}"));
+    }
+    if let Some(tags) = meta.tags().filter(|t| !t.is_empty()) {
+        list.push(format!("- Tags: {}", tags.join(", ")));
+    }
+    if !list.is_empty() {
+        blocks.push(list.join("\n"));
+    }
+}
+
+fn name_and_url(value: &NameAndUrl) -> Option<String> {
+    match (value.name(), value.url()) {
+        (Some(name), Some(url)) => Some(format!("[{name}]({url})")),
+        (Some(name), None) => Some(name.to_string()),
+        (None, Some(url)) => Some(format!("<{url}>")),
+        (None, None) => None,
+    }
+}
+
+fn ingredients(recipe: &ScaledRecipe, converter: &Converter, blocks: &mut Vec<String>) {
+    let mut list = Vec::new();
+    for entry in recipe.group_ingredients(converter) {
+        let igr = entry.ingredient;
+        if !igr.modifiers().should_be_listed() {
+            continue;
+        }
+        let mut line = format!("- {}", igr.display_name());
+        if !entry.quantity.is_empty() {
+            line += &format!(": {}", entry.quantity);
+        }
+        if igr.modifiers().is_optional() {
+            line += " (optional)";
+        }
+        if let Some(note) = &igr.note {
+            line += &format!(" ({note})");
+        }
+        list.push(line);
+    }
+    if !list.is_empty() {
+        blocks.push("## Ingredients".to_string());
+        blocks.push(list.join("\n"));
+    }
+}
+
+fn cookware(recipe: &ScaledRecipe, blocks: &mut Vec<String>) {
+    let mut list = Vec::new();
+    for entry in recipe.group_cookware() {
+        let item = entry.cookware;
+        if !item.modifiers().should_be_listed() {
+            continue;
+        }
+        let mut line = format!("- {}", item.display_name());
+        if !entry.amount.is_empty() {
+            line += &format!(": {}", entry.amount);
+        }
+        if item.modifiers().is_optional() {
+            line += " (optional)";
+        }
+        if let Some(note) = &item.note {
+            line += &format!(" ({note})");
+        }
+        list.push(line);
+    }
+    if !list.is_empty() {
+        blocks.push("## Cookware".to_string());
+        blocks.push(list.join("\n"));
+    }
+}
+
+fn section_blocks(recipe: &ScaledRecipe, section: &Section, blocks: &mut Vec<String>) {
+    if section.name.is_none() && section.content.is_empty() {
+        return;
+    }
+    let mut heading = format!("## {}", section.name.as_deref().unwrap_or("Steps"));
+    if section.optional {
+        heading += " (optional)";
+    }
+    blocks.push(heading);
+
+    // consecutive steps are a single list
+    let mut steps: Vec<String> = Vec::new();
+    for content in &section.content {
+        match content {
+            Content::Step(step) => {
+                let mut line = format!("{}. ", step.number);
+                for item in &step.items {
+                    render_item(recipe, item, &mut line);
+                }
+                steps.push(line);
+            }
+            Content::Text(text) => {
+                if !steps.is_empty() {
+                    blocks.push(steps.join("\n"));
+                    steps.clear();
+                }
+                blocks.push(text.clone());
+            }
+        }
+    }
+    if !steps.is_empty() {
+        blocks.push(steps.join("\n"));
+    }
+}
+
+fn render_item(recipe: &ScaledRecipe, item: &Item, out: &mut String) {
+    match item {
+        Item::Text { value } => out.push_str(value),
+        Item::Ingredient { index } => {
+            let igr = &recipe.ingredients[*index];
+            out.push_str(&igr.display_name());
+            if let Some(q) = &igr.quantity {
+                out.push_str(&format!(" ({q})"));
+            }
+        }
+        Item::Cookware { index } => {
+            let item = &recipe.cookware[*index];
+            out.push_str(item.display_name());
+        }
+        Item::Timer { index } => {
+            let timer = &recipe.timers[*index];
+            match (&timer.name, &timer.quantity) {
+                (Some(name), Some(q)) => out.push_str(&format!("{name} ({q})")),
+                (Some(name), None) => out.push_str(name),
+                (None, Some(q)) => out.push_str(&q.to_string()),
+                (None, None) => {}
+            }
+        }
+        Item::InlineQuantity { index } => {
+            let q = &recipe.inline_quantities[*index];
+            out.push_str(&q.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::CooklangParser;
+    use indoc::indoc;
+
+    #[test]
+    fn markdown_snapshot() {
+        let input = indoc! {"
+            >> title: Pancakes
+            >> description: Fluffy and quick.
+            >> servings: 2
+            >> tags: breakfast, sweet
+
+            Mix @flour{200%g}, @milk{300%ml} and @eggs{2}(beaten) in a #bowl.
+
+            Let it rest for ~{10%min}.
+
+            = Cooking
+
+            Cook in a #pan with some @butter until golden.
+        "};
+        let parser = CooklangParser::default();
+        let recipe = parser.parse(input).unwrap_output().default_scale();
+        let md = recipe.to_markdown(parser.converter());
+        let expected = indoc! {"
+            # Pancakes
+
+            Fluffy and quick.
+
+            - Servings: 2
+            - Tags: breakfast, sweet
+
+            ## Ingredients
+
+            - flour: 200 g
+            - milk: 300 ml
+            - eggs: 2 (beaten)
+            - butter
+
+            ## Cookware
+
+            - bowl
+            - pan
+
+            ## Steps
+
+            1. Mix flour (200 g), milk (300 ml) and eggs (2) in a bowl.
+            2. Let it rest for 10 min.
+
+            ## Cooking
+
+            1. Cook in a pan with some butter until golden.
+        "};
+        assert_eq!(md, expected);
+    }
+}
@@ -21,7 +21,11 @@ macro_rules! regex {
 pub(crate) use regex;
 
 use crate::{
-    convert::{ConvertError, ConvertTo, ConvertUnit, ConvertValue, PhysicalQuantity, UnknownUnit},
+    convert::{
+        ConvertError, ConvertTo, ConvertUnit, ConvertValue, PhysicalQuantity, System, UnknownUnit,
+    },
+    parser::parse_value_unit,
+    quantity::Value,
     Converter,
 };
 
@@ -39,6 +43,43 @@ pub struct Metadata {
     special: HashMap<SpecialKey, SpecialValue>,
     /// All the raw key/value pairs from the recipe
     pub map: IndexMap<String, String>,
+    /// Trailing comments of the entries in [`Self::map`]
+    ///
+    /// Only filled with [`ParseOptions::metadata_comments`](crate::analysis::ParseOptions::metadata_comments).
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    comments: IndexMap<String, String>,
+    /// All the values of the keys that appear more than once, in order
+    ///
+    /// The last one is the one in [`Self::map`].
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    repeated: IndexMap<String, Vec<String>>,
+}
+
+/// A raw metadata value with its comment
+///
+/// See [`Metadata::entry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetadataEntry<'a> {
+    /// Raw value, same as in [`Metadata::map`]
+    pub value: &'a str,
+    /// Trailing comment of the metadata line, if any
+    ///
+    /// ```cooklang
+    /// >> servings: 4 -- makes 2 big pizzas
+    /// ```
+    pub comment: Option<&'a str>,
+}
+
+/// What to do with a metadata key present in both recipes when merging
+///
+/// See [`Recipe::merge`](crate::model::Recipe::merge).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MetadataConflict {
+    /// Keep the value of the recipe being merged into
+    #[default]
+    Keep,
+    /// Use the value of the recipe being merged
+    Replace,
 }
 
 #[derive(
@@ -69,6 +110,9 @@ pub(crate) enum SpecialKey {
     #[strum(serialize = "cook_time", to_string = "cook time")]
     CookTime,
     Servings,
+    Yield,
+    Units,
+    System,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -78,6 +122,9 @@ enum SpecialValue {
     NameAndUrl(NameAndUrl),
     Time(RecipeTime),
     Servings(Vec<u32>),
+    UnitAliases(Vec<(String, String)>),
+    Yield(Yield),
+    System(System),
     String(String),
 }
 
@@ -148,6 +195,414 @@ impl Metadata {
             .get(&SpecialKey::Servings)
             .map(|v| unwrap_value!(Servings, v).as_slice())
     }
+
+    /// What the recipe makes, like `12 muffins`
+    ///
+    /// This is independent of the [servings](Self::servings).
+    pub fn yield_(&self) -> Option<&Yield> {
+        self.special
+            .get(&SpecialKey::Yield)
+            .map(|v| unwrap_value!(Yield, v))
+    }
+
+    /// Unit aliases declared in the recipe
+    ///
+    /// Pairs of an alias and the known unit it refers to, from an entry like
+    /// `>> units: tbsp=tablespoon, c=cup`. The quantities of the recipe after
+    /// the entry are parsed with these aliases.
+    pub fn unit_aliases(&self) -> Option<&[(String, String)]> {
+        self.special
+            .get(&SpecialKey::Units)
+            .map(|v| unwrap_value!(UnitAliases, v).as_slice())
+    }
+
+    /// Preferred unit system of the recipe, from `>> system: imperial`
+    ///
+    /// [`ScaledRecipe::convert`](crate::ScaledRecipe::convert) uses it when no
+    /// system is given.
+    pub fn system(&self) -> Option<System> {
+        self.special
+            .get(&SpecialKey::System)
+            .map(|v| *unwrap_value!(System, v))
+    }
+
+    /// Raw value of a key with its comment
+    ///
+    /// Comments are only kept with [`ParseOptions::metadata_comments`](crate::analysis::ParseOptions::metadata_comments),
+    /// otherwise [`MetadataEntry::comment`] is always `None`.
+    ///
+    /// ```
+    /// # use cooklang::{analysis::ParseOptions, CooklangParser};
+    /// let parser = CooklangParser::default();
+    /// let options = ParseOptions {
+    ///     metadata_comments: true,
+    ///     ..Default::default()
+    /// };
+    /// let r = parser
+    ///     .parse_with_options(">> servings: 4 -- two big pizzas", options)
+    ///     .unwrap_output();
+    /// let entry = r.metadata.entry("servings").unwrap();
+    /// assert_eq!(entry.value, "4");
+    /// assert_eq!(entry.comment, Some("two big pizzas"));
+    /// ```
+    pub fn entry(&self, key: &str) -> Option<MetadataEntry<'_>> {
+        let value = self.map.get(key)?;
+        Some(MetadataEntry {
+            value: value.as_str(),
+            comment: self.comments.get(key).map(String::as_str),
+        })
+    }
+
+    /// Get the raw value of a key
+    ///
+    /// If the key is repeated, this is the last value. See [`Self::all`].
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.map.get(key).map(String::as_str)
+    }
+
+    /// Get all the raw values of a key, in order
+    ///
+    /// Repeated keys are usually overwritten, but all their values are kept
+    /// here.
+    ///
+    /// ```
+    /// let r = cooklang::parse(">> tags: quick\n>> source: Mom\n>> tags: easy").unwrap_output();
+    /// assert_eq!(r.metadata.all("tags"), ["quick", "easy"]);
+    /// assert_eq!(r.metadata.get("tags"), Some("easy"));
+    /// assert_eq!(r.metadata.all("source"), ["Mom"]);
+    /// assert!(r.metadata.all("author").is_empty());
+    /// ```
+    pub fn all(&self, key: &str) -> &[String] {
+        if let Some(values) = self.repeated.get(key) {
+            return values;
+        }
+        self.map
+            .get(key)
+            .map(std::slice::from_ref)
+            .unwrap_or_default()
+    }
+
+    /// Inserts a raw value, keeping the previous ones for [`Self::all`]
+    pub(crate) fn insert_raw(&mut self, key: String, value: String) {
+        if let Some(prev) = self.map.get(&key) {
+            self.repeated
+                .entry(key.clone())
+                .or_insert_with(|| vec![prev.clone()])
+                .push(value.clone());
+        }
+        self.map.insert(key, value);
+    }
+
+    /// Checks if there are no entries
+    pub(crate) fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Iterate over all the raw entries with their comments, in order
+    pub fn entries(&self) -> impl Iterator<Item = (&str, MetadataEntry<'_>)> {
+        self.map.iter().map(|(key, value)| {
+            let entry = MetadataEntry {
+                value: value.as_str(),
+                comment: self.comments.get(key).map(String::as_str),
+            };
+            (key.as_str(), entry)
+        })
+    }
+
+    /// Sets or removes the comment of a key
+    pub(crate) fn set_comment(&mut self, key: &str, comment: Option<String>) {
+        match comment {
+            Some(comment) => {
+                self.comments.insert(key.to_string(), comment);
+            }
+            None => {
+                self.comments.shift_remove(key);
+            }
+        }
+    }
+
+    /// Adds the entries of `other`, resolving repeated keys with `conflict`
+    ///
+    /// The parsed special values and the comments go with their key.
+    pub(crate) fn merge(&mut self, mut other: Metadata, conflict: MetadataConflict) {
+        for (key, value) in other.map {
+            if self.map.contains_key(&key) && conflict == MetadataConflict::Keep {
+                continue;
+            }
+            let comment = other.comments.shift_remove(&key);
+            if let Ok(sp_key) = SpecialKey::from_str(&key) {
+                match other.special.remove(&sp_key) {
+                    Some(special) => {
+                        self.special.insert(sp_key, special);
+                    }
+                    None => {
+                        self.special.remove(&sp_key);
+                    }
+                }
+            }
+            self.set_comment(&key, comment);
+            match other.repeated.shift_remove(&key) {
+                Some(values) => {
+                    self.repeated.insert(key.clone(), values);
+                }
+                None => {
+                    self.repeated.shift_remove(&key);
+                }
+            }
+            self.map.insert(key, value);
+        }
+    }
+
+    /// Scales the parsed yield, the raw value in the map is not changed.
+    pub(crate) fn scale_yield(&mut self, factor: f64) {
+        if let Some(SpecialValue::Yield(y)) = self.special.get_mut(&SpecialKey::Yield) {
+            y.amount = match &y.amount {
+                Value::Number(n) => Value::Number((n.value() * factor).into()),
+                Value::Range { start, end } => Value::Range {
+                    start: (start.value() * factor).into(),
+                    end: (end.value() * factor).into(),
+                },
+                Value::Tolerant { center, tolerance } => Value::Tolerant {
+                    center: (center.value() * factor).into(),
+                    tolerance: (tolerance.value() * factor).into(),
+                },
+                Value::Text(_) | Value::Variable(_) => return,
+            };
+        }
+    }
+
+    /// Typed access to the known keys parsing the raw values
+    ///
+    /// See [`KnownMetadata`].
+    pub fn known(&self) -> KnownMetadata<'_> {
+        KnownMetadata { metadata: self }
+    }
+}
+
+/// Typed access to the known metadata keys
+///
+/// Unlike the methods in [`Metadata`], this parses the raw values in
+/// [`Metadata::map`] on every call, so it works even if the
+/// [`SPECIAL_METADATA`](crate::Extensions::SPECIAL_METADATA) extension is not
+/// enabled, and reports why a value is invalid.
+///
+/// Keys are normalized before comparing them, so `Prep-Time`, `prep_time`
+/// and `prep time` are the same key. Unknown keys are still accessible with
+/// [`KnownMetadata::get`] or in the raw [`Metadata::map`].
+///
+/// ```
+/// # use cooklang::{CooklangParser, Extensions, Converter};
+/// let parser = CooklangParser::new(Extensions::empty(), Converter::bundled());
+/// let r = parser.parse(">> Tags: vegan, quick\n>> Cook_Time: 1 hour 30 min").unwrap_output();
+/// let known = r.metadata.known();
+/// assert_eq!(known.tags(), ["vegan", "quick"]);
+/// let time = known.time(parser.converter()).unwrap().unwrap();
+/// assert_eq!(time.total(), 90);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct KnownMetadata<'a> {
+    metadata: &'a Metadata,
+}
+
+impl<'a> KnownMetadata<'a> {
+    /// Get the raw value of a key
+    ///
+    /// The key is normalized, so this also finds aliases of known keys, like
+    /// `tag` for `tags`.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.get_all(key).next()
+    }
+
+    fn get_all(&self, key: &str) -> impl Iterator<Item = &'a str> {
+        let key = normalize_key(key);
+        let special = SpecialKey::from_str(&key).ok();
+        let metadata: &'a Metadata = self.metadata;
+        metadata
+            .map
+            .iter()
+            .filter(move |(k, _)| {
+                let k = normalize_key(k);
+                k == key || (special.is_some() && SpecialKey::from_str(&k).ok() == special)
+            })
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Description of the recipe
+    pub fn description(&self) -> Option<&'a str> {
+        self.get(SpecialKey::Description.as_ref())
+    }
+
+    /// List of tags
+    ///
+    /// Tags are separated by commas. Empty and duplicate tags are removed.
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags = Vec::new();
+        for value in self.get_all(SpecialKey::Tags.as_ref()) {
+            parse_tags(value, &mut tags);
+        }
+        tags
+    }
+
+    /// Author
+    pub fn author(&self) -> Option<NameAndUrl> {
+        self.get(SpecialKey::Author.as_ref()).map(NameAndUrl::parse)
+    }
+
+    /// Source
+    pub fn source(&self) -> Option<NameAndUrl> {
+        self.get(SpecialKey::Source.as_ref()).map(NameAndUrl::parse)
+    }
+
+    /// Servings the recipe is made for
+    pub fn servings(&self) -> Option<Result<Vec<u32>, MetadataError>> {
+        self.get(SpecialKey::Servings.as_ref()).map(parse_servings)
+    }
+
+    /// What the recipe makes, like `12 muffins`
+    pub fn yield_(&self) -> Option<Result<Yield, MetadataError>> {
+        self.get(SpecialKey::Yield.as_ref()).map(Yield::parse)
+    }
+
+    /// Preferred unit system
+    pub fn system(&self) -> Option<Result<System, MetadataError>> {
+        self.get(SpecialKey::System.as_ref()).map(parse_system)
+    }
+
+    /// Time it takes to prepare/cook the recipe
+    ///
+    /// The `time` key takes precedence over `prep time` and `cook time`.
+    pub fn time(&self, converter: &Converter) -> Option<Result<RecipeTime, MetadataError>> {
+        if let Some(time) = self.get(SpecialKey::Time.as_ref()) {
+            return Some(
+                parse_time(time, converter)
+                    .map(RecipeTime::Total)
+                    .map_err(MetadataError::from),
+            );
+        }
+
+        let parse = |key: SpecialKey| {
+            self.get(key.as_ref())
+                .map(|t| parse_time(t, converter))
+                .transpose()
+        };
+        let prep_time = parse(SpecialKey::PrepTime);
+        let cook_time = parse(SpecialKey::CookTime);
+        let r = match (prep_time, cook_time) {
+            (Ok(None), Ok(None)) => return None,
+            (Ok(prep_time), Ok(cook_time)) => Ok(RecipeTime::Composed {
+                prep_time,
+                cook_time,
+            }),
+            (Err(e), _) | (_, Err(e)) => Err(e.into()),
+        };
+        Some(r)
+    }
+}
+
+/// Normalizes a metadata key
+///
+/// Lowercase, `_` and `-` as spaces and no repeated whitespace.
+fn normalize_key(key: &str) -> String {
+    key.split(|c: char| c.is_whitespace() || c == '_' || c == '-')
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn parse_tags(value: &str, tags: &mut Vec<String>) {
+    for tag in value.split(',') {
+        let tag = tag.trim().to_string();
+        // no empty
+        if tag.is_empty() {
+            continue;
+        }
+        // no duplicates
+        if tags.contains(&tag) {
+            continue;
+        }
+        // add tag
+        tags.push(tag);
+    }
+}
+
+fn parse_servings(value: &str) -> Result<Vec<u32>, MetadataError> {
+    let servings = value
+        .split('|')
+        .map(str::trim)
+        .map(str::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    let l = servings.len();
+    let dedup_l = {
+        let mut s = servings.clone();
+        s.sort_unstable();
+        s.dedup();
+        s.len()
+    };
+    if l != dedup_l {
+        return Err(MetadataError::DuplicateServings { servings });
+    }
+    Ok(servings)
+}
+
+fn parse_unit_aliases(
+    value: &str,
+    converter: &Converter,
+) -> Result<Vec<(String, String)>, MetadataError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (alias, unit) = entry
+                .split_once('=')
+                .map(|(alias, unit)| (alias.trim(), unit.trim()))
+                .filter(|(alias, unit)| !alias.is_empty() && !unit.is_empty())
+                .ok_or_else(|| MetadataError::InvalidUnitAlias {
+                    value: entry.to_string(),
+                })?;
+            if converter.find_unit(unit).is_none() {
+                return Err(UnknownUnit(unit.to_string()).into());
+            }
+            Ok((alias.to_string(), unit.to_string()))
+        })
+        .collect()
+}
+
+fn parse_system(value: &str) -> Result<System, MetadataError> {
+    System::from_str(&value.trim().to_lowercase()).map_err(|_| MetadataError::InvalidSystem {
+        value: value.to_string(),
+    })
+}
+
+/// What a recipe makes
+///
+/// Parsed from a numeric value and an optional unit, like `12 muffins`,
+/// `2 loaves` or `1-2 cakes`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Yield {
+    /// Amount, a number or a range
+    pub amount: Value,
+    /// What is made, `muffins` in `12 muffins`
+    pub unit: Option<String>,
+}
+
+impl Yield {
+    /// Parse a yield
+    ///
+    /// ```
+    /// # use cooklang::metadata::Yield;
+    /// let y = Yield::parse("12 muffins").unwrap();
+    /// assert_eq!(y.amount, 12.0.into());
+    /// assert_eq!(y.unit.as_deref(), Some("muffins"));
+    /// ```
+    pub fn parse(s: &str) -> Result<Self, MetadataError> {
+        let (amount, unit) = parse_value_unit(s).ok_or_else(|| MetadataError::InvalidYield {
+            value: s.to_string(),
+        })?;
+        Ok(Self { amount, unit })
+    }
 }
 
 /// Combination of name and URL.
@@ -197,19 +652,7 @@ impl Metadata {
                 } else {
                     Vec::new()
                 };
-                for tag in value.split(',') {
-                    let tag = tag.trim().to_string();
-                    // no empty
-                    if tag.is_empty() {
-                        continue;
-                    }
-                    // no duplicates
-                    if tags.contains(&tag) {
-                        continue;
-                    }
-                    // add tag
-                    tags.push(tag);
-                }
+                parse_tags(&value, &mut tags);
                 // add to the map
                 self.special.insert(key, SpecialValue::Tags(tags));
             }
@@ -259,24 +702,26 @@ impl Metadata {
                     .insert(SpecialKey::Time, SpecialValue::Time(time));
             }
             SpecialKey::Servings => {
-                let servings = value
-                    .split('|')
-                    .map(str::trim)
-                    .map(str::parse)
-                    .collect::<Result<Vec<_>, _>>()?;
-                let l = servings.len();
-                let dedup_l = {
-                    let mut s = servings.clone();
-                    s.sort_unstable();
-                    s.dedup();
-                    s.len()
-                };
-                if l != dedup_l {
-                    return Err(MetadataError::DuplicateServings { servings });
-                }
+                let servings = parse_servings(&value)?;
                 self.special
                     .insert(SpecialKey::Servings, SpecialValue::Servings(servings));
             }
+            SpecialKey::Yield => {
+                let y = Yield::parse(&value)?;
+                self.special.insert(key, SpecialValue::Yield(y));
+            }
+            SpecialKey::Units => {
+                // a value without aliases, like `metric`, is a regular entry
+                if !value.contains('=') {
+                    return Ok(());
+                }
+                let aliases = parse_unit_aliases(&value, converter)?;
+                self.special.insert(key, SpecialValue::UnitAliases(aliases));
+            }
+            SpecialKey::System => {
+                let system = parse_system(&value)?;
+                self.special.insert(key, SpecialValue::System(system));
+            }
         }
         Ok(())
     }
@@ -315,6 +760,116 @@ impl Metadata {
             .filter(|(key, _)| SpecialKey::from_str(key).is_err())
             .map(|(key, value)| (key.as_str(), value.as_str()))
     }
+
+    /// Sets the servings
+    ///
+    /// The raw value in [`Self::map`] is written like `2|4`, the same way it
+    /// is parsed, and the parsed value is updated. Fails if there are no
+    /// servings or a number is repeated, and nothing changes.
+    ///
+    /// ```
+    /// # use cooklang::metadata::Metadata;
+    /// let mut m = Metadata::default();
+    /// m.set_servings(&[2, 4]).unwrap();
+    /// assert_eq!(m.get("servings"), Some("2|4"));
+    /// assert_eq!(m.servings(), Some([2, 4].as_slice()));
+    /// assert!(m.set_servings(&[2, 2]).is_err());
+    /// ```
+    pub fn set_servings(&mut self, servings: &[u32]) -> Result<(), MetadataError> {
+        let value = servings
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join("|");
+        let servings = parse_servings(&value)?;
+        self.set_special_raw(SpecialKey::Servings, value);
+        self.special
+            .insert(SpecialKey::Servings, SpecialValue::Servings(servings));
+        Ok(())
+    }
+
+    /// Sets the time
+    ///
+    /// A [`RecipeTime::Total`] is written in the `time` key and a
+    /// [`RecipeTime::Composed`] in the `prep time` and `cook time` keys, in
+    /// minutes like `90 min`. The keys of the other form are removed, and the
+    /// parsed value is updated. Fails if the composed time has neither prep
+    /// nor cook time, and nothing changes.
+    ///
+    /// ```
+    /// # use cooklang::metadata::{Metadata, RecipeTime};
+    /// let mut m = Metadata::default();
+    /// m.set_time(RecipeTime::Composed {
+    ///     prep_time: Some(15),
+    ///     cook_time: Some(90),
+    /// })
+    /// .unwrap();
+    /// assert_eq!(m.get("prep time"), Some("15 min"));
+    /// assert_eq!(m.get("cook time"), Some("90 min"));
+    /// assert_eq!(m.time().unwrap().total(), 105);
+    /// ```
+    pub fn set_time(&mut self, time: RecipeTime) -> Result<(), MetadataError> {
+        let minutes = |m: u32| format!("{m} min");
+        match time {
+            RecipeTime::Total(total) => {
+                self.remove_special_raw(SpecialKey::PrepTime);
+                self.remove_special_raw(SpecialKey::CookTime);
+                self.set_special_raw(SpecialKey::Time, minutes(total));
+            }
+            RecipeTime::Composed {
+                prep_time,
+                cook_time,
+            } => {
+                if prep_time.is_none() && cook_time.is_none() {
+                    return Err(ParseTimeError::Empty.into());
+                }
+                self.remove_special_raw(SpecialKey::Time);
+                for (key, value) in [
+                    (SpecialKey::PrepTime, prep_time),
+                    (SpecialKey::CookTime, cook_time),
+                ] {
+                    match value {
+                        Some(m) => self.set_special_raw(key, minutes(m)),
+                        None => self.remove_special_raw(key),
+                    }
+                }
+            }
+        }
+        self.special
+            .insert(SpecialKey::Time, SpecialValue::Time(time));
+        Ok(())
+    }
+
+    /// Raw keys in the map that are `key` or one of its aliases
+    fn special_raw_keys(&self, key: SpecialKey) -> Vec<String> {
+        self.map
+            .keys()
+            .filter(|k| matches!(SpecialKey::from_str(k), Ok(k) if k == key))
+            .cloned()
+            .collect()
+    }
+
+    /// Removes the raw entries of a special key, with any alias
+    fn remove_special_raw(&mut self, key: SpecialKey) {
+        for k in self.special_raw_keys(key) {
+            self.map.shift_remove(&k);
+            self.comments.shift_remove(&k);
+            self.repeated.shift_remove(&k);
+        }
+    }
+
+    /// Sets the raw value of a special key, replacing any alias of it
+    fn set_special_raw(&mut self, key: SpecialKey, value: String) {
+        let name = key.to_string();
+        for k in self.special_raw_keys(key) {
+            if k != name {
+                self.map.shift_remove(&k);
+                self.comments.shift_remove(&k);
+            }
+            self.repeated.shift_remove(&k);
+        }
+        self.map.insert(name, value);
+    }
 }
 
 /// Returns minutes
@@ -334,8 +889,10 @@ fn parse_time(s: &str, converter: &Converter) -> Result<u32, ParseTimeError> {
     r
 }
 
+/// Error parsing a time value
 #[derive(Debug, thiserror::Error)]
-pub(crate) enum ParseTimeError {
+#[non_exhaustive]
+pub enum ParseTimeError {
     #[error("A value is missing a unit")]
     MissingUnit,
     #[error("Could not find minutes in the configuration")]
@@ -385,14 +942,23 @@ fn hard_coded_time_units(value: f64, unit: &str) -> Result<f64, ParseTimeError>
     Ok(minutes)
 }
 
+/// Converts a time value to minutes
+///
+/// An empty converter uses the common time units in English.
+pub(crate) fn time_to_minutes(
+    value: f64,
+    unit: &str,
+    converter: &Converter,
+) -> Result<f64, ParseTimeError> {
+    if converter.unit_count() == 0 {
+        hard_coded_time_units(value, unit)
+    } else {
+        dynamic_time_units(value, unit, converter)
+    }
+}
+
 fn parse_time_with_units(s: &str, converter: &Converter) -> Result<u32, ParseTimeError> {
-    let to_minutes = |value, unit| {
-        if converter.unit_count() == 0 {
-            hard_coded_time_units(value, unit)
-        } else {
-            dynamic_time_units(value, unit, converter)
-        }
-    };
+    let to_minutes = |value, unit| time_to_minutes(value, unit, converter);
 
     let mut total = 0.0;
     let mut parts = s.split_whitespace();
@@ -479,9 +1045,10 @@ impl RecipeTime {
     }
 }
 
+/// Error parsing a metadata value
 #[derive(Debug, Error)]
 #[non_exhaustive]
-pub(crate) enum MetadataError {
+pub enum MetadataError {
     #[error("Value is not an emoji: {value}")]
     NotEmoji { value: String },
     #[error(transparent)]
@@ -490,6 +1057,14 @@ pub(crate) enum MetadataError {
     DuplicateServings { servings: Vec<u32> },
     #[error(transparent)]
     ParseTimeError(#[from] ParseTimeError),
+    #[error("Invalid yield, expected a number and a unit: {value}")]
+    InvalidYield { value: String },
+    #[error("Invalid unit alias, expected 'alias=unit': {value}")]
+    InvalidUnitAlias { value: String },
+    #[error("Invalid unit system, expected 'metric' or 'imperial': {value}")]
+    InvalidSystem { value: String },
+    #[error(transparent)]
+    UnknownUnit(#[from] UnknownUnit),
 }
 
 #[cfg(test)]
@@ -540,6 +1115,9 @@ mod tests {
         t("cook time", SpecialKey::CookTime);
         t_alias("cook_time", SpecialKey::CookTime);
         t("servings", SpecialKey::Servings);
+        t("yield", SpecialKey::Yield);
+        t("units", SpecialKey::Units);
+        t("system", SpecialKey::System);
     }
 
     macro_rules! insert {
@@ -683,6 +1261,225 @@ mod tests {
         assert!(matches!(m.servings(), Some(_)));
     }
 
+    #[test]
+    fn repeated_keys() {
+        let mut m = Metadata::default();
+        m.insert_raw("tags".into(), "quick".into());
+        m.insert_raw("source".into(), "Mom".into());
+        m.insert_raw("tags".into(), "easy".into());
+        m.insert_raw("tags".into(), "vegan".into());
+        assert_eq!(m.get("tags"), Some("vegan"));
+        assert_eq!(m.all("tags"), ["quick", "easy", "vegan"]);
+        assert_eq!(m.all("source"), ["Mom"]);
+        assert!(m.all("other").is_empty());
+
+        let mut other = Metadata::default();
+        other.insert_raw("source".into(), "Grandma".into());
+        other.insert_raw("source".into(), "Aunt".into());
+        other.insert_raw("tags".into(), "dessert".into());
+        m.merge(other, MetadataConflict::Replace);
+        assert_eq!(m.all("source"), ["Grandma", "Aunt"]);
+        assert_eq!(m.all("tags"), ["dessert"]);
+    }
+
+    #[test]
+    fn setters() {
+        let converter = Converter::bundled();
+        let mut m = Metadata::default();
+        m.insert_raw("servings".into(), "1".into());
+        m.insert_raw("servings".into(), "3".into());
+        m.insert_raw("time".into(), "1 hour".into());
+        m.insert_raw("cook_time".into(), "1 hour".into());
+
+        m.set_servings(&[2, 4]).unwrap();
+        assert_eq!(m.get("servings"), Some("2|4"));
+        assert_eq!(m.all("servings"), ["2|4"]);
+        assert!(m.set_servings(&[]).is_err());
+        assert!(m.set_servings(&[4, 4]).is_err());
+        assert_eq!(m.servings(), Some([2, 4].as_slice()));
+
+        let time = RecipeTime::Composed {
+            prep_time: None,
+            cook_time: Some(75),
+        };
+        m.set_time(time).unwrap();
+        assert_eq!(m.get("time"), None);
+        assert_eq!(m.get("cook_time"), None);
+        assert_eq!(m.get("cook time"), Some("75 min"));
+        assert_eq!(m.get("prep time"), None);
+        assert_eq!(m.time(), Some(&time));
+        assert!(m
+            .set_time(RecipeTime::Composed {
+                prep_time: None,
+                cook_time: None
+            })
+            .is_err());
+        assert_eq!(m.time(), Some(&time));
+
+        // the raw values parse back to the same
+        let mut parsed = Metadata {
+            map: m.map.clone(),
+            ..Default::default()
+        };
+        parsed.parse_special(&converter).unwrap();
+        assert_eq!(parsed.servings(), m.servings());
+        assert_eq!(parsed.time(), m.time());
+        assert_eq!(m.known().servings().unwrap().unwrap(), [2, 4]);
+        assert_eq!(m.known().time(&converter).unwrap().unwrap(), time);
+
+        m.set_time(RecipeTime::Total(90)).unwrap();
+        assert_eq!(m.get("time"), Some("90 min"));
+        assert_eq!(m.get("cook time"), None);
+        parsed.map = m.map.clone();
+        parsed.parse_special(&Converter::empty()).unwrap();
+        assert_eq!(parsed.time(), Some(&RecipeTime::Total(90)));
+    }
+
+    #[test]
+    fn known_tags() {
+        let mut m = Metadata::default();
+        m.map.insert("Tags".into(), "vegan, quick,,vegan , ".into());
+        m.map.insert("tag".into(), "dessert".into());
+        assert_eq!(m.known().tags(), ["vegan", "quick", "dessert"]);
+
+        let m = Metadata::default();
+        assert!(m.known().tags().is_empty());
+    }
+
+    #[test]
+    fn known_time() {
+        let converter = Converter::bundled();
+        let time = |entries: &[(&str, &str)]| {
+            let mut m = Metadata::default();
+            for (k, v) in entries {
+                m.map.insert(k.to_string(), v.to_string());
+            }
+            let known = m.known();
+            known.time(&converter).map(|r| r.ok())
+        };
+
+        assert_eq!(time(&[]), None);
+        assert_eq!(
+            time(&[("time", "1 hour 30 min")]),
+            Some(Some(RecipeTime::Total(90)))
+        );
+        assert_eq!(time(&[("TIME", "20")]), Some(Some(RecipeTime::Total(20))));
+        assert_eq!(
+            time(&[("Prep-Time", "10 min"), ("cook_time", "1 hour")]),
+            Some(Some(RecipeTime::Composed {
+                prep_time: Some(10),
+                cook_time: Some(60)
+            }))
+        );
+        assert_eq!(
+            time(&[("cook time", "5min")]),
+            Some(Some(RecipeTime::Composed {
+                prep_time: None,
+                cook_time: Some(5)
+            }))
+        );
+        assert_eq!(time(&[("time", "1 kilometer")]), Some(None));
+        assert_eq!(time(&[("prep time", "soon")]), Some(None));
+    }
+
+    #[test]
+    fn known_keys() {
+        let mut m = Metadata::default();
+        m.map
+            .insert("Source".into(), "Rachel <https://rachel.url>".into());
+        m.map.insert("servings".into(), "2|2".into());
+        m.map.insert("my  Custom_key".into(), "value".into());
+        let known = m.known();
+        assert_eq!(known.source().unwrap().name(), Some("Rachel"));
+        assert!(known.author().is_none());
+        assert!(matches!(
+            known.servings(),
+            Some(Err(MetadataError::DuplicateServings { .. }))
+        ));
+        assert_eq!(known.get("my custom-key"), Some("value"));
+        assert_eq!(known.get("other"), None);
+    }
+
+    #[test]
+    fn parse_yield() {
+        let converter = Converter::empty();
+        let mut m = Metadata::default();
+        assert!(insert!(m, converter, SpecialKey::Yield, "12 muffins").is_ok());
+        assert_eq!(
+            m.yield_(),
+            Some(&Yield {
+                amount: 12.0.into(),
+                unit: Some("muffins".into())
+            })
+        );
+        assert!(insert!(m, converter, SpecialKey::Yield, "2 loaves").is_ok());
+        assert_eq!(
+            m.yield_(),
+            Some(&Yield {
+                amount: 2.0.into(),
+                unit: Some("loaves".into())
+            })
+        );
+
+        let y = Yield::parse("1 1/2 large cakes").unwrap();
+        assert_eq!(y.amount.to_string(), "1 1/2");
+        assert_eq!(y.unit.as_deref(), Some("large cakes"));
+        let y = Yield::parse("2-3 loaves").unwrap();
+        assert!(matches!(y.amount, Value::Range { .. }));
+        let y = Yield::parse(" 4 ").unwrap();
+        assert_eq!(y.amount, 4.0.into());
+        assert_eq!(y.unit, None);
+
+        assert!(matches!(
+            insert!(m, converter, SpecialKey::Yield, "a lot of muffins"),
+            Err(MetadataError::InvalidYield { .. })
+        ));
+        assert!(Yield::parse("").is_err());
+        assert!(Yield::parse("12muffins").is_err());
+
+        m.scale_yield(1.5);
+        assert_eq!(m.yield_().unwrap().amount, 3.0.into());
+    }
+
+    #[test]
+    fn parse_unit_aliases() {
+        let converter = Converter::bundled();
+        let mut m = Metadata::default();
+        assert!(insert!(m, converter, SpecialKey::Units, "T = tbsp, c=cup").is_ok());
+        assert_eq!(
+            m.unit_aliases().unwrap(),
+            [
+                ("T".to_string(), "tbsp".to_string()),
+                ("c".to_string(), "cup".to_string())
+            ]
+        );
+        assert!(matches!(
+            insert!(m, converter, SpecialKey::Units, "T=tbsp, c"),
+            Err(MetadataError::InvalidUnitAlias { .. })
+        ));
+        // not aliases, the previous ones are kept
+        assert!(insert!(m, converter, SpecialKey::Units, "metric").is_ok());
+        assert_eq!(m.unit_aliases().unwrap().len(), 2);
+        assert!(matches!(
+            insert!(m, converter, SpecialKey::Units, "x=unknown"),
+            Err(MetadataError::UnknownUnit(_))
+        ));
+    }
+
+    #[test]
+    fn parse_system() {
+        let converter = Converter::empty();
+        let mut m = Metadata::default();
+        assert!(insert!(m, converter, SpecialKey::System, "imperial").is_ok());
+        assert_eq!(m.system(), Some(System::Imperial));
+        assert!(insert!(m, converter, SpecialKey::System, " Metric ").is_ok());
+        assert_eq!(m.system(), Some(System::Metric));
+        assert!(matches!(
+            insert!(m, converter, SpecialKey::System, "nautical"),
+            Err(MetadataError::InvalidSystem { .. })
+        ));
+    }
+
     #[test]
     fn shortcode_emoji() {
         let converter = Converter::empty();
@@ -1,17 +1,28 @@
 //! Recipe representation
 
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    time::Duration,
+};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    convert::Converter,
-    metadata::Metadata,
+    convert::{ConversionCache, Converter},
+    error::{SourceDiag, SourceReport},
+    metadata::{time_to_minutes, Metadata, MetadataConflict, ParseTimeError},
     parser::Modifiers,
-    quantity::{GroupedValue, Quantity, QuantityValue, ScalableValue, ScaledQuantity},
+    quantity::{
+        GroupedValue, PerUnitQuantity, Quantity, QuantityValue, ScalableValue, ScaledQuantity,
+    },
+    scale::linear_scale,
     GroupedQuantity, Value,
 };
 
+#[cfg(feature = "spans")]
+use crate::span::Span;
+
 /// A complete recipe
 ///
 /// The recipes does not have a name. You give it externally or maybe use
@@ -55,6 +66,944 @@ pub type ScalableRecipe = Recipe<(), ScalableValue>;
 /// scaled once.
 pub type ScaledRecipe = Recipe<crate::scale::Scaled, Value>;
 
+impl<D, V: QuantityValue> Recipe<D, V> {
+    /// Mutable access to the metadata
+    ///
+    /// Use [`Metadata::set_servings`] and [`Metadata::set_time`] to change
+    /// the known keys keeping the raw and parsed values in sync. Editing
+    /// [`Metadata::map`] directly does not update the parsed values until
+    /// [`Metadata::parse_special`] is called.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    /// Keeps only the ingredients of a variant
+    ///
+    /// Ingredients without a variant are used in all of them, so they are
+    /// always kept. The removed ingredients are also removed from the steps and
+    /// the indices of the rest are updated.
+    ///
+    /// References take the variant of the definition unless they have their
+    /// own. If a definition is removed, all its references are removed too.
+    ///
+    /// This is only useful with the [`VARIANTS`](crate::Extensions::VARIANTS)
+    /// extension.
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, Extensions, Converter};
+    /// let parser = CooklangParser::new(Extensions::all(), Converter::bundled());
+    /// let recipe = parser
+    ///     .parse("Mix @flour{} with @[vegan]margarine{} or @[classic]butter{}.")
+    ///     .unwrap_output()
+    ///     .select_variant("vegan");
+    /// let names: Vec<_> = recipe.ingredients.iter().map(|i| i.name.as_str()).collect();
+    /// assert_eq!(names, ["flour", "margarine"]);
+    /// ```
+    pub fn select_variant(mut self, name: &str) -> Self {
+        let in_variant = |igr: &Ingredient<V>| igr.variant.as_deref().is_none_or(|v| v == name);
+        let keep = self
+            .ingredients
+            .iter()
+            .map(|igr| {
+                in_variant(igr)
+                    && igr
+                        .relation
+                        .references_to()
+                        .filter(|&(_, target)| target == IngredientReferenceTarget::Ingredient)
+                        .is_none_or(|(def, _)| in_variant(&self.ingredients[def]))
+            })
+            .collect::<Vec<_>>();
+
+        // old index -> new index
+        let mut new_index = vec![None; keep.len()];
+        let mut next = 0;
+        for (old, &k) in keep.iter().enumerate() {
+            if k {
+                new_index[old] = Some(next);
+                next += 1;
+            }
+        }
+
+        let mut old = 0;
+        self.ingredients.retain(|_| {
+            old += 1;
+            keep[old - 1]
+        });
+        for igr in &mut self.ingredients {
+            if let Some(referenced_from) = igr.relation.referenced_from_mut() {
+                referenced_from.retain_mut(|i| match new_index[*i] {
+                    Some(new) => {
+                        *i = new;
+                        true
+                    }
+                    None => false,
+                });
+            }
+            if let ComponentRelation::Reference { references_to } = &mut igr.relation.relation {
+                if igr.relation.reference_target == Some(IngredientReferenceTarget::Ingredient) {
+                    *references_to = new_index[*references_to].expect("removed definition");
+                }
+            }
+        }
+
+        for section in &mut self.sections {
+            for content in &mut section.content {
+                let Content::Step(step) = content else {
+                    continue;
+                };
+                step.items.retain_mut(|item| match item {
+                    Item::Ingredient { index } => match new_index[*index] {
+                        Some(new) => {
+                            *index = new;
+                            true
+                        }
+                        None => false,
+                    },
+                    _ => true,
+                });
+            }
+        }
+
+        self
+    }
+
+    /// Checks the recipe for semantic problems
+    ///
+    /// The parser already reports most of the problems, but a recipe can be
+    /// modified or deserialized after that. This reports:
+    ///
+    /// - References to undefined ingredients or cookware and step items
+    ///   pointing to missing components (errors).
+    /// - Timers with a zero duration (warning).
+    /// - Quantities with units unknown to the `converter` (warning).
+    /// - Ranges that start or end in zero (warning).
+    /// - Intermediate references that form a cycle (errors). See
+    ///   [`Self::check_reference_cycles`].
+    ///
+    /// The diagnostics have a location only with the `spans` feature.
+    pub fn validate(&self, converter: &Converter) -> SourceReport {
+        crate::analysis::validate(self, converter)
+    }
+
+    /// Finds intermediate references that form a cycle
+    ///
+    /// With the [`INTERMEDIATE_PREPARATIONS`](crate::Extensions::INTERMEDIATE_PREPARATIONS)
+    /// extension a step can use the result of another step or section. The
+    /// parser only allows referencing previous ones, but after modifying or
+    /// deserializing a recipe a step may end up depending on itself, directly
+    /// or through other steps and sections.
+    ///
+    /// This walks the [`IngredientRelation::references_to`] targets of the
+    /// ingredients in the steps and returns an error for each cycle, at the
+    /// ingredient reference that closes it.
+    pub fn check_reference_cycles(&self) -> Vec<SourceDiag> {
+        crate::analysis::reference_cycles(self)
+    }
+
+    /// Replaces the variables in the quantities with a value
+    ///
+    /// With the [`VARIABLES`](crate::Extensions::VARIABLES) extension a
+    /// quantity value can be a placeholder like `$amount`. Scaling leaves
+    /// variables as they are, so bind them before scaling for the values to
+    /// be scaled.
+    ///
+    /// All the variables in `vars` are replaced even if some are missing.
+    /// The error has the names of the missing ones, in order of appearance.
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, Extensions, Converter};
+    /// # use std::collections::HashMap;
+    /// let parser = CooklangParser::new(Extensions::all(), Converter::bundled());
+    /// let mut recipe = parser.parse("Add @flour{$amount%g}.").unwrap_output();
+    /// recipe.bind(&HashMap::from([("amount".to_string(), 250.0)])).unwrap();
+    /// let recipe = recipe.default_scale();
+    /// let flour = recipe.ingredients[0].quantity.as_ref().unwrap();
+    /// assert_eq!(flour.to_string(), "250 g");
+    /// ```
+    pub fn bind(&mut self, vars: &HashMap<String, f64>) -> Result<(), BindError> {
+        let mut values: Vec<&mut Value> = Vec::new();
+        for igr in &mut self.ingredients {
+            let quantities = igr
+                .quantity
+                .iter_mut()
+                .chain(&mut igr.alternative_quantities)
+                .chain(&mut igr.purchase_quantity);
+            values.extend(quantities.flat_map(|q| q.value.inner_values_mut()));
+            if let Some(per_unit) = &mut igr.per_unit {
+                values.extend(per_unit.count.inner_values_mut());
+                values.extend(per_unit.per_unit.value.inner_values_mut());
+            }
+        }
+        for cw in &mut self.cookware {
+            values.extend(cw.quantity.iter_mut().flat_map(|v| v.inner_values_mut()));
+        }
+        for timer in &mut self.timers {
+            values.extend(
+                timer
+                    .quantity
+                    .iter_mut()
+                    .flat_map(|q| q.value.inner_values_mut()),
+            );
+        }
+        values.extend(self.inline_quantities.iter_mut().map(|q| &mut q.value));
+
+        let mut unbound: Vec<String> = Vec::new();
+        for value in values {
+            let Value::Variable(name) = value else {
+                continue;
+            };
+            match vars.get(name.as_str()) {
+                Some(&n) => *value = Value::from(n),
+                None if !unbound.contains(name) => unbound.push(name.clone()),
+                None => {}
+            }
+        }
+        if unbound.is_empty() {
+            Ok(())
+        } else {
+            Err(BindError { unbound })
+        }
+    }
+
+    /// All the units used in the quantities
+    ///
+    /// This has the units of the ingredients, including the alternative and
+    /// per unit quantities, the timers and the inline quantities. What a
+    /// quantity is for each of, [`Quantity::per`], is also included. Quantities
+    /// without unit are skipped. Useful to check beforehand that a
+    /// [`Converter`] knows all of them.
+    ///
+    /// ```
+    /// # use cooklang::CooklangParser;
+    /// let recipe = CooklangParser::default()
+    ///     .parse("Add @flour{2-3%cups}, @eggs{2} and @milk{100%ml/serving}, bake for ~{20%min}.")
+    ///     .unwrap_output();
+    /// let mut units: Vec<_> = recipe.units_used().into_iter().collect();
+    /// units.sort();
+    /// assert_eq!(units, ["cups", "min", "ml", "serving"]);
+    /// ```
+    pub fn units_used(&self) -> HashSet<String> {
+        let mut units = HashSet::new();
+        for igr in &self.ingredients {
+            let quantities = igr
+                .quantity
+                .iter()
+                .chain(&igr.alternative_quantities)
+                .chain(&igr.purchase_quantity)
+                .chain(igr.per_unit.iter().map(|p| &p.per_unit));
+            units.extend(quantities.flat_map(quantity_units).map(String::from));
+        }
+        let timers = self.timers.iter().filter_map(|t| t.quantity.as_ref());
+        units.extend(timers.flat_map(quantity_units).map(String::from));
+        let inline = self.inline_quantities.iter();
+        units.extend(inline.flat_map(quantity_units).map(String::from));
+        units
+    }
+
+    /// Groups the ingredient definitions by category
+    ///
+    /// The category is [`Ingredient::category`] or, if it's [`None`], the
+    /// category of the ingredient name in `categories`. Ingredients without
+    /// category will be placed in `"other"`.
+    ///
+    /// The map of categories can be obtained from an aisle configuration with
+    /// `AisleConf::reverse`.
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, Extensions, Converter};
+    /// # use std::collections::HashMap;
+    /// let parser = CooklangParser::new(Extensions::all(), Converter::bundled());
+    /// let recipe = parser
+    ///     .parse("Mix @flour{}[baking] with @milk{} and @salt{}.")
+    ///     .unwrap_output();
+    /// let categories = HashMap::from([("milk", "dairy")]);
+    /// let grouped = recipe.group_by_category(&categories);
+    /// assert_eq!(grouped["baking"][0].name, "flour");
+    /// assert_eq!(grouped["dairy"][0].name, "milk");
+    /// assert_eq!(grouped["other"][0].name, "salt");
+    /// ```
+    pub fn group_by_category(
+        &self,
+        categories: &HashMap<&str, &str>,
+    ) -> BTreeMap<String, Vec<&Ingredient<V>>> {
+        let mut grouped: BTreeMap<String, Vec<&Ingredient<V>>> = BTreeMap::new();
+        for igr in self
+            .ingredients
+            .iter()
+            .filter(|i| i.relation.is_definition())
+        {
+            let category = igr
+                .category
+                .as_deref()
+                .or_else(|| categories.get(igr.name.as_str()).copied())
+                .unwrap_or("other");
+            grouped.entry(category.to_string()).or_default().push(igr);
+        }
+        grouped
+    }
+
+    /// Paths of the recipes used as ingredients
+    ///
+    /// This is the [`Ingredient::name`] of every ingredient with the
+    /// [`RECIPE`](Modifiers::RECIPE) modifier, in order of appearance. The
+    /// paths are not resolved. References to an ingredient are skipped, so
+    /// the same path is only repeated if it's defined more than once.
+    ///
+    /// ```
+    /// # use cooklang::CooklangParser;
+    /// let parser = CooklangParser::default();
+    /// let recipe = parser
+    ///     .parse("Add @@../sauces/Tomato Sauce{} and @cheese{}.")
+    ///     .unwrap_output();
+    /// let refs = recipe.referenced_recipes().collect::<Vec<_>>();
+    /// assert_eq!(refs, ["../sauces/Tomato Sauce"]);
+    /// ```
+    pub fn referenced_recipes(&self) -> impl Iterator<Item = &str> {
+        self.ingredients
+            .iter()
+            .filter(|i| i.modifiers.contains(Modifiers::RECIPE) && i.relation.is_definition())
+            .map(|i| i.name.as_str())
+    }
+
+    /// Ingredients used in each step
+    ///
+    /// Every step of the recipe is listed in order, with the ingredients it
+    /// uses. References are resolved to their definition, so an ingredient
+    /// used in many steps is the same in all of them. An ingredient is only
+    /// listed once per step and references to other steps or sections are
+    /// skipped.
+    ///
+    /// ```
+    /// # use cooklang::CooklangParser;
+    /// let parser = CooklangParser::default();
+    /// let recipe = parser
+    ///     .parse("Mix @flour{} and @water{}.\n\nKnead the @&flour{}.")
+    ///     .unwrap_output();
+    /// let mep = recipe.mise_en_place();
+    /// assert_eq!(mep.len(), 2);
+    /// assert_eq!(mep[1].0.content, 1);
+    /// assert_eq!(mep[1].1[0].name, "flour");
+    /// ```
+    pub fn mise_en_place(&self) -> Vec<(StepRef, Vec<&Ingredient<V>>)> {
+        let mut steps = Vec::new();
+        for (section_index, section) in self.sections.iter().enumerate() {
+            for (content_index, content) in section.content.iter().enumerate() {
+                let Content::Step(step) = content else {
+                    continue;
+                };
+                let mut used: Vec<usize> = Vec::new();
+                for index in step.ingredient_indices() {
+                    let definition = match self.ingredients[index].relation.references_to() {
+                        None => index,
+                        Some((target, IngredientReferenceTarget::Ingredient)) => target,
+                        Some(_) => continue,
+                    };
+                    if !used.contains(&definition) {
+                        used.push(definition);
+                    }
+                }
+                let step_ref = StepRef {
+                    section: section_index,
+                    content: content_index,
+                };
+                steps.push((
+                    step_ref,
+                    used.into_iter().map(|i| &self.ingredients[i]).collect(),
+                ));
+            }
+        }
+        steps
+    }
+
+    /// Raw metadata value of a section
+    ///
+    /// If the section doesn't have the key, the value of the recipe is used.
+    /// Section metadata is only parsed with
+    /// [`ParseOptions::section_metadata`](crate::ParseOptions::section_metadata).
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, ParseOptions};
+    /// let parser = CooklangParser::default();
+    /// let options = ParseOptions {
+    ///     section_metadata: true,
+    ///     ..Default::default()
+    /// };
+    /// let input = ">> time: 1 hour\n= Dough\n>> time: 20 min\nKnead.\n= Sauce\nStir.";
+    /// let recipe = parser.parse_with_options(input, options).unwrap_output();
+    /// assert_eq!(recipe.section_metadata(0, "time"), Some("20 min"));
+    /// assert_eq!(recipe.section_metadata(1, "time"), Some("1 hour"));
+    /// ```
+    ///
+    /// # Panics
+    /// If the section index is out of bounds.
+    pub fn section_metadata(&self, section: usize, key: &str) -> Option<&str> {
+        self.sections[section]
+            .metadata
+            .map
+            .get(key)
+            .or_else(|| self.metadata.map.get(key))
+            .map(String::as_str)
+    }
+
+    /// Ingredient definitions in order of first appearance
+    ///
+    /// References are collapsed into their definition, which is listed where
+    /// the definition or the first of its references appears, whatever comes
+    /// first. References to steps or sections are skipped. Different
+    /// definitions with the same name are listed separately.
+    ///
+    /// ```
+    /// # use cooklang::CooklangParser;
+    /// let parser = CooklangParser::default();
+    /// let recipe = parser
+    ///     .parse("Mix @flour{} and @water{}.\n\nAdd @&water{} and @salt{}.")
+    ///     .unwrap_output();
+    /// let names: Vec<_> = recipe.unique_ingredients().iter().map(|i| i.name.as_str()).collect();
+    /// assert_eq!(names, ["flour", "water", "salt"]);
+    /// ```
+    pub fn unique_ingredients(&self) -> Vec<&Ingredient<V>> {
+        let mut seen = vec![false; self.ingredients.len()];
+        let mut unique = Vec::new();
+        for (index, igr) in self.ingredients.iter().enumerate() {
+            let definition = match igr.relation.references_to() {
+                None => index,
+                Some((target, IngredientReferenceTarget::Ingredient)) => target,
+                Some(_) => continue,
+            };
+            if !seen[definition] {
+                seen[definition] = true;
+                unique.push(&self.ingredients[definition]);
+            }
+        }
+        unique
+    }
+
+    /// Rewrites the ingredient names with `normalizer`
+    ///
+    /// This helps merging names that are the same ingredient, like `Tomato`
+    /// and `tomatoes`, when grouping them in a list. [`normalize_name`] is a
+    /// simple normalizer for English names, but any function can be used.
+    ///
+    /// References are renamed like their definition. The name before the
+    /// first change is kept in [`Ingredient::original_name`]. Ingredients that
+    /// are recipes are not changed.
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, model::normalize_name};
+    /// let parser = CooklangParser::default();
+    /// let mut recipe = parser.parse("Cut @Tomatoes{2} and @tomato{1}.").unwrap_output();
+    /// recipe.canonicalize_names(normalize_name);
+    /// assert_eq!(recipe.ingredients[0].name, "tomato");
+    /// assert_eq!(recipe.ingredients[0].original_name.as_deref(), Some("Tomatoes"));
+    /// assert_eq!(recipe.ingredients[1].name, "tomato");
+    /// assert_eq!(recipe.ingredients[1].original_name, None);
+    /// ```
+    pub fn canonicalize_names(&mut self, normalizer: impl Fn(&str) -> String) {
+        for igr in &mut self.ingredients {
+            if igr.modifiers.contains(Modifiers::RECIPE) {
+                continue;
+            }
+            let name = normalizer(&igr.name);
+            if name != igr.name {
+                let original = std::mem::replace(&mut igr.name, name);
+                igr.original_name.get_or_insert(original);
+            }
+        }
+    }
+
+    /// Borrowed [view](RecipeRef) of the recipe
+    pub fn view(&self) -> RecipeRef<'_, V> {
+        RecipeRef::from(self)
+    }
+
+    /// Compares two recipes ignoring incidental differences
+    ///
+    /// The order of the [`referenced_from`](ComponentRelation::referenced_from)
+    /// lists and the spans (with the `spans` feature) are not compared, so
+    /// the same recipe built in different ways is equal.
+    pub fn structurally_eq(&self, other: &Self) -> bool
+    where
+        D: Clone + PartialEq,
+        V: PartialEq,
+    {
+        self.clone().normalized() == other.clone().normalized()
+    }
+
+    /// Sorts the reference lists and clears the spans
+    fn normalized(mut self) -> Self {
+        for igr in &mut self.ingredients {
+            if let Some(referenced_from) = igr.relation.referenced_from_mut() {
+                referenced_from.sort_unstable();
+            }
+        }
+        for cw in &mut self.cookware {
+            if let ComponentRelation::Definition {
+                referenced_from, ..
+            } = &mut cw.relation
+            {
+                referenced_from.sort_unstable();
+            }
+        }
+        #[cfg(feature = "spans")]
+        for span in self.spans_mut() {
+            *span = Span::pos(0);
+        }
+        self
+    }
+
+    /// Moves all the spans `offset` bytes forward
+    ///
+    /// Used when a part of a larger input is parsed on its own.
+    #[cfg(feature = "spans")]
+    pub(crate) fn shift_spans(&mut self, offset: usize) {
+        for span in self.spans_mut() {
+            *span = span.shift(offset);
+        }
+    }
+
+    /// All the spans of the steps and components
+    #[cfg(feature = "spans")]
+    fn spans_mut(&mut self) -> impl Iterator<Item = &mut Span> {
+        let steps = self
+            .sections
+            .iter_mut()
+            .flat_map(|s| &mut s.content)
+            .filter_map(|c| match c {
+                Content::Step(step) => Some(&mut step.span),
+                Content::Text(_) => None,
+            });
+        self.ingredients
+            .iter_mut()
+            .map(|i| &mut i.span)
+            .chain(self.cookware.iter_mut().map(|c| &mut c.span))
+            .chain(self.timers.iter_mut().map(|t| &mut t.span))
+            .chain(steps)
+    }
+
+    /// Which ingredients are in a step of an [optional](Section::optional)
+    /// section, indexed like [`Self::ingredients`]
+    pub(crate) fn optional_ingredients(&self) -> Vec<bool> {
+        let mut optional = vec![false; self.ingredients.len()];
+        for section in self.sections.iter().filter(|s| s.optional) {
+            for content in &section.content {
+                if let Content::Step(step) = content {
+                    for index in step.ingredient_indices() {
+                        optional[index] = true;
+                    }
+                }
+            }
+        }
+        optional
+    }
+}
+
+impl<D> Recipe<D, Value> {
+    /// Sum of the durations of all the timers
+    ///
+    /// Timers without a quantity are skipped, a range counts as its end and a
+    /// tolerance as its highest value.
+    /// A timer with a text value, without a time unit or with a negative or
+    /// too long duration is an error, unless `skip_invalid` is true, then it's
+    /// skipped. The sum saturates at [`Duration::MAX`].
+    ///
+    /// ```
+    /// # use cooklang::CooklangParser;
+    /// # use std::time::Duration;
+    /// let parser = CooklangParser::default();
+    /// let recipe = parser
+    ///     .parse("Bake for ~{1%h}, then rest ~{5-10%min}.")
+    ///     .unwrap_output()
+    ///     .default_scale();
+    /// let duration = recipe.timers_duration(parser.converter(), false).unwrap();
+    /// assert_eq!(duration, Duration::from_secs(70 * 60));
+    /// ```
+    pub fn timers_duration(
+        &self,
+        converter: &Converter,
+        skip_invalid: bool,
+    ) -> Result<Duration, TimerDurationError> {
+        let mut total = Duration::ZERO;
+        for (index, timer) in self.timers.iter().enumerate() {
+            let Some(quantity) = &timer.quantity else {
+                continue;
+            };
+            let duration = timer_minutes(index, quantity, converter).and_then(|m| {
+                Duration::try_from_secs_f64(m * 60.0)
+                    .map_err(|source| TimerDurationError::InvalidDuration { index, source })
+            });
+            match duration {
+                Ok(d) => total = total.saturating_add(d),
+                Err(_) if skip_invalid => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(total)
+    }
+
+    /// Total time of the recipe
+    ///
+    /// This is the duration of the timers, skipping the invalid ones, plus the
+    /// [time](Metadata::time) in the metadata. [`None`] if there is neither.
+    pub fn total_time(&self, converter: &Converter) -> Option<Duration> {
+        let timers = self
+            .timers_duration(converter, true)
+            .expect("invalid timers are skipped");
+        let metadata = self
+            .metadata
+            .time()
+            .map(|time| Duration::from_secs(u64::from(time.total()) * 60));
+        match metadata {
+            Some(metadata) => Some(timers.saturating_add(metadata)),
+            None => (!timers.is_zero()).then_some(timers),
+        }
+    }
+}
+
+/// Unit text and [`Quantity::per`] of a quantity
+fn quantity_units<V: QuantityValue>(q: &Quantity<V>) -> impl Iterator<Item = &str> {
+    q.unit_text().into_iter().chain(q.per())
+}
+
+fn timer_minutes(
+    index: usize,
+    quantity: &Quantity<Value>,
+    converter: &Converter,
+) -> Result<f64, TimerDurationError> {
+    let value = match &quantity.value {
+        Value::Number(n) => n.value(),
+        Value::Range { end, .. } => end.value(),
+        Value::Tolerant { center, tolerance } => center.value() + tolerance.value(),
+        Value::Text(_) => return Err(TimerDurationError::TextValue { index }),
+        Value::Variable(_) => return Err(TimerDurationError::Variable { index }),
+    };
+    let unit = quantity
+        .unit()
+        .ok_or(TimerDurationError::MissingUnit { index })?;
+    time_to_minutes(value, unit.text(), converter)
+        .map_err(|source| TimerDurationError::InvalidUnit { index, source })
+}
+
+/// Error from [`Recipe::bind`]
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Unbound variables: {}", .unbound.join(", "))]
+pub struct BindError {
+    /// Names of the variables without a value, without the `$`
+    pub unbound: Vec<String>,
+}
+
+/// Error from [`Recipe::timers_duration`]
+#[derive(Debug, thiserror::Error)]
+pub enum TimerDurationError {
+    #[error("Timer {index} has a text value")]
+    TextValue { index: usize },
+    #[error("Timer {index} has an unbound variable")]
+    Variable { index: usize },
+    #[error("Timer {index} has no unit")]
+    MissingUnit { index: usize },
+    #[error("Timer {index} unit is not a time unit")]
+    InvalidUnit {
+        index: usize,
+        #[source]
+        source: ParseTimeError,
+    },
+    #[error("Timer {index} duration is negative or too long")]
+    InvalidDuration {
+        index: usize,
+        #[source]
+        source: std::time::TryFromFloatSecsError,
+    },
+}
+
+impl ScalableRecipe {
+    /// Appends another recipe to this one
+    ///
+    /// The sections of `other` are added after the ones of `self` and all
+    /// its components are added to the end of the lists, so the indices in
+    /// the steps and relations of `other` are updated. Metadata keys present
+    /// in both recipes are resolved with `conflict`.
+    ///
+    /// The recipes are merged before scaling, so the
+    /// [servings](Metadata::servings) used to scale are the merged ones. The
+    /// values of a recipe with other servings are changed to the merged ones:
+    /// linear values are scaled and values by servings are picked for each of
+    /// the merged servings. It's an error if one of them is not defined, and
+    /// then `self` is not changed.
+    ///
+    /// With the `spans` feature, the locations of the components of `other`
+    /// still refer to its own source.
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, ScalableValue, metadata::MetadataConflict};
+    /// let parser = CooklangParser::default();
+    /// let mut dinner = parser
+    ///     .parse(">> servings: 2\nBoil @pasta{200%g}.\n\nServe the @&pasta{}.")
+    ///     .unwrap_output();
+    /// let dessert = parser
+    ///     .parse(">> servings: 4\nWhip @cream{200*%ml}.")
+    ///     .unwrap_output();
+    /// dinner.merge(dessert, MetadataConflict::Keep).unwrap();
+    /// assert_eq!(dinner.metadata.servings(), Some([2].as_slice()));
+    /// assert_eq!(dinner.ingredients.len(), 3);
+    /// // the cream was for 4 servings
+    /// let cream = dinner.ingredients[2].quantity.as_ref().unwrap();
+    /// assert_eq!(cream.value, ScalableValue::Linear(100.0.into()));
+    /// assert_eq!(dinner.sections.len(), 2);
+    /// let step = dinner.sections[1].content[0].unwrap_step();
+    /// assert_eq!(step.ingredient_indices().collect::<Vec<_>>(), [2]);
+    /// ```
+    pub fn merge(&mut self, mut other: Self, conflict: MetadataConflict) -> Result<(), MergeError> {
+        let self_servings = self.metadata.servings().map(<[u32]>::to_vec);
+        let other_servings = other.metadata.servings().map(<[u32]>::to_vec);
+        let mut metadata = self.metadata.clone();
+        metadata.merge(other.metadata.clone(), conflict);
+        let servings = metadata.servings();
+        rebase_servings(
+            other.scalable_values_mut(),
+            other_servings.as_deref(),
+            servings,
+        )?;
+        rebase_servings(
+            self.scalable_values_mut(),
+            self_servings.as_deref(),
+            servings,
+        )?;
+        self.metadata = metadata;
+
+        let ingredient_offset = self.ingredients.len();
+        let cookware_offset = self.cookware.len();
+        let timer_offset = self.timers.len();
+        let inline_offset = self.inline_quantities.len();
+        let section_offset = self.sections.len();
+
+        for mut section in other.sections {
+            for content in &mut section.content {
+                let Content::Step(step) = content else {
+                    continue;
+                };
+                for item in &mut step.items {
+                    match item {
+                        Item::Text { .. } => {}
+                        Item::Ingredient { index } => *index += ingredient_offset,
+                        Item::Cookware { index } => *index += cookware_offset,
+                        Item::Timer { index } => *index += timer_offset,
+                        Item::InlineQuantity { index } => *index += inline_offset,
+                    }
+                }
+            }
+            self.sections.push(section);
+        }
+
+        for mut igr in other.ingredients {
+            let relation = &mut igr.relation;
+            for i in relation.referenced_from_mut().into_iter().flatten() {
+                *i += ingredient_offset;
+            }
+            if let ComponentRelation::Reference { references_to } = &mut relation.relation {
+                // step references are relative to the section, so they
+                // don't change
+                match relation.reference_target {
+                    Some(IngredientReferenceTarget::Ingredient) => {
+                        *references_to += ingredient_offset
+                    }
+                    Some(IngredientReferenceTarget::Section) => *references_to += section_offset,
+                    Some(IngredientReferenceTarget::Step) | None => {}
+                }
+            }
+            self.ingredients.push(igr);
+        }
+
+        for mut cw in other.cookware {
+            match &mut cw.relation {
+                ComponentRelation::Definition {
+                    referenced_from, ..
+                } => {
+                    for i in referenced_from {
+                        *i += cookware_offset;
+                    }
+                }
+                ComponentRelation::Reference { references_to } => *references_to += cookware_offset,
+            }
+            self.cookware.push(cw);
+        }
+
+        self.timers.extend(other.timers);
+        self.inline_quantities.extend(other.inline_quantities);
+        Ok(())
+    }
+
+    /// All the values that can be scaled
+    fn scalable_values_mut(&mut self) -> Vec<&mut ScalableValue> {
+        let mut values = Vec::new();
+        for igr in &mut self.ingredients {
+            let quantities = igr
+                .quantity
+                .iter_mut()
+                .chain(&mut igr.alternative_quantities)
+                .chain(&mut igr.purchase_quantity);
+            values.extend(quantities.map(|q| &mut q.value));
+            if let Some(per_unit) = &mut igr.per_unit {
+                values.push(&mut per_unit.count);
+                values.push(&mut per_unit.per_unit.value);
+            }
+        }
+        values.extend(
+            self.cookware
+                .iter_mut()
+                .filter_map(|cw| cw.quantity.as_mut()),
+        );
+        values.extend(
+            self.timers
+                .iter_mut()
+                .filter_map(|t| t.quantity.as_mut())
+                .map(|q| &mut q.value),
+        );
+        values
+    }
+}
+
+/// Changes the values of a recipe for the `from` servings to the `to` ones
+///
+/// If a value by servings is not defined for all the `to` servings, it errors
+/// before changing anything.
+fn rebase_servings(
+    values: Vec<&mut ScalableValue>,
+    from: Option<&[u32]>,
+    to: Option<&[u32]>,
+) -> Result<(), MergeError> {
+    let (Some(from), Some(to)) = (from, to) else {
+        return Ok(());
+    };
+    if from == to {
+        return Ok(());
+    }
+    let by_servings = |values: &[Value]| {
+        to.iter()
+            .map(|s| from.iter().position(|f| f == s))
+            .map(|i| values.get(i?).cloned())
+            .collect::<Option<Vec<_>>>()
+    };
+    for value in &values {
+        if let ScalableValue::ByServings(v) = &**value {
+            if by_servings(v).is_none() {
+                return Err(MergeError::ServingsNotDefined {
+                    servings: to.to_vec(),
+                });
+            }
+        }
+    }
+
+    let factor = match (from.first(), to.first()) {
+        (Some(&from), Some(&to)) if from != 0 => f64::from(to) / f64::from(from),
+        _ => 1.0,
+    };
+    for value in values {
+        match value {
+            ScalableValue::Fixed(_) => {}
+            ScalableValue::Linear(v) => {
+                // text values can't be scaled anyway
+                if let Ok(scaled) = linear_scale(v.clone(), factor) {
+                    *v = scaled;
+                }
+            }
+            ScalableValue::ByServings(v) => *v = by_servings(v).expect("checked before"),
+        }
+    }
+    Ok(())
+}
+
+/// Error from [`ScalableRecipe::merge`]
+#[derive(Debug, thiserror::Error)]
+pub enum MergeError {
+    #[error("Values by servings are not defined for the merged servings {servings:?}")]
+    ServingsNotDefined { servings: Vec<u32> },
+}
+
+/// Borrowed view of a [`Recipe`]
+///
+/// It has the same fields as the recipe but with slices, so parts of it can
+/// be passed around for read-only traversal without cloning. Get one with
+/// [`Recipe::view`] or [`From`].
+///
+/// ```
+/// # use cooklang::{CooklangParser, RecipeRef};
+/// let parser = CooklangParser::default();
+/// let recipe = parser.parse("Mix @flour{} and @water{}.").unwrap_output();
+/// let view = RecipeRef::from(&recipe);
+/// let names: Vec<_> = view.ingredients.iter().map(|i| i.name.as_str()).collect();
+/// assert_eq!(names, ["flour", "water"]);
+/// ```
+#[derive(Debug, Serialize)]
+pub struct RecipeRef<'a, V: QuantityValue> {
+    /// Metadata
+    pub metadata: &'a Metadata,
+    /// Each of the sections
+    pub sections: &'a [Section],
+    /// All the ingredients
+    pub ingredients: &'a [Ingredient<V>],
+    /// All the cookware
+    pub cookware: &'a [Cookware<V>],
+    /// All the timers
+    pub timers: &'a [Timer<V>],
+    /// All the inline quantities
+    pub inline_quantities: &'a [ScaledQuantity],
+}
+
+impl<'a, V: QuantityValue> RecipeRef<'a, V> {
+    /// Iterate over all the steps with their location
+    pub fn steps(&self) -> impl Iterator<Item = (StepRef, &'a Step)> {
+        self.sections
+            .iter()
+            .enumerate()
+            .flat_map(|(section_index, section)| {
+                section
+                    .content
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(content_index, content)| match content {
+                        Content::Step(step) => Some((
+                            StepRef {
+                                section: section_index,
+                                content: content_index,
+                            },
+                            step,
+                        )),
+                        Content::Text(_) => None,
+                    })
+            })
+    }
+}
+
+// manual impls to not require `V: Clone`
+impl<V: QuantityValue> Clone for RecipeRef<'_, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V: QuantityValue> Copy for RecipeRef<'_, V> {}
+
+impl<'a, D, V: QuantityValue> From<&'a Recipe<D, V>> for RecipeRef<'a, V> {
+    fn from(recipe: &'a Recipe<D, V>) -> Self {
+        Self {
+            metadata: &recipe.metadata,
+            sections: &recipe.sections,
+            ingredients: &recipe.ingredients,
+            cookware: &recipe.cookware,
+            timers: &recipe.timers,
+            inline_quantities: &recipe.inline_quantities,
+        }
+    }
+}
+
+/// Location of a [`Step`] in a [`Recipe`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StepRef {
+    /// Index in [`Recipe::sections`]
+    pub section: usize,
+    /// Index in [`Section::content`] of that section
+    pub content: usize,
+}
+
 /// A section holding steps
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Section {
@@ -62,24 +1011,97 @@ pub struct Section {
     pub name: Option<String>,
     /// Content inside
     pub content: Vec<Content>,
+    /// Metadata of the section
+    ///
+    /// Only filled with [`ParseOptions::section_metadata`](crate::ParseOptions::section_metadata).
+    /// Use [`Recipe::section_metadata`] to fall back to the recipe metadata.
+    #[serde(default, skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: Metadata,
+    /// The section is optional, like a garnish
+    ///
+    /// Marked with a `?` before the name, like `= ?Garnish`. The ingredients
+    /// only used in optional sections are not added to an
+    /// [`IngredientList`](crate::ingredient_list::IngredientList).
+    #[serde(default)]
+    pub optional: bool,
+    /// Named groups of consecutive content, like `Make the sauce:`
+    ///
+    /// Only with the [`STEP_GROUPS`](crate::Extensions::STEP_GROUPS)
+    /// extension. The content stays in [`Self::content`], so content outside
+    /// any group is still there.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub step_groups: Vec<StepGroup>,
 }
 
 impl Section {
-    pub(crate) fn new(name: Option<String>) -> Section {
+    pub(crate) fn new(name: Option<String>, optional: bool) -> Section {
         Self {
             name,
             content: Vec::new(),
+            metadata: Metadata::default(),
+            optional,
+            step_groups: Vec::new(),
         }
     }
 
     /// Check if the section is empty
     ///
-    /// A section is empty when it has no name and no content.
+    /// A section is empty when it has no name, no content, no metadata, no
+    /// step groups and is not optional.
     pub fn is_empty(&self) -> bool {
-        self.name.is_none() && self.content.is_empty()
+        self.name.is_none()
+            && self.content.is_empty()
+            && self.metadata.is_empty()
+            && !self.optional
+            && self.step_groups.is_empty()
+    }
+
+    /// Sets the step numbers again, starting at 1
+    ///
+    /// Text content does not take a number. Call this after inserting or
+    /// removing content so the [`Step::number`] are still sequential.
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, Content};
+    /// let mut recipe = CooklangParser::default()
+    ///     .parse("First.\n\nSecond.")
+    ///     .unwrap_output();
+    /// let section = &mut recipe.sections[0];
+    /// section.content.remove(0);
+    /// section.renumber_steps();
+    /// assert_eq!(section.content[0].unwrap_step().number, 1);
+    /// ```
+    pub fn renumber_steps(&mut self) {
+        let steps = self.content.iter_mut().filter_map(|c| match c {
+            Content::Step(step) => Some(step),
+            Content::Text(_) => None,
+        });
+        for (step, number) in steps.zip(1..) {
+            step.number = number;
+        }
+    }
+
+    /// Group of the content at an index of [`Self::content`], if any
+    pub fn step_group_of(&self, content_index: usize) -> Option<&StepGroup> {
+        self.step_groups
+            .iter()
+            .find(|g| g.steps.contains(&content_index))
     }
 }
 
+/// A titled group of consecutive content in a [`Section`]
+///
+/// The group goes from its title to the next title or the end of the section.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct StepGroup {
+    /// Title, without the `:`
+    pub title: String,
+    /// Range of indices in [`Section::content`]
+    ///
+    /// It's empty if the title is not followed by any content.
+    pub steps: std::ops::Range<usize>,
+}
+
 /// Each type of content inside a section
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(tag = "type", content = "value", rename_all = "camelCase")]
@@ -136,6 +1158,74 @@ pub struct Step {
     /// The step numbers start at 1 in each section and increase with non
     /// text step.
     pub number: u32,
+
+    /// Location of the step content in the source
+    ///
+    /// Only available with the `spans` feature.
+    #[cfg(feature = "spans")]
+    pub span: Span,
+}
+
+impl Step {
+    /// Indices of the [ingredients](Recipe::ingredients) in the step, in order
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, Extensions, Converter};
+    /// let parser = CooklangParser::new(Extensions::all(), Converter::bundled());
+    /// let r = parser
+    ///     .parse("Mix @flour{} and @water{} in a #bowl for ~{2%min}.")
+    ///     .unwrap_output();
+    /// let step = r.sections[0].content[0].unwrap_step();
+    /// assert_eq!(step.ingredient_indices().collect::<Vec<_>>(), [0, 1]);
+    /// assert_eq!(step.cookware_indices().collect::<Vec<_>>(), [0]);
+    /// assert_eq!(step.timer_indices().collect::<Vec<_>>(), [0]);
+    /// assert_eq!(step.text(), "Mix  and  in a  for .");
+    /// ```
+    pub fn ingredient_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.items.iter().filter_map(|item| match item {
+            Item::Ingredient { index } => Some(*index),
+            _ => None,
+        })
+    }
+
+    /// Indices of the [cookware](Recipe::cookware) in the step, in order
+    pub fn cookware_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.items.iter().filter_map(|item| match item {
+            Item::Cookware { index } => Some(*index),
+            _ => None,
+        })
+    }
+
+    /// Indices of the [timers](Recipe::timers) in the step, in order
+    pub fn timer_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.items.iter().filter_map(|item| match item {
+            Item::Timer { index } => Some(*index),
+            _ => None,
+        })
+    }
+
+    /// Check if the step has no items or only whitespace text
+    ///
+    /// See [`ParseOptions::skip_empty_steps`](crate::ParseOptions::skip_empty_steps).
+    pub fn is_empty(&self) -> bool {
+        self.items
+            .iter()
+            .all(|item| matches!(item, Item::Text { value } if value.trim().is_empty()))
+    }
+
+    /// All the [`Item::Text`] of the step concatenated
+    ///
+    /// Components are skipped, so this is usually not the text a user wants
+    /// to read, but it's useful to search in the step.
+    pub fn text(&self) -> String {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Text { value } => Some(value.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 /// A step item
@@ -172,13 +1262,89 @@ pub struct Ingredient<V: QuantityValue = Value> {
     pub name: String,
     /// Alias
     pub alias: Option<String>,
+    /// Variant of the recipe this ingredient is used in
+    ///
+    /// [`None`] means it's used in all variants. See [`Recipe::select_variant`].
+    pub variant: Option<String>,
     /// Quantity
     pub quantity: Option<Quantity<V>>,
+    /// Alternative quantities, like the `120 g` in `@flour{1 cup / 120 g}`
+    ///
+    /// These are the same amount as [`Self::quantity`] in a different
+    /// unit. They are not used to calculate the total amount of the
+    /// ingredient.
+    // not `default` because it would require `V: Default`
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub alternative_quantities: Vec<Quantity<V>>,
+    /// Count and quantity of each unit, like `2 x 50 g`
+    ///
+    /// Only with the [`PER_UNIT_QUANTITIES`](crate::Extensions::PER_UNIT_QUANTITIES)
+    /// extension. When this is [`Some`], [`Self::quantity`] and
+    /// [`Self::alternative_quantities`] are the totals.
+    pub per_unit: Option<PerUnitQuantity<V>>,
+    /// Quantity to buy, like `1 l` in `@milk{2%cups; 1%l}`
+    ///
+    /// Only with the [`PURCHASE_QUANTITIES`](crate::Extensions::PURCHASE_QUANTITIES)
+    /// extension. [`Self::quantity`] is still the one shown in the recipe,
+    /// this is the one used in an [`IngredientList`](crate::ingredient_list::IngredientList).
+    /// See [`Self::shopping_quantity`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purchase_quantity: Option<Quantity<V>>,
+    /// [`Self::quantity`] is a copy of the quantity of the definition
+    ///
+    /// Only in references without a quantity when parsed with
+    /// [`ParseOptions::inherit_reference_quantities`](crate::ParseOptions::inherit_reference_quantities).
+    /// The quantity is not added again to the total, see [`Self::all_quantities`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub quantity_inherited: bool,
     /// Note
     pub note: Option<String>,
+    /// Other ingredients that can be used instead, like `margarine` in
+    /// `@butter{}(or margarine)`
+    ///
+    /// Only with the [`SUBSTITUTES`](crate::Extensions::SUBSTITUTES)
+    /// extension, which is not in [`Extensions::default`](crate::Extensions::default).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub substitutes: Vec<String>,
+    /// Category, usually the aisle of the store where it's found
+    pub category: Option<String>,
+    /// Name before [`Recipe::canonicalize_names`] changed it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_name: Option<String>,
+    /// Ingredient of the referenced recipe, like `tomato` in `@@./sauce#tomato{}`
+    ///
+    /// Only with the [`REFERENCE_FRAGMENTS`](crate::Extensions::REFERENCE_FRAGMENTS)
+    /// extension. [`Self::name`] is the recipe without the fragment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_fragment: Option<String>,
+    /// Preparation, like `chopped` in `@onion, chopped{1}`
+    ///
+    /// Only with the [`PREPARATIONS`](crate::Extensions::PREPARATIONS)
+    /// extension. It's not part of [`Self::name`], so the same ingredient
+    /// with different preparations is added only once to an
+    /// [`IngredientList`](crate::ingredient_list::IngredientList).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preparation: Option<String>,
+    /// [`Self::name`] is the title of a recipe, like `@{{Tomato Sauce}}`
+    ///
+    /// Only with the [`TITLE_REFERENCES`](crate::Extensions::TITLE_REFERENCES)
+    /// extension. Resolve it with a [`TitleIndex`](crate::collection::TitleIndex).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub title_reference: bool,
+    /// Nutrition facts, like `cal=40` in `@sugar{10%g}{cal=40}`
+    ///
+    /// Only with the [`NUTRITION`](crate::Extensions::NUTRITION) extension.
+    /// The values are as written, they are not scaled with the recipe.
+    #[serde(default)]
+    pub nutrition: Option<HashMap<String, Value>>,
     /// How the cookware is related to others
     pub relation: IngredientRelation,
     pub(crate) modifiers: Modifiers,
+    /// Location of the ingredient in the source
+    ///
+    /// Only available with the `spans` feature.
+    #[cfg(feature = "spans")]
+    pub span: Span,
 }
 
 impl<V: QuantityValue> Ingredient<V> {
@@ -200,6 +1366,24 @@ impl<V: QuantityValue> Ingredient<V> {
     pub fn modifiers(&self) -> Modifiers {
         self.modifiers
     }
+
+    /// Gets the quantity to buy
+    ///
+    /// This is [`Self::purchase_quantity`] if any, or [`Self::quantity`].
+    ///
+    /// ```
+    /// let recipe = cooklang::parse("Add @milk{2%cups; 1%l} and @salt{1%tsp}.")
+    ///     .unwrap_output()
+    ///     .default_scale();
+    /// let milk = &recipe.ingredients[0];
+    /// assert_eq!(milk.quantity.as_ref().unwrap().to_string(), "2 cups");
+    /// assert_eq!(milk.shopping_quantity().unwrap().to_string(), "1 l");
+    /// let salt = &recipe.ingredients[1];
+    /// assert_eq!(salt.shopping_quantity().unwrap().to_string(), "1 tsp");
+    /// ```
+    pub fn shopping_quantity(&self) -> Option<&Quantity<V>> {
+        self.purchase_quantity.as_ref().or(self.quantity.as_ref())
+    }
 }
 
 impl Ingredient<Value> {
@@ -248,7 +1432,50 @@ impl Ingredient<Value> {
         grouped
     }
 
+    /// Same as [`Self::group_quantities`] but using a [`ConversionCache`]
+    ///
+    /// Use this to group many ingredients with the same converter.
+    pub fn group_quantities_cached(
+        &self,
+        all_ingredients: &[Self],
+        converter: &Converter,
+        cache: &mut ConversionCache,
+    ) -> GroupedQuantity {
+        let mut grouped = GroupedQuantity::default();
+        for q in self.all_quantities(all_ingredients) {
+            grouped.add_cached(q, converter, cache);
+        }
+        let _ = grouped.fit(converter);
+        grouped
+    }
+
+    /// Gets the quantity or a fallback when it doesn't have one
+    ///
+    /// The fallback is chosen with the [`MissingQuantityPolicy`].
+    ///
+    /// ```
+    /// # use cooklang::MissingQuantityPolicy;
+    /// let recipe = cooklang::parse("Add @salt{} and @oil{2%tbsp}.")
+    ///     .into_output()
+    ///     .unwrap()
+    ///     .default_scale();
+    /// let policy = MissingQuantityPolicy::Text("to taste".into());
+    /// let salt = recipe.ingredients[0].quantity_or_default(&policy);
+    /// assert_eq!(salt.to_string(), "to taste");
+    /// let oil = recipe.ingredients[1].quantity_or_default(&policy);
+    /// assert_eq!(oil.to_string(), "2 tbsp");
+    /// ```
+    pub fn quantity_or_default(&self, policy: &MissingQuantityPolicy) -> Cow<'_, ScaledQuantity> {
+        match &self.quantity {
+            Some(q) => Cow::Borrowed(q),
+            None => Cow::Owned(policy.quantity()),
+        }
+    }
+
     /// Gets an iterator over all quantities of this ingredient and its references.
+    ///
+    /// The quantities [inherited](Self::quantity_inherited) from the
+    /// definition are skipped.
     pub fn all_quantities<'a>(
         &'a self,
         all_ingredients: &'a [Self],
@@ -258,13 +1485,70 @@ impl Ingredient<Value> {
                 self.relation
                     .referenced_from()
                     .iter()
-                    .copied()
-                    .map(|i| all_ingredients[i].quantity.as_ref()),
+                    .map(|&i| &all_ingredients[i])
+                    .filter(|igr| !igr.quantity_inherited)
+                    .map(|igr| igr.quantity.as_ref()),
             )
             .flatten()
     }
 }
 
+/// Fallback for ingredients without quantity
+///
+/// See [`Ingredient::quantity_or_default`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum MissingQuantityPolicy {
+    /// A count of 1, without unit
+    #[default]
+    One,
+    /// A text value without unit, like `to taste`
+    Text(String),
+    /// Any quantity
+    Quantity(ScaledQuantity),
+}
+
+impl MissingQuantityPolicy {
+    fn quantity(&self) -> ScaledQuantity {
+        match self {
+            Self::One => Quantity::new(Value::from(1.0), None),
+            Self::Text(text) => Quantity::new(Value::Text(text.clone()), None),
+            Self::Quantity(q) => q.clone(),
+        }
+    }
+}
+
+/// Lowercase singular form of an English ingredient name
+///
+/// A simple normalizer for [`Recipe::canonicalize_names`]. Only regular plurals
+/// are handled, like `tomatoes`, `cherries`, `peaches` and `eggs`.
+///
+/// ```
+/// # use cooklang::model::normalize_name;
+/// assert_eq!(normalize_name("Tomatoes"), "tomato");
+/// assert_eq!(normalize_name("cherries"), "cherry");
+/// assert_eq!(normalize_name("green beans"), "green bean");
+/// assert_eq!(normalize_name("Swiss"), "swiss");
+/// ```
+pub fn normalize_name(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    if let Some(stem) = name.strip_suffix("ies").filter(|s| s.len() > 1) {
+        return format!("{stem}y");
+    }
+    for suffix in ["oes", "ches", "shes", "sses", "xes"] {
+        if let Some(stem) = name.strip_suffix(suffix) {
+            if !stem.is_empty() {
+                return format!("{stem}{}", &suffix[..suffix.len() - 2]);
+            }
+        }
+    }
+    if name.len() > 2 && name.ends_with('s') && !name.ends_with("ss") && !name.ends_with("us") {
+        let mut name = name;
+        name.pop();
+        return name;
+    }
+    name
+}
+
 /// A recipe cookware item
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Cookware<V: QuantityValue = Value> {
@@ -275,12 +1559,21 @@ pub struct Cookware<V: QuantityValue = Value> {
     /// Amount needed
     ///
     /// Note that this is a value, not a quantity, so it doesn't have units.
+    ///
+    /// A single amount doesn't change when scaling, unless the item has the
+    /// [`SCALE`](Modifiers::SCALE) modifier, like `#*pan{2}`. Many values,
+    /// like `#pan{1|2}`, are still selected by the servings.
     pub quantity: Option<V>,
     /// Note
     pub note: Option<String>,
     /// How the cookware is related to others
     pub relation: ComponentRelation,
     pub(crate) modifiers: Modifiers,
+    /// Location of the cookware item in the source
+    ///
+    /// Only available with the `spans` feature.
+    #[cfg(feature = "spans")]
+    pub span: Span,
 }
 
 impl<V: QuantityValue> Cookware<V> {
@@ -538,4 +1831,9 @@ pub struct Timer<V: QuantityValue = Value> {
     /// - If the [`TIMER_REQUIRES_TIME`](crate::Extensions::TIMER_REQUIRES_TIME)
     /// extension is enabled, this is guaranteed to be [`Some`].
     pub quantity: Option<Quantity<V>>,
+    /// Location of the timer in the source
+    ///
+    /// Only available with the `spans` feature.
+    #[cfg(feature = "spans")]
+    pub span: Span,
 }
@@ -1,6 +1,6 @@
 use std::collections::VecDeque;
 
-use super::{token_stream::Token, tokens_span, Event};
+use super::{token_stream::Token, tokens_span, Event, QuantityLints, TextValueOptions};
 use crate::{
     error::SourceDiag,
     lexer::{TokenKind, T},
@@ -23,6 +23,8 @@ pub(crate) struct BlockParser<'t, 'i> {
     pub(crate) input: &'i str,
     pub(crate) extensions: Extensions,
     pub(crate) events: &'t mut VecDeque<Event<'i>>,
+    pub(crate) text_values: TextValueOptions,
+    pub(crate) quantity_lints: QuantityLints,
 }
 
 impl<'t, 'i> BlockParser<'t, 'i> {
@@ -49,6 +51,8 @@ impl<'t, 'i> BlockParser<'t, 'i> {
             input,
             extensions,
             events,
+            text_values: TextValueOptions::default(),
+            quantity_lints: QuantityLints::default(),
         }
     }
 
@@ -153,9 +157,17 @@ impl<'t, 'i> BlockParser<'t, 'i> {
                 }
                 T![escaped] => {
                     t.append_str(&self.input[start..end], start);
-                    debug_assert_eq!(token.len(), 2, "unexpected escaped token length");
-                    start = token.span.start() + 1; // skip "\"
-                    end = token.span.end()
+                    let escaped_start = token.span.start() + 1; // skip "\"
+                    let escaped = &self.input[escaped_start..token.span.end()];
+                    if matches!(escaped, "\n" | "\r\n") {
+                        // line continuation, the lines are concatenated
+                        start = token.span.end();
+                        end = start;
+                    } else {
+                        debug_assert_eq!(token.len(), 2, "unexpected escaped token length");
+                        start = escaped_start;
+                        end = token.span.end();
+                    }
                 }
                 _ => end = token.span.end(),
             }
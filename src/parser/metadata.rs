@@ -1,4 +1,4 @@
-use crate::{error::label, lexer::T};
+use crate::{error::label, lexer::T, text::Text};
 
 use super::{error, warning, BlockParser, Event};
 
@@ -21,6 +21,13 @@ pub(crate) fn metadata_entry<'i>(block: &mut BlockParser<'_, 'i>) -> Option<Even
     let value_pos = block.current_offset();
     let value_tokens = block.consume_rest();
     let value = block.text(value_pos, value_tokens);
+    let comment = value_tokens
+        .iter()
+        .rfind(|t| t.kind == T![line comment])
+        .map(|t| {
+            let text = block.token_str(*t);
+            Text::from_str(&text[2..], t.span.start() + 2)
+        });
 
     // Checks
     if key.is_text_empty() {
@@ -41,7 +48,11 @@ pub(crate) fn metadata_entry<'i>(block: &mut BlockParser<'_, 'i>) -> Option<Even
         );
     }
 
-    Some(Event::Metadata { key, value })
+    Some(Event::Metadata {
+        key,
+        value,
+        comment,
+    })
 }
 
 #[cfg(test)]
@@ -50,7 +61,10 @@ mod tests {
 
     use super::*;
     use crate::{
-        parser::{token_stream::tokens, BlockParser},
+        parser::{
+            token_stream::{tokens, TokenStream},
+            BlockParser,
+        },
         span::Span,
         Extensions,
     };
@@ -63,7 +77,7 @@ mod tests {
         let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
         let entry = metadata_entry(&mut bp).unwrap();
         bp.finish();
-        let Event::Metadata { key, value } = entry else {
+        let Event::Metadata { key, value, .. } = entry else {
             panic!()
         };
         assert_eq!(key.text(), " key");
@@ -81,7 +95,7 @@ mod tests {
         let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
         let entry = metadata_entry(&mut bp).unwrap();
         bp.finish();
-        let Event::Metadata { key, value } = entry else {
+        let Event::Metadata { key, value, .. } = entry else {
             panic!()
         };
         assert_eq!(key.text(), "");
@@ -99,7 +113,7 @@ mod tests {
         let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
         let entry = metadata_entry(&mut bp).unwrap();
         bp.finish();
-        let Event::Metadata { key, value } = entry else {
+        let Event::Metadata { key, value, .. } = entry else {
             panic!()
         };
         assert_eq!(key.text_trimmed(), "key");
@@ -114,7 +128,7 @@ mod tests {
         let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
         let entry = metadata_entry(&mut bp).unwrap();
         bp.finish();
-        let Event::Metadata { key, value } = entry else {
+        let Event::Metadata { key, value, .. } = entry else {
             panic!()
         };
         assert_eq!(key.text_trimmed(), "key");
@@ -132,7 +146,7 @@ mod tests {
         let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
         let entry = metadata_entry(&mut bp).unwrap();
         bp.finish();
-        let Event::Metadata { key, value } = entry else {
+        let Event::Metadata { key, value, .. } = entry else {
             panic!()
         };
         assert!(key.text().is_empty());
@@ -148,4 +162,31 @@ mod tests {
         let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
         assert!(metadata_entry(&mut bp).is_none());
     }
+
+    #[test]
+    fn metadata_comment() {
+        let input = ">> key: value -- a note";
+        let tokens = TokenStream::new(input).collect::<Vec<_>>();
+        let mut events = VecDeque::new();
+        let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
+        let entry = metadata_entry(&mut bp).unwrap();
+        bp.finish();
+        let Event::Metadata { value, comment, .. } = entry else {
+            panic!()
+        };
+        assert_eq!(value.text_trimmed(), "value");
+        let comment = comment.unwrap();
+        assert_eq!(comment.text_trimmed(), "a note");
+        assert_eq!(comment.span(), Span::new(16, 23));
+
+        let input = ">> key: value";
+        let tokens = tokens![meta.2, ws.1, word.3, :.1, ws.1, word.5];
+        let mut events = VecDeque::new();
+        let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
+        let Event::Metadata { comment, .. } = metadata_entry(&mut bp).unwrap() else {
+            panic!()
+        };
+        bp.finish();
+        assert!(comment.is_none());
+    }
 }
@@ -58,35 +58,56 @@ mod model;
 mod quantity;
 mod section;
 mod step;
+mod step_group;
 mod text_block;
 mod token_stream;
 
 pub use model::*;
+pub use quantity::LEADING_UNIT_SYMBOLS;
+pub use token_stream::{Token, TokenKind};
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 use crate::{
+    analysis::ParseOptions,
     error::SourceDiag,
     lexer::T,
     located::Located,
     parser::{
-        metadata::metadata_entry, section::section, step::parse_step, text_block::parse_text_block,
+        metadata::metadata_entry, section::section, step::parse_step, step_group::step_group,
+        text_block::parse_text_block,
     },
     span::Span,
-    text::Text,
+    text::{Text, TextFragment},
     Extensions,
 };
 
 pub(crate) use block_parser::BlockParser;
-use token_stream::{Token, TokenStream};
+pub(crate) use quantity::{parse_numeric_value, parse_value_unit};
+pub(crate) use token_stream::TokenStream;
 
 /// Events generated by [`PullParser`]
 #[derive(Debug, Clone, PartialEq)]
 pub enum Event<'i> {
     /// Metadata entry (single line block)
-    Metadata { key: Text<'i>, value: Text<'i> },
+    ///
+    /// `comment` is the text of a trailing line comment, without the `--`.
+    Metadata {
+        key: Text<'i>,
+        value: Text<'i>,
+        comment: Option<Text<'i>>,
+    },
     /// Section (single line block)
-    Section { name: Option<Text<'i>> },
+    ///
+    /// `optional` is true when the name starts with `?`, like `= ?Garnish`.
+    Section {
+        name: Option<Text<'i>>,
+        optional: bool,
+    },
+    /// Title of a group of steps (single line block)
+    ///
+    /// Only with the [`STEP_GROUPS`](crate::Extensions::STEP_GROUPS) extension.
+    StepGroup { title: Text<'i> },
     /// Start of an element that can contain others.
     ///
     /// If this is emitted, a later [`Event::End`] of the same kind is
@@ -100,7 +121,7 @@ pub enum Event<'i> {
     /// Text item
     Text(Text<'i>),
     /// Ingredient item
-    Ingredient(Located<Ingredient<'i>>),
+    Ingredient(Box<Located<Ingredient<'i>>>),
     /// Cookware item
     Cookware(Located<Cookware<'i>>),
     /// Timer item
@@ -144,6 +165,9 @@ where
     block: Vec<Token>,
     queue: VecDeque<Event<'i>>,
     extensions: Extensions,
+    text_values: TextValueOptions,
+    quantity_lints: QuantityLints,
+    comment_notes: bool,
 }
 
 impl<'i> PullParser<'i, TokenStream<'i>> {
@@ -153,6 +177,90 @@ impl<'i> PullParser<'i, TokenStream<'i>> {
     }
 }
 
+/// Runtime configuration of the parser extensions
+///
+/// The flags are named like an extension, for example `STEP_GROUPS` or
+/// `step_groups`, and enable or disable that extension. Flags with other
+/// names are ignored. Other parser behaviours are options in
+/// [`ParseOptions`].
+///
+/// ```
+/// # use cooklang::{CooklangParser, Extensions, ParserConfig};
+/// let config = ParserConfig::new().with_flag("step_groups", true);
+/// let parser = CooklangParser::default().with_config(config);
+/// assert!(parser.extensions().contains(Extensions::STEP_GROUPS));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParserConfig {
+    /// Flag name and if it's enabled
+    pub flags: HashMap<String, bool>,
+}
+
+impl ParserConfig {
+    /// Empty configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a flag
+    pub fn with_flag(mut self, name: impl Into<String>, enabled: bool) -> Self {
+        self.flags.insert(name.into(), enabled);
+        self
+    }
+
+    /// Checks a flag
+    ///
+    /// `None` if it is not set.
+    pub fn flag(&self, name: &str) -> Option<bool> {
+        self.flags.get(name).copied()
+    }
+
+    /// Applies the flags named like an extension to `extensions`
+    pub fn apply(&self, mut extensions: Extensions) -> Extensions {
+        for (name, &enabled) in &self.flags {
+            if let Some(ext) = Extensions::from_name(&name.to_ascii_uppercase()) {
+                extensions.set(ext, enabled);
+            }
+        }
+        extensions
+    }
+}
+
+/// How the text values of quantities are cleaned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TextValueOptions {
+    pub trim: bool,
+    pub collapse_whitespace: bool,
+}
+
+impl Default for TextValueOptions {
+    fn default() -> Self {
+        Self {
+            trim: true,
+            collapse_whitespace: false,
+        }
+    }
+}
+
+/// Optional warnings for suspicious quantity values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct QuantityLints {
+    pub zero: bool,
+    pub negative: bool,
+    /// `{2%}`, which is a count when units are required
+    pub empty_unit: bool,
+}
+
+impl Default for QuantityLints {
+    fn default() -> Self {
+        Self {
+            zero: false,
+            negative: true,
+            empty_unit: true,
+        }
+    }
+}
+
 impl<'i, T> PullParser<'i, T>
 where
     T: Iterator<Item = Token>,
@@ -163,10 +271,37 @@ where
             tokens: tokens.peekable(),
             block: Vec::new(),
             extensions,
+            text_values: TextValueOptions::default(),
+            quantity_lints: QuantityLints::default(),
+            comment_notes: false,
             queue: VecDeque::new(),
         }
     }
 
+    /// Applies a [`ParserConfig`] to the parser
+    pub fn with_config(mut self, config: &ParserConfig) -> Self {
+        self.extensions = config.apply(self.extensions);
+        self
+    }
+
+    /// Applies the parser behaviours of a [`ParseOptions`] to the parser
+    ///
+    /// [`CooklangParser`](crate::CooklangParser) does this with the options
+    /// given to it.
+    pub fn with_options(mut self, options: &ParseOptions) -> Self {
+        self.text_values = TextValueOptions {
+            trim: !options.keep_text_whitespace,
+            collapse_whitespace: options.collapse_text_whitespace,
+        };
+        self.quantity_lints = QuantityLints {
+            zero: options.warn_zero_quantities,
+            negative: !options.delta_quantities,
+            empty_unit: !options.require_units,
+        };
+        self.comment_notes = options.comment_notes;
+        self
+    }
+
     /// Transforms the parser into another [`Event`] iterator that only
     /// generates [`Event::Metadata`] blocks.
     ///
@@ -186,6 +321,49 @@ fn is_empty_token(tok: &Token) -> bool {
     )
 }
 
+/// Emits a text block with the comments of an empty line
+///
+/// Returns `false` if the line has no comments.
+fn comment_note<'i>(input: &'i str, line: &[Token], queue: &mut VecDeque<Event<'i>>) -> bool {
+    let mut note: Option<Text<'i>> = None;
+    for tok in line {
+        let range = match tok.kind {
+            T![line comment] => tok.span.start() + 2..tok.span.end(),
+            T![block comment] => {
+                let end = if input[tok.span.range()].ends_with("-]") {
+                    tok.span.end() - 2
+                } else {
+                    tok.span.end()
+                };
+                tok.span.start() + 2..end.max(tok.span.start() + 2)
+            }
+            _ => continue,
+        };
+        let content = &input[range.clone()];
+        let trimmed = content.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let offset = range.start + (content.len() - content.trim_start().len());
+        match &mut note {
+            // the comments in the same line are separated by a space
+            Some(note) => {
+                let prev_end = note.span().end();
+                note.append_fragment(TextFragment::soft_break(&input[prev_end..offset], prev_end));
+                note.append_str(trimmed, offset);
+            }
+            None => note = Some(Text::from_str(trimmed, offset)),
+        }
+    }
+    let Some(note) = note else {
+        return false;
+    };
+    queue.push_back(Event::Start(BlockKind::Text));
+    queue.push_back(Event::Text(note));
+    queue.push_back(Event::End(BlockKind::Text));
+    true
+}
+
 fn is_single_line_marker(first: Option<&Token>) -> bool {
     matches!(first, Some(mt![meta | =]))
 }
@@ -238,6 +416,10 @@ where
 
         // Eat empty lines
         while current_line.is_empty {
+            if self.comment_notes && comment_note(self.input, &self.block[start..], &mut self.queue)
+            {
+                return Some(());
+            }
             start = self.block.len();
             current_line = self.pull_line()?;
         }
@@ -245,6 +427,8 @@ where
         // Check if more lines have to be consumed
         let multiline = multiline_ext && !current_line.is_single_line;
         end = self.block.len();
+        // empty line that ended the block, it may have a comment
+        let mut ending_line = None;
         if multiline {
             loop {
                 if is_single_line_marker(self.tokens.peek()) {
@@ -252,7 +436,10 @@ where
                 }
                 match self.pull_line() {
                     None => break,
-                    Some(line) if line.is_empty => break,
+                    Some(line) if line.is_empty => {
+                        ending_line = Some(end..self.block.len());
+                        break;
+                    }
                     _ => {}
                 }
                 end = self.block.len();
@@ -273,9 +460,15 @@ where
         }
 
         let mut bp = BlockParser::new(trimmed_block, self.input, &mut self.queue, self.extensions);
+        bp.text_values = self.text_values;
+        bp.quantity_lints = self.quantity_lints;
         parse_block(&mut bp);
         bp.finish();
 
+        if let Some(range) = ending_line.filter(|_| self.comment_notes) {
+            comment_note(self.input, &self.block[range], &mut self.queue);
+        }
+
         Some(())
     }
 
@@ -369,6 +562,8 @@ fn parse_multiline_block(bp: &mut BlockParser) {
 
     if is_text {
         parse_text_block(bp);
+    } else if let Some(ev) = bp.with_recover(step_group) {
+        bp.event(ev);
     } else {
         parse_step(bp);
     }
@@ -432,11 +627,13 @@ mod tests {
             vec![
                 Event::Metadata {
                     key: Text::from_str(" entry", 2),
-                    value: Text::from_str(" true", 10)
+                    value: Text::from_str(" true", 10),
+                    comment: None,
                 },
                 Event::Metadata {
                     key: Text::from_str(" entry2", 126),
-                    value: Text::from_str(" uwu", 134)
+                    value: Text::from_str(" uwu", 134),
+                    comment: None,
                 },
             ]
         );
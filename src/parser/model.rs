@@ -1,4 +1,10 @@
-use crate::{error::Recover, located::Located, quantity::Value, span::Span, text::Text};
+use crate::{
+    error::Recover,
+    located::Located,
+    quantity::{QuantityValue as _, Value},
+    span::Span,
+    text::Text,
+};
 
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
@@ -15,7 +21,15 @@ pub enum Block<'a> {
     /// Section divider
     ///
     /// In the ast, a section does not own steps, it just exists in between.
-    Section { name: Option<Text<'a>> },
+    Section {
+        name: Option<Text<'a>>,
+        /// The name starts with `?`
+        optional: bool,
+    },
+    /// Title of a group of steps
+    ///
+    /// Like sections, it does not own the steps after it.
+    StepGroup { title: Text<'a> },
     /// Recipe step
     Step {
         /// Items that compose the step.
@@ -62,10 +76,66 @@ pub struct Ingredient<'a> {
     ///
     /// If any of those modifiers is present, this will be.
     pub intermediate_data: Option<Located<IntermediateData>>,
+    /// Variant label, like `vegan` in `@[vegan]butter{}`
+    ///
+    /// Only with the [`VARIANTS`](crate::Extensions::VARIANTS) extension.
+    pub variant: Option<Text<'a>>,
     pub name: Text<'a>,
     pub alias: Option<Text<'a>>,
     pub quantity: Option<Located<Quantity<'a>>>,
+    /// Other quantities after the first one separated by `/`
+    ///
+    /// Only with the [`ALTERNATIVE_QUANTITIES`](crate::Extensions::ALTERNATIVE_QUANTITIES)
+    /// extension. If not empty, [`Self::quantity`] is [`Some`].
+    pub alternative_quantities: Vec<Located<Quantity<'a>>>,
+    /// Number of units, like the `2` in `@eggs{2 x 50%g}`
+    ///
+    /// Only with the [`PER_UNIT_QUANTITIES`](crate::Extensions::PER_UNIT_QUANTITIES)
+    /// extension. If [`Some`], [`Self::quantity`] is [`Some`] and it's the
+    /// quantity of each unit. It's always a number or a range.
+    pub count: Option<Located<Value>>,
+    /// Quantity to buy, like the `1%l` in `@milk{2%cups; 1%l}`
+    ///
+    /// Only with the [`PURCHASE_QUANTITIES`](crate::Extensions::PURCHASE_QUANTITIES)
+    /// extension. If [`Some`], [`Self::quantity`] is [`Some`].
+    pub purchase_quantity: Option<Located<Quantity<'a>>>,
     pub note: Option<Text<'a>>,
+    /// Substitutes, like `margarine` in `@butter{}(or margarine)`
+    ///
+    /// Only with the [`SUBSTITUTES`](crate::Extensions::SUBSTITUTES)
+    /// extension. If not empty, [`Self::note`] is [`None`].
+    pub substitutes: Vec<Located<String>>,
+    /// Category, like `baking` in `@flour{}[baking]`
+    ///
+    /// Only with the [`CATEGORIES`](crate::Extensions::CATEGORIES) extension.
+    pub category: Option<Text<'a>>,
+    /// Nutrition facts, like `cal=40` in `@sugar{10%g}{cal=40}`
+    ///
+    /// Only with the [`NUTRITION`](crate::Extensions::NUTRITION) extension.
+    pub nutrition: Option<Vec<NutritionFact<'a>>>,
+    /// Ingredient of the referenced recipe, like `tomato` in `@@./sauce#tomato{}`
+    ///
+    /// Only with the [`REFERENCE_FRAGMENTS`](crate::Extensions::REFERENCE_FRAGMENTS)
+    /// extension and the recipe modifier.
+    pub reference_fragment: Option<Text<'a>>,
+    /// Preparation, like `chopped` in `@onion, chopped{1}`
+    ///
+    /// Only with the [`PREPARATIONS`](crate::Extensions::PREPARATIONS)
+    /// extension. It's not part of [`Self::name`].
+    pub preparation: Option<Text<'a>>,
+    /// The name is the title of a recipe, like `@{{Tomato Sauce}}`
+    ///
+    /// Only with the [`TITLE_REFERENCES`](crate::Extensions::TITLE_REFERENCES)
+    /// extension.
+    pub title_reference: bool,
+}
+
+/// A `key=value` nutrition fact of an [`Ingredient`]
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct NutritionFact<'a> {
+    pub key: Text<'a>,
+    /// Always a number or a range
+    pub value: Located<Value>,
 }
 
 /// Cookware [`Item`]
@@ -104,6 +174,18 @@ pub struct Quantity<'a> {
     ///
     /// It's just the text, no checks
     pub unit: Option<Text<'a>>,
+    /// Unit text of the start of a range, when each end has its own unit
+    ///
+    /// Only with the [`RANGE_UNITS`](crate::Extensions::RANGE_UNITS)
+    /// extension. When this is [`Some`], [`Self::unit`] is the unit of the end
+    /// of the range and is also [`Some`].
+    pub start_unit: Option<Text<'a>>,
+    /// What the quantity is for each of, like `serving` in `200 ml/serving`
+    ///
+    /// Only with the [`ADVANCED_UNITS`](crate::Extensions::ADVANCED_UNITS)
+    /// extension. When this is [`Some`], [`Self::unit`] is also [`Some`] and
+    /// doesn't include it.
+    pub per: Option<Text<'a>>,
 }
 
 /// Quantity value(s)
@@ -143,6 +225,28 @@ impl QuantityValue {
             }
         }
     }
+
+    /// Check if every value is a number or a range
+    pub fn is_numeric(&self) -> bool {
+        self.values().all(|v| !v.is_text())
+    }
+
+    /// Check if any value is text
+    pub fn contains_text(&self) -> bool {
+        self.values().any(|v| v.is_text())
+    }
+
+    /// Iterate over the contained values
+    ///
+    /// Yields one item for [`QuantityValue::Single`] and every value for
+    /// [`QuantityValue::Many`].
+    pub fn values(&self) -> impl Iterator<Item = &Located<Value>> {
+        let values: &[Located<Value>] = match self {
+            QuantityValue::Single { value, .. } => std::slice::from_ref(value),
+            QuantityValue::Many(v) => v,
+        };
+        values.iter()
+    }
 }
 
 impl Recover for Text<'_> {
@@ -156,6 +260,8 @@ impl Recover for Quantity<'_> {
         Self {
             value: Recover::recover(),
             unit: Recover::recover(),
+            start_unit: None,
+            per: None,
         }
     }
 }
@@ -191,6 +297,8 @@ bitflags! {
         const OPT            = 1 << 3;
         /// forces to create a new ingredient
         const NEW            = 1 << 4;
+        /// scales the cookware amount with the servings, only for cookware
+        const SCALE          = 1 << 5;
     }
 }
 
@@ -215,6 +323,10 @@ impl Modifiers {
     pub fn is_reference(&self) -> bool {
         self.contains(Modifiers::REF)
     }
+
+    pub fn is_scaled(&self) -> bool {
+        self.contains(Modifiers::SCALE)
+    }
 }
 
 impl std::fmt::Display for Modifiers {
@@ -227,7 +339,7 @@ impl std::fmt::Display for Modifiers {
 ///
 /// This is not checked, and may point to inexistent or future steps/sections
 /// which is invalid.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IntermediateData {
     /// The mode in which `val` works
     pub ref_mode: IntermediateRefMode,
@@ -246,11 +358,13 @@ pub struct IntermediateData {
     /// [`Section`]: IntermediateTargetKind::Section
     /// [`Number`]: IntermediateRefMode::Number
     /// [`Relative`]: IntermediateRefMode::Relative
+    ///
+    /// With [`IntermediateRefMode::Name`] this is always 0.
     pub val: i16,
 }
 
 /// How to treat the value in [`IntermediateData`]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum IntermediateRefMode {
     /// Step or section number
     Number,
@@ -258,6 +372,10 @@ pub enum IntermediateRefMode {
     ///
     /// When it is steps, is number of non text steps back.
     Relative,
+    /// Section name, like `Dough` in `@&(=Dough)dough{}`
+    ///
+    /// Only sections can be referenced by name.
+    Name(String),
 }
 
 /// What the target of [`IntermediateData`] is
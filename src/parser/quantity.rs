@@ -1,3 +1,6 @@
+use std::{collections::VecDeque, num::IntErrorKind};
+
+use either::Either;
 use smallvec::SmallVec;
 
 use crate::{
@@ -5,12 +8,19 @@ use crate::{
     error::{label, SourceDiag},
     lexer::T,
     located::Located,
-    quantity::{Number, Value},
+    quantity::{add_numbers, div_numbers, mul_numbers, Number, Value},
     span::Span,
+    text::{Text, TextFragment},
     Extensions,
 };
 
-use super::{error, model::*, mt, token_stream::Token, tokens_span, warning, BlockParser};
+use super::{
+    error,
+    model::*,
+    mt,
+    token_stream::{Token, TokenStream},
+    tokens_span, warning, BlockParser, TextValueOptions,
+};
 
 pub struct ParsedQuantity<'a> {
     pub quantity: Located<Quantity<'a>>,
@@ -27,13 +37,259 @@ pub(crate) fn parse_quantity<'i>(
 
     // create an insolated sub-block for the quantity tokens
     let mut bp2 = BlockParser::new(tokens, bp.input, bp.events, bp.extensions);
+    bp2.text_values = bp.text_values;
+    bp2.quantity_lints = bp.quantity_lints;
+
+    if bp2.extension(Extensions::LEADING_UNITS) {
+        if let Some(q) = bp2.with_recover(parse_symbol_unit_quantity) {
+            lint_quantity(&mut bp2, &q.quantity);
+            return q;
+        }
+    }
+
+    let advanced = if bp2.extension(Extensions::ADVANCED_UNITS) {
+        let range_units = if bp2.extension(Extensions::RANGE_UNITS | Extensions::RANGE_VALUES) {
+            bp2.with_recover(parse_range_units_quantity)
+        } else {
+            None
+        };
+        range_units.or_else(|| bp2.with_recover(parse_advanced_quantity))
+    } else {
+        None
+    };
+
+    let mut parsed = advanced.unwrap_or_else(|| parse_regular_quantity(&mut bp2));
+    if bp2.extension(Extensions::ADVANCED_UNITS) {
+        parsed.quantity.per = split_per_unit(&mut parsed.quantity.unit);
+    }
+    lint_quantity(&mut bp2, &parsed.quantity);
+    parsed
+}
+
+/// Warns about values that are almost always a mistake, if enabled in the
+/// [`ParserConfig`](super::ParserConfig)
+fn lint_quantity(bp: &mut BlockParser, quantity: &Quantity) {
+    if !bp.quantity_lints.zero {
+        return;
+    }
+    for value in quantity.value.values() {
+        if value.is_zero() {
+            bp.warn(
+                warning!(
+                    "Quantity value is zero",
+                    label!(value.span(), "this is zero")
+                )
+                .hint("Write the amount that is needed or remove the quantity"),
+            );
+        }
+    }
+}
+
+/// Splits what a unit is for each of, like `serving` in `ml/serving`
+///
+/// It has to be a single word right after the `/`, and the `/` right after the
+/// unit. Otherwise the `/` is part of the unit.
+fn split_per_unit<'i>(unit: &mut Option<Text<'i>>) -> Option<Text<'i>> {
+    let (head, per_unit) = unit.as_ref()?.split_once('/')?;
+    let head_text = head.text();
+    let per_unit_text = per_unit.text();
+    let per_unit_word = per_unit_text.trim_end();
+    if head_text.trim().is_empty()
+        || head_text.ends_with(char::is_whitespace)
+        || per_unit_word.is_empty()
+        || per_unit_word
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_numeric() || c == '/')
+    {
+        return None;
+    }
+    *unit = Some(head);
+    Some(per_unit)
+}
+
+/// Timer duration written in ISO 8601, like `PT1H30M`
+///
+/// The value is converted to minutes, or to seconds if it doesn't fit in
+/// whole minutes. Returns [`None`] if the tokens are not a duration.
+pub(crate) fn iso_duration_quantity<'i>(
+    bp: &BlockParser<'_, 'i>,
+    tokens: &[Token],
+) -> Option<ParsedQuantity<'i>> {
+    let tokens = trim_tokens(tokens);
+    if tokens.is_empty() {
+        return None;
+    }
+    let seconds = parse_iso_duration(bp.slice_str(tokens))?;
+    let (value, unit) = if seconds % 60.0 == 0.0 {
+        (seconds / 60.0, "min")
+    } else {
+        (seconds, "s")
+    };
+
+    // the unit is not in the source, so it points to the start of the
+    // duration, which is always longer than the unit
+    let span = tokens_span(tokens);
+    let mut unit_text = Text::empty(span.start());
+    unit_text.append_fragment(TextFragment::new(unit, span.start()));
+
+    Some(ParsedQuantity {
+        quantity: Located::new(
+            Quantity {
+                value: QuantityValue::Single {
+                    value: Located::new(Value::from(value), span),
+                    auto_scale: None,
+                },
+                unit: Some(unit_text),
+                start_unit: None,
+                per: None,
+            },
+            span,
+        ),
+        unit_separator: None,
+    })
+}
+
+/// Parses an ISO 8601 duration into seconds
+///
+/// Only weeks, days and the time part are supported, years and months
+/// don't have a fixed duration.
+fn parse_iso_duration(s: &str) -> Option<f64> {
+    let rest = s.strip_prefix('P')?;
+    let (date, time) = match rest.split_once('T') {
+        Some((_, "")) => return None,
+        Some((date, time)) => (date, time),
+        None => (rest, ""),
+    };
+
+    let mut seconds = 0.0;
+    let mut components = 0;
+    let mut add = |part: &str, designators: &[(char, f64)]| -> Option<()> {
+        // designators have to be in order
+        let mut designators = designators.iter();
+        let mut number_start = 0;
+        for (i, c) in part.char_indices() {
+            if c.is_ascii_digit() || c == '.' {
+                continue;
+            }
+            let &(_, factor) = designators.find(|(d, _)| *d == c)?;
+            let value = part[number_start..i].parse::<f64>().ok()?;
+            seconds += value * factor;
+            components += 1;
+            number_start = i + c.len_utf8();
+        }
+        (number_start == part.len()).then_some(())
+    };
+    add(date, &[('W', 7.0 * 86400.0), ('D', 86400.0)])?;
+    add(time, &[('H', 3600.0), ('M', 60.0), ('S', 1.0)])?;
+    (components > 0).then_some(seconds)
+}
+
+/// Splits the count from the quantity of each unit in `2 x 50%g`
+///
+/// The count is a number or a range before an `x` (or `×`) and the rest of
+/// the tokens must be a quantity with a numeric value. Returns [`None`] if
+/// it's not a count, like `2 x large`, so the tokens are a regular quantity.
+pub(crate) fn split_count<'t>(
+    bp: &mut BlockParser,
+    tokens: &'t [Token],
+) -> Option<(Located<Value>, &'t [Token])> {
+    let mid = tokens
+        .iter()
+        .position(|t| t.kind == T![word] && matches!(bp.token_str(*t), "x" | "×"))?;
+    let (count, rest) = tokens.split_at(mid);
+    let rest = &rest[1..];
+    if !is_numeric_quantity(rest, bp) {
+        return None;
+    }
+    let count = trim_tokens(count);
+    let value = match range_value(count, bp).or_else(|| numeric_value(count, bp))? {
+        Ok(value) => value,
+        Err(err) => {
+            bp.error(err);
+            Value::recover()
+        }
+    };
+    Some((Located::new(value, tokens_span(count)), rest))
+}
+
+/// Checks if the value of the quantity in the tokens is a number, like `50%g`
+fn is_numeric_quantity(tokens: &[Token], bp: &BlockParser) -> bool {
+    let value = match tokens.iter().position(|t| t.kind == T![%]) {
+        Some(sep) => &tokens[..sep],
+        None => split_value_unit(tokens).map_or(tokens, |(value, _)| value),
+    };
+    // the auto scale marker is not part of the value
+    let value = match trim_tokens(value) {
+        [value @ .., marker] if marker.kind == T![*] => value,
+        value => value,
+    };
+    matches!(numeric_value(value, bp), Some(Ok(_)))
+}
+
+/// Splits the purchase quantity from the recipe one in `2%cups; 1%l`
+///
+/// Both sides of the `;` must not be empty. Returns [`None`] if there is no
+/// purchase quantity, so the tokens are a regular quantity.
+pub(crate) fn split_purchase_quantity<'t>(
+    bp: &BlockParser,
+    tokens: &'t [Token],
+) -> Option<(&'t [Token], &'t [Token])> {
+    let mid = tokens
+        .iter()
+        .position(|t| t.kind == T![punctuation] && bp.token_str(*t) == ";")?;
+    let (quantity, purchase) = (&tokens[..mid], &tokens[mid + 1..]);
+    if trim_tokens(quantity).is_empty() || trim_tokens(purchase).is_empty() {
+        return None;
+    }
+    Some((quantity, purchase))
+}
 
-    let advanced = bp2
-        .extension(Extensions::ADVANCED_UNITS)
-        .then(|| bp2.with_recover(parse_advanced_quantity))
-        .flatten();
+/// Parses a standalone `<value> [<unit>]` like `12 muffins` or `1-2 loaves`
+///
+/// This is for values outside the recipe steps, like metadata, so it returns
+/// [`None`] instead of reporting errors. The value must be numeric.
+pub(crate) fn parse_value_unit(input: &str) -> Option<(Value, Option<String>)> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+    let tokens = TokenStream::new(input).collect::<Vec<_>>();
+    let mut events = VecDeque::new();
+    let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::RANGE_VALUES);
+
+    let (value, unit) = match split_value_unit(&tokens) {
+        Some((value, unit)) => (value, Some(unit)),
+        None => (tokens.as_slice(), None),
+    };
+    let value = range_value(value, &mut bp)
+        .or_else(|| numeric_value(value, &bp))?
+        .ok()?;
+    let unit = unit.map(|unit| {
+        bp.text(unit.first().unwrap().span.start(), unit)
+            .text_trimmed()
+            .into_owned()
+    });
+    Some((value, unit))
+}
 
-    advanced.unwrap_or_else(|| parse_regular_quantity(&mut bp2))
+/// Parses a standalone numeric value like `1/2` or `2-3`
+///
+/// Returns [`None`] if the input is not numeric. Ranges and tolerances are
+/// always allowed. Variables, like `$amount`, are parsed too, so a displayed
+/// [`Value::Variable`] is parsed back.
+pub(crate) fn parse_numeric_value(input: &str) -> Option<Result<Value, SourceDiag>> {
+    let tokens = TokenStream::new(input).collect::<Vec<_>>();
+    let mut events = VecDeque::new();
+    let mut bp = BlockParser::new(
+        &tokens,
+        input,
+        &mut events,
+        Extensions::RANGE_VALUES | Extensions::TOLERANCES | Extensions::VARIABLES,
+    );
+    tolerance_value(&tokens, &bp)
+        .or_else(|| range_value(&tokens, &mut bp))
+        .or_else(|| numeric_value(&tokens, &bp))
+        .or_else(|| variable_value(&tokens, &bp))
 }
 
 fn parse_regular_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> ParsedQuantity<'i> {
@@ -51,7 +307,7 @@ fn parse_regular_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> ParsedQuantity<'i
         _ => {
             bp.consume_while(|t| t != T![%]);
             let text = bp.text(bp.span().start(), bp.parsed());
-            let text_val = Value::Text(text.text_trimmed().into_owned());
+            let text_val = clean_text_value(&text, bp.text_values);
             value = QuantityValue::Single {
                 value: Located::new(text_val, text.span()),
                 auto_scale: None,
@@ -69,20 +325,34 @@ fn parse_regular_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> ParsedQuantity<'i
     let (unit_separator, mut unit) = unit.unzip();
     if let Some(unit_text) = &unit {
         if unit_text.is_text_empty() {
-            bp.warn(
-                warning!(
-                    "Empty quantity unit",
-                    label!(unit_text.span(), "add unit here")
-                )
-                .label(label!(unit_separator.unwrap(), "or remove this"))
-                .hint("It will be as if the quantity has no unit"),
-            );
+            if bp.quantity_lints.empty_unit {
+                bp.warn(
+                    warning!(
+                        "Empty quantity unit",
+                        label!(unit_text.span(), "add unit here")
+                    )
+                    .label(label!(unit_separator.unwrap(), "or remove this"))
+                    .hint("It will be as if the quantity has no unit")
+                    .with_suggestion(
+                        Span::new(unit_separator.unwrap().start(), unit_text.span().end()),
+                        "",
+                    ),
+                );
+            }
             unit = None;
         }
     }
 
     ParsedQuantity {
-        quantity: Located::new(Quantity { value, unit }, tokens_span(bp.tokens())),
+        quantity: Located::new(
+            Quantity {
+                value,
+                unit,
+                start_unit: None,
+                per: None,
+            },
+            tokens_span(bp.tokens()),
+        ),
         unit_separator,
     }
 }
@@ -97,7 +367,16 @@ fn parse_advanced_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<ParsedQua
     }
 
     bp.ws_comments();
-    let value_tokens = bp.consume_while(|t| !matches!(t, T![word]));
+    let tolerances = bp.extension(Extensions::TOLERANCES);
+    let value_tokens = bp.capture_slice(|bp| {
+        while let Some(&t) = bp.rest().first() {
+            // `±` is a word, but it's part of the value
+            if t.kind == T![word] && !(tolerances && bp.token_str(t) == "±") {
+                break;
+            }
+            bp.bump_any();
+        }
+    });
 
     if value_tokens.is_empty() || value_tokens.last().unwrap().kind != T![ws] {
         return None;
@@ -122,7 +401,10 @@ fn parse_advanced_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<ParsedQua
         Span::new(start, end)
     };
 
-    let result = range_value(value_tokens, bp).or_else(|| numeric_value(value_tokens, bp))?;
+    let result = tolerance_value(value_tokens, bp)
+        .or_else(|| range_value(value_tokens, bp))
+        .or_else(|| sum_value(value_tokens, bp))
+        .or_else(|| numeric_value(value_tokens, bp))?;
     let value = match result {
         Ok(value) => value,
         Err(err) => {
@@ -141,6 +423,106 @@ fn parse_advanced_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<ParsedQua
                     auto_scale: None,
                 },
                 unit: Some(unit),
+                start_unit: None,
+                per: None,
+            },
+            tokens_span(bp.tokens()),
+        ),
+        unit_separator: None,
+    })
+}
+
+/// Symbols that can be a unit before the value, like `$` in `$5`
+pub const LEADING_UNIT_SYMBOLS: &[&str] = &["$", "€", "£", "¥", "¢", "₹", "₩", "₽", "₺", "₪"];
+
+/// Quantity with a symbol as unit before or right after the value, like `$5`
+/// or `5€`
+fn parse_symbol_unit_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<ParsedQuantity<'i>> {
+    let tokens = trim_tokens(bp.consume_rest());
+    let is_symbol =
+        |t: &Token| t.kind == T![word] && LEADING_UNIT_SYMBOLS.contains(&bp.token_str(*t));
+
+    let (unit_token, value_tokens) = match tokens {
+        [first, rest @ ..] if is_symbol(first) => (*first, rest),
+        // with a space it's already a regular advanced unit
+        [rest @ .., last] if is_symbol(last) && !rest.last().is_some_and(|t| t.kind == T![ws]) => {
+            (*last, rest)
+        }
+        _ => return None,
+    };
+    let value_tokens = trim_tokens(value_tokens);
+    if value_tokens.is_empty() {
+        return None;
+    }
+
+    let value = match range_value(value_tokens, bp).or_else(|| numeric_value(value_tokens, bp))? {
+        Ok(value) => value,
+        Err(err) => {
+            bp.error(err);
+            Value::recover()
+        }
+    };
+    let value = Located::new(value, tokens_span(value_tokens));
+    let unit = bp.text(unit_token.span.start(), &[unit_token]);
+    Some(ParsedQuantity {
+        quantity: Located::new(
+            Quantity {
+                value: QuantityValue::Single {
+                    value,
+                    auto_scale: None,
+                },
+                unit: Some(unit),
+                start_unit: None,
+                per: None,
+            },
+            tokens_span(bp.tokens()),
+        ),
+        unit_separator: None,
+    })
+}
+
+/// Range where each end has it's own unit, like `1 cup - 250 ml`
+fn parse_range_units_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<ParsedQuantity<'i>> {
+    let tokens = bp.consume_rest();
+    if tokens
+        .iter()
+        .any(|t| matches!(t.kind, T![|] | T![*] | T![%]))
+    {
+        return None;
+    }
+
+    let mid = tokens.iter().position(|t| t.kind == T![-])?;
+    let (start_value, start_unit) = split_value_unit(&tokens[..mid])?;
+    let (end_value, end_unit) = split_value_unit(&tokens[mid + 1..])?;
+
+    let value = match (
+        numeric_value(start_value, bp)?,
+        numeric_value(end_value, bp)?,
+    ) {
+        (Ok(Value::Number(start)), Ok(Value::Number(end))) => Value::Range { start, end },
+        (Err(err), _) | (_, Err(err)) => {
+            bp.error(err);
+            Value::recover()
+        }
+        _ => unreachable!("numeric_value not number"),
+    };
+    let value_span = Span::new(
+        start_value.first().unwrap().span.start(),
+        end_value.last().unwrap().span.end(),
+    );
+
+    let start_unit = bp.text(start_unit.first().unwrap().span.start(), start_unit);
+    let end_unit = bp.text(end_unit.first().unwrap().span.start(), end_unit);
+    Some(ParsedQuantity {
+        quantity: Located::new(
+            Quantity {
+                value: QuantityValue::Single {
+                    value: Located::new(value, value_span),
+                    auto_scale: None,
+                },
+                unit: Some(end_unit),
+                start_unit: Some(start_unit),
+                per: None,
             },
             tokens_span(bp.tokens()),
         ),
@@ -148,12 +530,51 @@ fn parse_advanced_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<ParsedQua
     })
 }
 
+/// Splits `<value> <unit>` where the unit starts at the first word. Both
+/// returned slices are trimmed and not empty.
+fn split_value_unit(tokens: &[Token]) -> Option<(&[Token], &[Token])> {
+    let tokens = trim_tokens(tokens);
+    let unit_start = tokens.iter().position(|t| t.kind == T![word])?;
+    let (value, unit) = tokens.split_at(unit_start);
+    if value.last().is_none_or(|t| t.kind != T![ws]) {
+        return None;
+    }
+    let value = trim_tokens(value);
+    if value.is_empty() {
+        return None;
+    }
+    Some((value, unit))
+}
+
 fn many_values(bp: &mut BlockParser) -> QuantityValue {
     let mut values: Vec<Located<Value>> = vec![];
     let mut auto_scale = None;
+    let expressions = bp.extension(Extensions::VALUE_EXPRESSIONS);
 
     loop {
-        let value_tokens = bp.consume_while(|t| !matches!(t, T![|] | T![*] | T![%]));
+        let value_tokens = if expressions {
+            // a `*` is the auto scale marker only at the end of the value
+            let rest = bp.rest();
+            let end = rest
+                .iter()
+                .enumerate()
+                .position(|(i, t)| match t.kind {
+                    T![|] | T![%] => true,
+                    T![*] => rest[i + 1..]
+                        .iter()
+                        .take_while(|t| !matches!(t.kind, T![|] | T![%]))
+                        .all(|t| !not_ws_comment(t)),
+                    _ => false,
+                })
+                .unwrap_or(rest.len());
+            bp.capture_slice(|bp| {
+                for _ in 0..end {
+                    bp.bump_any();
+                }
+            })
+        } else {
+            bp.consume_while(|t| !matches!(t, T![|] | T![*] | T![%]))
+        };
         values.push(parse_value(value_tokens, bp));
 
         match bp.peek() {
@@ -200,8 +621,13 @@ fn parse_value(tokens: &[Token], bp: &mut BlockParser) -> Located<Value> {
     let end = bp.current_offset();
     let span = Span::new(start, end);
 
-    let result = range_value(tokens, bp)
+    let result = tolerance_value(tokens, bp)
+        .or_else(|| range_value(tokens, bp))
+        .or_else(|| negative_value(tokens, bp))
+        .or_else(|| expression_value(tokens, bp))
+        .or_else(|| sum_value(tokens, bp))
         .or_else(|| numeric_value(tokens, bp))
+        .or_else(|| variable_value(tokens, bp))
         .unwrap_or_else(|| Ok(text_value(tokens, start, bp)));
 
     let val = match result {
@@ -215,6 +641,23 @@ fn parse_value(tokens: &[Token], bp: &mut BlockParser) -> Located<Value> {
     Located::new(val, span)
 }
 
+/// Named variable, like `$amount`
+///
+/// The name starts with a letter and is made of letters, digits and `_`.
+fn variable_value(tokens: &[Token], bp: &BlockParser) -> Option<Result<Value, SourceDiag>> {
+    if !bp.extension(Extensions::VARIABLES) {
+        return None;
+    }
+    let tokens = trim_tokens(tokens);
+    if tokens.is_empty() {
+        return None;
+    }
+    let name = bp.slice_str(tokens).strip_prefix('$')?;
+    let valid = name.starts_with(char::is_alphabetic)
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    valid.then(|| Ok(Value::Variable(name.to_string())))
+}
+
 fn text_value(tokens: &[Token], offset: usize, bp: &mut BlockParser) -> Value {
     let text = bp.text(offset, tokens);
     if text.is_text_empty() {
@@ -223,10 +666,34 @@ fn text_value(tokens: &[Token], offset: usize, bp: &mut BlockParser) -> Value {
             label!(text.span(), "add value here"),
         ));
     }
-    Value::Text(text.text_trimmed().into_owned())
+    clean_text_value(&text, bp.text_values)
+}
+
+/// Text value trimmed and collapsed as configured
+fn clean_text_value(text: &Text, options: TextValueOptions) -> Value {
+    let raw = if options.trim {
+        text.text_outer_trimmed()
+    } else {
+        text.text()
+    };
+    if !options.collapse_whitespace {
+        return Value::Text(raw.into_owned());
+    }
+    let mut collapsed = String::with_capacity(raw.len());
+    let mut last_ws = false;
+    for c in raw.chars() {
+        let is_ws = c.is_whitespace();
+        if !is_ws {
+            collapsed.push(c);
+        } else if !last_ws {
+            collapsed.push(' ');
+        }
+        last_ws = is_ws;
+    }
+    Value::Text(collapsed)
 }
 
-fn range_value(tokens: &[Token], bp: &BlockParser) -> Option<Result<Value, SourceDiag>> {
+fn range_value(tokens: &[Token], bp: &mut BlockParser) -> Option<Result<Value, SourceDiag>> {
     if !bp.extension(Extensions::RANGE_VALUES) {
         return None;
     }
@@ -247,9 +714,288 @@ fn range_value(tokens: &[Token], bp: &BlockParser) -> Option<Result<Value, Sourc
 
     let start = unwrap_numeric!(numeric_value(start, bp)?);
     let end = unwrap_numeric!(numeric_value(end, bp)?);
+    if start.value() > end.value() {
+        bp.warn(
+            warning!(
+                "Range start is greater than the end",
+                label!(tokens_span(trim_tokens(tokens)), "this range")
+            )
+            .hint(format!("Swap the values: `{end}-{start}`")),
+        );
+    }
     Some(Ok(Value::Range { start, end }))
 }
 
+/// Negative number, like `-5` or `-1/2`
+///
+/// Negative quantities are unusual, so there is a warning unless
+/// [`ParseOptions::delta_quantities`](crate::ParseOptions::delta_quantities)
+/// is enabled.
+fn negative_value(tokens: &[Token], bp: &mut BlockParser) -> Option<Result<Value, SourceDiag>> {
+    let trimmed = trim_tokens(tokens);
+    let (minus, rest) = trimmed.split_first()?;
+    if minus.kind != T![-] {
+        return None;
+    }
+    let number = match numeric_value(rest, bp)? {
+        Ok(Value::Number(n)) => n,
+        Err(err) => return Some(Err(err)),
+        Ok(_) => unreachable!("numeric_value not number"),
+    };
+    if bp.quantity_lints.negative {
+        bp.warn(
+            warning!(
+                "Negative quantity value",
+                label!(tokens_span(trimmed), "this is negative")
+            )
+            .hint("Negative quantities are unusual in a recipe")
+            .hint("If the recipe lists changes to amounts, enable the `delta_quantities` option"),
+        );
+    }
+    let negated = match number {
+        Number::Regular(n) => Number::Regular(-n),
+        // fractions can't be negative
+        Number::Fraction { .. } => Number::Regular(-number.value()),
+    };
+    Some(Ok(Value::Number(negated)))
+}
+
+/// Number with a tolerance, like `100±5` or `100+-5`
+fn tolerance_value(tokens: &[Token], bp: &BlockParser) -> Option<Result<Value, SourceDiag>> {
+    if !bp.extension(Extensions::TOLERANCES) {
+        return None;
+    }
+
+    let (mid, len) = tokens.iter().enumerate().find_map(|(i, t)| match t.kind {
+        T![word] if bp.token_str(*t) == "±" => Some((i, 1)),
+        T![+] if tokens.get(i + 1).is_some_and(|t| t.kind == T![-]) => Some((i, 2)),
+        _ => None,
+    })?;
+    let (center, rest) = tokens.split_at(mid);
+    let tolerance = &rest[len..];
+
+    let number = |part: &[Token], what: &str| match numeric_value(part, bp) {
+        Some(Ok(Value::Number(n))) => Ok(n),
+        Some(Err(err)) => Err(err),
+        Some(Ok(_)) => unreachable!("numeric_value not number"),
+        None => {
+            let part = trim_tokens(part);
+            let span = if part.is_empty() {
+                tokens_span(&rest[..len])
+            } else {
+                tokens_span(part)
+            };
+            Err(error!(
+                format!("Invalid tolerance value: the {what} is not a number"),
+                label!(span, "expected a number here")
+            ))
+        }
+    };
+    let center = match number(center, "center") {
+        Ok(n) => n,
+        Err(err) => return Some(Err(err)),
+    };
+    let tolerance = match number(tolerance, "tolerance") {
+        Ok(n) => n,
+        Err(err) => return Some(Err(err)),
+    };
+    Some(Ok(Value::Tolerant { center, tolerance }))
+}
+
+/// Sum of numbers, like `1+1/2`
+///
+/// Fractions are kept exact when possible. If any term is not a number, like
+/// in `salt+pepper`, it's not a sum and returns [`None`], so the value is text.
+fn sum_value(tokens: &[Token], bp: &BlockParser) -> Option<Result<Value, SourceDiag>> {
+    if !bp.extension(Extensions::VALUE_SUMS) || !tokens.iter().any(|t| t.kind == T![+]) {
+        return None;
+    }
+
+    let terms = tokens
+        .split(|t| t.kind == T![+])
+        .map(|term| numeric_value(term, bp))
+        .collect::<Option<Vec<_>>>()?;
+    let mut total: Option<Number> = None;
+    for term in terms {
+        let number = match term {
+            Ok(Value::Number(number)) => number,
+            Err(err) => return Some(Err(err)),
+            Ok(_) => unreachable!("numeric_value not number"),
+        };
+        total = Some(match total {
+            Some(total) => add_numbers(total, number),
+            None => number,
+        });
+    }
+    total.map(|total| Ok(Value::Number(total)))
+}
+
+/// Arithmetic expression with parentheses, like `(1+1/2)*2`
+///
+/// `*` and `/` go before `+`. Returns [`None`] if there are no parentheses or
+/// `*`, or if there is anything other than numbers and operators, so sums,
+/// plain numbers and text are parsed as usual.
+fn expression_value(tokens: &[Token], bp: &BlockParser) -> Option<Result<Value, SourceDiag>> {
+    if !bp.extension(Extensions::VALUE_EXPRESSIONS) {
+        return None;
+    }
+    let tokens = trim_tokens(tokens);
+    let has_operators = tokens
+        .iter()
+        .any(|t| matches!(t.kind, T!['('] | T![')'] | T![*]));
+    let only_expression = tokens.iter().all(|t| {
+        matches!(
+            t.kind,
+            T![int] | T![zeroint] | T![.] | T![+] | T![*] | T!['('] | T![')']
+        ) || !not_ws_comment(t)
+            || is_frac_slash(*t, bp)
+    });
+    if !has_operators || !only_expression {
+        return None;
+    }
+
+    let mut expr = Expression { tokens, pos: 0, bp };
+    let result = expr.sum().and_then(|(number, _)| match expr.peek() {
+        None => Ok(Value::Number(number)),
+        Some(t) => Err(error!(
+            "Invalid quantity expression: unexpected token",
+            label!(t.span, "remove this")
+        )
+        .hint("Expressions can only have numbers, `+`, `*`, `/` and parentheses")),
+    });
+    Some(result)
+}
+
+/// Recursive descent parser for [`expression_value`]
+struct Expression<'a, 't, 'i> {
+    tokens: &'a [Token],
+    pos: usize,
+    bp: &'a BlockParser<'t, 'i>,
+}
+
+impl Expression<'_, '_, '_> {
+    /// Next token that is not whitespace or a comment
+    fn peek(&mut self) -> Option<Token> {
+        while self
+            .tokens
+            .get(self.pos)
+            .is_some_and(|t| !not_ws_comment(t))
+        {
+            self.pos += 1;
+        }
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn bump_if(&mut self, f: impl Fn(Token) -> bool) -> Option<Token> {
+        let t = self.peek().filter(|t| f(*t))?;
+        self.pos += 1;
+        Some(t)
+    }
+
+    /// `product ('+' product)*`
+    fn sum(&mut self) -> Result<(Number, Span), SourceDiag> {
+        let (mut total, mut span) = self.product()?;
+        while self.bump_if(|t| t.kind == T![+]).is_some() {
+            let (number, s) = self.product()?;
+            total = add_numbers(total, number);
+            span = Span::new(span.start(), s.end());
+        }
+        Ok((total, span))
+    }
+
+    /// `factor (('*' | '/') factor)*`
+    fn product(&mut self) -> Result<(Number, Span), SourceDiag> {
+        let bp = self.bp;
+        let (mut total, mut span) = self.factor()?;
+        while let Some(op) = self.bump_if(|t| t.kind == T![*] || is_frac_slash(t, bp)) {
+            let (number, s) = self.factor()?;
+            total = if op.kind == T![*] {
+                mul_numbers(total, number)
+            } else if number.value() == 0.0 {
+                return Err(error!("Division by zero", label!(s))
+                    .hint("Change this please, we don't want an infinite amount of anything"));
+            } else {
+                div_numbers(total, number)
+            };
+            span = Span::new(span.start(), s.end());
+        }
+        Ok((total, span))
+    }
+
+    /// `'(' sum ')'` or a number
+    fn factor(&mut self) -> Result<(Number, Span), SourceDiag> {
+        let Some(open) = self.bump_if(|t| t.kind == T!['(']) else {
+            return self.number();
+        };
+        let (number, _) = self.sum()?;
+        match self.bump_if(|t| t.kind == T![')']) {
+            Some(close) => Ok((number, Span::new(open.span.start(), close.span.end()))),
+            None => Err(error!(
+                "Invalid quantity expression: unclosed parenthesis",
+                label!(open.span, "this is not closed")
+            )
+            .hint("Add a `)` where the group ends")),
+        }
+    }
+
+    /// A number, like `2`, `1.5` or the mixed number `1 1/2`
+    fn number(&mut self) -> Result<(Number, Span), SourceDiag> {
+        let Some(first) = self.peek() else {
+            let end = self.tokens.last().map_or(0, |t| t.span.end());
+            return Err(expected_number(Span::pos(end)));
+        };
+        let rest = &self.tokens[self.pos..];
+        let len = match rest {
+            [mt![int], mt![.], mt![int | zeroint], ..] => 3,
+            [mt![.], mt![int | zeroint], ..] => 2,
+            [mt![int], ..] => 1,
+            _ => return Err(expected_number(first.span)),
+        };
+        let tokens = &rest[..len];
+        self.pos += len;
+
+        if len == 1 {
+            let checkpoint = self.pos;
+            let bp = self.bp;
+            let mixed = self.bump_if(|t| t.kind == T![int]).and_then(|a| {
+                self.bump_if(|t| is_frac_slash(t, bp))?;
+                let b = self.bump_if(|t| t.kind == T![int])?;
+                Some((a, b))
+            });
+            match mixed {
+                Some((a, b)) => {
+                    let span = Span::new(first.span.start(), b.span.end());
+                    return mixed_num(first, a, b, bp).map(|n| (n, span));
+                }
+                None => self.pos = checkpoint,
+            }
+        }
+
+        match numeric_value(tokens, self.bp) {
+            Some(Ok(Value::Number(number))) => Ok((number, tokens_span(tokens))),
+            Some(Err(err)) => Err(err),
+            _ => Err(expected_number(tokens_span(tokens))),
+        }
+    }
+}
+
+fn expected_number(span: Span) -> SourceDiag {
+    error!(
+        "Invalid quantity expression: expected a number",
+        label!(span, "expected a number here")
+    )
+}
+
+/// Parses a number or a range, like `2`, `1/2` or `2-3`
+///
+/// Returns [`None`] if the tokens are not numeric.
+pub(crate) fn number_or_range(
+    tokens: &[Token],
+    bp: &mut BlockParser,
+) -> Option<Result<Value, SourceDiag>> {
+    range_value(tokens, bp).or_else(|| numeric_value(tokens, bp))
+}
+
 fn not_ws_comment(t: &Token) -> bool {
     !matches!(t.kind, T![ws] | T![line comment] | T![block comment])
 }
@@ -297,43 +1043,58 @@ fn numeric_value(tokens: &[Token], bp: &BlockParser) -> Option<Result<Value, Sou
     // check complex values
     let r = match *filtered_tokens.as_slice() {
         // mixed number
-        [i @ mt![int], a @ mt![int], mt![/], b @ mt![int]] => mixed_num(i, a, b, bp),
+        [i @ mt![int], a @ mt![int], s, b @ mt![int]] if is_frac_slash(s, bp) => {
+            mixed_num(i, a, b, bp)
+        }
         // frac
-        [a @ mt![int], mt![/], b @ mt![int]] => frac(a, b, bp),
+        [a @ mt![int], s, b @ mt![int]] if is_frac_slash(s, bp) => frac(a, b, bp),
         // other => not numeric
         _ => return None,
     };
     Some(r.map(Value::Number))
 }
 
+/// `/` or the unicode fraction slash `⁄`
+fn is_frac_slash(tok: Token, bp: &BlockParser) -> bool {
+    tok.kind == T![/] || (tok.kind == T![punctuation] && bp.token_str(tok) == "⁄")
+}
+
 fn mixed_num(i: Token, a: Token, b: Token, bp: &BlockParser) -> Result<Number, SourceDiag> {
-    let i = int(i, bp)?;
-    let Number::Fraction { num, den, .. } = frac(a, b, bp)? else {
-        unreachable!()
-    };
-    Ok(Number::Fraction {
-        whole: i,
-        num,
-        den,
-        err: 0.0,
-    })
+    let whole = int_or_float(i, bp)?;
+    match (whole, frac(a, b, bp)?) {
+        (Either::Left(whole), Number::Fraction { num, den, .. }) => Ok(Number::Fraction {
+            whole,
+            num,
+            den,
+            err: 0.0,
+        }),
+        // some part is too large, degrade to a regular number
+        (whole, frac) => Ok(Number::Regular(
+            whole.either(f64::from, |w| w) + frac.value(),
+        )),
+    }
 }
 
 fn frac(a: Token, b: Token, line: &BlockParser) -> Result<Number, SourceDiag> {
     let span = Span::new(a.span.start(), b.span.end());
-    let a = int(a, line)?;
-    let b = int(b, line)?;
+    let den_span = b.span;
+    let a = int_or_float(a, line)?;
+    let b = int_or_float(b, line)?;
 
-    if b == 0 {
-        Err(error!("Division by zero", label!(span))
-            .hint("Change this please, we don't want an infinite amount of anything"))
-    } else {
-        Ok(Number::Fraction {
+    match (a, b) {
+        (_, Either::Left(0)) => Err(error!("Division by zero", label!(span))
+            .hint("Change this please, we don't want an infinite amount of anything")
+            .with_suggestion(den_span, "1")),
+        (Either::Left(num), Either::Left(den)) => Ok(Number::Fraction {
             whole: 0,
-            num: a,
-            den: b,
+            num,
+            den,
             err: 0.0,
-        })
+        }),
+        // some part is too large, degrade to a regular number
+        (a, b) => Ok(Number::Regular(
+            a.either(f64::from, |a| a) / b.either(f64::from, |b| b),
+        )),
     }
 }
 
@@ -342,7 +1103,22 @@ fn int(tok: Token, block: &BlockParser) -> Result<u32, SourceDiag> {
     block
         .token_str(tok)
         .parse()
-        .map_err(|e| error!("Error parsing integer number", label!(tok.span)).set_source(e))
+        .map_err(|e: std::num::ParseIntError| {
+            let diag = if *e.kind() == IntErrorKind::PosOverflow {
+                error!("Integer number too large", label!(tok.span))
+                    .hint(format!("The maximum value is {}", u32::MAX))
+            } else {
+                error!("Error parsing integer number", label!(tok.span))
+            };
+            diag.set_source(e)
+        })
+}
+
+/// Same as [`int`] but if the number is too large it's parsed as a float
+fn int_or_float(tok: Token, bp: &BlockParser) -> Result<Either<u32, f64>, SourceDiag> {
+    int(tok, bp)
+        .map(Either::Left)
+        .or_else(|err| float(&[tok], bp).map(Either::Right).map_err(|_| err))
 }
 
 fn float(tokens: &[Token], bp: &BlockParser) -> Result<f64, SourceDiag> {
@@ -526,6 +1302,217 @@ mod tests {
         assert_eq!(q.unit, None);
     }
 
+    #[test]
+    fn range_units() {
+        let (q, s, ctx) = t!("1 cup - 250 ml");
+        assert_eq!(
+            q.value,
+            QuantityValue::Single {
+                value: Located::new(range!(1.0, 250.0), 0..11),
+                auto_scale: None
+            }
+        );
+        assert_eq!(s, None);
+        assert_eq!(q.start_unit.unwrap().text(), "cup");
+        assert_eq!(q.unit.unwrap().text(), "ml");
+        assert!(ctx.is_empty());
+
+        // same as before without the extension
+        let (q, _, ctx) = t!(
+            "1 cup - 250 ml",
+            Extensions::all() ^ Extensions::RANGE_UNITS
+        );
+        assert_eq!(
+            q.value,
+            QuantityValue::Single {
+                value: Located::new(num!(1.0), 0..1),
+                auto_scale: None
+            }
+        );
+        assert_eq!(q.start_unit, None);
+        assert_eq!(q.unit.unwrap().text(), "cup - 250 ml");
+        assert!(ctx.is_empty());
+
+        // only one unit is a regular range
+        let (q, _, _) = t!("1 - 250 ml");
+        assert_eq!(q.start_unit, None);
+        assert_eq!(q.unit.unwrap().text(), "ml");
+    }
+
+    #[test_case("200 ml/serving" => (Some("ml".into()), Some("serving".into())) ; "advanced")]
+    #[test_case("200%ml/serving" => (Some("ml".into()), Some("serving".into())) ; "separator")]
+    #[test_case("200%ml/serving " => (Some("ml".into()), Some("serving".into())) ; "trailing space")]
+    #[test_case("1/2 cup" => (Some("cup".into()), None) ; "fraction")]
+    #[test_case("1⁄2 cup" => (Some("cup".into()), None) ; "fraction slash")]
+    #[test_case("1/2%cup" => (Some("cup".into()), None) ; "fraction separator")]
+    #[test_case("1/2%cup/serving" => (Some("cup".into()), Some("serving".into())) ; "fraction and per unit")]
+    #[test_case("200%ml / serving" => (Some("ml / serving".into()), None) ; "spaces")]
+    #[test_case("200%ml/2 servings" => (Some("ml/2 servings".into()), None) ; "number")]
+    #[test_case("200%ml/big serving" => (Some("ml/big serving".into()), None) ; "many words")]
+    #[test_case("200%/serving" => (Some("/serving".into()), None) ; "no unit")]
+    fn per_unit(input: &str) -> (Option<String>, Option<String>) {
+        let (q, _, ctx) = t!(input);
+        assert!(ctx.is_empty());
+        let text = |t: Option<Text>| t.map(|t| t.text_trimmed().into_owned());
+        (text(q.unit), text(q.per))
+    }
+
+    #[test]
+    fn per_unit_needs_advanced_units() {
+        let (q, _, _) = t!("200%ml/serving", Extensions::empty());
+        assert_eq!(q.unit.unwrap().text(), "ml/serving");
+        assert!(q.per.is_none());
+    }
+
+    #[test_case("5-2" => 1 ; "inverted")]
+    #[test_case("2-5" => 0 ; "regular")]
+    #[test_case("2-2" => 0 ; "same")]
+    #[test_case("1/2-1/4%cup" => 1 ; "fractions")]
+    fn inverted_range(input: &str) -> usize {
+        let (q, _, ctx) = t!(input);
+        // the value is kept as written
+        assert!(matches!(
+            q.value,
+            QuantityValue::Single { ref value, .. } if matches!(**value, Value::Range { .. })
+        ));
+        assert_eq!(ctx.errors().count(), 0);
+        ctx.warnings().count()
+    }
+
+    #[test_case("100±5" => (100.0, 5.0) ; "symbol")]
+    #[test_case("100 ± 5" => (100.0, 5.0) ; "spaces")]
+    #[test_case("100+-5" => (100.0, 5.0) ; "ascii")]
+    #[test_case("1/2±1/8%cup" => (0.5, 0.125) ; "fractions")]
+    #[test_case("100±5%g" => (100.0, 5.0) ; "unit")]
+    fn tolerance_value(input: &str) -> (f64, f64) {
+        let (q, _, ctx) = t!(input);
+        assert!(ctx.is_empty(), "{ctx:?}");
+        let QuantityValue::Single { value, .. } = q.value else {
+            panic!("not single")
+        };
+        let Value::Tolerant { center, tolerance } = value.into_inner() else {
+            panic!("not a tolerance")
+        };
+        (center.value(), tolerance.value())
+    }
+
+    #[test_case("a ± 5" ; "center")]
+    #[test_case("100±" ; "missing")]
+    #[test_case("100+-a bit" ; "tolerance")]
+    fn tolerance_value_errors(input: &str) {
+        let (_, _, ctx) = t!(input);
+        assert_eq!(ctx.errors().count(), 1);
+    }
+
+    #[test]
+    fn tolerance_value_no_extension() {
+        let (q, _, _) = t!("100±5", Extensions::all() ^ Extensions::TOLERANCES);
+        assert_eq!(
+            q.value,
+            QuantityValue::Single {
+                value: Located::new(Value::Text("100±5".into()), 0..6),
+                auto_scale: None,
+            }
+        );
+    }
+
+    #[test]
+    fn text_value_options() {
+        let text = |input: &str, trim, collapse_whitespace| {
+            let tokens = TokenStream::new(input).collect::<Vec<_>>();
+            let mut events = std::collections::VecDeque::new();
+            let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::empty());
+            bp.text_values = TextValueOptions {
+                trim,
+                collapse_whitespace,
+            };
+            let q = parse_quantity(&mut bp, &tokens);
+            bp.consume_rest();
+            bp.finish();
+            match q.quantity.into_inner().value {
+                QuantityValue::Single { value, .. } => value.into_inner(),
+                _ => panic!("not a single value"),
+            }
+        };
+        let input = " to   taste ";
+        assert_eq!(text(input, true, false), Value::Text("to   taste".into()));
+        assert_eq!(
+            text(input, false, false),
+            Value::Text(" to   taste ".into())
+        );
+        assert_eq!(text(input, true, true), Value::Text("to taste".into()));
+        assert_eq!(text(input, false, true), Value::Text(" to taste ".into()));
+    }
+
+    #[test_case("0%g" => 1 ; "zero")]
+    #[test_case("0.5%g" => 0 ; "not zero")]
+    #[test_case("0" => 1 ; "no unit")]
+    #[test_case("0|2" => 1 ; "many")]
+    #[test_case("0-0%g" => 1 ; "range")]
+    #[test_case("0-2%g" => 0 ; "range from zero")]
+    #[test_case("none" => 0 ; "text")]
+    fn zero_quantity_lint(input: &str) -> usize {
+        let tokens = TokenStream::new(input).collect::<Vec<_>>();
+        let mut events = std::collections::VecDeque::new();
+        let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
+        bp.quantity_lints = crate::parser::QuantityLints {
+            zero: true,
+            negative: false,
+            empty_unit: true,
+        };
+        parse_quantity(&mut bp, &tokens);
+        bp.consume_rest();
+        bp.finish();
+        events
+            .iter()
+            .filter(|ev| matches!(ev, crate::parser::Event::Warning(_)))
+            .count()
+    }
+
+    #[test_case("-5" => -5.0 ; "int")]
+    #[test_case("-1.5%g" => -1.5 ; "float")]
+    #[test_case("- 1/2%cup" => -0.5 ; "fraction")]
+    fn negative_value(input: &str) -> f64 {
+        let (q, _, ctx) = t!(input);
+        assert_eq!(ctx.errors().count(), 0);
+        let warnings = ctx.warnings().collect::<Vec<_>>();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].to_string(), "Negative quantity value");
+        match q.value {
+            QuantityValue::Single { value, .. } => match value.into_inner() {
+                Value::Number(n) => n.value(),
+                other => panic!("not a number: {other:?}"),
+            },
+            _ => panic!("not a single value"),
+        }
+    }
+
+    #[test]
+    fn negative_value_delta_mode() {
+        let input = "-5%g";
+        let tokens = TokenStream::new(input).collect::<Vec<_>>();
+        let mut events = std::collections::VecDeque::new();
+        let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
+        bp.quantity_lints.negative = false;
+        let q = parse_quantity(&mut bp, &tokens);
+        bp.consume_rest();
+        bp.finish();
+        assert!(events.is_empty());
+        assert_eq!(
+            q.quantity.into_inner().value,
+            QuantityValue::Single {
+                value: Located::new(num!(-5.0), 0..2),
+                auto_scale: None
+            }
+        );
+    }
+
+    #[test]
+    fn zero_quantity_lint_disabled() {
+        let (_, _, ctx) = t!("0%g");
+        assert!(ctx.is_empty());
+    }
+
     #[test]
     fn range_value_no_extension() {
         let (q, _, _) = t!("2-3", Extensions::empty());
@@ -539,6 +1526,68 @@ mod tests {
         assert_eq!(q.unit, None);
     }
 
+    #[test_case("$5" => (num!(5.0), 1..2) ; "leading")]
+    #[test_case("5$" => (num!(5.0), 0..1) ; "trailing")]
+    #[test_case("€ 2-3" => (range!(2.0, 3.0), 4..7) ; "leading range")]
+    #[test_case("2 €" => (num!(2.0), 0..1) ; "advanced")]
+    fn leading_unit(input: &str) -> (Value, std::ops::Range<usize>) {
+        let (q, s, ctx) = t!(input);
+        assert!(ctx.is_empty());
+        assert_eq!(s, None);
+        let unit = q.unit.unwrap();
+        assert!(unit.text_trimmed() == "$" || unit.text_trimmed() == "€");
+        let QuantityValue::Single { value, .. } = q.value else {
+            panic!("many values")
+        };
+        let span = value.span().range();
+        (value.into_inner(), span)
+    }
+
+    #[test]
+    fn leading_unit_no_extension() {
+        let (q, _, _) = t!("$5", Extensions::all() ^ Extensions::LEADING_UNITS);
+        assert_eq!(
+            q.value,
+            QuantityValue::Single {
+                value: Located::new(Value::Text("$5".into()), 0..2),
+                auto_scale: None
+            }
+        );
+        assert_eq!(q.unit, None);
+
+        let (q, _, _) = t!("5 $cup");
+        assert_eq!(q.unit.unwrap().text(), "$cup");
+    }
+
+    #[test_case("$amount%g" => Value::Variable("amount".into()) ; "with unit")]
+    #[test_case(" $amount " => Value::Variable("amount".into()) ; "trimmed")]
+    #[test_case("$egg_count2" => Value::Variable("egg_count2".into()) ; "underscore and digits")]
+    #[test_case("$5" => num!(5.0) ; "leading unit")]
+    #[test_case("$2x" => Value::Text("$2x".into()) ; "starts with digit")]
+    #[test_case("$ amount" => Value::Text("$ amount".into()) ; "space")]
+    #[test_case("$amount-ish" => Value::Text("$amount-ish".into()) ; "symbol")]
+    fn variable_value(input: &str) -> Value {
+        let (q, _, ctx) = t!(input);
+        assert!(ctx.is_empty());
+        let QuantityValue::Single { value, .. } = q.value else {
+            panic!("many values")
+        };
+        value.into_inner()
+    }
+
+    #[test]
+    fn variable_value_no_extension() {
+        let (q, _, _) = t!("$amount%g", Extensions::all() ^ Extensions::VARIABLES);
+        assert_eq!(
+            q.value,
+            QuantityValue::Single {
+                value: Located::new(Value::Text("$amount".into()), 0..7),
+                auto_scale: None
+            }
+        );
+        assert_eq!(q.unit.unwrap().text(), "g");
+    }
+
     #[test]
     fn range_mixed_value() {
         let (q, _, _) = t!("2 1/2-3");
@@ -576,6 +1625,8 @@ mod tests {
     #[test_case("0 1/2" => (0, 1, 2); "zero whole")]
     #[test_case("01/2" => panics "not number"; "bad fraction")]
     #[test_case("2 1/2" => (2, 1, 2); "mixed value")]
+    #[test_case("1⁄2" => (0, 1, 2); "fraction slash")]
+    #[test_case("2 1⁄2" => (2, 1, 2); "mixed value fraction slash")]
     fn fractional_val(s: &str) -> (u32, u32, u32) {
         let (q, _, _) = t!(s);
         let QuantityValue::Single { value, .. } = q.value else {
@@ -598,6 +1649,137 @@ mod tests {
         (whole, num, den)
     }
 
+    #[test_case("1+1/2" => (1, 1, 2); "integer and fraction")]
+    #[test_case("1/3 + 1/6" => (0, 1, 2); "fractions")]
+    #[test_case("1 1/2+1/2 cup" => (2, 0, 1); "advanced unit")]
+    #[test_case("1+1+1/4%cup" => (2, 1, 4); "many terms")]
+    fn sum_value(s: &str) -> (u32, u32, u32) {
+        let (q, _, r) = t!(s);
+        assert!(r.is_empty(), "source error");
+        let QuantityValue::Single { value, .. } = q.value else {
+            panic!("not single value")
+        };
+        let Value::Number(Number::Fraction {
+            whole, num, den, ..
+        }) = value.into_inner()
+        else {
+            panic!("not fraction")
+        };
+        (whole, num, den)
+    }
+
+    #[test_case("salt+pepper" ; "words")]
+    #[test_case("to taste + more" ; "text with spaces")]
+    #[test_case("1+a bit" ; "number and text")]
+    #[test_case("1+" ; "missing term")]
+    fn sum_value_text(s: &str) {
+        let (q, _, ctx) = t!(s);
+        assert!(ctx.is_empty());
+        assert!(matches!(
+            q.value,
+            QuantityValue::Single { ref value, .. } if matches!(**value, Value::Text(_))
+        ));
+    }
+
+    #[test]
+    fn sum_value_errors() {
+        let (_, _, ctx) = t!("1/0+1");
+        assert_eq!(ctx.errors().count(), 1);
+
+        let (q, _, ctx) = t!("1+1/2", Extensions::all() ^ Extensions::VALUE_SUMS);
+        assert_eq!(
+            q.value,
+            QuantityValue::Single {
+                value: Located::new(Value::Text("1+1/2".into()), 0..5),
+                auto_scale: None
+            }
+        );
+        assert!(ctx.is_empty());
+    }
+
+    #[test_case("(1+1/2)*2" => (3, 0, 1); "parentheses")]
+    #[test_case("1+1/2*2" => (2, 0, 1); "precedence")]
+    #[test_case("((1+1) * (1/4+1/4))" => (1, 0, 1); "nested")]
+    #[test_case("1 1/2 * 2%cups" => (3, 0, 1); "mixed number and unit")]
+    #[test_case("(3)/4*" => (0, 3, 4); "division and auto scale")]
+    fn expression_value(s: &str) -> (u32, u32, u32) {
+        let (q, _, r) = t!(s);
+        assert!(r.is_empty(), "source error");
+        let QuantityValue::Single { value, .. } = q.value else {
+            panic!("not single value")
+        };
+        let Value::Number(Number::Fraction {
+            whole, num, den, ..
+        }) = value.into_inner()
+        else {
+            panic!("not fraction")
+        };
+        (whole, num, den)
+    }
+
+    #[test_case("(1+1/2" ; "unclosed")]
+    #[test_case("(1+1)/0" ; "division by zero")]
+    #[test_case("2*(1+)" ; "missing operand")]
+    #[test_case("2**3" ; "double operator")]
+    #[test_case("(1)(2)" ; "missing operator")]
+    fn expression_value_errors(s: &str) {
+        let (_, _, ctx) = t!(s);
+        assert_eq!(ctx.errors().count(), 1);
+    }
+
+    #[test]
+    fn expression_value_needs_extension() {
+        let (q, _, ctx) = t!("(1)*2", Extensions::all() ^ Extensions::VALUE_EXPRESSIONS);
+        assert_eq!(
+            q.value,
+            QuantityValue::Single {
+                value: Located::new(Value::Text("(1)*2".into()), 0..5),
+                auto_scale: None
+            }
+        );
+        assert!(ctx.is_empty());
+
+        // text with parentheses is not an expression
+        let (q, _, ctx) = t!("2 (heaped)");
+        let QuantityValue::Single { value, .. } = q.value else {
+            panic!("not single value")
+        };
+        assert!(matches!(value.into_inner(), Value::Text(_)));
+        assert!(ctx.is_empty());
+    }
+
+    #[test_case("PT30M" => Some(1800.0))]
+    #[test_case("PT1H30M" => Some(5400.0))]
+    #[test_case("PT1.5H" => Some(5400.0))]
+    #[test_case("P1DT12H" => Some(129600.0))]
+    #[test_case("P1W" => Some(604800.0))]
+    #[test_case("PT45S" => Some(45.0))]
+    #[test_case("PT30M1H" => None; "wrong order")]
+    #[test_case("P1M" => None; "months")]
+    #[test_case("PT" => None; "empty time")]
+    #[test_case("P" => None; "empty")]
+    #[test_case("PTM" => None; "missing number")]
+    #[test_case("PT30" => None; "missing designator")]
+    fn iso_duration(s: &str) -> Option<f64> {
+        parse_iso_duration(s)
+    }
+
+    #[test_case("5000000000/2" => 2500000000.0; "overflow numerator")]
+    #[test_case("1/5000000000" => 1.0 / 5000000000.0; "overflow denominator")]
+    #[test_case("5000000000 1/2" => 5000000000.5; "overflow whole")]
+    #[test_case("2 5000000000/2" => 2500000002.0; "overflow in mixed numerator")]
+    fn overflowing_fractions(s: &str) -> f64 {
+        let (q, _, r) = t!(s);
+        assert!(r.is_empty(), "source error");
+        let QuantityValue::Single { value, .. } = q.value else {
+            panic!("not single value")
+        };
+        let Value::Number(Number::Regular(n)) = value.into_inner() else {
+            panic!("not regular number")
+        };
+        n
+    }
+
     #[test_case("1" => 1.0)]
     #[test_case("1.0" => 1.0)]
     #[test_case("10" => 10.0)]
@@ -620,4 +1802,18 @@ mod tests {
         assert!(r.is_empty(), "source error");
         n
     }
+
+    #[test]
+    fn suggestions() {
+        let (_, _, ctx) = t!("100 %");
+        let warning = ctx.warnings().next().unwrap();
+        assert_eq!(warning.suggestion(), Some((Span::new(4, 5), "")));
+
+        let (_, _, ctx) = t!("1/0%cup");
+        let error = ctx.errors().next().unwrap();
+        assert_eq!(error.suggestion(), Some((Span::new(2, 3), "1")));
+
+        let (_, _, ctx) = t!("1%cup");
+        assert!(ctx.iter().all(|d| d.suggestion().is_none()));
+    }
 }
@@ -9,6 +9,15 @@ pub(crate) fn section<'i>(block: &mut BlockParser<'_, 'i>) -> Option<Event<'i>>
 
     block.consume(T![=])?;
     block.consume_while(|t| t == T![=]);
+    let optional = block
+        .rest()
+        .iter()
+        .find(|t| t.kind != T![ws])
+        .is_some_and(|t| t.kind == T![?]);
+    if optional {
+        block.consume_while(|t| t == T![ws]);
+        block.bump(T![?]);
+    }
     let name_pos = block.current_offset();
     let name_tokens = block.consume_while(|t| t != T![=]);
     let name = block.text(name_pos, name_tokens);
@@ -32,7 +41,7 @@ pub(crate) fn section<'i>(block: &mut BlockParser<'_, 'i>) -> Option<Event<'i>>
     } else {
         Some(name)
     };
-    Some(Event::Section { name })
+    Some(Event::Section { name, optional })
 }
 
 #[cfg(test)]
@@ -74,9 +83,26 @@ mod tests {
         let event = section(&mut bp).expect("failed to parse section");
         bp.finish();
         assert!(events.is_empty());
-        let Event::Section { name } = event else {
+        let Event::Section { name, .. } = event else {
             panic!()
         };
         name.map(|text| (text.text().into_owned(), text.span()))
     }
+
+    #[test_case("= ?Garnish" => (true, Some("Garnish".into())) ; "optional")]
+    #[test_case("== ? Garnish ==" => (true, Some("Garnish".into())) ; "optional fenced")]
+    #[test_case("= ?" => (true, None) ; "optional no name")]
+    #[test_case("= Garnish?" => (false, Some("Garnish?".into())) ; "not at the start")]
+    #[test_case("= Garnish" => (false, Some("Garnish".into())) ; "not optional")]
+    fn optional_section(input: &'static str) -> (bool, Option<String>) {
+        let tokens = TokenStream::new(input).collect::<Vec<_>>();
+        let mut events = VecDeque::new();
+        let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
+        let event = section(&mut bp).expect("failed to parse section");
+        bp.finish();
+        let Event::Section { name, optional } = event else {
+            panic!()
+        };
+        (optional, name.map(|text| text.text_trimmed().into_owned()))
+    }
 }
@@ -6,8 +6,13 @@ use crate::{
 };
 
 use super::{
-    error, mt, quantity::parse_quantity, token_stream::Token, tokens_span, warning, BlockKind,
-    BlockParser, Event,
+    error, mt,
+    quantity::{
+        iso_duration_quantity, number_or_range, parse_quantity, split_count,
+        split_purchase_quantity,
+    },
+    token_stream::Token,
+    tokens_span, warning, BlockKind, BlockParser, Event,
 };
 
 pub(crate) fn parse_step(bp: &mut BlockParser<'_, '_>) {
@@ -45,8 +50,30 @@ struct Body<'t> {
 }
 
 fn comp_body<'t>(bp: &mut BlockParser<'t, '_>) -> Option<Body<'t>> {
+    comp_body_impl(bp, false)
+}
+
+/// Same as [`comp_body`] but `allow_hash` lets a `#` be in a multi word name
+///
+/// Only the first `#` can be in the name, and only inside the first word, like
+/// in `./sauce#tomato paste`. Any other `#` ends the name as usual.
+fn comp_body_impl<'t>(bp: &mut BlockParser<'t, '_>, allow_hash: bool) -> Option<Body<'t>> {
     bp.with_recover(|line| {
-        let name = line.until(|t| matches!(t, T!['{'] | T![@] | T![#] | T![~]))?;
+        let rest = line.rest();
+        let name_hash = allow_hash
+            .then(|| {
+                let hash = rest.iter().position(|t| t.kind == T![#])?;
+                let ws = rest
+                    .iter()
+                    .position(|t| matches!(t.kind, T![ws] | T![newline]));
+                (hash > 0 && ws.is_none_or(|ws| hash < ws)).then_some(hash)
+            })
+            .flatten();
+        let name_len = rest.iter().enumerate().position(|(i, t)| {
+            matches!(t.kind, T!['{'] | T![@] | T![~]) || (t.kind == T![#] && Some(i) != name_hash)
+        })?;
+        let name = &rest[..name_len];
+        line.current += name_len;
         let close_span_start = line.consume(T!['{'])?.span.start();
         let quantity = line.until(|t| t == T!['}'])?;
         let close_span_end = line.bump(T!['}']).span.end();
@@ -62,7 +89,8 @@ fn comp_body<'t>(bp: &mut BlockParser<'t, '_>) -> Option<Body<'t>> {
     })
     .or_else(|| {
         bp.with_recover(|bp| {
-            let tokens = bp.consume_while(|t| matches!(t, T![word] | T![int] | T![zeroint]));
+            let tokens =
+                bp.consume_while(|t| matches!(t, T![word] | T![int] | T![zeroint] | T![escaped]));
             if tokens.is_empty() {
                 if !bp.rest().is_empty() && !bp.at(T![ws]) {
                     bp.warn(
@@ -87,7 +115,8 @@ fn comp_body<'t>(bp: &mut BlockParser<'t, '_>) -> Option<Body<'t>> {
     })
 }
 
-fn modifiers<'t>(bp: &mut BlockParser<'t, '_>) -> &'t [Token] {
+/// Modifiers tokens, `*` is only a modifier if `scale` is true
+fn modifiers<'t>(bp: &mut BlockParser<'t, '_>, scale: bool) -> &'t [Token] {
     if !bp.extension(Extensions::COMPONENT_MODIFIERS) {
         return &[];
     }
@@ -98,6 +127,9 @@ fn modifiers<'t>(bp: &mut BlockParser<'t, '_>) -> &'t [Token] {
             T![@] | T![?] | T![+] | T![-] => {
                 bp.bump_any();
             }
+            T![*] if scale => {
+                bp.bump_any();
+            }
             T![&] => {
                 bp.bump_any();
                 if bp.extension(Extensions::INTERMEDIATE_PREPARATIONS) {
@@ -115,6 +147,44 @@ fn modifiers<'t>(bp: &mut BlockParser<'t, '_>) -> &'t [Token] {
     &bp.tokens()[start..bp.current]
 }
 
+fn variant<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Text<'i>> {
+    if !bp.extension(Extensions::VARIANTS) {
+        return None;
+    }
+    square_brackets(bp)
+}
+
+fn category<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Text<'i>> {
+    if !bp.extension(Extensions::CATEGORIES) {
+        return None;
+    }
+    square_brackets(bp)
+}
+
+/// Text inside `[...]` in the same component
+fn square_brackets<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Text<'i>> {
+    bp.with_recover(|bp| {
+        let open = bp.consume(T![punctuation])?;
+        if bp.token_str(open) != "[" {
+            return None;
+        }
+        let offset = bp.current_offset();
+        let rest = bp.rest();
+        let close = rest.iter().position(|t| {
+            t.kind == T![punctuation] && bp.token_str(*t) == "]"
+                || matches!(t.kind, T!['{'] | T![@] | T![#] | T![~])
+        })?;
+        if rest[close].kind != T![punctuation] {
+            return None;
+        }
+        let inner = &rest[..close];
+        for _ in 0..=close {
+            bp.bump_any();
+        }
+        Some(bp.text(offset, inner))
+    })
+}
+
 fn note<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Text<'i>> {
     bp.extension(Extensions::COMPONENT_NOTE)
         .then(|| {
@@ -164,6 +234,7 @@ fn parse_modifiers(
                 T![?] => Modifiers::OPT,
                 T![+] => Modifiers::NEW,
                 T![-] => Modifiers::HIDDEN,
+                T![*] => Modifiers::SCALE,
                 _ => panic!("Bad modifiers token sequence. Unexpected token: {tok:?}"),
             };
 
@@ -193,7 +264,8 @@ fn parse_intermediate_ref_data(
 ) -> Option<Located<IntermediateData>> {
     use IntermediateRefMode::*;
     use IntermediateTargetKind::*;
-    const INTER_PREP_HELP: &str = "The target is something like: `1`, `~1`, `=1` or `=~1`";
+    const INTER_PREP_HELP: &str =
+        "The target is something like: `1`, `~1`, `=1`, `=~1` or `=Section name`";
     const INVALID: &str = "Invalid intermediate preparation reference";
 
     // if '(' has been taken as a modifier token, it has taken until
@@ -256,6 +328,19 @@ fn parse_intermediate_ref_data(
             );
             return None;
         }
+        // a name can't start like a number, so `= 1` is still section 1
+        [mt![=], next, ..]
+            if !matches!(next.kind, T![~] | T![-] | T![+] | T![int] | T![zeroint]) =>
+        {
+            let eq_pos = inner_slice.iter().position(|t| t.kind == T![=]).unwrap();
+            let name = bp.slice_str(&inner_slice[eq_pos + 1..]).trim();
+            let data = IntermediateData {
+                ref_mode: Name(name.to_string()),
+                target_kind: Section,
+                val: 0,
+            };
+            return Some(Located::new(data, tokens_span(slice)));
+        }
         _ => {
             bp.error(error!(INVALID, label!(tokens_span(inner_slice))).hint(INTER_PREP_HELP));
             return None;
@@ -333,36 +418,394 @@ fn ingredient<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
     let start = bp.current_offset();
     bp.consume(T![@])?;
     let modifiers_pos = bp.current_offset();
-    let modifiers_tokens = modifiers(bp);
-    let name_offset = bp.current_offset();
-    let body = comp_body(bp)?;
-    let note = note(bp);
+    let modifiers_tokens = modifiers(bp, false);
+    let variant = variant(bp);
+    let title_reference =
+        bp.extension(Extensions::TITLE_REFERENCES) && matches!(bp.rest(), [mt!['{'], mt!['{'], ..]);
+    let allow_fragment = !title_reference
+        && bp.extension(Extensions::REFERENCE_FRAGMENTS)
+        && modifiers_tokens.iter().any(|t| t.kind == T![@]);
+    let (body, name_offset) = if title_reference {
+        title_body(bp)?
+    } else {
+        let name_offset = bp.current_offset();
+        (comp_body_impl(bp, allow_fragment)?, name_offset)
+    };
+    let nutrition = if body.close.is_some() {
+        nutrition(bp)
+    } else {
+        None
+    };
+    let mut note = note(bp);
+    let category = category(bp);
     let end = bp.current_offset();
 
     // Build text(s) and checks
-    let (name, alias) = parse_alias(INGREDIENT, bp, body.name, name_offset);
+    let (mut name, mut alias) = parse_alias(INGREDIENT, bp, body.name, name_offset);
+    // titles can have commas
+    let preparation =
+        if body.close.is_some() && !title_reference && bp.extension(Extensions::PREPARATIONS) {
+            // the preparation goes at the end, after the alias if any
+            preparation(bp, alias.as_mut().unwrap_or(&mut name))
+        } else {
+            None
+        };
+    let reference_fragment = if allow_fragment {
+        reference_fragment(bp, &mut name)
+    } else {
+        None
+    };
+    let category = category.and_then(|category| {
+        if category.is_text_empty() {
+            bp.error(
+                error!(
+                    "Invalid ingredient: empty category",
+                    label!(category.span(), "add the category name here"),
+                )
+                .hint("Remove the `[]` if the ingredient has no category"),
+            );
+            None
+        } else {
+            Some(category)
+        }
+    });
+    let variant = variant.and_then(|variant| {
+        if variant.is_text_empty() {
+            bp.error(
+                error!(
+                    "Invalid ingredient: empty variant",
+                    label!(variant.span(), "add the variant name here"),
+                )
+                .hint("Remove the `[]` if the ingredient is used in all variants"),
+            );
+            None
+        } else {
+            Some(variant)
+        }
+    });
     check_empty_name(INGREDIENT, bp, &name);
+    let substitutes = match &note {
+        Some(n) if bp.extension(Extensions::SUBSTITUTES) => substitutes(n),
+        _ => Vec::new(),
+    };
+    if !substitutes.is_empty() {
+        note = None;
+    }
 
     let ParsedModifiers {
         flags: modifiers,
         intermediate_data,
     } = parse_modifiers(bp, modifiers_tokens, modifiers_pos);
 
-    let quantity = body
-        .quantity
-        .map(|tokens| parse_quantity(bp, tokens).quantity);
+    let mut count = None;
+    let mut purchase_quantity = None;
+    let (quantity, alternative_quantities) = match body.quantity {
+        Some(mut tokens) => {
+            if bp.extension(Extensions::PURCHASE_QUANTITIES) {
+                if let Some((q, purchase)) = split_purchase_quantity(bp, tokens) {
+                    purchase_quantity = Some(parse_quantity(bp, purchase).quantity);
+                    tokens = q;
+                }
+            }
+            if bp.extension(Extensions::PER_UNIT_QUANTITIES) {
+                if let Some((c, rest)) = split_count(bp, tokens) {
+                    count = Some(c);
+                    tokens = rest;
+                }
+            }
+            let mut groups = split_alternative_quantities(bp, tokens).into_iter();
+            let quantity = parse_quantity(bp, groups.next().unwrap()).quantity;
+            let alternatives = groups
+                .map(|tokens| parse_quantity(bp, tokens).quantity)
+                .collect();
+            (Some(quantity), alternatives)
+        }
+        None => (None, Vec::new()),
+    };
 
-    Some(Event::Ingredient(Located::new(
+    Some(Event::Ingredient(Box::new(Located::new(
         Ingredient {
             modifiers,
             intermediate_data,
+            variant,
             name,
             alias,
             quantity,
+            alternative_quantities,
+            count,
+            purchase_quantity,
             note,
+            substitutes,
+            category,
+            nutrition,
+            reference_fragment,
+            preparation,
+            title_reference,
         },
         start..end,
-    )))
+    ))))
+}
+
+/// Body of a reference to a recipe by its title, like `{{Tomato Sauce}}{200%g}`
+///
+/// The quantity is optional. Returns the body and the offset of the name.
+fn title_body<'t>(bp: &mut BlockParser<'t, '_>) -> Option<(Body<'t>, usize)> {
+    bp.with_recover(|bp| {
+        bp.consume(T!['{'])?;
+        bp.consume(T!['{'])?;
+        let name_offset = bp.current_offset();
+        let name = bp.until(|t| matches!(t, T!['{'] | T!['}']))?;
+        let close_start = bp.consume(T!['}'])?.span.start();
+        let close_end = bp.consume(T!['}'])?.span.end();
+        let quantity = bp.with_recover(|bp| {
+            bp.consume(T!['{'])?;
+            let quantity = bp.until(|t| t == T!['}'])?;
+            bp.bump(T!['}']);
+            Some(quantity)
+        });
+        let quantity = quantity.filter(|q| {
+            q.iter()
+                .any(|t| !matches!(t.kind, T![ws] | T![block comment]))
+        });
+        let body = Body {
+            name,
+            close: Some(Span::new(close_start, close_end)),
+            quantity,
+        };
+        Some((body, name_offset))
+    })
+}
+
+/// Splits the fragment of a recipe reference name, like `tomato` in
+/// `@@./sauce#tomato{}`
+fn reference_fragment<'i>(bp: &mut BlockParser<'_, 'i>, name: &mut Text<'i>) -> Option<Text<'i>> {
+    let (head, fragment) = name.split_once('#')?;
+    *name = head;
+    if fragment.is_text_empty() {
+        bp.error(
+            error!(
+                "Invalid ingredient: empty reference fragment",
+                label!(fragment.span(), "add the ingredient name here"),
+            )
+            .hint("Remove the `#` to reference the whole recipe"),
+        );
+        return None;
+    }
+    Some(fragment)
+}
+
+/// Splits the preparation of an ingredient name, like `chopped` in
+/// `@onion, chopped{1}`
+fn preparation<'i>(bp: &mut BlockParser<'_, 'i>, name: &mut Text<'i>) -> Option<Text<'i>> {
+    let (head, preparation) = name.split_once(',')?;
+    *name = head;
+    if preparation.is_text_empty() {
+        bp.error(
+            error!(
+                "Invalid ingredient: empty preparation",
+                label!(preparation.span(), "add the preparation here"),
+            )
+            .hint("Remove the `,` if the ingredient has no preparation"),
+        );
+        return None;
+    }
+    Some(preparation)
+}
+
+/// Nutrition facts inside `{...}` right after the ingredient quantity, like
+/// `{cal=40, fat=1.5}`
+///
+/// Malformed entries are reported and skipped.
+fn nutrition<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Vec<NutritionFact<'i>>> {
+    if !bp.extension(Extensions::NUTRITION) {
+        return None;
+    }
+    let (tokens, span) = bp.with_recover(|bp| {
+        let open = bp.consume(T!['{'])?;
+        let tokens = bp.until(|t| t == T!['}'])?;
+        let close = bp.bump(T!['}']);
+        Some((tokens, Span::new(open.span.start(), close.span.end())))
+    })?;
+
+    let entries = tokens
+        .split(|t| t.kind == T![punctuation] && bp.token_str(*t) == ",")
+        .collect::<Vec<_>>();
+    let mut facts: Vec<NutritionFact> = Vec::new();
+    let mut malformed = false;
+    for entry in entries {
+        if entry
+            .iter()
+            .all(|t| matches!(t.kind, T![ws] | T![block comment]))
+        {
+            continue;
+        }
+        let Some(eq) = entry.iter().position(|t| t.kind == T![=]) else {
+            bp.error(
+                error!(
+                    "Invalid nutrition fact: missing value",
+                    label!(tokens_span(entry), "expected `key=value`"),
+                )
+                .hint("Nutrition facts are separated by commas, like `{cal=40, fat=1.5}`"),
+            );
+            malformed = true;
+            continue;
+        };
+        let (key, value) = (&entry[..eq], &entry[eq + 1..]);
+
+        let key = bp.text(entry[0].span.start(), key);
+        if key.is_text_empty() {
+            bp.error(error!(
+                "Invalid nutrition fact: empty key",
+                label!(Span::pos(entry[eq].span.start()), "add the key here"),
+            ));
+            malformed = true;
+            continue;
+        }
+        if facts
+            .iter()
+            .any(|f| f.key.text_trimmed() == key.text_trimmed())
+        {
+            bp.error(error!(
+                format!("Duplicate nutrition fact: {}", key.text_trimmed()),
+                label!(key.span(), "remove this fact"),
+            ));
+            malformed = true;
+            continue;
+        }
+
+        let value_span = if value.is_empty() {
+            Span::pos(entry[eq].span.end())
+        } else {
+            tokens_span(value)
+        };
+        let value = match number_or_range(value, bp) {
+            Some(Ok(value)) => value,
+            Some(Err(err)) => {
+                bp.error(err);
+                malformed = true;
+                continue;
+            }
+            None => {
+                bp.error(error!(
+                    format!("Invalid nutrition fact value: {}", key.text_trimmed()),
+                    label!(value_span, "expected a number here"),
+                ));
+                malformed = true;
+                continue;
+            }
+        };
+        facts.push(NutritionFact {
+            key,
+            value: Located::new(value, value_span),
+        });
+    }
+
+    if facts.is_empty() {
+        // the errors already explain why there are no facts
+        if malformed {
+            return None;
+        }
+        bp.warn(
+            warning!(
+                "Empty nutrition facts",
+                label!(span, "add facts like `{cal=40}` or remove this"),
+            )
+            .with_suggestion(span, ""),
+        );
+        return None;
+    }
+    Some(facts)
+}
+
+/// Splits a note like `or margarine, ghee or oil` in the substitutes
+///
+/// Empty if the note does not start with `or`.
+fn substitutes(note: &Text) -> Vec<Located<String>> {
+    let text = note.text();
+    let trimmed = text.trim_start();
+    let is_or = |p: &&str| {
+        p.get(..2).is_some_and(|or| or.eq_ignore_ascii_case("or"))
+            && p.ends_with(char::is_whitespace)
+    };
+    let Some(rest) = trimmed.get(..3).filter(is_or).map(|_| &trimmed[3..]) else {
+        return Vec::new();
+    };
+    // locations are only exact if the note has no comments inside
+    let offset = match note.fragments() {
+        [fragment] => Some(fragment.start()),
+        _ => None,
+    };
+
+    let mut substitutes = Vec::new();
+    let mut start = text.len() - rest.len();
+    for part in rest.split(',') {
+        for (i, sub) in split_or(part).enumerate() {
+            if i > 0 {
+                start += " or ".len();
+            }
+            let name = sub.trim();
+            if !name.is_empty() {
+                let span = match offset {
+                    Some(offset) => {
+                        let name_start = offset + start + (sub.len() - sub.trim_start().len());
+                        Span::new(name_start, name_start + name.len())
+                    }
+                    None => note.span(),
+                };
+                substitutes.push(Located::new(name.to_string(), span));
+            }
+            start += sub.len();
+        }
+        start += ",".len();
+    }
+    substitutes
+}
+
+/// Splits `s` by ` or `, ignoring the case
+fn split_or(s: &str) -> impl Iterator<Item = &str> {
+    let lower = s.to_ascii_lowercase();
+    let mut parts = Vec::new();
+    let mut last = 0;
+    for (i, sep) in lower.match_indices(" or ") {
+        parts.push(&s[last..i]);
+        last = i + sep.len();
+    }
+    parts.push(&s[last..]);
+    parts.into_iter()
+}
+
+/// Splits the quantity tokens of `1 cup / 120 g` in each quantity.
+///
+/// A `/` is only a separator between a value with unit and another one, so
+/// fractions like `1/2` and units like `km/h` are not split.
+fn split_alternative_quantities<'t>(bp: &BlockParser, tokens: &'t [Token]) -> Vec<&'t [Token]> {
+    if !bp.extension(Extensions::ALTERNATIVE_QUANTITIES) {
+        return vec![tokens];
+    }
+    let is_ws = |t: &&Token| matches!(t.kind, T![ws] | T![block comment]);
+    // a value followed by a unit, like `120 g`
+    let value_unit = |group: &[Token]| {
+        let mut group = group.iter().filter(|t| !is_ws(t));
+        group.next().is_some_and(|t| t.kind != T![word]) && group.any(|t| t.kind == T![word])
+    };
+
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for (i, t) in tokens.iter().enumerate() {
+        if t.kind != T![/] {
+            continue;
+        }
+        let prev = tokens[start..i].iter().rev().find(|t| !is_ws(t));
+        if matches!(prev, Some(mt![word]))
+            && value_unit(&tokens[start..i])
+            && value_unit(&tokens[i + 1..])
+        {
+            groups.push(&tokens[start..i]);
+            start = i + 1;
+        }
+    }
+    groups.push(&tokens[start..]);
+    groups
 }
 
 fn cookware<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
@@ -370,7 +813,7 @@ fn cookware<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
     let start = bp.current_offset();
     bp.consume(T![#])?;
     let modifiers_pos = bp.current_offset();
-    let modifiers_tokens = modifiers(bp);
+    let modifiers_tokens = modifiers(bp, true);
     let name_offset = bp.current_offset();
     let body = comp_body(bp)?;
     let note = note(bp);
@@ -406,7 +849,7 @@ fn cookware<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
                     "Invalid cookware quantity: auto scale marker",
                     label!(auto_scale, "remove this"),
                 )
-                .hint("Cookware items amount can't be auto scaled"),
+                .hint("Use the scale modifier to scale the amount, like `#*pan{2}`"),
             );
         }
         q.quantity.map(|q| q.value)
@@ -445,7 +888,7 @@ fn timer<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
     // Parse
     let start = bp.current_offset();
     bp.consume(T![~])?;
-    let modifiers_tokens = modifiers(bp);
+    let modifiers_tokens = modifiers(bp, false);
     let name_offset = bp.current_offset();
     let body = comp_body(bp)?;
     let end = bp.current_offset();
@@ -458,6 +901,11 @@ fn timer<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
     let name = bp.text(name_offset, body.name);
 
     let mut quantity = body.quantity.map(|tokens| {
+        if bp.extension(Extensions::ISO_DURATIONS) {
+            if let Some(q) = iso_duration_quantity(bp, tokens) {
+                return q.quantity;
+            }
+        }
         let q = parse_quantity(bp, tokens);
         if let QuantityValue::Single {
             auto_scale: Some(auto_scale),
@@ -658,7 +1106,7 @@ mod tests {
         Located::new(IntermediateData {
             ref_mode: IntermediateRefMode::Relative,
             target_kind: IntermediateTargetKind::Step,
-            val: 1
+            val: 1,
             }, 2..6)
     ); "step relative")]
     #[test_case("@&(1)step index 1{}" => (
@@ -666,7 +1114,7 @@ mod tests {
         Located::new(IntermediateData {
             ref_mode: IntermediateRefMode::Number,
             target_kind: IntermediateTargetKind::Step,
-            val: 1
+            val: 1,
         }, 2..5)
     ); "step index")]
     #[test_case("@&(=~1)one section back{}" => (
@@ -674,7 +1122,7 @@ mod tests {
         Located::new(IntermediateData {
             ref_mode: IntermediateRefMode::Relative,
             target_kind: IntermediateTargetKind::Section,
-            val: 1
+            val: 1,
         }, 2..7)
     ); "section relative")]
     #[test_case("@&(=1)section index 1{}" => (
@@ -682,14 +1130,30 @@ mod tests {
         Located::new(IntermediateData {
             ref_mode: IntermediateRefMode::Number,
             target_kind: IntermediateTargetKind::Section,
-            val: 1
+            val: 1,
         }, 2..6)
     ); "section index")]
+    #[test_case("@&(= Make the dough )dough{}" => (
+        Located::new(Modifiers::REF, 1..21),
+        Located::new(IntermediateData {
+            ref_mode: IntermediateRefMode::Name("Make the dough".into()),
+            target_kind: IntermediateTargetKind::Section,
+            val: 0,
+        }, 2..21)
+    ); "section name")]
+    #[test_case("@&(= 1)section index 1{}" => (
+        Located::new(Modifiers::REF, 1..7),
+        Located::new(IntermediateData {
+            ref_mode: IntermediateRefMode::Number,
+            target_kind: IntermediateTargetKind::Section,
+            val: 1,
+        }, 2..7)
+    ); "section index with space")]
     fn intermediate_ref(input: &str) -> (Located<Modifiers>, Located<IntermediateData>) {
         let (s, ctx) = t(input);
         let igr = igr!(&s[0]);
         assert!(ctx.is_empty());
-        (igr.modifiers, igr.intermediate_data.unwrap())
+        (igr.modifiers, igr.intermediate_data.clone().unwrap())
     }
 
     #[test_case("@&(~=1)name{}"; "swap ~ =")]
@@ -718,4 +1182,357 @@ mod tests {
         let body = comp_body(&mut bp).expect("not parsed");
         bp.text(0, body.name).text_trimmed().into_owned()
     }
+
+    #[test_case("salt\\&pepper" => "salt&pepper" ; "escaped ref marker")]
+    #[test_case("back\\\\slash" => "back\\slash" ; "escaped backslash")]
+    fn single_word_escaped(input: &str) -> String {
+        single_word_component(input)
+    }
+
+    #[test_case(r"@salt \& pepper{}" => "salt & pepper" ; "ref marker")]
+    #[test_case(r"@curly \{brace\}{}" => "curly {brace}" ; "braces")]
+    #[test_case(r"@70\% chocolate{100%g}" => "70% chocolate" ; "percent")]
+    #[test_case(r"@back\\slash{}" => r"back\slash" ; "backslash")]
+    #[test_case(r"@not\|alias{}" => "not|alias" ; "alias separator")]
+    fn escaped_names(input: &str) -> String {
+        let (s, ctx) = t(input);
+        assert!(ctx.is_empty());
+        let igr = igr!(&s[0]);
+        assert!(igr.alias.is_none());
+        igr.name.text_trimmed().into_owned()
+    }
+
+    #[test]
+    fn escaped_quantity() {
+        let (s, ctx) = t(r"@chocolate{70\%%g}");
+        assert!(ctx.is_empty());
+        let igr = igr!(&s[0]);
+        let q = igr.quantity.as_ref().unwrap();
+        assert_eq!(q.value.span(), Span::new(11, 15));
+        assert_eq!(q.unit.as_ref().unwrap().text_trimmed(), "g");
+        let QuantityValue::Single { value, .. } = &q.value else {
+            panic!("expected single value");
+        };
+        assert_eq!(value.value(), &crate::quantity::Value::Text("70%".into()));
+    }
+
+    #[test]
+    fn alternative_quantities() {
+        let (s, ctx) = t("@flour{1 cup / 120 g}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        let q = igr.quantity.as_ref().unwrap();
+        assert_eq!(q.unit.as_ref().unwrap().text_trimmed(), "cup");
+        assert_eq!(igr.alternative_quantities.len(), 1);
+        let alt = &igr.alternative_quantities[0];
+        assert_eq!(alt.unit.as_ref().unwrap().text_trimmed(), "g");
+
+        // fractions are not split
+        let (s, ctx) = t("@flour{1/2 cup}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert!(igr.alternative_quantities.is_empty());
+        let q = igr.quantity.as_ref().unwrap();
+        assert_eq!(q.unit.as_ref().unwrap().text_trimmed(), "cup");
+    }
+
+    #[test_case("1 cup / 120 g" => 2 ; "alternative")]
+    #[test_case("1/2 cup" => 1 ; "fraction")]
+    #[test_case("100 km/h" => 1 ; "unit with slash")]
+    #[test_case("100 km/h / 62 mph" => 2 ; "unit with slash and alternative")]
+    #[test_case("1 cup / big" => 1 ; "no value after")]
+    fn split_alternatives(input: &str) -> usize {
+        let tokens = TokenStream::new(input).collect::<Vec<_>>();
+        let mut events = VecDeque::new();
+        let bp = BlockParser::new(
+            &tokens,
+            input,
+            &mut events,
+            Extensions::ALTERNATIVE_QUANTITIES,
+        );
+        split_alternative_quantities(&bp, &tokens).len()
+    }
+
+    #[test]
+    fn reference_fragment() {
+        let (s, ctx) = t("@@./sauce#tomato{}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert!(igr.modifiers.contains(Modifiers::RECIPE));
+        assert_eq!(igr.name.text_trimmed(), "./sauce");
+        assert_eq!(
+            igr.reference_fragment.as_ref().unwrap().text_trimmed(),
+            "tomato"
+        );
+
+        let (s, ctx) = t("@@./sauce#tomato paste|paste{100%g}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert_eq!(igr.name.text_trimmed(), "./sauce");
+        assert_eq!(
+            igr.reference_fragment.as_ref().unwrap().text_trimmed(),
+            "tomato paste"
+        );
+        assert_eq!(igr.alias.as_ref().unwrap().text_trimmed(), "paste");
+
+        let (s, ctx) = t("@@./sauce{}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert_eq!(igr.name.text_trimmed(), "./sauce");
+        assert!(igr.reference_fragment.is_none());
+
+        let (s, ctx) = t("@@./sauce#{}");
+        let igr = igr!(&s[0]);
+        assert_eq!(ctx.errors().count(), 1);
+        assert_eq!(igr.name.text_trimmed(), "./sauce");
+        assert!(igr.reference_fragment.is_none());
+
+        // only recipe references have fragments
+        let (s, _) = t("@salt #pan{}");
+        assert!(igr!(&s[0]).reference_fragment.is_none());
+        assert!(matches!(s[2], Event::Cookware(_)));
+
+        // a `#` after a space is a cookware item
+        let (s, ctx) = t("@@sauce in the #pan{}.");
+        assert!(ctx.is_empty());
+        let igr = igr!(&s[0]);
+        assert_eq!(igr.name.text_trimmed(), "sauce");
+        assert!(igr.reference_fragment.is_none());
+        assert!(matches!(s[2], Event::Cookware(_)));
+    }
+
+    #[test]
+    fn preparation() {
+        let (s, ctx) = t("@onion, finely chopped{1}(red)");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert_eq!(igr.name.text_trimmed(), "onion");
+        assert_eq!(
+            igr.preparation.as_ref().unwrap().text_trimmed(),
+            "finely chopped"
+        );
+        assert_eq!(igr.note.as_ref().unwrap().text_trimmed(), "red");
+
+        let (s, ctx) = t("@onion|onions, chopped{1}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert_eq!(igr.name.text_trimmed(), "onion");
+        assert_eq!(igr.alias.as_ref().unwrap().text_trimmed(), "onions");
+        assert_eq!(igr.preparation.as_ref().unwrap().text_trimmed(), "chopped");
+
+        let (s, ctx) = t("@onion{1}(chopped)");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert!(igr.preparation.is_none());
+        assert_eq!(igr.note.as_ref().unwrap().text_trimmed(), "chopped");
+
+        let (s, ctx) = t("@onion, {1}");
+        let igr = igr!(&s[0]);
+        assert_eq!(ctx.errors().count(), 1);
+        assert_eq!(igr.name.text_trimmed(), "onion");
+        assert!(igr.preparation.is_none());
+
+        // single word names end before the comma
+        let (s, _) = t("@onion, chopped");
+        let igr = igr!(&s[0]);
+        assert_eq!(igr.name.text_trimmed(), "onion");
+        assert!(igr.preparation.is_none());
+    }
+
+    #[test]
+    fn variant() {
+        let (s, ctx) = t("@[vegan]butter{100%g}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert_eq!(igr.variant.as_ref().unwrap().text_trimmed(), "vegan");
+        assert_eq!(igr.name.text_trimmed(), "butter");
+
+        let (s, ctx) = t("@?[vegan]butter{}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert!(igr.modifiers.contains(Modifiers::OPT));
+        assert_eq!(igr.variant.as_ref().unwrap().text_trimmed(), "vegan");
+
+        let (s, ctx) = t("@butter{}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert!(igr.variant.is_none());
+
+        let (_, ctx) = t("@[]butter{}");
+        assert_eq!(ctx.errors().count(), 1);
+    }
+
+    #[test]
+    fn category() {
+        let (s, ctx) = t("@flour{1%kg}[baking]");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert_eq!(igr.category.as_ref().unwrap().text_trimmed(), "baking");
+
+        let (s, ctx) = t("@flour{1%kg}(sifted)[baking] and more");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert_eq!(igr.note.as_ref().unwrap().text_trimmed(), "sifted");
+        assert_eq!(igr.category.as_ref().unwrap().text_trimmed(), "baking");
+
+        let (s, ctx) = t("@flour{1%kg} [baking]");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert!(igr.category.is_none());
+
+        let (_, ctx) = t("@flour{}[]");
+        assert_eq!(ctx.errors().count(), 1);
+    }
+
+    #[test]
+    fn nutrition() {
+        let (s, ctx) = t("@sugar{10%g}{cal=40, fat = 1-2}(brown)");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        let facts = igr
+            .nutrition
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|f| format!("{}={}", f.key.text_trimmed(), f.value))
+            .collect::<Vec<_>>();
+        assert_eq!(facts, ["cal=40", "fat=1-2"]);
+        assert_eq!(igr.note.as_ref().unwrap().text_trimmed(), "brown");
+
+        let (s, ctx) = t("@sugar{10%g} {cal=40}");
+        assert!(ctx.is_empty());
+        assert!(igr!(&s[0]).nutrition.is_none());
+    }
+
+    #[test_case("@sugar{}{cal}" => (1, 0); "missing value")]
+    #[test_case("@sugar{}{cal=lots}" => (1, 0); "text value")]
+    #[test_case("@sugar{}{=40}" => (1, 0); "empty key")]
+    #[test_case("@sugar{}{cal=40, cal=50}" => (1, 0); "duplicate key")]
+    #[test_case("@sugar{}{}" => (0, 1); "empty")]
+    fn malformed_nutrition(input: &str) -> (usize, usize) {
+        let (_, ctx) = t(input);
+        (ctx.errors().count(), ctx.warnings().count())
+    }
+
+    #[test]
+    fn substitutes() {
+        let (s, ctx) = t("@butter{1%cup}(or margarine)");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert!(igr.note.is_none());
+        assert_eq!(
+            igr.substitutes,
+            vec![Located::new("margarine".to_string(), 18..27)]
+        );
+
+        let (s, _) = t("@butter{}(Or margarine, ghee OR  oil )");
+        let igr = igr!(&s[0]);
+        let names = igr
+            .substitutes
+            .iter()
+            .map(|s| s.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["margarine", "ghee", "oil"]);
+        assert_eq!(igr.substitutes[2].span(), Span::new(33, 36));
+
+        let (s, _) = t("@butter{}(ordinary, or salted)");
+        let igr = igr!(&s[0]);
+        assert!(igr.substitutes.is_empty());
+        assert_eq!(
+            igr.note.as_ref().unwrap().text_trimmed(),
+            "ordinary, or salted"
+        );
+    }
+
+    #[test]
+    fn per_unit_quantities() {
+        let (s, ctx) = t("@eggs{2 x 50%g}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        let count = igr.count.as_ref().unwrap();
+        assert_eq!(count.to_string(), "2");
+        assert_eq!(count.span(), Span::new(6, 7));
+        let q = igr.quantity.as_ref().unwrap();
+        assert_eq!(q.unit.as_ref().unwrap().text_trimmed(), "g");
+        assert_eq!(q.span(), Span::new(9, 14));
+
+        let (s, ctx) = t("@eggs{1-2 × 50 g / 2 oz}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert_eq!(igr.count.as_ref().unwrap().to_string(), "1-2");
+        assert_eq!(igr.alternative_quantities.len(), 1);
+
+        // with the auto scale marker
+        let (s, ctx) = t("@eggs{2 x 50*%g}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert_eq!(igr.count.as_ref().unwrap().to_string(), "2");
+        assert!(matches!(
+            igr.quantity.as_ref().unwrap().value,
+            QuantityValue::Single {
+                auto_scale: Some(_),
+                ..
+            }
+        ));
+
+        // not a count
+        for input in ["@eggs{a few x 50%g}", "@eggs{2 x}", "@eggs{2%box}"] {
+            let (s, _) = t(input);
+            assert!(igr!(&s[0]).count.is_none(), "{input}");
+        }
+
+        // not a quantity after the `x`
+        let (s, ctx) = t("@eggs{2 x large}");
+        assert!(ctx.is_empty());
+        let igr = igr!(&s[0]);
+        assert!(igr.count.is_none());
+        let q = igr.quantity.as_ref().unwrap();
+        assert_eq!(q.unit.as_ref().unwrap().text_trimmed(), "x large");
+    }
+
+    #[test]
+    fn title_reference() {
+        let (s, ctx) = t("@{{Tomato Sauce, homemade}}{200%g}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert!(igr.title_reference);
+        assert_eq!(igr.name.text_trimmed(), "Tomato Sauce, homemade");
+        assert!(igr.preparation.is_none());
+        let q = igr.quantity.as_ref().unwrap();
+        assert_eq!(q.unit.as_ref().unwrap().text_trimmed(), "g");
+
+        let (s, ctx) = t("@{{Tomato Sauce}} and more");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert!(igr.title_reference);
+        assert_eq!(igr.name.text_trimmed(), "Tomato Sauce");
+        assert!(igr.quantity.is_none());
+
+        let (s, _) = t("@tomato sauce{200%g}");
+        assert!(!igr!(&s[0]).title_reference);
+    }
+
+    #[test]
+    fn purchase_quantity() {
+        let (s, ctx) = t("@milk{2%cups; 1%l}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        let q = igr.quantity.as_ref().unwrap();
+        assert_eq!(q.unit.as_ref().unwrap().text_trimmed(), "cups");
+        let purchase = igr.purchase_quantity.as_ref().unwrap();
+        assert_eq!(purchase.unit.as_ref().unwrap().text_trimmed(), "l");
+        assert_eq!(purchase.span(), Span::new(13, 17));
+
+        let (s, ctx) = t("@eggs{2 x 50%g; 1%dozen}");
+        let igr = igr!(&s[0]);
+        assert!(ctx.is_empty());
+        assert!(igr.count.is_some());
+        assert!(igr.purchase_quantity.is_some());
+
+        // not a purchase quantity
+        for input in ["@milk{2%cups}", "@milk{2%cups;}", "@milk{; 1%l}"] {
+            let (s, _) = t(input);
+            assert!(igr!(&s[0]).purchase_quantity.is_none(), "{input}");
+        }
+    }
 }
@@ -0,0 +1,63 @@
+use crate::{lexer::T, Extensions};
+
+use super::{BlockParser, Event};
+
+/// Title of a group of steps, a single line ending with `:`, like
+/// `Make the sauce:`
+pub(crate) fn step_group<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
+    if !bp.extension(Extensions::STEP_GROUPS) {
+        return None;
+    }
+
+    let tokens = bp.consume_rest();
+    if tokens
+        .iter()
+        .any(|t| matches!(t.kind, T![newline] | T![@] | T![#] | T![~]))
+    {
+        return None;
+    }
+    let end = tokens
+        .iter()
+        .rposition(|t| !matches!(t.kind, T![ws] | T![line comment] | T![block comment]))?;
+    // only one `:`, so a step like `Serve :taco:` is not a title
+    if tokens[end].kind != T![:] || tokens[..end].iter().any(|t| t.kind == T![:]) {
+        return None;
+    }
+    let title = bp.text(tokens[0].span.start(), &tokens[..end]);
+    if title.is_text_empty() {
+        return None;
+    }
+    Some(Event::StepGroup { title })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::TokenStream;
+    use std::collections::VecDeque;
+    use test_case::test_case;
+
+    fn t(input: &str) -> Option<String> {
+        let tokens = TokenStream::new(input).collect::<Vec<_>>();
+        let mut events = VecDeque::new();
+        let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
+        let title = match bp.with_recover(step_group)? {
+            Event::StepGroup { title } => title.text_trimmed().into_owned(),
+            _ => panic!("not a step group"),
+        };
+        bp.finish();
+        Some(title)
+    }
+
+    #[test_case("Make the sauce:" => Some("Make the sauce".to_string()); "basic")]
+    #[test_case("Make the sauce: -- comment" => Some("Make the sauce".to_string()); "comment")]
+    #[test_case("Make the sauce" => None; "no colon")]
+    #[test_case(":" => None; "empty title")]
+    #[test_case("Add the @salt{}:" => None; "component")]
+    #[test_case("Mix: then bake." => None; "colon inside")]
+    #[test_case("Make the sauce\\:" => None; "escaped")]
+    #[test_case("Serve :taco:" => None; "emoji shortcode")]
+    fn step_group_title(input: &str) -> Option<String> {
+        t(input)
+    }
+}
@@ -36,14 +36,19 @@ impl<'i> Iterator for TokenStream<'i> {
     }
 }
 
+/// A token of the recipe source
+///
+/// See [`tokens`](crate::tokens).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Token {
+    /// What the token is
     pub kind: TokenKind,
+    /// Location in the input, in bytes
     pub span: Span,
 }
 
 impl Token {
-    pub fn len(&self) -> usize {
+    pub(crate) fn len(&self) -> usize {
         self.span.len()
     }
 }
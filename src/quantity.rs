@@ -1,6 +1,6 @@
 //! Quantity model
 
-use std::{collections::HashMap, fmt::Display, sync::Arc};
+use std::{collections::HashMap, fmt::Display, str::FromStr, sync::Arc};
 
 use enum_map::EnumMap;
 use once_cell::sync::{Lazy, OnceCell};
@@ -8,7 +8,8 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    convert::{ConvertError, Converter, PhysicalQuantity, Unit},
+    convert::{ConversionCache, ConvertError, Converter, PhysicalQuantity, System, Unit},
+    error::SourceDiag,
     parser,
 };
 
@@ -18,11 +19,38 @@ pub struct Quantity<V: QuantityValue = Value> {
     /// Value
     pub value: V,
     pub(crate) unit: Option<QuantityUnit>,
+    /// Unit text as written in the recipe
+    ///
+    /// Only with [`ParseOptions::canonical_units`](crate::ParseOptions::canonical_units).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) unit_raw: Option<String>,
+    /// What the quantity is for each of, like `serving` in `200 ml/serving`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) per: Option<String>,
+    /// Decimal places the value was written with, like 2 for `10.00`
+    ///
+    /// Only with [`ParseOptions::keep_decimals`](crate::ParseOptions::keep_decimals).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) decimals: Option<u8>,
 }
 
 pub type ScalableQuantity = Quantity<ScalableValue>;
 pub type ScaledQuantity = Quantity<Value>;
 
+/// A count of units of the same size, like `2 x 50 g`
+///
+/// Only with the [`PER_UNIT_QUANTITIES`](crate::Extensions::PER_UNIT_QUANTITIES)
+/// extension. See [`Ingredient::per_unit`](crate::model::Ingredient::per_unit).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PerUnitQuantity<V: QuantityValue = Value> {
+    /// Number of units
+    ///
+    /// It's always a number or a range.
+    pub count: V,
+    /// Quantity of each unit
+    pub per_unit: Quantity<V>,
+}
+
 /// A value with scaling support
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", content = "value", rename_all = "camelCase")]
@@ -43,10 +71,21 @@ pub enum Value {
     Number(Number),
     /// Range
     Range { start: Number, end: Number },
+    /// Number with a tolerance, like `100±5`
+    ///
+    /// Only with the [`TOLERANCES`](crate::Extensions::TOLERANCES) extension.
+    /// Scaling and converting changes both numbers.
+    Tolerant { center: Number, tolerance: Number },
     /// Text
     ///
     /// It is not possible to operate with this variant.
     Text(String),
+    /// Named placeholder, like `$amount`, without the `$`
+    ///
+    /// Only with the [`VARIABLES`](crate::Extensions::VARIABLES) extension.
+    /// Scaling leaves it as it is and it is not possible to operate with it
+    /// until it is replaced with [`Recipe::bind`](crate::Recipe::bind).
+    Variable(String),
 }
 
 /// A wrapper for different kinds of numbers
@@ -56,12 +95,14 @@ pub enum Value {
 /// fraction when the user inputs a fraction. Using fractions is common in
 /// cooking, especially when using imperial units.
 ///
-/// Also, the [`Display`] implementation round `f64` to 3 decimal places.
+/// Also, the [`Display`] implementation round `f64` to 3 decimal places,
+/// unless a precision is given.
 ///
 /// ```
 /// # use cooklang::quantity::Number;
 /// let num = Number::Regular(14.0);
 /// assert_eq!(num.to_string(), "14");
+/// assert_eq!(format!("{num:.2}"), "14.00");
 /// let num = Number::Regular(14.57893);
 /// assert_eq!(num.to_string(), "14.579");
 /// let num = Number::Fraction { whole: 0, num: 1, den: 2, err: 0.0 };
@@ -119,6 +160,135 @@ impl Number {
             } => whole as f64 + err + num as f64 / den as f64,
         }
     }
+
+    /// Number of decimal places of a written decimal number, like 2 for
+    /// `10.00`
+    ///
+    /// [`None`] if the text is not a decimal number.
+    pub(crate) fn decimal_places(text: &str) -> Option<u8> {
+        let text = text.trim();
+        let (_, decimals) = text.split_once('.')?;
+        if !decimals.bytes().all(|b| b.is_ascii_digit()) || text.parse::<f64>().is_err() {
+            return None;
+        }
+        decimals.len().try_into().ok()
+    }
+
+    /// Adds two numbers keeping the result as an exact fraction
+    ///
+    /// At least one of them has to be a fraction and the other a fraction or
+    /// an integer. Returns [`None`] if that's not the case or the result
+    /// does not fit in a fraction.
+    ///
+    /// ```
+    /// # use cooklang::quantity::Number;
+    /// let third = Number::Fraction { whole: 0, num: 1, den: 3, err: 0.0 };
+    /// let sum = third.checked_add(third).unwrap();
+    /// assert_eq!(sum.to_string(), "2/3");
+    /// assert!(third.checked_add(Number::Regular(0.5)).is_none());
+    /// ```
+    pub fn checked_add(self, rhs: Number) -> Option<Number> {
+        if !matches!(self, Number::Fraction { .. }) && !matches!(rhs, Number::Fraction { .. }) {
+            return None;
+        }
+        let (w1, n1, d1, e1) = self.fraction_parts()?;
+        let (w2, n2, d2, e2) = rhs.fraction_parts()?;
+
+        let den = (d1 / gcd(d1, d2)).checked_mul(d2)?;
+        let total = (w1 * d1 + n1)
+            .checked_mul(den / d1)?
+            .checked_add((w2 * d2 + n2).checked_mul(den / d2)?)?;
+
+        reduced_fraction(total, den, e1 + e2)
+    }
+
+    /// `(whole, num, den, err)` of a fraction or integer
+    fn fraction_parts(self) -> Option<(u64, u64, u64, f64)> {
+        match self {
+            Number::Fraction { den: 0, .. } => None,
+            Number::Fraction {
+                whole,
+                num,
+                den,
+                err,
+            } => Some((whole.into(), num.into(), den.into(), err)),
+            Number::Regular(n) => {
+                let is_int = n.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(&n);
+                is_int.then_some((n as u64, 0, 1, 0.0))
+            }
+        }
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The fraction `total / den` with the whole part taken out and reduced
+fn reduced_fraction(total: u64, den: u64, err: f64) -> Option<Number> {
+    if den == 0 {
+        return None;
+    }
+    let (whole, rem) = (total / den, total % den);
+    let (num, den) = match rem {
+        0 => (0, 1),
+        _ => {
+            let g = gcd(rem, den);
+            (rem / g, den / g)
+        }
+    };
+    Some(Number::Fraction {
+        whole: whole.try_into().ok()?,
+        num: num.try_into().ok()?,
+        den: den.try_into().ok()?,
+        err,
+    })
+}
+
+/// Adds two numbers as fractions if possible, or as floats otherwise
+pub(crate) fn add_numbers(a: Number, b: Number) -> Number {
+    a.checked_add(b)
+        .unwrap_or_else(|| Number::Regular(a.value() + b.value()))
+}
+
+/// Multiplies two numbers as fractions if possible, or as floats otherwise
+///
+/// Like [`add_numbers`], at least one of them has to be a fraction to get a
+/// fraction.
+pub(crate) fn mul_numbers(a: Number, b: Number) -> Number {
+    let exact = || {
+        if !matches!(a, Number::Fraction { .. }) && !matches!(b, Number::Fraction { .. }) {
+            return None;
+        }
+        let (w1, n1, d1, e1) = a.fraction_parts()?;
+        let (w2, n2, d2, e2) = b.fraction_parts()?;
+        if e1 != 0.0 || e2 != 0.0 {
+            return None;
+        }
+        let total = (w1 * d1 + n1).checked_mul(w2 * d2 + n2)?;
+        reduced_fraction(total, d1.checked_mul(d2)?, 0.0)
+    };
+    exact().unwrap_or_else(|| Number::Regular(a.value() * b.value()))
+}
+
+/// Divides two numbers as fractions if possible, or as floats otherwise
+///
+/// Two integers make a fraction, like `1/2`. The divisor must not be zero.
+pub(crate) fn div_numbers(a: Number, b: Number) -> Number {
+    debug_assert!(b.value() != 0.0, "division by zero");
+    let exact = || {
+        let (w1, n1, d1, e1) = a.fraction_parts()?;
+        let (w2, n2, d2, e2) = b.fraction_parts()?;
+        if e1 != 0.0 || e2 != 0.0 {
+            return None;
+        }
+        let total = (w1 * d1 + n1).checked_mul(d2)?;
+        reduced_fraction(total, d1.checked_mul(w2 * d2 + n2)?, 0.0)
+    };
+    exact().unwrap_or_else(|| Number::Regular(a.value() / b.value()))
 }
 
 impl PartialEq for Number {
@@ -127,9 +297,63 @@ impl PartialEq for Number {
     }
 }
 
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.value().partial_cmp(&other.value())
+    }
+}
+
+/// Numbers, ranges and tolerances are compared by their midpoint. When it is
+/// the same, the one starting lower goes first, and then a number goes before
+/// a range and a range before a tolerance.
+///
+/// Comparing text with anything else is [`None`].
+///
+/// ```
+/// # use cooklang::quantity::{Number, Value};
+/// let half = Value::Number(Number::Fraction { whole: 0, num: 1, den: 2, err: 0.0 });
+/// assert!(half < Value::from(1.0));
+/// let range = Value::Range { start: 1.0.into(), end: 3.0.into() };
+/// assert!(Value::from(1.5) < range);
+/// assert!(Value::from("a pinch".to_string()).partial_cmp(&half).is_none());
+/// ```
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        fn key(v: &Value) -> Option<(f64, f64, u8)> {
+            match v {
+                Value::Number(n) => Some((n.value(), n.value(), 0)),
+                Value::Range { start, end } => {
+                    Some(((start.value() + end.value()) / 2.0, start.value(), 1))
+                }
+                Value::Tolerant { center, tolerance } => {
+                    Some((center.value(), center.value() - tolerance.value(), 2))
+                }
+                Value::Text(_) | Value::Variable(_) => None,
+            }
+        }
+        match (key(self), key(other)) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            (None, None) if self == other => Some(std::cmp::Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
 pub trait QuantityValue: Display + Clone + sealed::Sealed {
     /// Check if the value is or contains text
     fn is_text(&self) -> bool;
+
+    /// Check if every contained value is a number or a range
+    fn is_numeric(&self) -> bool {
+        self.inner_values()
+            .iter()
+            .all(|v| !v.is_text() && !v.is_variable())
+    }
+
+    /// Check if any contained value is text
+    fn contains_text(&self) -> bool {
+        self.inner_values().iter().any(|v| v.is_text())
+    }
 }
 
 impl QuantityValue for ScalableValue {
@@ -149,9 +373,40 @@ impl QuantityValue for Value {
 }
 
 mod sealed {
-    pub trait Sealed {}
-    impl Sealed for super::ScalableValue {}
-    impl Sealed for super::Value {}
+    use super::{ScalableValue, Value};
+
+    pub trait Sealed {
+        /// All the inner values, for crate internal checks
+        fn inner_values(&self) -> Vec<&Value>;
+        /// All the inner values, mutable
+        fn inner_values_mut(&mut self) -> Vec<&mut Value>;
+    }
+
+    impl Sealed for ScalableValue {
+        fn inner_values(&self) -> Vec<&Value> {
+            match self {
+                ScalableValue::Fixed(value) | ScalableValue::Linear(value) => vec![value],
+                ScalableValue::ByServings(values) => values.iter().collect(),
+            }
+        }
+
+        fn inner_values_mut(&mut self) -> Vec<&mut Value> {
+            match self {
+                ScalableValue::Fixed(value) | ScalableValue::Linear(value) => vec![value],
+                ScalableValue::ByServings(values) => values.iter_mut().collect(),
+            }
+        }
+    }
+
+    impl Sealed for Value {
+        fn inner_values(&self) -> Vec<&Value> {
+            vec![self]
+        }
+
+        fn inner_values_mut(&mut self) -> Vec<&mut Value> {
+            vec![self]
+        }
+    }
 }
 
 /// Unit text with lazy rich information
@@ -198,13 +453,24 @@ impl QuantityUnit {
             .get_or_init(|| UnitInfo::new(&self.text, converter))
             .clone()
     }
+
+    /// Same as [`Self::unit_info_or_parse`] but using a [`ConversionCache`]
+    pub(crate) fn unit_info_cached(
+        &self,
+        converter: &Converter,
+        cache: &mut ConversionCache,
+    ) -> UnitInfo {
+        self.info
+            .get_or_init(|| cache.unit_info(&self.text, converter))
+            .clone()
+    }
 }
 
 impl UnitInfo {
     /// Parse the unit with the given converter
     pub fn new(text: &str, converter: &Converter) -> Self {
         match converter.get_unit(&text.into()) {
-            Ok(unit) => Self::Known(Arc::clone(unit)),
+            Ok(unit) => Self::Known(unit),
             Err(_) => Self::Unknown,
         }
     }
@@ -219,6 +485,9 @@ impl<V: QuantityValue> Quantity<V> {
                 text,
                 info: OnceCell::new(),
             }),
+            unit_raw: None,
+            per: None,
+            decimals: None,
         }
     }
 
@@ -230,6 +499,9 @@ impl<V: QuantityValue> Quantity<V> {
                 info: OnceCell::from(UnitInfo::new(&text, converter)),
                 text,
             }),
+            unit_raw: None,
+            per: None,
+            decimals: None,
         }
     }
 
@@ -241,6 +513,62 @@ impl<V: QuantityValue> Quantity<V> {
                 text: unit.to_string(),
                 info: OnceCell::from(UnitInfo::Known(unit)),
             }),
+            unit_raw: None,
+            per: None,
+            decimals: None,
+        }
+    }
+
+    /// Creates a new quantity with an alias of a known unit
+    ///
+    /// Unlike [`Self::with_known_unit`], the alias is kept as the unit text.
+    pub(crate) fn with_aliased_unit(value: V, alias: String, unit: Arc<Unit>) -> Self {
+        Self {
+            value,
+            unit: Some(QuantityUnit {
+                text: alias,
+                info: OnceCell::from(UnitInfo::Known(unit)),
+            }),
+            unit_raw: None,
+            per: None,
+            decimals: None,
+        }
+    }
+
+    /// Replaces the value and unit, keeping the rest of the quantity as is
+    ///
+    /// The written [`Self::decimals`] are dropped if the value changes.
+    pub(crate) fn set_known_unit(&mut self, value: V, unit: Arc<Unit>)
+    where
+        V: PartialEq,
+    {
+        let new = Self::with_known_unit(value, unit);
+        if new.value != self.value {
+            self.decimals = None;
+        }
+        self.value = new.value;
+        self.unit = new.unit;
+    }
+
+    /// Replaces the unit with the canonical form of the known unit
+    ///
+    /// The original text is stored in [`Self::unit_raw`]. If the unit is not
+    /// known, it's not changed.
+    pub(crate) fn canonicalize_unit(&mut self, converter: &Converter) {
+        let Some(unit) = self.unit.as_mut() else {
+            return;
+        };
+        let known = match unit.unit_info_or_parse(converter) {
+            UnitInfo::Known(known) => Some(known),
+            UnitInfo::Unknown => converter.find_unit(&unit.text.to_lowercase()),
+        };
+        match known {
+            Some(known) => {
+                let canonical = known.symbol().to_string();
+                self.unit_raw = Some(std::mem::replace(&mut unit.text, canonical));
+                unit.info = OnceCell::from(UnitInfo::Known(known));
+            }
+            None => self.unit_raw = Some(unit.text.clone()),
         }
     }
 
@@ -263,9 +591,230 @@ impl<V: QuantityValue> Quantity<V> {
     pub fn unit_text(&self) -> Option<&str> {
         self.unit.as_ref().map(|u| u.text.as_ref())
     }
+
+    /// Get the unit text as written in the recipe
+    ///
+    /// This is only [`Some`] when the recipe was parsed with
+    /// [`ParseOptions::canonical_units`](crate::ParseOptions::canonical_units).
+    /// Then, [`Self::unit_text`] is the canonical unit.
+    pub fn unit_raw(&self) -> Option<&str> {
+        self.unit_raw.as_deref()
+    }
+
+    /// Get what the quantity is for each of, like `serving` in `200 ml/serving`
+    ///
+    /// Only with the [`ADVANCED_UNITS`](crate::Extensions::ADVANCED_UNITS)
+    /// extension. It's not part of [`Self::unit_text`].
+    ///
+    /// ```
+    /// let r = cooklang::parse("Drink @water{200 ml/serving}.")
+    ///     .unwrap_output()
+    ///     .default_scale();
+    /// let q = r.ingredients[0].quantity.as_ref().unwrap();
+    /// assert_eq!(q.unit_text(), Some("ml"));
+    /// assert_eq!(q.per(), Some("serving"));
+    /// assert_eq!(q.to_string(), "200 ml/serving");
+    /// ```
+    pub fn per(&self) -> Option<&str> {
+        self.per.as_deref()
+    }
+
+    /// Decimal places the value was written with, like 2 for `10.00`
+    ///
+    /// Only with [`ParseOptions::keep_decimals`](crate::ParseOptions::keep_decimals),
+    /// otherwise [`None`]. The [`Display`] impl uses them. Converting the
+    /// quantity to another unit discards them.
+    ///
+    /// ```
+    /// # use cooklang::*;
+    /// let options = ParseOptions {
+    ///     keep_decimals: true,
+    ///     ..Default::default()
+    /// };
+    /// let r = CooklangParser::default()
+    ///     .parse_with_options("@flour{10.00%g}", options)
+    ///     .unwrap_output()
+    ///     .default_scale();
+    /// let q = r.ingredients[0].quantity.as_ref().unwrap();
+    /// assert_eq!(q.decimals(), Some(2));
+    /// assert_eq!(q.to_string(), "10.00 g");
+    /// ```
+    pub fn decimals(&self) -> Option<u8> {
+        self.decimals
+    }
+
+    /// Checks if the quantity is a count of things, like `@eggs{3}`
+    ///
+    /// A count is a numeric value without unit. These are never added to a
+    /// quantity with a unit, so a [`GroupedQuantity`] keeps them apart, see
+    /// [`GroupedQuantity::count`].
+    ///
+    /// ```
+    /// # use cooklang::quantity::*;
+    /// assert!(Quantity::new(Value::from(3.0), None).is_count());
+    /// assert!(!Quantity::new(Value::from(3.0), Some("g".into())).is_count());
+    /// assert!(!Quantity::new(Value::from("a few".to_string()), None).is_count());
+    /// ```
+    pub fn is_count(&self) -> bool {
+        self.unit.is_none() && !self.value.is_text()
+    }
+}
+
+impl PerUnitQuantity<Value> {
+    /// Quantity of all the units together
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, Extensions, Converter};
+    /// let parser = CooklangParser::new(Extensions::all(), Converter::bundled());
+    /// let r = parser.parse("@eggs{2 x 50%g}").unwrap_output().default_scale();
+    /// let eggs = r.ingredients[0].per_unit.as_ref().unwrap();
+    /// assert_eq!(eggs.to_string(), "2 x 50 g");
+    /// assert_eq!(eggs.total().unwrap().to_string(), "100 g");
+    /// ```
+    pub fn total(&self) -> Result<ScaledQuantity, TextValueError> {
+        Ok(Quantity {
+            value: self.per_unit.value.try_mul(&self.count)?,
+            ..self.per_unit.clone()
+        })
+    }
+}
+
+impl<V: QuantityValue + Display> Display for PerUnitQuantity<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} x {}", self.count, self.per_unit)
+    }
+}
+
+impl Value {
+    /// Checks if the value is a variable waiting to be bound
+    pub fn is_variable(&self) -> bool {
+        matches!(self, Value::Variable(_))
+    }
+
+    /// Checks if the value is numeric and zero
+    ///
+    /// A range is zero when both ends are zero, and a tolerance when both the
+    /// center and the tolerance are. Text and variables are never zero.
+    ///
+    /// ```
+    /// # use cooklang::quantity::Value;
+    /// assert!(Value::from(0.0).is_zero());
+    /// assert!(!Value::from(0.5).is_zero());
+    /// assert!(!Value::Text("0".into()).is_zero());
+    /// ```
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Value::Number(n) => n.value() == 0.0,
+            Value::Range { start, end } => start.value() == 0.0 && end.value() == 0.0,
+            Value::Tolerant { center, tolerance } => {
+                center.value() == 0.0 && tolerance.value() == 0.0
+            }
+            Value::Text(_) | Value::Variable(_) => false,
+        }
+    }
+
+    /// Rounds the value to some decimal places
+    ///
+    /// Both ends of a range and both numbers of a tolerance are rounded and
+    /// fractions are converted to regular numbers. Text and variables are
+    /// left unchanged.
+    ///
+    /// ```
+    /// # use cooklang::quantity::{Number, Value};
+    /// let third = Value::Number(Number::Fraction { whole: 0, num: 1, den: 3, err: 0.0 });
+    /// assert_eq!(third.round_to(2), Value::from(0.33));
+    /// ```
+    pub fn round_to(&self, decimals: u8) -> Value {
+        let factor = 10f64.powi(decimals.into());
+        let round = |n: &Number| Number::Regular((n.value() * factor).round() / factor);
+        match self {
+            Value::Number(n) => Value::Number(round(n)),
+            Value::Range { start, end } => Value::Range {
+                start: round(start),
+                end: round(end),
+            },
+            Value::Tolerant { center, tolerance } => Value::Tolerant {
+                center: round(center),
+                tolerance: round(tolerance),
+            },
+            Value::Text(_) | Value::Variable(_) => self.clone(),
+        }
+    }
+
+    /// Range between the lowest and highest value of a tolerance
+    ///
+    /// Other values are returned as they are.
+    ///
+    /// ```
+    /// # use cooklang::quantity::Value;
+    /// let value = Value::Tolerant { center: 100.0.into(), tolerance: 5.0.into() };
+    /// assert_eq!(value.tolerance_bounds().to_string(), "95-105");
+    /// ```
+    pub fn tolerance_bounds(&self) -> Value {
+        match self {
+            Value::Tolerant { center, tolerance } => Value::Range {
+                start: (center.value() - tolerance.value()).into(),
+                end: (center.value() + tolerance.value()).into(),
+            },
+            _ => self.clone(),
+        }
+    }
+
+    /// Multiplies two numeric values
+    ///
+    /// A range multiplied by a number multiplies both ends, and the same for
+    /// a tolerance. A tolerance multiplied by a range or another tolerance is
+    /// multiplied as the range of its bounds.
+    pub(crate) fn try_mul(&self, rhs: &Value) -> Result<Value, TextValueError> {
+        let mul = |a: &Number, b: &Number| Number::from(a.value() * b.value());
+        let value = match (self, rhs) {
+            (Value::Text(_) | Value::Variable(_), _) => return Err(TextValueError(self.clone())),
+            (_, Value::Text(_) | Value::Variable(_)) => return Err(TextValueError(rhs.clone())),
+            (Value::Number(a), Value::Number(b)) => Value::Number(mul(a, b)),
+            (Value::Number(n), Value::Tolerant { center, tolerance })
+            | (Value::Tolerant { center, tolerance }, Value::Number(n)) => Value::Tolerant {
+                center: mul(center, n),
+                tolerance: mul(tolerance, n),
+            },
+            (Value::Tolerant { .. }, _) | (_, Value::Tolerant { .. }) => {
+                return self.tolerance_bounds().try_mul(&rhs.tolerance_bounds())
+            }
+            (Value::Number(n), Value::Range { start, end })
+            | (Value::Range { start, end }, Value::Number(n)) => Value::Range {
+                start: mul(start, n),
+                end: mul(end, n),
+            },
+            (
+                Value::Range { start, end },
+                Value::Range {
+                    start: rhs_start,
+                    end: rhs_end,
+                },
+            ) => Value::Range {
+                start: mul(start, rhs_start),
+                end: mul(end, rhs_end),
+            },
+        };
+        Ok(value)
+    }
 }
 
 impl ScalableValue {
+    /// Multiplies all the inner values, see [`Value::try_mul`]
+    pub(crate) fn try_mul(&self, rhs: &Value) -> Result<Self, TextValueError> {
+        let value = match self {
+            Self::Fixed(v) => Self::Fixed(v.try_mul(rhs)?),
+            Self::Linear(v) => Self::Linear(v.try_mul(rhs)?),
+            Self::ByServings(values) => Self::ByServings(
+                values
+                    .iter()
+                    .map(|v| v.try_mul(rhs))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+        Ok(value)
+    }
+
     pub(crate) fn from_ast(value: parser::QuantityValue) -> Self {
         match value {
             parser::QuantityValue::Single {
@@ -289,11 +838,19 @@ impl ScalableValue {
 
 impl<V: QuantityValue + Display> Display for Quantity<V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.value.fmt(f)?;
+        match self.decimals {
+            Some(decimals) if f.precision().is_none() && !self.value.contains_text() => {
+                write!(f, "{:.*}", decimals as usize, self.value)?
+            }
+            _ => self.value.fmt(f)?,
+        }
         if let Some(unit) = &self.unit {
             f.write_str(" ")?;
             unit.fmt(f)?;
         }
+        if let Some(per) = &self.per {
+            write!(f, "/{per}")?;
+        }
         Ok(())
     }
 }
@@ -302,12 +859,16 @@ impl Display for ScalableValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Fixed(value) => value.fmt(f),
-            Self::Linear(value) => write!(f, "{value}*"),
+            Self::Linear(value) => {
+                value.fmt(f)?;
+                f.write_str("*")
+            }
             Self::ByServings(values) => {
                 for value in &values[..values.len() - 1] {
-                    write!(f, "{}|", value)?;
+                    value.fmt(f)?;
+                    f.write_str("|")?;
                 }
-                write!(f, "{}", values.last().unwrap())
+                values.last().unwrap().fmt(f)
             }
         }
     }
@@ -317,8 +878,18 @@ impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Value::Number(n) => n.fmt(f),
-            Value::Range { start, end } => write!(f, "{start}-{end}"),
+            Value::Range { start, end } => {
+                start.fmt(f)?;
+                f.write_str("-")?;
+                end.fmt(f)
+            }
+            Value::Tolerant { center, tolerance } => {
+                center.fmt(f)?;
+                f.write_str("±")?;
+                tolerance.fmt(f)
+            }
             Value::Text(t) => t.fmt(f),
+            Value::Variable(name) => write!(f, "${name}"),
         }
     }
 }
@@ -330,7 +901,10 @@ fn round_float(n: f64) -> f64 {
 impl Display for Number {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
-            Number::Regular(n) => write!(f, "{}", round_float(n)),
+            Number::Regular(n) => match f.precision() {
+                Some(decimals) => write!(f, "{n:.decimals$}"),
+                None => write!(f, "{}", round_float(n)),
+            },
             Number::Fraction {
                 whole,
                 num,
@@ -357,6 +931,52 @@ impl Display for Number {
     }
 }
 
+/// How to display a [`Number`], see [`Number::format`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NumberFormat {
+    /// Always a decimal number, like `0.5`
+    Decimal,
+    /// A fraction if the number is close enough to one, like `1/2` or `1/8`
+    Fraction,
+    /// A fraction only for halves, thirds and quarters, which are easy to
+    /// measure, and a decimal otherwise
+    #[default]
+    Auto,
+}
+
+impl Number {
+    /// Displays the number as a decimal or a fraction, regardless of how it
+    /// is stored
+    ///
+    /// Integers are always displayed as integers. When a fraction can't
+    /// approximate the number, it's displayed as a decimal.
+    ///
+    /// ```
+    /// # use cooklang::quantity::{Number, NumberFormat};
+    /// let n = Number::Regular(0.125);
+    /// assert_eq!(n.format(NumberFormat::Decimal), "0.125");
+    /// assert_eq!(n.format(NumberFormat::Fraction), "1/8");
+    /// assert_eq!(n.format(NumberFormat::Auto), "0.125");
+    /// ```
+    pub fn format(self, fmt: NumberFormat) -> String {
+        let (accuracy, max_den) = match fmt {
+            NumberFormat::Decimal => {
+                return match self {
+                    Number::Fraction { .. } => round_float(self.value()).to_string(),
+                    _ => self.to_string(),
+                }
+            }
+            NumberFormat::Fraction => (0.05, 16),
+            NumberFormat::Auto => (0.01, 4),
+        };
+        match Number::new_approx(self.value(), accuracy, max_den, u32::MAX) {
+            Some(n) => n.to_string(),
+            None => self.format(NumberFormat::Decimal),
+        }
+    }
+}
+
 impl Display for QuantityUnit {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.text)
@@ -375,6 +995,198 @@ impl From<String> for Value {
     }
 }
 
+/// Error from parsing a [`Value`] or a [`Number`] from a string
+#[derive(Debug, Error, Clone)]
+pub enum ParseValueError {
+    /// The input is empty
+    #[error("Empty value")]
+    Empty,
+    /// The input is not a single number
+    ///
+    /// Only for [`Number`], a [`Value`] falls back to text.
+    #[error("Expected a number, found '{0}'")]
+    NotANumber(String),
+    /// The input looks like a number but is not valid, like `1/0`
+    #[error(transparent)]
+    Invalid(SourceDiag),
+}
+
+impl FromStr for Value {
+    type Err = ParseValueError;
+
+    /// Parses a value like it's written in a quantity
+    ///
+    /// Numbers, fractions, ranges and variables are parsed like in a recipe.
+    /// Anything else is a text value.
+    ///
+    /// ```
+    /// # use cooklang::quantity::{Number, Value};
+    /// assert_eq!("2".parse::<Value>().unwrap(), Value::from(2.0));
+    /// assert_eq!("1 1/2".parse::<Value>().unwrap().to_string(), "1 1/2");
+    /// assert_eq!(
+    ///     "1-2".parse::<Value>().unwrap(),
+    ///     Value::Range { start: 1.0.into(), end: 2.0.into() }
+    /// );
+    /// assert_eq!("$amount".parse::<Value>().unwrap(), Value::Variable("amount".into()));
+    /// assert_eq!("a pinch".parse::<Value>().unwrap(), Value::from("a pinch".to_string()));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseValueError::Empty);
+        }
+        match parser::parse_numeric_value(s) {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(err)) => Err(ParseValueError::Invalid(err)),
+            None => Ok(Value::Text(s.to_string())),
+        }
+    }
+}
+
+impl FromStr for Number {
+    type Err = ParseValueError;
+
+    /// Parses a single number, like `2`, `0.5` or `1/2`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<Value>()? {
+            Value::Number(n) => Ok(n),
+            Value::Range { .. } | Value::Tolerant { .. } | Value::Text(_) | Value::Variable(_) => {
+                Err(ParseValueError::NotANumber(s.trim().to_string()))
+            }
+        }
+    }
+}
+
+/// Wrapper to serialize a [`Value`] or a [`Number`] in a compact form
+///
+/// The default representation is a tagged enum, like
+/// `{"type":"number","value":{"type":"regular","value":2.5}}`. The compact
+/// one is a number for regular numbers and the displayed string for
+/// everything else, like `"2 1/2"`, `"1-2"` or `"a pinch"`. Strings are
+/// parsed like a quantity value when deserializing, so a text that looks
+/// like a number is a number, and decimal places and fraction errors are
+/// not kept.
+///
+/// For a field, use [`compact`] with `#[serde(with = "cooklang::quantity::compact")]`.
+///
+/// ```
+/// # use cooklang::quantity::{Compact, Value};
+/// let value: Value = "2 1/2".parse().unwrap();
+/// let json = serde_json::to_string(&Compact(&value)).unwrap();
+/// assert_eq!(json, r#""2 1/2""#);
+/// let Compact(back) = serde_json::from_str::<Compact<Value>>(&json).unwrap();
+/// assert_eq!(back, value);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Compact<T>(pub T);
+
+/// Types with a [`Compact`] serde representation
+pub trait CompactForm: Sized {
+    /// Serialize in the compact form
+    fn serialize_compact<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+    /// Deserialize from the compact form
+    fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error>;
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CompactRepr {
+    Number(f64),
+    Text(String),
+}
+
+impl CompactForm for Number {
+    fn serialize_compact<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Number::Regular(n) => serializer.serialize_f64(*n),
+            _ => serializer.collect_str(self),
+        }
+    }
+
+    fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        match CompactRepr::deserialize(deserializer)? {
+            CompactRepr::Number(n) => Ok(Number::Regular(n)),
+            CompactRepr::Text(s) => s.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl CompactForm for Value {
+    fn serialize_compact<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Number(n) => n.serialize_compact(serializer),
+            _ => serializer.collect_str(self),
+        }
+    }
+
+    fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        match CompactRepr::deserialize(deserializer)? {
+            CompactRepr::Number(n) => Ok(Value::from(n)),
+            CompactRepr::Text(s) if s.trim().is_empty() => Ok(Value::Text(s)),
+            CompactRepr::Text(s) => s.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl<T: CompactForm> CompactForm for &T {
+    fn serialize_compact<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize_compact(serializer)
+    }
+
+    fn deserialize_compact<'de, D: serde::Deserializer<'de>>(_: D) -> Result<Self, D::Error> {
+        Err(serde::de::Error::custom("can't deserialize a reference"))
+    }
+}
+
+impl<T: CompactForm> Serialize for Compact<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_compact(serializer)
+    }
+}
+
+impl<'de, T: CompactForm> Deserialize<'de> for Compact<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize_compact(deserializer).map(Compact)
+    }
+}
+
+/// Serde functions for the [`Compact`] form of a field
+///
+/// ```
+/// # use cooklang::quantity::Value;
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Amount {
+///     #[serde(with = "cooklang::quantity::compact")]
+///     value: Value,
+/// }
+/// let amount: Amount = serde_json::from_str(r#"{"value":"1-2"}"#).unwrap();
+/// assert_eq!(amount.value.to_string(), "1-2");
+/// ```
+pub mod compact {
+    use super::CompactForm;
+
+    /// Serialize the value in the compact form
+    pub fn serialize<T: CompactForm, S: serde::Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.serialize_compact(serializer)
+    }
+
+    /// Deserialize the value from the compact form
+    pub fn deserialize<'de, T: CompactForm, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        T::deserialize_compact(deserializer)
+    }
+}
+
 /// Error during adding of quantities
 #[derive(Debug, Error)]
 pub enum QuantityAddError {
@@ -402,6 +1214,11 @@ pub enum IncompatibleUnits {
     },
     #[error("Unknown units differ: '{a}' '{b}'")]
     UnknownDifferentUnits { a: String, b: String },
+    #[error("Different per unit: {a:?} {b:?}")]
+    DifferentPerUnit {
+        a: Option<String>,
+        b: Option<String>,
+    },
 }
 
 impl<V: QuantityValue> Quantity<V> {
@@ -412,6 +1229,20 @@ impl<V: QuantityValue> Quantity<V> {
         rhs: &Self,
         converter: &Converter,
     ) -> Result<Option<Arc<Unit>>, IncompatibleUnits> {
+        self.compatible_unit_with(rhs, |u| u.unit_info_or_parse(converter))
+    }
+
+    fn compatible_unit_with(
+        &self,
+        rhs: &Self,
+        mut unit_info: impl FnMut(&QuantityUnit) -> UnitInfo,
+    ) -> Result<Option<Arc<Unit>>, IncompatibleUnits> {
+        if self.per != rhs.per {
+            return Err(IncompatibleUnits::DifferentPerUnit {
+                a: self.per.clone(),
+                b: rhs.per.clone(),
+            });
+        }
         let base = match (&self.unit, &rhs.unit) {
             // No units = ok
             (None, None) => None,
@@ -428,8 +1259,8 @@ impl<V: QuantityValue> Quantity<V> {
             }
             // Units -> check
             (Some(a), Some(b)) => {
-                let a_unit = a.unit_info_or_parse(converter);
-                let b_unit = b.unit_info_or_parse(converter);
+                let a_unit = unit_info(a);
+                let b_unit = unit_info(b);
 
                 match (a_unit, b_unit) {
                     (UnitInfo::Known(a_unit), UnitInfo::Known(b_unit)) => {
@@ -462,13 +1293,51 @@ impl<V: QuantityValue> Quantity<V> {
 impl ScaledQuantity {
     /// Try adding two quantities
     pub fn try_add(&self, rhs: &Self, converter: &Converter) -> Result<Self, QuantityAddError> {
+        self.try_add_impl(rhs, converter, None)
+    }
+
+    /// Same as [`Self::try_add`] but using a [`ConversionCache`]
+    ///
+    /// Use this when adding many quantities with the same converter.
+    pub fn try_add_cached(
+        &self,
+        rhs: &Self,
+        converter: &Converter,
+        cache: &mut ConversionCache,
+    ) -> Result<Self, QuantityAddError> {
+        self.try_add_impl(rhs, converter, Some(cache))
+    }
+
+    fn try_add_impl(
+        &self,
+        rhs: &Self,
+        converter: &Converter,
+        mut cache: Option<&mut ConversionCache>,
+    ) -> Result<Self, QuantityAddError> {
         // 1. Check if the units are compatible and (maybe) get a common unit
-        let convert_to = self.compatible_unit(rhs, converter)?;
+        let convert_to = self.compatible_unit_with(rhs, |u| match cache.as_deref_mut() {
+            Some(cache) => u.unit_info_cached(converter, cache),
+            None => u.unit_info_or_parse(converter),
+        })?;
 
         // 2. Convert rhs to the unit of the first one if needed
-        let mut rhs = rhs.clone();
-        if let Some(to) = convert_to {
-            rhs.convert(&to, converter)?;
+        let rhs = match (convert_to, cache) {
+            (Some(to), Some(cache)) => {
+                // compatible units are known
+                let Some(UnitInfo::Known(from)) = rhs.unit().and_then(|u| u.unit_info()) else {
+                    unreachable!("compatible unit is not known")
+                };
+                let value = cache.convert_value(&rhs.value, &from, &to)?;
+                let mut rhs = Quantity::with_known_unit(value, to);
+                rhs.try_fraction(converter);
+                rhs
+            }
+            (Some(to), None) => {
+                let mut rhs = rhs.clone();
+                rhs.convert(&to, converter)?;
+                rhs
+            }
+            (None, _) => rhs.clone(),
         };
 
         // 3. Sum values
@@ -478,43 +1347,126 @@ impl ScaledQuantity {
         let qty = Quantity {
             value,
             unit: self.unit.clone(), // unit is mantained
+            unit_raw: self.unit_raw.clone(),
+            per: self.per.clone(),
+            decimals: self.decimals,
         };
 
         Ok(qty)
     }
+
+    /// Returns the quantity converted to a unit
+    ///
+    /// Like [`Self::convert`] to a unit, but the quantity is not modified.
+    /// Both ends of ranges are converted. Text values, unknown units and
+    /// quantities without unit can't be converted.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, quantity::{Quantity, Value}};
+    /// let converter = Converter::bundled();
+    /// let q = Quantity::new(Value::from(2.0), Some("kg".into()));
+    /// assert_eq!(q.convert_to("g", &converter).unwrap().to_string(), "2000 g");
+    /// ```
+    pub fn convert_to(&self, unit: &str, converter: &Converter) -> Result<Self, ConvertError> {
+        let mut q = self.clone();
+        q.convert(unit, converter)?;
+        Ok(q)
+    }
+
+    /// Formats the quantity to show it to a user
+    ///
+    /// With a `system`, it's converted to the best unit of that system.
+    /// Otherwise, it's fitted to the best unit of its own system, like
+    /// [`Self::fit`](ScaledQuantity::fit). Text values, unknown units and
+    /// quantities without a unit are formatted as they are.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, quantity::{Quantity, Value}, convert::System};
+    /// let converter = Converter::bundled();
+    /// let q = Quantity::new(Value::from(1500.0), Some("ml".into()));
+    /// assert_eq!(q.display_with(&converter, None), "1.5 l");
+    /// ```
+    pub fn display_with(&self, converter: &Converter, system: Option<System>) -> String {
+        let mut q = self.clone();
+        let res = match system {
+            Some(system) => q.convert(system, converter),
+            None => q.fit(converter),
+        };
+        match res {
+            Ok(()) => q.to_string(),
+            Err(_) => self.to_string(),
+        }
+    }
 }
 
+/// Fallible addition
 pub trait TryAdd: Sized {
     type Err;
 
     fn try_add(&self, rhs: &Self) -> Result<Self, Self::Err>;
 }
 
-/// Error when try to operate on a text value
+/// Error when try to operate on a text value or a variable
 #[derive(Debug, Error, Clone)]
-#[error("Cannot operate on a text value")]
+#[error("Cannot operate on a {} value", value_kind(.0))]
 pub struct TextValueError(pub Value);
 
+fn value_kind(value: &Value) -> &'static str {
+    if value.is_variable() {
+        "variable"
+    } else {
+        "text"
+    }
+}
+
+/// Numbers are added normally. Ranges are added by their ends, so `2-3` plus
+/// `1-2` is `3-5`, and a number added to a range is added to both ends, so
+/// `2-3` plus `1` is `3-4`. Tolerances add their centers and tolerances, so
+/// `100±5` plus `50±2` is `150±7`, and with a range they are added as the
+/// range of their bounds. Text values and variables can't be added.
 impl TryAdd for Value {
     type Err = TextValueError;
 
     fn try_add(&self, rhs: &Self) -> Result<Value, TextValueError> {
         let val = match (self, rhs) {
-            (Value::Number(a), Value::Number(b)) => Value::Number((a.value() + b.value()).into()),
+            (Value::Number(a), Value::Number(b)) => Value::Number(add_numbers(*a, *b)),
             (Value::Number(n), Value::Range { start, end })
             | (Value::Range { start, end }, Value::Number(n)) => Value::Range {
-                start: (start.value() + n.value()).into(),
-                end: (end.value() + n.value()).into(),
+                start: add_numbers(*start, *n),
+                end: add_numbers(*end, *n),
             },
             (Value::Range { start: s1, end: e1 }, Value::Range { start: s2, end: e2 }) => {
                 Value::Range {
-                    start: (s1.value() + s2.value()).into(),
-                    end: (e1.value() + e2.value()).into(),
+                    start: add_numbers(*s1, *s2),
+                    end: add_numbers(*e1, *e2),
                 }
             }
-            (t @ Value::Text(_), _) | (_, t @ Value::Text(_)) => {
+            (t @ (Value::Text(_) | Value::Variable(_)), _)
+            | (_, t @ (Value::Text(_) | Value::Variable(_))) => {
                 return Err(TextValueError(t.to_owned()));
             }
+            (Value::Number(n), Value::Tolerant { center, tolerance })
+            | (Value::Tolerant { center, tolerance }, Value::Number(n)) => Value::Tolerant {
+                center: add_numbers(*center, *n),
+                tolerance: *tolerance,
+            },
+            (
+                Value::Tolerant {
+                    center: c1,
+                    tolerance: t1,
+                },
+                Value::Tolerant {
+                    center: c2,
+                    tolerance: t2,
+                },
+            ) => Value::Tolerant {
+                center: add_numbers(*c1, *c2),
+                tolerance: add_numbers(*t1, *t2),
+            },
+            (Value::Tolerant { .. }, Value::Range { .. })
+            | (Value::Range { .. }, Value::Tolerant { .. }) => {
+                return self.tolerance_bounds().try_add(&rhs.tolerance_bounds())
+            }
         };
 
         Ok(val)
@@ -543,6 +1495,27 @@ pub struct GroupedQuantity {
     no_unit: Option<ScaledQuantity>,
     /// could not operate/add to others
     other: Vec<ScaledQuantity>,
+    /// by unit text, without conversions
+    components: Vec<ScaledQuantity>,
+}
+
+/// What [`GroupedQuantity::add_with_policy`] does with units that can't be
+/// added, like `g` and `cup` without a density
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GroupingPolicy {
+    /// Convert and add the compatible units, and keep the incompatible ones in
+    /// their own group
+    ///
+    /// This is what [`GroupedQuantity::add`] does.
+    #[default]
+    Merge,
+    /// Don't convert units, only add quantities with the same unit
+    ///
+    /// Compatible units, like `kg` and `g`, are kept separate too.
+    KeepSeparate,
+    /// Same as [`Self::Merge`], but a quantity that can't be added to the ones
+    /// in the group is an error
+    Error,
 }
 
 impl GroupedQuantity {
@@ -553,9 +1526,101 @@ impl GroupedQuantity {
 
     /// Add a new quantity to the group
     pub fn add(&mut self, q: &ScaledQuantity, converter: &Converter) {
+        self.add_component(q);
+        self.add_impl(q, converter, None)
+    }
+
+    /// Same as [`Self::add`] but using a [`ConversionCache`]
+    ///
+    /// ```
+    /// # use cooklang::{convert::ConversionCache, Converter, GroupedQuantity, Quantity, Value};
+    /// let converter = Converter::bundled();
+    /// let mut cache = ConversionCache::new();
+    /// let mut grouped = GroupedQuantity::empty();
+    /// for _ in 0..10 {
+    ///     let q = Quantity::new(Value::from(100.0), Some("ml".into()));
+    ///     grouped.add_cached(&q, &converter, &mut cache);
+    /// }
+    /// assert_eq!(grouped.to_string(), "1000 ml");
+    /// ```
+    pub fn add_cached(
+        &mut self,
+        q: &ScaledQuantity,
+        converter: &Converter,
+        cache: &mut ConversionCache,
+    ) {
+        self.add_component(q);
+        self.add_impl(q, converter, Some(cache))
+    }
+
+    /// Same as [`Self::add`] but choosing what happens with incompatible units
+    ///
+    /// With [`GroupingPolicy::Error`], the quantity is not added if its unit
+    /// is not compatible with all the numeric quantities in the group. Text
+    /// values are always added. Use the same policy for all the quantities of
+    /// a group.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, GroupedQuantity, Quantity, Value};
+    /// # use cooklang::quantity::GroupingPolicy;
+    /// let converter = Converter::bundled();
+    /// let mut grouped = GroupedQuantity::empty();
+    /// let flour = Quantity::new(Value::from(100.0), Some("g".into()));
+    /// let milk = Quantity::new(Value::from(1.0), Some("cup".into()));
+    /// grouped.add_with_policy(&flour, &converter, GroupingPolicy::Error).unwrap();
+    /// assert!(grouped.add_with_policy(&milk, &converter, GroupingPolicy::Error).is_err());
+    /// assert_eq!(grouped.to_string(), "100 g");
+    /// ```
+    pub fn add_with_policy(
+        &mut self,
+        q: &ScaledQuantity,
+        converter: &Converter,
+        policy: GroupingPolicy,
+    ) -> Result<(), QuantityAddError> {
+        match policy {
+            GroupingPolicy::Merge => self.add(q, converter),
+            GroupingPolicy::KeepSeparate => {
+                self.add_component(q);
+                if let Some(stored) = self
+                    .other
+                    .iter_mut()
+                    .find(|s| s.unit_text() == q.unit_text() && s.per == q.per)
+                {
+                    if let Ok(value) = stored.value.try_add(&q.value) {
+                        stored.value = value;
+                        return Ok(());
+                    }
+                }
+                self.other.push(q.clone());
+            }
+            GroupingPolicy::Error => {
+                if q.value.is_numeric() {
+                    let stored = self
+                        .known
+                        .values()
+                        .flatten()
+                        .chain(self.unknown.values())
+                        .chain(&self.no_unit)
+                        .chain(self.other.iter().filter(|s| s.value.is_numeric()));
+                    for s in stored {
+                        s.compatible_unit(q, converter)?;
+                    }
+                }
+                self.add(q, converter)
+            }
+        }
+        Ok(())
+    }
+
+    fn add_impl(
+        &mut self,
+        q: &ScaledQuantity,
+        converter: &Converter,
+        mut cache: Option<&mut ConversionCache>,
+    ) {
         macro_rules! add {
             ($stored:expr, $quantity:ident, $converter:expr, $other:expr) => {
-                match $stored.try_add($quantity, $converter) {
+                match $stored.try_add_impl($quantity, $converter, cache.as_deref_mut()) {
                     Ok(q) => *$stored = q,
                     Err(_) => {
                         $other.push($quantity.clone());
@@ -565,7 +1630,7 @@ impl GroupedQuantity {
             };
         }
 
-        if q.value.is_text() {
+        if !q.value.is_numeric() {
             self.other.push(q.clone());
             return;
         }
@@ -579,7 +1644,10 @@ impl GroupedQuantity {
         }
 
         let unit = q.unit.as_ref().unwrap();
-        let info = unit.unit_info_or_parse(converter);
+        let info = match cache.as_deref_mut() {
+            Some(cache) => unit.unit_info_cached(converter, cache),
+            None => unit.unit_info_or_parse(converter),
+        };
         match info {
             UnitInfo::Known(unit) => {
                 if let Some(stored) = &mut self.known[unit.physical_quantity] {
@@ -598,10 +1666,81 @@ impl GroupedQuantity {
         };
     }
 
+    fn add_component(&mut self, q: &ScaledQuantity) {
+        if let Some(stored) = self
+            .components
+            .iter_mut()
+            .find(|c| c.unit_text() == q.unit_text())
+        {
+            if let Ok(value) = stored.value.try_add(&q.value) {
+                stored.value = value;
+                return;
+            }
+        }
+        self.components.push(q.clone());
+    }
+
+    /// Iterate over the added quantities by unit, without conversions
+    ///
+    /// Quantities with the same unit are added together, but, unlike
+    /// [`Self::iter`], different units of the same physical quantity are not
+    /// converted and are kept separated. Quantities without unit have an empty
+    /// unit.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, GroupedQuantity, Quantity, Value};
+    /// let converter = Converter::bundled();
+    /// let mut grouped = GroupedQuantity::empty();
+    /// grouped.add(&Quantity::new(Value::from(2.0), Some("cup".into())), &converter);
+    /// grouped.add(&Quantity::new(Value::from(100.0), Some("g".into())), &converter);
+    /// let components = grouped.components().collect::<Vec<_>>();
+    /// assert_eq!(components, [("cup", &Value::from(2.0)), ("g", &Value::from(100.0))]);
+    /// ```
+    pub fn components(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.components
+            .iter()
+            .map(|q| (q.unit_text().unwrap_or(""), &q.value))
+    }
+
+    /// Total of the [counts](Quantity::is_count) added
+    ///
+    /// ```
+    /// # use cooklang::{Converter, GroupedQuantity, Quantity, Value};
+    /// let converter = Converter::bundled();
+    /// let mut grouped = GroupedQuantity::empty();
+    /// grouped.add(&Quantity::new(Value::from(2.0), None), &converter);
+    /// grouped.add(&Quantity::new(Value::from(100.0), Some("g".into())), &converter);
+    /// grouped.add(&Quantity::new(Value::from(1.0), None), &converter);
+    /// assert_eq!(grouped.count().unwrap().value, Value::from(3.0));
+    /// let measured = grouped.measured().collect::<Vec<_>>();
+    /// assert_eq!(measured.len(), 1);
+    /// assert_eq!(measured[0].to_string(), "100 g");
+    /// ```
+    pub fn count(&self) -> Option<&ScaledQuantity> {
+        self.no_unit.as_ref()
+    }
+
+    /// Iterate over the added quantities that are not a count
+    ///
+    /// This is the same as [`Self::iter`] without [`Self::count`].
+    pub fn measured(&self) -> impl Iterator<Item = &ScaledQuantity> {
+        self.known
+            .values()
+            .filter_map(|q| q.as_ref())
+            .chain(self.unknown.values())
+            .chain(self.other.iter())
+    }
+
     /// Merge the group with another one
+    ///
+    /// The [components](Self::components) of both groups are merged too, so
+    /// they are still without conversions.
     pub fn merge(&mut self, other: &Self, converter: &Converter) {
         for q in other.iter() {
-            self.add(q, converter)
+            self.add_impl(q, converter, None)
+        }
+        for q in &other.components {
+            self.add_component(q);
         }
     }
 
@@ -625,12 +1764,7 @@ impl GroupedQuantity {
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &ScaledQuantity> {
-        self.known
-            .values()
-            .filter_map(|q| q.as_ref())
-            .chain(self.unknown.values())
-            .chain(self.other.iter())
-            .chain(self.no_unit.iter())
+        self.measured().chain(self.no_unit.iter())
     }
 
     pub fn len(&self) -> usize {
@@ -682,9 +1816,9 @@ impl GroupedValue {
             return;
         }
 
-        if value.is_text() {
+        if !value.is_numeric() {
             self.0.push(value.clone());
-        } else if self.0[0].is_text() {
+        } else if !self.0[0].is_numeric() {
             self.0.insert(0, value.clone());
         } else {
             self.0[0] = self.0[0]
@@ -888,6 +2022,74 @@ impl Number {
         })
     }
 
+    /// Snaps a number to the nearest fraction easy to measure in a kitchen.
+    ///
+    /// The fractional part is rounded to one of 1/8, 1/4, 1/3, 1/2, 2/3, 3/4
+    /// or a whole number. The difference with the original value is stored
+    /// in the `err` field of the fraction.
+    ///
+    /// It returns none if the number is not positive or if it would be
+    /// rounded to 0.
+    ///
+    /// ```
+    /// # use cooklang::quantity::Number;
+    /// let n = Number::new_culinary(0.416).unwrap();
+    /// assert_eq!(n.to_string(), "1/3");
+    /// assert!((n.value() - 0.416).abs() < 1e-10);
+    /// ```
+    pub fn new_culinary(value: f64) -> Option<Self> {
+        const FRACTIONS: [(u32, u32); 8] = [
+            (0, 1),
+            (1, 8),
+            (1, 4),
+            (1, 3),
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (1, 1),
+        ];
+        Self::nearest_fraction(value, &FRACTIONS)
+    }
+
+    /// Snaps a number to the nearest of the given fractions for its decimal
+    /// part, like [`Self::new_culinary`]
+    ///
+    /// `fractions` must contain `(0, 1)` and `(1, 1)`.
+    pub(crate) fn nearest_fraction(value: f64, fractions: &[(u32, u32)]) -> Option<Self> {
+        if value <= 0.0 || !value.is_finite() || value >= u32::MAX as f64 {
+            return None;
+        }
+
+        let whole = value.trunc();
+        let decimal = value.fract();
+        let (num, den) = fractions
+            .iter()
+            .copied()
+            .min_by(|(a_num, a_den), (b_num, b_den)| {
+                let a = (decimal - *a_num as f64 / *a_den as f64).abs();
+                let b = (decimal - *b_num as f64 / *b_den as f64).abs();
+                a.total_cmp(&b)
+            })
+            .unwrap();
+
+        // 1/1 is the next whole number
+        let (whole, num, den) = if num == den {
+            (whole as u32 + 1, 0, 1)
+        } else {
+            (whole as u32, num, den)
+        };
+        if whole == 0 && num == 0 {
+            return None;
+        }
+        let err = value - (whole as f64 + num as f64 / den as f64);
+        Some(Self::Fraction {
+            whole,
+            num,
+            den,
+            err,
+        })
+    }
+
     /// Tries to approximate the number to a fraction if possible and not an
     /// integer
     pub fn try_approx(&mut self, accuracy: f32, max_den: u8, max_whole: u32) -> bool {
@@ -939,4 +2141,531 @@ mod tests {
         }
         num
     }
+
+    #[test_case(0.5, NumberFormat::Decimal => "0.5" ; "half decimal")]
+    #[test_case(0.5, NumberFormat::Fraction => "1/2" ; "half fraction")]
+    #[test_case(0.5, NumberFormat::Auto => "1/2" ; "half auto")]
+    #[test_case(0.333, NumberFormat::Decimal => "0.333" ; "third decimal")]
+    #[test_case(0.333, NumberFormat::Fraction => "1/3" ; "third fraction")]
+    #[test_case(0.333, NumberFormat::Auto => "1/3" ; "third auto")]
+    #[test_case(2.0, NumberFormat::Decimal => "2" ; "integer decimal")]
+    #[test_case(2.0, NumberFormat::Fraction => "2" ; "integer fraction")]
+    #[test_case(2.0, NumberFormat::Auto => "2" ; "integer auto")]
+    #[test_case(0.123, NumberFormat::Fraction => "1/8" ; "eighth fraction")]
+    #[test_case(0.123, NumberFormat::Auto => "0.123" ; "eighth auto")]
+    #[test_case(0.01, NumberFormat::Fraction => "0.01" ; "no fraction")]
+    fn number_format(value: f64, fmt: NumberFormat) -> String {
+        Number::Regular(value).format(fmt)
+    }
+
+    #[test]
+    fn number_format_fraction_as_decimal() {
+        let third = Number::Fraction {
+            whole: 1,
+            num: 1,
+            den: 3,
+            err: 0.0,
+        };
+        assert_eq!(third.format(NumberFormat::Decimal), "1.333");
+        assert_eq!(third.format(NumberFormat::Auto), "1 1/3");
+    }
+
+    #[test_case(Value::from(1500.0), Some("ml"), None => "1.5 l" ; "number")]
+    #[test_case(Value::Range { start: 2000.0.into(), end: 3000.0.into() }, Some("ml"), None => "2-3 l" ; "range")]
+    #[test_case(Value::from(1.5), Some("cup"), None => "1 1/2 c" ; "fraction")]
+    #[test_case(Value::from(1.0), Some("l"), Some(System::Metric) => "1 l" ; "same system")]
+    #[test_case(Value::from("a pinch".to_string()), None, Some(System::Imperial) => "a pinch" ; "text")]
+    #[test_case(Value::from(3.0), None, Some(System::Imperial) => "3" ; "unitless")]
+    #[test_case(Value::from(2.0), Some("handful"), None => "2 handful" ; "unknown unit")]
+    fn display_with(value: Value, unit: Option<&str>, system: Option<System>) -> String {
+        let converter = Converter::bundled();
+        Quantity::new(value, unit.map(String::from)).display_with(&converter, system)
+    }
+
+    #[test]
+    fn grouped_components() {
+        let converter = Converter::bundled();
+        let mut grouped = GroupedQuantity::empty();
+        for (value, unit) in [
+            (Value::from(2.0), Some("cup")),
+            (Value::from(100.0), Some("g")),
+            (Value::from(1.0), Some("cup")),
+            (Value::from(250.0), Some("ml")),
+            (Value::from(3.0), None),
+            (Value::from("a bit".to_string()), Some("g")),
+        ] {
+            grouped.add(&Quantity::new(value, unit.map(String::from)), &converter);
+        }
+        let components = grouped.components().collect::<Vec<_>>();
+        assert_eq!(
+            components,
+            [
+                ("cup", &Value::from(3.0)),
+                ("g", &Value::from(100.0)),
+                ("ml", &Value::from(250.0)),
+                ("", &Value::from(3.0)),
+                ("g", &Value::from("a bit".to_string())),
+            ]
+        );
+        // the grouped quantities are still converted
+        assert_eq!(grouped.len(), 4);
+
+        let mut merged = GroupedQuantity::empty();
+        merged.add(
+            &Quantity::new(Value::from(1.0), Some("cup".to_string())),
+            &converter,
+        );
+        merged.merge(&grouped, &converter);
+        let components = merged.components().collect::<Vec<_>>();
+        assert_eq!(
+            components,
+            [
+                ("cup", &Value::from(4.0)),
+                ("g", &Value::from(100.0)),
+                ("ml", &Value::from(250.0)),
+                ("", &Value::from(3.0)),
+                ("g", &Value::from("a bit".to_string())),
+            ]
+        );
+        let json = serde_json::to_value(&merged).unwrap();
+        assert_eq!(json["components"].as_array().unwrap().len(), 5);
+    }
+
+    #[test_case(GroupingPolicy::Merge => (vec![true, true, true, true], 2) ; "merge")]
+    #[test_case(GroupingPolicy::KeepSeparate => (vec![true, true, true, true], 3) ; "keep separate")]
+    #[test_case(GroupingPolicy::Error => (vec![true, false, true, true], 1) ; "error")]
+    fn grouping_policy(policy: GroupingPolicy) -> (Vec<bool>, usize) {
+        let converter = Converter::bundled();
+        let mut grouped = GroupedQuantity::empty();
+        let added = [(100.0, "g"), (1.0, "cup"), (1.0, "kg"), (50.0, "g")]
+            .into_iter()
+            .map(|(value, unit)| {
+                let q = Quantity::new(Value::from(value), Some(unit.to_string()));
+                grouped.add_with_policy(&q, &converter, policy).is_ok()
+            })
+            .collect();
+        (added, grouped.len())
+    }
+
+    #[test]
+    fn grouping_policy_error() {
+        let converter = Converter::bundled();
+        let mut grouped = GroupedQuantity::empty();
+        let policy = GroupingPolicy::Error;
+        let flour = Quantity::new(Value::from(100.0), Some("g".into()));
+        grouped.add_with_policy(&flour, &converter, policy).unwrap();
+        let milk = Quantity::new(Value::from(1.0), Some("cup".into()));
+        let err = grouped
+            .add_with_policy(&milk, &converter, policy)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            QuantityAddError::IncompatibleUnits(
+                IncompatibleUnits::DifferentPhysicalQuantities { .. }
+            )
+        ));
+        // text values are not units, so they are added
+        let text = Quantity::new(Value::from("a pinch".to_string()), None);
+        assert!(grouped.add_with_policy(&text, &converter, policy).is_ok());
+        assert_eq!(grouped.to_string(), "100 g, a pinch");
+    }
+
+    #[test_case(0.416 => matches frac!(1, 3) ; "third")]
+    #[test_case(0.1 => matches frac!(1, 8) ; "eighth")]
+    #[test_case(1.7 => matches frac!(1, 2, 3) ; "with whole")]
+    #[test_case(2.9 => matches frac!(3) ; "round up")]
+    #[test_case(0.05 => None ; "no round to 0")]
+    #[test_case(-1.0 => None ; "negative")]
+    fn culinary_fractions(value: f64) -> Option<Number> {
+        let num = Number::new_culinary(value);
+        if let Some(num) = num {
+            assert!((num.value() - value).abs() < 10e-9);
+        }
+        num
+    }
+
+    fn fraction(whole: u32, num: u32, den: u32) -> Number {
+        Number::Fraction {
+            whole,
+            num,
+            den,
+            err: 0.0,
+        }
+    }
+
+    #[test_case(Number::Regular(0.5), fraction(0, 1, 2) => Some(std::cmp::Ordering::Equal) ; "frac eq regular")]
+    #[test_case(fraction(0, 1, 3), fraction(0, 1, 2) => Some(std::cmp::Ordering::Less) ; "frac")]
+    #[test_case(fraction(2, 1, 4), Number::Regular(2.2) => Some(std::cmp::Ordering::Greater) ; "mixed")]
+    #[test_case(Number::Regular(f64::NAN), Number::Regular(1.0) => None ; "nan")]
+    fn number_ordering(a: Number, b: Number) -> Option<std::cmp::Ordering> {
+        a.partial_cmp(&b)
+    }
+
+    #[test]
+    fn value_ordering() {
+        use std::cmp::Ordering;
+        let range = |start: f64, end: f64| Value::Range {
+            start: start.into(),
+            end: end.into(),
+        };
+        let text = Value::Text("a pinch".into());
+
+        assert!(Value::from(1.0) < Value::from(2.0));
+        assert!(range(1.0, 2.0) < Value::from(2.0));
+        assert!(range(1.0, 5.0) > Value::from(2.0));
+        assert!(range(1.0, 3.0) < range(2.0, 3.0));
+        // same midpoint
+        assert!(range(1.0, 3.0) < range(2.0, 2.0));
+        assert!(Value::from(2.0) > range(1.0, 3.0));
+        assert!(Value::from(2.0) < range(2.0, 2.0));
+        assert_eq!(
+            range(1.0, 3.0).partial_cmp(&range(1.0, 3.0)),
+            Some(Ordering::Equal)
+        );
+        let tolerant = Value::Tolerant {
+            center: 100.0.into(),
+            tolerance: 5.0.into(),
+        };
+        assert!(range(95.0, 105.0) < tolerant);
+        assert_eq!(
+            tolerant.partial_cmp(&tolerant.clone()),
+            Some(Ordering::Equal)
+        );
+
+        assert_eq!(text.partial_cmp(&Value::from(1.0)), None);
+        assert_eq!(range(1.0, 2.0).partial_cmp(&text), None);
+        assert_eq!(text.partial_cmp(&Value::Text("salt".into())), None);
+        assert_eq!(text.partial_cmp(&text.clone()), Some(Ordering::Equal));
+    }
+
+    #[test_case("10.00" => Some(2))]
+    #[test_case("10.5" => Some(1))]
+    #[test_case(" .250 " => Some(3))]
+    #[test_case("10" => None)]
+    #[test_case("1/2" => None)]
+    #[test_case("1.2.3" => None)]
+    fn decimal_places(text: &str) -> Option<u8> {
+        Number::decimal_places(text)
+    }
+
+    #[test]
+    fn decimals_display() {
+        let q = |value: Value, decimals| Quantity {
+            decimals,
+            ..Quantity::new(value, Some("g".into()))
+        };
+        let range = Value::Range {
+            start: 1.5.into(),
+            end: 2.0.into(),
+        };
+        assert_eq!(q(10.0.into(), None).to_string(), "10 g");
+        assert_eq!(q(10.0.into(), Some(2)).to_string(), "10.00 g");
+        assert_eq!(q(2.5.into(), Some(3)).to_string(), "2.500 g");
+        assert_eq!(q(range.clone(), Some(2)).to_string(), "1.50-2.00 g");
+        assert_eq!(q(range, None).to_string(), "1.5-2 g");
+        assert_eq!(q("some".to_string().into(), Some(2)).to_string(), "some g");
+    }
+
+    #[test_case(fraction(0, 1, 3), fraction(0, 1, 3) => Some((0, 2, 3)) ; "thirds")]
+    #[test_case(fraction(0, 1, 2), fraction(0, 1, 3) => Some((0, 5, 6)) ; "different den")]
+    #[test_case(fraction(1, 3, 4), fraction(0, 1, 4) => Some((2, 0, 1)) ; "whole result")]
+    #[test_case(fraction(0, 1, 2), Number::Regular(2.0) => Some((2, 1, 2)) ; "integer")]
+    #[test_case(fraction(0, 1, 2), Number::Regular(0.25) => None ; "float")]
+    #[test_case(Number::Regular(1.0), Number::Regular(2.0) => None ; "no fractions")]
+    fn checked_add(a: Number, b: Number) -> Option<(u32, u32, u32)> {
+        match a.checked_add(b)? {
+            Number::Fraction {
+                whole, num, den, ..
+            } => Some((whole, num, den)),
+            _ => panic!("not a fraction"),
+        }
+    }
+
+    #[test]
+    fn convert_to() {
+        let converter = Converter::bundled();
+        let cup = Quantity::new(Value::from(1.0), Some("cup".into()));
+        let ml = cup.convert_to("ml", &converter).unwrap();
+        assert_eq!(ml.unit_text(), Some("ml"));
+        let Value::Number(n) = ml.value else {
+            panic!("not a number")
+        };
+        assert!((n.value() - 236.588).abs() < 0.001);
+        // the original is not changed
+        assert_eq!(cup.to_string(), "1 cup");
+
+        let range = Quantity::new(
+            Value::Range {
+                start: 1.0.into(),
+                end: 2.0.into(),
+            },
+            Some("kg".into()),
+        );
+        assert_eq!(
+            range.convert_to("g", &converter).unwrap().to_string(),
+            "1000-2000 g"
+        );
+
+        let text = Quantity::new(Value::from("a pinch".to_string()), Some("g".into()));
+        assert!(matches!(
+            text.convert_to("kg", &converter),
+            Err(ConvertError::TextValue(_))
+        ));
+        let no_unit = Quantity::new(Value::from(1.0), None);
+        assert!(matches!(
+            no_unit.convert_to("g", &converter),
+            Err(ConvertError::NoUnit(_))
+        ));
+        assert!(cup.convert_to("g", &converter).is_err());
+    }
+
+    #[test]
+    fn tolerances() {
+        let tol = |center: f64, tolerance: f64| Value::Tolerant {
+            center: center.into(),
+            tolerance: tolerance.into(),
+        };
+        assert_eq!(tol(100.0, 5.0).to_string(), "100±5");
+        assert_eq!("100±5".parse::<Value>().unwrap(), tol(100.0, 5.0));
+        assert_eq!("100 +- 5".parse::<Value>().unwrap(), tol(100.0, 5.0));
+        assert!("100±5".parse::<Number>().is_err());
+
+        // add
+        assert_eq!(
+            tol(100.0, 5.0).try_add(&tol(50.0, 2.0)).unwrap(),
+            tol(150.0, 7.0)
+        );
+        assert_eq!(
+            tol(100.0, 5.0).try_add(&Value::from(1.0)).unwrap(),
+            tol(101.0, 5.0)
+        );
+        let range = Value::Range {
+            start: 1.0.into(),
+            end: 2.0.into(),
+        };
+        assert_eq!(
+            tol(100.0, 5.0).try_add(&range).unwrap().to_string(),
+            "96-107"
+        );
+
+        // multiply
+        assert_eq!(
+            tol(100.0, 5.0).try_mul(&Value::from(2.0)).unwrap(),
+            tol(200.0, 10.0)
+        );
+
+        // convert
+        let converter = Converter::bundled();
+        let q = Quantity::new(tol(100.0, 5.0), Some("g".into()));
+        assert_eq!(
+            q.convert_to("kg", &converter).unwrap().to_string(),
+            "0.1±0.005 kg"
+        );
+        // the tolerance is a difference, the offset is not applied
+        let q = Quantity::new(tol(20.0, 1.0), Some("°C".into()));
+        let f = q.convert_to("°F", &converter).unwrap();
+        let Value::Tolerant { center, tolerance } = f.value else {
+            panic!("not a tolerance")
+        };
+        assert!((center.value() - 68.0).abs() < 0.001);
+        assert!((tolerance.value() - 1.8).abs() < 0.001);
+
+        let round_trip = serde_json::to_string(&Compact(&tol(100.0, 5.0))).unwrap();
+        assert_eq!(round_trip, r#""100±5""#);
+    }
+
+    #[test]
+    fn try_add_ranges() {
+        let range = |start: f64, end: f64| Value::Range {
+            start: start.into(),
+            end: end.into(),
+        };
+
+        assert_eq!(
+            range(2.0, 3.0).try_add(&range(1.0, 2.0)).unwrap(),
+            range(3.0, 5.0)
+        );
+        assert_eq!(
+            range(2.0, 3.0).try_add(&Value::from(1.0)).unwrap(),
+            range(3.0, 4.0)
+        );
+        assert_eq!(
+            Value::from(1.0).try_add(&range(2.0, 3.0)).unwrap(),
+            range(3.0, 4.0)
+        );
+        assert!(range(2.0, 3.0)
+            .try_add(&Value::from("a pinch".to_string()))
+            .is_err());
+
+        // through quantities, with conversions
+        let converter = Converter::bundled();
+        let a = Quantity::new(range(1.0, 2.0), Some("l".into()));
+        let b = Quantity::new(Value::from(500.0), Some("ml".into()));
+        assert_eq!(a.try_add(&b, &converter).unwrap().to_string(), "1.5-2.5 l");
+
+        let mut grouped = GroupedQuantity::empty();
+        grouped.add(&Quantity::new(range(2.0, 3.0), None), &converter);
+        grouped.add(&Quantity::new(range(1.0, 2.0), None), &converter);
+        grouped.add(&Quantity::new(Value::from(1.0), None), &converter);
+        assert_eq!(grouped.to_string(), "4-6");
+    }
+
+    #[test]
+    fn try_add_fractions() {
+        let third = Value::Number(fraction(0, 1, 3));
+        let sum = third.try_add(&third).unwrap();
+        assert_eq!(sum.to_string(), "2/3");
+        let sum = third.try_add(&Value::from(0.5)).unwrap();
+        assert_eq!(sum.to_string(), "0.833");
+    }
+
+    #[test_case("3" => Value::from(3.0) ; "int")]
+    #[test_case(" 2.5 " => Value::from(2.5) ; "float")]
+    #[test_case("1/2" => Value::Number(fraction(0, 1, 2)) ; "simple fraction")]
+    #[test_case("1 3/4" => Value::Number(fraction(1, 3, 4)) ; "mixed number")]
+    #[test_case("2-3" => Value::Range { start: 2.0.into(), end: 3.0.into() } ; "range")]
+    #[test_case("1/2 - 1" => Value::Range { start: fraction(0, 1, 2), end: 1.0.into() } ; "fraction range")]
+    #[test_case("a pinch" => Value::from("a pinch".to_string()) ; "text")]
+    #[test_case("2 cups" => Value::from("2 cups".to_string()) ; "not only a number")]
+    fn value_from_str(s: &str) -> Value {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn number_from_str() {
+        assert_eq!("4".parse::<Number>().unwrap(), Number::Regular(4.0));
+        assert_eq!("1/3".parse::<Number>().unwrap(), fraction(0, 1, 3));
+        assert!(matches!(
+            "1-2".parse::<Number>(),
+            Err(ParseValueError::NotANumber(_))
+        ));
+        assert!(matches!(
+            "some".parse::<Number>(),
+            Err(ParseValueError::NotANumber(_))
+        ));
+        assert!(matches!(
+            "1/0".parse::<Value>(),
+            Err(ParseValueError::Invalid(_))
+        ));
+        assert!(matches!("  ".parse::<Value>(), Err(ParseValueError::Empty)));
+    }
+
+    #[test]
+    fn numeric_predicates() {
+        use crate::{located::Located, parser, span::Span};
+
+        let text = Value::from("a pinch".to_string());
+        let number = Value::from(2.0);
+
+        let single = |value: Value| parser::QuantityValue::Single {
+            value: Located::new(value, Span::new(0, 1)),
+            auto_scale: None,
+        };
+        assert!(single(text.clone()).contains_text());
+        assert!(!single(text.clone()).is_numeric());
+        assert!(single(number.clone()).is_numeric());
+        assert!(!single(number.clone()).contains_text());
+
+        let many = parser::QuantityValue::Many(vec![
+            Located::new(number.clone(), Span::new(0, 1)),
+            Located::new(text.clone(), Span::new(2, 3)),
+        ]);
+        assert!(many.contains_text());
+        assert!(!many.is_numeric());
+
+        assert!(ScalableValue::Linear(number.clone()).is_numeric());
+        assert!(ScalableValue::Fixed(text.clone()).contains_text());
+        let by_servings = ScalableValue::ByServings(vec![number, text]);
+        assert!(by_servings.contains_text());
+        assert!(!by_servings.is_numeric());
+    }
+
+    #[test]
+    fn quantity_value_values() {
+        use crate::{located::Located, parser, span::Span};
+
+        let single = parser::QuantityValue::Single {
+            value: Located::new(Value::from(2.0), Span::new(0, 1)),
+            auto_scale: Some(Span::new(1, 2)),
+        };
+        let values: Vec<_> = single.values().collect();
+        assert_eq!(values.len(), 1);
+        assert_eq!(*values[0].value(), Value::from(2.0));
+        assert_eq!(values[0].span(), Span::new(0, 1));
+
+        let many = parser::QuantityValue::Many(vec![
+            Located::new(Value::from(1.0), Span::new(0, 1)),
+            Located::new(Value::from(2.0), Span::new(2, 3)),
+            Located::new(Value::from("a lot".to_string()), Span::new(4, 9)),
+        ]);
+        let values: Vec<_> = many.values().map(|v| v.value().clone()).collect();
+        assert_eq!(
+            values,
+            vec![
+                Value::from(1.0),
+                Value::from(2.0),
+                Value::from("a lot".to_string())
+            ]
+        );
+        let spans: Vec<_> = many.values().map(|v| v.span()).collect();
+        assert_eq!(
+            spans,
+            vec![Span::new(0, 1), Span::new(2, 3), Span::new(4, 9)]
+        );
+    }
+
+    #[test]
+    fn round_to() {
+        let fraction = Value::Number(Number::Fraction {
+            whole: 1,
+            num: 2,
+            den: 3,
+            err: 0.0,
+        });
+        let rounded = fraction.round_to(1);
+        assert!(matches!(rounded, Value::Number(Number::Regular(_))));
+        assert_eq!(rounded, Value::from(1.7));
+        assert_eq!(fraction.round_to(0), Value::from(2.0));
+
+        let range = Value::Range {
+            start: 1.234.into(),
+            end: 5.678.into(),
+        };
+        assert_eq!(
+            range.round_to(2),
+            Value::Range {
+                start: 1.23.into(),
+                end: 5.68.into(),
+            }
+        );
+
+        let text = Value::from("a pinch".to_string());
+        assert_eq!(text.round_to(2), text);
+    }
+
+    #[test]
+    fn compact_serde() {
+        let round_trip = |value: Value, json: &str| {
+            assert_eq!(serde_json::to_string(&Compact(&value)).unwrap(), json);
+            let Compact(back) = serde_json::from_str::<Compact<Value>>(json).unwrap();
+            assert_eq!(back, value);
+        };
+        round_trip(Value::from(2.5), "2.5");
+        round_trip(Value::Number(fraction(2, 1, 2)), r#""2 1/2""#);
+        round_trip(
+            Value::Range {
+                start: fraction(0, 1, 2),
+                end: 1.0.into(),
+            },
+            r#""1/2-1""#,
+        );
+        round_trip(Value::from("a pinch".to_string()), r#""a pinch""#);
+        round_trip(Value::Variable("amount".into()), r#""$amount""#);
+
+        let number = serde_json::to_string(&Compact(fraction(0, 3, 4))).unwrap();
+        assert_eq!(number, r#""3/4""#);
+        let Compact(back) = serde_json::from_str::<Compact<Number>>(&number).unwrap();
+        assert_eq!(back, fraction(0, 3, 4));
+        assert!(serde_json::from_str::<Compact<Number>>(r#""1-2""#).is_err());
+    }
 }
@@ -1,12 +1,17 @@
 //! Support for recipe scaling
 
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    convert::Converter,
-    quantity::{ScalableQuantity, ScalableValue, ScaledQuantity, TextValueError, Value},
-    Cookware, Ingredient, Quantity, ScalableRecipe, ScaledRecipe, Timer,
+    convert::{ConvertError, Converter, PhysicalQuantity},
+    quantity::{
+        Number, PerUnitQuantity, QuantityValue, ScalableQuantity, ScalableValue, ScaledQuantity,
+        TextValueError, Value,
+    },
+    Cookware, Ingredient, Quantity, ScalableRecipe, ScaledRecipe, Timer, UnitInfo,
 };
 
 /// Configures the scaling target
@@ -15,6 +20,8 @@ pub struct ScaleTarget {
     base: u32,
     target: u32,
     index: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    factor: Option<f64>,
 }
 
 impl ScaleTarget {
@@ -33,12 +40,25 @@ impl ScaleTarget {
             base,
             target,
             index: declared_servings.iter().position(|&s| s == target),
+            factor: None,
+        }
+    }
+
+    /// Creates a [`ScaleTarget`] from a custom factor instead of a target
+    /// number of servings. The target servings are rounded.
+    pub(crate) fn from_factor(base: u32, factor: f64) -> Self {
+        ScaleTarget {
+            base,
+            target: (base as f64 * factor).round() as u32,
+            index: None,
+            factor: Some(factor),
         }
     }
 
     /// Get the calculated scaling factor
     pub fn factor(&self) -> f64 {
-        self.target as f64 / self.base as f64
+        self.factor
+            .unwrap_or_else(|| self.target as f64 / self.base as f64)
     }
 
     /// Get the index into a [`ScalableValue::ByServings`]
@@ -110,6 +130,15 @@ pub enum ScaleError {
         value: ScalableValue,
     },
 
+    #[error("Ingredient not found: {name}")]
+    IngredientNotFound { name: String },
+
+    #[error("Can't scale to ingredient '{name}': {reason}")]
+    InvalidIngredientTarget { name: String, reason: &'static str },
+
+    #[error(transparent)]
+    ConvertError(#[from] Arc<ConvertError>),
+
     /// There has been an error but it can't be determined
     ///
     /// This is used when deserializing, because serializing the [`ScaleOutcome`]
@@ -119,19 +148,228 @@ pub enum ScaleError {
     UndefinedError,
 }
 
+/// Options for [`ScalableRecipe::scale_with_options`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScaleOptions {
+    /// Snap the scaled values to fractions easy to measure in a kitchen.
+    ///
+    /// This only applies to the scaled quantities of ingredients with volume
+    /// units or without unit and the amounts of cookware. See
+    /// [`Number::new_culinary`].
+    pub culinary_rounding: bool,
+    /// Also scale the parsed [yield](crate::metadata::Metadata::yield_) by
+    /// the scaling factor.
+    ///
+    /// The raw value in [`Metadata::map`](crate::metadata::Metadata::map) is
+    /// not changed.
+    pub scale_yield: bool,
+    /// How to round the scaled values of ingredients and cookware
+    ///
+    /// This is applied after [`Self::culinary_rounding`].
+    pub rounding: RoundingPolicy,
+}
+
+/// Rounding of scaled values, so they are easy to measure
+///
+/// Values that were not scaled or are already whole numbers are not changed.
+/// When a value is rounded, it's a [`Number::Fraction`] and the difference
+/// with the scaled value is in its `err`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoundingPolicy {
+    /// Keep the scaled values
+    #[default]
+    Exact,
+    /// Snap the values to the nearest quarter, third or half, like `2 2/3`
+    Fractions,
+    /// Same as [`Self::Fractions`] but values without a unit, which are
+    /// usually counts like `3 eggs`, are rounded up to a whole number
+    FractionsAndWholeCounts,
+}
+
+impl RoundingPolicy {
+    fn round_quantity(self, q: &mut ScaledQuantity) {
+        let is_count = q.unit().is_none();
+        self.round_value(&mut q.value, is_count);
+    }
+
+    fn round_value(self, value: &mut Value, is_count: bool) {
+        const FRACTIONS: [(u32, u32); 7] = [(0, 1), (1, 4), (1, 3), (1, 2), (2, 3), (3, 4), (1, 1)];
+
+        let round = |n: &mut Number| {
+            let v = n.value();
+            if self == Self::Exact || (v - v.round()).abs() < 1e-10 {
+                return;
+            }
+            let rounded = if is_count && self == Self::FractionsAndWholeCounts {
+                Some(Number::Fraction {
+                    whole: v.ceil() as u32,
+                    num: 0,
+                    den: 1,
+                    err: v - v.ceil(),
+                })
+                .filter(|_| v > 0.0 && v < u32::MAX as f64)
+            } else {
+                Number::nearest_fraction(v, &FRACTIONS)
+            };
+            if let Some(rounded) = rounded {
+                *n = rounded;
+            }
+        };
+        match value {
+            Value::Number(n) => round(n),
+            Value::Range { start, end } => {
+                round(start);
+                round(end);
+            }
+            Value::Tolerant { center, tolerance } => {
+                round(center);
+                round(tolerance);
+            }
+            Value::Text(_) | Value::Variable(_) => {}
+        }
+    }
+}
+
 impl ScalableRecipe {
+    /// Checks if scaling would change any quantity
+    ///
+    /// This is true if at least one ingredient, cookware or timer quantity is
+    /// numeric and not [fixed](ScalableValue::Fixed). Recipes with only text
+    /// quantities can't be scaled, so UIs may want to disable scaling for them.
+    ///
+    /// ```
+    /// let recipe = cooklang::parse("Add @salt{a pinch} and @pepper{to taste}.").unwrap_output();
+    /// assert!(!recipe.is_scalable());
+    /// let recipe = cooklang::parse("Add @salt{a pinch} and @flour{100*%g}.").unwrap_output();
+    /// assert!(recipe.is_scalable());
+    /// ```
+    pub fn is_scalable(&self) -> bool {
+        let scales = |v: &ScalableValue| match v {
+            ScalableValue::Fixed(_) => false,
+            ScalableValue::Linear(_) | ScalableValue::ByServings(_) => v.is_numeric(),
+        };
+        let ingredients = self.ingredients.iter().flat_map(|igr| {
+            igr.quantity
+                .iter()
+                .chain(&igr.alternative_quantities)
+                .chain(&igr.purchase_quantity)
+                .map(|q| &q.value)
+                .chain(igr.per_unit.iter().map(|p| &p.count))
+        });
+        let cookware = self.cookware.iter().filter_map(|cw| cw.quantity.as_ref());
+        let timers = self
+            .timers
+            .iter()
+            .filter_map(|t| t.quantity.as_ref().map(|q| &q.value));
+        ingredients.chain(cookware).chain(timers).any(scales)
+    }
+
     /// Scale a recipe
     ///
     /// Note that this returns a [`ScaledRecipe`] wich doesn't implement this
     /// method. A recipe can only be scaled once.
     pub fn scale(self, target: u32, converter: &Converter) -> ScaledRecipe {
+        self.scale_with_options(target, converter, ScaleOptions::default())
+    }
+
+    /// Same as [`Self::scale`] but with aditional options
+    pub fn scale_with_options(
+        self,
+        target: u32,
+        converter: &Converter,
+        options: ScaleOptions,
+    ) -> ScaledRecipe {
         let target = if let Some(servings) = self.metadata.servings() {
             let base = servings.first().copied().unwrap_or(1);
             ScaleTarget::new(base, target, servings)
         } else {
             ScaleTarget::new(1, target, &[])
         };
+        self.scale_to_target(target, converter, options)
+    }
+
+    /// Scale a recipe so an ingredient has the given quantity
+    ///
+    /// The factor is calculated from the quantity of the first definition of
+    /// the ingredient with that name. If the units are different, the target
+    /// is converted to the ingredient unit.
+    ///
+    /// This errors if the ingredient is not found or if its quantity or the
+    /// target are not a single number or can't be converted.
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, Extensions, Converter, Quantity, Value};
+    /// let parser = CooklangParser::new(Extensions::all(), Converter::bundled());
+    /// let recipe = parser.parse("Mix @flour{1*%kg} and @water{600*%ml}.").unwrap_output();
+    /// let target = Quantity::new(Value::from(500.0), Some("g".into()));
+    /// let scaled = recipe
+    ///     .scale_to_ingredient("flour", target, parser.converter())
+    ///     .unwrap();
+    /// assert_eq!(scaled.ingredients[1].quantity.as_ref().unwrap().to_string(), "300 ml");
+    /// ```
+    pub fn scale_to_ingredient(
+        self,
+        name: &str,
+        mut target: ScaledQuantity,
+        converter: &Converter,
+    ) -> Result<ScaledRecipe, ScaleError> {
+        let invalid = |reason| ScaleError::InvalidIngredientTarget {
+            name: name.to_string(),
+            reason,
+        };
+
+        let igr = self
+            .ingredients
+            .iter()
+            .find(|i| i.relation.is_definition() && i.name == name)
+            .ok_or_else(|| ScaleError::IngredientNotFound {
+                name: name.to_string(),
+            })?;
+        let base = igr
+            .quantity
+            .as_ref()
+            .ok_or_else(|| invalid("it has no quantity"))?;
+        let base_value = match &base.value {
+            ScalableValue::Linear(value) => number_value(value, || invalid("it is a range"))?,
+            ScalableValue::Fixed(_) => return Err(invalid("it has a fixed quantity")),
+            ScalableValue::ByServings(_) => {
+                return Err(invalid("it has the quantity defined by servings"))
+            }
+        };
+        if base_value == 0.0 {
+            return Err(invalid("its quantity is zero"));
+        }
+
+        match (base.unit(), target.unit()) {
+            (None, None) => {}
+            (Some(a), Some(b)) if a.text() == b.text() => {}
+            (Some(unit), Some(_)) => match unit.unit_info_or_parse(converter) {
+                UnitInfo::Known(unit) => target
+                    .convert(&unit, converter)
+                    .map_err(|e| ScaleError::from(Arc::new(e)))?,
+                UnitInfo::Unknown => return Err(invalid("it has an unknown unit")),
+            },
+            (Some(_), None) | (None, Some(_)) => {
+                return Err(invalid("the target and the ingredient units don't match"))
+            }
+        }
+        let target_value = number_value(&target.value, || invalid("the target is a range"))?;
+
+        let base_servings = self
+            .metadata
+            .servings()
+            .and_then(|s| s.first().copied())
+            .unwrap_or(1);
+        let target = ScaleTarget::from_factor(base_servings, target_value / base_value);
+        Ok(self.scale_to_target(target, converter, ScaleOptions::default()))
+    }
 
+    fn scale_to_target(
+        self,
+        target: ScaleTarget,
+        converter: &Converter,
+        options: ScaleOptions,
+    ) -> ScaledRecipe {
         if target.index() == Some(0) {
             return self.default_scale();
         }
@@ -141,15 +379,38 @@ impl ScalableRecipe {
             .into_iter()
             .map(|i| i.scale(target))
             .map(|(mut i, o)| {
-                if let Some(q) = &mut i.quantity {
+                let quantities = i
+                    .quantity
+                    .iter_mut()
+                    .chain(&mut i.alternative_quantities)
+                    .chain(&mut i.purchase_quantity);
+                for q in quantities {
                     let _ = q.fit(converter);
+                    if matches!(o, ScaleOutcome::Scaled) {
+                        if options.culinary_rounding {
+                            culinary_round_quantity(q, converter);
+                        }
+                        options.rounding.round_quantity(q);
+                    }
                 }
                 (i, o)
             })
             .unzip();
 
-        let (cookware, cookware_outcomes): (Vec<_>, Vec<_>) =
-            self.cookware.into_iter().map(|c| c.scale(target)).unzip();
+        let (cookware, cookware_outcomes): (Vec<_>, Vec<_>) = self
+            .cookware
+            .into_iter()
+            .map(|c| c.scale(target))
+            .map(|(mut c, o)| {
+                if let (Some(v), ScaleOutcome::Scaled) = (&mut c.quantity, &o) {
+                    if options.culinary_rounding {
+                        culinary_round(v);
+                    }
+                    options.rounding.round_value(v, true);
+                }
+                (c, o)
+            })
+            .unzip();
 
         let (timers, timer_outcomes): (Vec<_>, Vec<_>) = self
             .timers
@@ -163,6 +424,11 @@ impl ScalableRecipe {
             })
             .unzip();
 
+        let mut metadata = self.metadata;
+        if options.scale_yield {
+            metadata.scale_yield(target.factor());
+        }
+
         let data = ScaledData {
             target,
             ingredients: ingredient_outcomes,
@@ -171,7 +437,7 @@ impl ScalableRecipe {
         };
 
         ScaledRecipe {
-            metadata: self.metadata,
+            metadata,
             sections: self.sections,
             ingredients,
             cookware,
@@ -267,7 +533,18 @@ impl Scale for ScalableValue {
     }
 }
 
-fn linear_scale(value: Value, factor: f64) -> Result<Value, ScaleError> {
+/// Gets the value of a number, errors for ranges and text
+///
+/// A tolerance is its center.
+fn number_value(value: &Value, range_err: impl FnOnce() -> ScaleError) -> Result<f64, ScaleError> {
+    match value {
+        Value::Number(n) | Value::Tolerant { center: n, .. } => Ok(n.value()),
+        Value::Range { .. } => Err(range_err()),
+        Value::Text(_) | Value::Variable(_) => Err(TextValueError(value.clone()).into()),
+    }
+}
+
+pub(crate) fn linear_scale(value: Value, factor: f64) -> Result<Value, ScaleError> {
     match value {
         Value::Number(n) => Ok(Value::Number((n.value() * factor).into())),
         Value::Range { start, end } => {
@@ -275,25 +552,100 @@ fn linear_scale(value: Value, factor: f64) -> Result<Value, ScaleError> {
             let end = (end.value() * factor).into();
             Ok(Value::Range { start, end })
         }
+        Value::Tolerant { center, tolerance } => Ok(Value::Tolerant {
+            center: (center.value() * factor).into(),
+            tolerance: (tolerance.value() * factor).into(),
+        }),
+        v @ Value::Variable(_) => Ok(v),
         v @ Value::Text(_) => Err(TextValueError(v).into()),
     }
 }
 
+fn culinary_round_quantity(q: &mut ScaledQuantity, converter: &Converter) {
+    let applies = match q.unit().map(|u| u.unit_info_or_parse(converter)) {
+        None => true,
+        Some(UnitInfo::Known(unit)) => unit.physical_quantity == PhysicalQuantity::Volume,
+        Some(UnitInfo::Unknown) => false,
+    };
+    if applies {
+        culinary_round(&mut q.value);
+    }
+}
+
+fn culinary_round(value: &mut Value) {
+    let round = |n: &mut Number| {
+        if let Some(rounded) = Number::new_culinary(n.value()) {
+            *n = rounded;
+        }
+    };
+    match value {
+        Value::Number(n) => round(n),
+        Value::Range { start, end } => {
+            round(start);
+            round(end);
+        }
+        Value::Tolerant { center, tolerance } => {
+            round(center);
+            round(tolerance);
+        }
+        Value::Text(_) | Value::Variable(_) => {}
+    }
+}
+
+impl Scale for PerUnitQuantity<ScalableValue> {
+    type Output = PerUnitQuantity<Value>;
+
+    fn scale(self, target: ScaleTarget) -> (Self::Output, ScaleOutcome) {
+        // each unit is usually fixed, the count is what changes
+        let (count, outcome) = self.count.scale(target);
+        let (per_unit, _) = self.per_unit.scale(target);
+        (PerUnitQuantity { count, per_unit }, outcome)
+    }
+
+    fn default_scale(self) -> Self::Output {
+        PerUnitQuantity {
+            count: self.count.default_scale(),
+            per_unit: self.per_unit.default_scale(),
+        }
+    }
+}
+
 impl Scale for ScalableQuantity {
     type Output = ScaledQuantity;
 
     fn scale(self, target: ScaleTarget) -> (Self::Output, ScaleOutcome) {
-        let Self { value, unit } = self;
+        let Self {
+            value,
+            unit,
+            unit_raw,
+            per,
+            decimals,
+        } = self;
         let (value, outcome) = value.scale(target);
-        let scaled = ScaledQuantity { value, unit };
+        let scaled = ScaledQuantity {
+            value,
+            unit,
+            unit_raw,
+            per,
+            decimals,
+        };
         (scaled, outcome)
     }
 
     fn default_scale(self) -> Self::Output {
-        let Self { value, unit } = self;
+        let Self {
+            value,
+            unit,
+            unit_raw,
+            per,
+            decimals,
+        } = self;
         Self::Output {
             value: value.default_scale(),
             unit,
+            unit_raw,
+            per,
+            decimals,
         }
     }
 }
@@ -304,13 +656,35 @@ impl Scale for Ingredient<ScalableValue> {
     fn scale(self, target: ScaleTarget) -> (Self::Output, ScaleOutcome) {
         let (quantity, outcome) = self.quantity.map(|q| q.scale(target)).unzip();
         let outcome = outcome.unwrap_or(ScaleOutcome::NoQuantity);
+        // the outcome of the main quantity is the outcome of the ingredient
+        let alternative_quantities = self
+            .alternative_quantities
+            .into_iter()
+            .map(|q| q.scale(target).0)
+            .collect();
+        let per_unit = self.per_unit.map(|p| p.scale(target).0);
+        let purchase_quantity = self.purchase_quantity.map(|q| q.scale(target).0);
         let scaled = Ingredient {
             name: self.name,
             alias: self.alias,
+            variant: self.variant,
             quantity,
+            alternative_quantities,
+            per_unit,
+            purchase_quantity,
+            quantity_inherited: self.quantity_inherited,
             note: self.note,
+            substitutes: self.substitutes,
+            category: self.category,
+            original_name: self.original_name,
+            reference_fragment: self.reference_fragment,
+            preparation: self.preparation,
+            title_reference: self.title_reference,
+            nutrition: self.nutrition,
             relation: self.relation,
             modifiers: self.modifiers,
+            #[cfg(feature = "spans")]
+            span: self.span,
         };
         (scaled, outcome)
     }
@@ -319,10 +693,28 @@ impl Scale for Ingredient<ScalableValue> {
         Ingredient {
             name: self.name,
             alias: self.alias,
+            variant: self.variant,
             quantity: self.quantity.map(Quantity::default_scale),
+            alternative_quantities: self
+                .alternative_quantities
+                .into_iter()
+                .map(Quantity::default_scale)
+                .collect(),
+            per_unit: self.per_unit.map(PerUnitQuantity::default_scale),
+            purchase_quantity: self.purchase_quantity.map(Quantity::default_scale),
+            quantity_inherited: self.quantity_inherited,
             note: self.note,
+            substitutes: self.substitutes,
+            category: self.category,
+            original_name: self.original_name,
+            reference_fragment: self.reference_fragment,
+            preparation: self.preparation,
+            title_reference: self.title_reference,
+            nutrition: self.nutrition,
             relation: self.relation,
             modifiers: self.modifiers,
+            #[cfg(feature = "spans")]
+            span: self.span,
         }
     }
 }
@@ -330,6 +722,8 @@ impl Scale for Ingredient<ScalableValue> {
 impl Scale for Cookware<ScalableValue> {
     type Output = Cookware<Value>;
 
+    // the amount is only linear with the scale modifier, otherwise it's fixed
+    // or selected by the servings
     fn scale(self, target: ScaleTarget) -> (Self::Output, ScaleOutcome) {
         let (quantity, outcome) = self.quantity.map(|q| q.scale(target)).unzip();
         let outcome = outcome.unwrap_or(ScaleOutcome::NoQuantity);
@@ -340,6 +734,8 @@ impl Scale for Cookware<ScalableValue> {
             note: self.note,
             relation: self.relation,
             modifiers: self.modifiers,
+            #[cfg(feature = "spans")]
+            span: self.span,
         };
         (scaled, outcome)
     }
@@ -352,6 +748,8 @@ impl Scale for Cookware<ScalableValue> {
             note: self.note,
             relation: self.relation,
             modifiers: self.modifiers,
+            #[cfg(feature = "spans")]
+            span: self.span,
         }
     }
 }
@@ -365,6 +763,8 @@ impl Scale for Timer<ScalableValue> {
         let scaled = Timer {
             name: self.name,
             quantity,
+            #[cfg(feature = "spans")]
+            span: self.span,
         };
         (scaled, outcome)
     }
@@ -373,6 +773,8 @@ impl Scale for Timer<ScalableValue> {
         Timer {
             name: self.name,
             quantity: self.quantity.map(Quantity::default_scale),
+            #[cfg(feature = "spans")]
+            span: self.span,
         }
     }
 }
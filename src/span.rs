@@ -4,8 +4,9 @@ use std::ops::{Deref, Range};
 
 /// Location in the source code
 ///
-/// The offsets are zero-indexed charactere offsets from the beginning of the source
-/// code.
+/// The offsets are zero-indexed byte offsets from the beginning of the source
+/// code. Use [`Span::to_char_range`] and [`Span::from_char_range`] to work with
+/// char offsets instead.
 #[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, PartialOrd, Ord)]
 pub struct Span {
     start: usize,
@@ -24,6 +25,11 @@ impl Span {
         }
     }
 
+    /// Moves the span `offset` bytes forward
+    pub(crate) fn shift(self, offset: usize) -> Self {
+        Self::new(self.start + offset, self.end + offset)
+    }
+
     /// Start offset of the span
     pub fn start(&self) -> usize {
         self.start
@@ -48,6 +54,49 @@ impl Span {
     pub fn is_empty(&self) -> bool {
         self.start == self.end
     }
+
+    /// Converts the span to a range of char offsets in `source`
+    ///
+    /// If an offset lands in the middle of a multibyte char, the range is
+    /// widened to include the whole char, so the start moves back to the
+    /// beginning of the char and the end moves forward to the end of it. An
+    /// empty span stays empty. Offsets past the end of `source` are clamped
+    /// to its length.
+    ///
+    /// ```
+    /// # use cooklang::span::Span;
+    /// let source = "Add @jalapeño{1} and @🧂{}";
+    /// let span = Span::from_char_range(source, 4..16);
+    /// assert_eq!(&source[span.range()], "@jalapeño{1}");
+    /// assert_eq!(span.to_char_range(source), 4..16);
+    /// ```
+    pub fn to_char_range(&self, source: &str) -> Range<usize> {
+        let start = floor_char_boundary(source, self.start);
+        let end = if self.is_empty() {
+            start
+        } else {
+            ceil_char_boundary(source, self.end).max(start)
+        };
+        let char_start = source[..start].chars().count();
+        char_start..char_start + source[start..end].chars().count()
+    }
+
+    /// Creates a span from a range of char offsets in `source`
+    ///
+    /// This is the inverse of [`Span::to_char_range`]. Char offsets past the
+    /// end of `source` are clamped to its length, and if the end is before the
+    /// start the span is empty.
+    pub fn from_char_range(source: &str, range: Range<usize>) -> Self {
+        let byte_offset = |char_offset: usize| {
+            source
+                .char_indices()
+                .nth(char_offset)
+                .map_or(source.len(), |(i, _)| i)
+        };
+        let start = byte_offset(range.start);
+        let end = byte_offset(range.end.max(range.start));
+        Self::new(start, end)
+    }
 }
 
 impl std::fmt::Debug for Span {
@@ -58,15 +107,31 @@ impl std::fmt::Debug for Span {
 
 impl Span {
     pub(crate) fn to_chars_span<Id>(self, all_source: &str, source_id: Id) -> CharsSpan<Id> {
-        let start = all_source[..self.start].chars().count();
-        let len = all_source[self.range()].chars().count();
         CharsSpan {
-            span: Span::new(start, start + len),
+            span: self.to_char_range(all_source).into(),
             source_id,
         }
     }
 }
 
+/// Largest char boundary less or equal to `index`, or the len of `s`
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    let mut index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Smallest char boundary greater or equal to `index`, or the len of `s`
+fn ceil_char_boundary(s: &str, index: usize) -> usize {
+    let mut index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
 pub(crate) struct CharsSpan<Id> {
     span: Span,
     source_id: Id,
@@ -122,3 +187,56 @@ where
         self.span.end
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "Añade @jalapeño{1} y @🧂{}";
+
+    #[test]
+    fn char_range() {
+        let start = SOURCE.find('@').unwrap();
+        let end = SOURCE.find('}').unwrap() + 1;
+        let span = Span::new(start, end);
+        assert_eq!(&SOURCE[span.range()], "@jalapeño{1}");
+        assert_eq!(span.to_char_range(SOURCE), 6..18);
+        assert_eq!(Span::from_char_range(SOURCE, 6..18), span);
+
+        let salt = SOURCE.rfind('@').unwrap();
+        let span = Span::new(salt, SOURCE.len());
+        assert_eq!(&SOURCE[span.range()], "@🧂{}");
+        assert_eq!(span.to_char_range(SOURCE), 21..25);
+        assert_eq!(Span::from_char_range(SOURCE, 21..25), span);
+
+        // char offsets are the same as the ones of the chars iterator
+        let chars = SOURCE.chars().collect::<Vec<_>>();
+        assert_eq!(chars[21..25].iter().collect::<String>(), "@🧂{}");
+    }
+
+    #[test]
+    fn char_range_clamping() {
+        // the `ñ` is 2 bytes, starting at 2
+        let span = Span::new(3, 4);
+        assert_eq!(span.to_char_range(SOURCE), 2..3);
+        let span = Span::new(3, 3);
+        assert_eq!(span.to_char_range(SOURCE), 2..2);
+
+        // the emoji is 4 bytes
+        let emoji = SOURCE.find('🧂').unwrap();
+        let span = Span::new(emoji + 1, emoji + 2);
+        assert_eq!(span.to_char_range(SOURCE), 22..23);
+
+        // past the end
+        let span = Span::new(SOURCE.len(), SOURCE.len() + 10);
+        assert_eq!(span.to_char_range(SOURCE), 25..25);
+        assert_eq!(
+            Span::from_char_range(SOURCE, 20..100),
+            Span::new(SOURCE.rfind(' ').unwrap(), SOURCE.len())
+        );
+        // inverted
+        #[allow(clippy::reversed_empty_ranges)]
+        let range = 10..5;
+        assert!(Span::from_char_range(SOURCE, range).is_empty());
+    }
+}
@@ -63,7 +63,6 @@ impl<'a> Text<'a> {
         }
     }
 
-    #[cfg(test)] // only used in tests
     pub(crate) fn from_str(s: &'a str, offset: usize) -> Self {
         let mut t = Self::empty(offset);
         t.append_fragment(TextFragment::new(s, offset));
@@ -135,6 +134,33 @@ impl<'a> Text<'a> {
         Cow::from(t)
     }
 
+    /// Splits the text at the first `c`, which is not in any of the parts
+    pub(crate) fn split_once(&self, c: char) -> Option<(Self, Self)> {
+        let fragments = self.fragments();
+        let (index, pos) = fragments.iter().enumerate().find_map(|(i, f)| {
+            if f.kind != TextFragmentKind::Text {
+                return None;
+            }
+            f.text.find(c).map(|pos| (i, pos))
+        })?;
+        let split = &fragments[index];
+
+        let mut head = Self::empty(self.span().start());
+        for f in &fragments[..index] {
+            head.append_fragment(*f);
+        }
+        head.append_str(&split.text[..pos], split.offset);
+
+        let tail_start = split.offset + pos + c.len_utf8();
+        let mut tail = Self::empty(tail_start);
+        tail.append_str(&split.text[pos + c.len_utf8()..], tail_start);
+        for f in &fragments[index + 1..] {
+            tail.append_fragment(*f);
+        }
+
+        Some((head, tail))
+    }
+
     /// Checks that the text is not empty or blank, i.e. whitespace does not count
     pub fn is_text_empty(&self) -> bool {
         self.fragments().iter().all(|f| f.text.trim().is_empty())
@@ -268,4 +294,20 @@ mod tests {
         let t = Text::from_str(t, 0);
         t.text_trimmed().into_owned()
     }
+
+    #[test]
+    fn split_once() {
+        let t = Text::from_str("./sauce#tomato", 2);
+        let (head, tail) = t.split_once('#').unwrap();
+        assert_eq!(head, Text::from_str("./sauce", 2));
+        assert_eq!(tail, Text::from_str("tomato", 10));
+        assert_eq!(tail.span(), (10..16).into());
+
+        let (head, tail) = Text::from_str("#", 0).split_once('#').unwrap();
+        assert!(head.is_text_empty());
+        assert!(tail.is_text_empty());
+        assert_eq!(tail.span(), (1..1).into());
+
+        assert!(Text::from_str("sauce", 0).split_once('#').is_none());
+    }
 }
@@ -173,6 +173,8 @@ impl TestValue {
                 Value::Number(num) => TestValue::Number(num.value()),
                 Value::Range { .. } => panic!("unexpected range value"),
                 Value::Text(value) => TestValue::Text(value),
+                Value::Tolerant { .. } => panic!("unexpected tolerance value"),
+                Value::Variable(_) => panic!("unexpected variable value"),
             },
             ScalableValue::Linear(_) => panic!("unexpected linear value"),
             ScalableValue::ByServings(_) => panic!("unexpected value by servings"),
@@ -1,4 +1,6 @@
-use cooklang::{Content, CooklangParser, Extensions, Item};
+use std::collections::HashMap;
+
+use cooklang::{Content, CooklangParser, Extensions, Item, ParseOptions, Value};
 use indoc::indoc;
 use test_case::test_case;
 
@@ -180,6 +182,43 @@ fn single_line_no_separator() {
     assert_eq!(r.metadata.map.len(), 1);
 }
 
+#[test_case("\n" ; "lf")]
+#[test_case("\r\n" ; "crlf")]
+fn line_continuation(newline: &str) {
+    let input = format!(
+        "Mix the @flour{{200%g}} \\{newline}with the @water{{100%ml}}.{newline}Add @choco{{70\\%%g}}{newline}Bake."
+    );
+    let parser = CooklangParser::new(
+        Extensions::all() ^ Extensions::MULTILINE_STEPS,
+        Default::default(),
+    );
+    let r = parser.parse(&input).unwrap_output();
+    let content = &r.sections[0].content;
+    assert_eq!(content.len(), 3);
+    let Content::Step(first) = &content[0] else {
+        panic!()
+    };
+    assert_eq!(
+        first.items,
+        vec![
+            Item::Text {
+                value: "Mix the ".into()
+            },
+            Item::Ingredient { index: 0 },
+            Item::Text {
+                value: " with the ".into()
+            },
+            Item::Ingredient { index: 1 },
+            Item::Text { value: ".".into() },
+        ]
+    );
+    // an escape in a quantity at the end of the line is not a continuation
+    assert_eq!(
+        r.ingredients[2].quantity.as_ref().unwrap().unit_text(),
+        Some("g")
+    );
+}
+
 #[test]
 fn multiple_temperatures() {
     let input = "text 2ºC more text 150 F end text";
@@ -245,3 +284,2449 @@ fn text_steps_extension() {
         [Content::Step(_)]
     ));
 }
+
+#[test_case("mll" => Some("ml".to_string()); "extra letter")]
+#[test_case("gramm" => Some("gram".to_string()); "long name")]
+#[test_case("Kg" => Some("kg".to_string()); "casing")]
+#[test_case("ml" => None; "known")]
+#[test_case("xyzzy" => None; "far")]
+#[test_case("handful" => None; "not an unit")]
+fn suggest_unit(unit: &str) -> Option<String> {
+    let converter = cooklang::Converter::bundled();
+    converter.suggest_unit(unit)
+}
+
+#[test]
+fn unknown_unit_suggestion() {
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse("@water{1%mll}");
+    let warnings = r.report().warnings().collect::<Vec<_>>();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].hints.iter().any(|h| h == "did you mean ml?"));
+
+    // no suggestion, no warning
+    let r = parser.parse("@salt{1%handful}");
+    assert!(r.report().warnings().next().is_none());
+
+    // only in advanced units mode
+    let parser = CooklangParser::new(Extensions::empty(), Default::default());
+    let r = parser.parse("@water{1%mll}");
+    assert!(r.report().warnings().next().is_none());
+}
+
+#[test]
+fn range_units() {
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse("@water{1 cup - 250 ml}").unwrap_output();
+    let q = r.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(q.unit_text(), Some("ml"));
+    let cooklang::ScalableValue::Fixed(Value::Range { start, end }) = &q.value else {
+        panic!("not a range: {}", q.value)
+    };
+    assert!((start.value() - 236.588).abs() < 0.001);
+    assert_eq!(end.value(), 250.0);
+
+    let r = parser.parse("@water{1 cup - 250 g}");
+    assert!(r.report().has_errors());
+}
+
+#[test]
+fn parse_section() {
+    let input = indoc! {r#"
+        = Dough
+        Mix @flour{500%g} and @water{300%ml}.
+
+        = Sauce
+        Cook @tomato{2} in a #pan.
+
+        Add more @&flour{10%g}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let section = parser
+        .parse_section(input, "Sauce")
+        .unwrap_output()
+        .expect("section not found");
+    assert_eq!(section.name.as_deref(), Some("Sauce"));
+    assert_eq!(section.content.len(), 2);
+
+    // indices are for the whole recipe and references are resolved
+    let Content::Step(step) = &section.content[1] else {
+        panic!("not a step")
+    };
+    let r = parser.parse(input).unwrap_output();
+    let flour = step
+        .items
+        .iter()
+        .find_map(|i| match i {
+            Item::Ingredient { index } => Some(&r.ingredients[*index]),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(flour.name, "flour");
+    assert_eq!(
+        flour.relation.references_to(),
+        Some((0, cooklang::IngredientReferenceTarget::Ingredient))
+    );
+
+    assert!(parser
+        .parse_section(input, "Missing")
+        .unwrap_output()
+        .is_none());
+}
+
+#[cfg(feature = "spans")]
+#[test]
+fn model_spans() {
+    let input = "Put @flour{200%g} in a #bowl{} for ~{2%min}.";
+    let r = cooklang::parse(input).unwrap_output();
+    let slice = |span: cooklang::Span| &input[span.range()];
+    assert_eq!(slice(r.ingredients[0].span), "@flour{200%g}");
+    assert_eq!(slice(r.cookware[0].span), "#bowl{}");
+    assert_eq!(slice(r.timers[0].span), "~{2%min}");
+    let step = r.sections[0].content[0].unwrap_step();
+    assert_eq!(slice(step.span), input);
+}
+
+#[test]
+fn alternative_quantities() {
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse("@flour{1 cup / 120 g}").unwrap_output();
+    let flour = &r.ingredients[0];
+    assert_eq!(flour.quantity.as_ref().unwrap().unit_text(), Some("cup"));
+    assert_eq!(flour.alternative_quantities.len(), 1);
+    assert_eq!(flour.alternative_quantities[0].unit_text(), Some("g"));
+
+    // single quantity
+    let r = parser.parse("@flour{120%g}").unwrap_output();
+    let flour = &r.ingredients[0];
+    assert_eq!(flour.quantity.as_ref().unwrap().unit_text(), Some("g"));
+    assert!(flour.alternative_quantities.is_empty());
+
+    // not serialized when empty, and JSON without them still deserializes
+    let json = serde_json::to_value(flour).unwrap();
+    assert!(json.get("alternative_quantities").is_none());
+    let back: cooklang::Ingredient<cooklang::ScalableValue> = serde_json::from_value(json).unwrap();
+    assert_eq!(&back, flour);
+
+    // same physical quantity
+    let r = parser.parse("@flour{120 g / 0.12 kg}");
+    assert_eq!(r.report().warnings().count(), 1);
+
+    // disabled
+    let parser = CooklangParser::new(
+        Extensions::all() ^ Extensions::ALTERNATIVE_QUANTITIES,
+        Default::default(),
+    );
+    let r = parser.parse("@flour{1 cup / 120 g}").unwrap_output();
+    let flour = &r.ingredients[0];
+    assert_eq!(
+        flour.quantity.as_ref().unwrap().unit_text(),
+        Some("cup / 120 g")
+    );
+    assert!(flour.alternative_quantities.is_empty());
+}
+
+#[test]
+fn culinary_rounding() {
+    use cooklang::quantity::Number;
+    use cooklang::scale::ScaleOptions;
+
+    let input = indoc! {r#"
+        >> servings: 12
+        @eggs{5*} and @flour{500*%g}
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input).unwrap_output();
+    let options = ScaleOptions {
+        culinary_rounding: true,
+        ..Default::default()
+    };
+    let r = r.scale_with_options(1, parser.converter(), options);
+
+    let eggs = r.ingredients[0].quantity.as_ref().unwrap();
+    let Value::Number(
+        n @ Number::Fraction {
+            whole,
+            num,
+            den,
+            err,
+        },
+    ) = eggs.value
+    else {
+        panic!("not a fraction: {:?}", eggs.value);
+    };
+    assert_eq!((whole, num, den), (0, 1, 3));
+    assert!((err - (5.0 / 12.0 - 1.0 / 3.0)).abs() < 1e-10);
+    assert!((n.value() - 5.0 / 12.0).abs() < 1e-10);
+    assert_eq!(eggs.value.to_string(), "1/3");
+
+    // not a volume, untouched
+    let flour = r.ingredients[1].quantity.as_ref().unwrap();
+    assert!(matches!(flour.value, Value::Number(Number::Regular(_))));
+}
+
+#[test]
+fn rounding_policy() {
+    use cooklang::scale::{RoundingPolicy, ScaleOptions};
+
+    let input = indoc! {r#"
+        >> servings: 3
+        @eggs{2*}, @flour{2*%kg} and @salt{1*%tsp}
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input).unwrap_output();
+    let scale = |rounding| {
+        let options = ScaleOptions {
+            rounding,
+            ..Default::default()
+        };
+        let r = r.clone().scale_with_options(4, parser.converter(), options);
+        r.ingredients
+            .iter()
+            .map(|i| i.quantity.as_ref().unwrap().value.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let exact = scale(RoundingPolicy::Exact);
+    assert_eq!(exact[0], Value::from(8.0 / 3.0).to_string());
+    assert_eq!(
+        scale(RoundingPolicy::Fractions),
+        ["2 2/3", "2 2/3", "1 1/3"]
+    );
+    assert_eq!(
+        scale(RoundingPolicy::FractionsAndWholeCounts),
+        ["3", "2 2/3", "1 1/3"]
+    );
+
+    // whole values are left alone
+    let r = r.scale_with_options(
+        6,
+        parser.converter(),
+        ScaleOptions {
+            rounding: RoundingPolicy::FractionsAndWholeCounts,
+            ..Default::default()
+        },
+    );
+    let eggs = r.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(eggs.value, Value::from(4.0));
+}
+
+#[test]
+fn duplicate_metadata_related_info() {
+    let input = indoc! {r#"
+        >> source: Mom
+        >> tags: quick
+        >> tags: easy
+        >> note: one
+        >> note: two
+        >> source: Grandma
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input);
+    let warnings = r.report().warnings().collect::<Vec<_>>();
+    assert_eq!(warnings.len(), 1);
+    let w = warnings[0];
+    assert!(w.message.contains("source"));
+    assert_eq!(w.labels[0].0.start(), input.rfind(" source").unwrap());
+    assert_eq!(w.related_info().len(), 1);
+    assert_eq!(
+        w.related_info()[0].0.start(),
+        input.find(" source").unwrap()
+    );
+
+    let json = serde_json::to_value(w).unwrap();
+    assert_eq!(json["related"].as_array().unwrap().len(), 1);
+    assert_eq!(json["severity"], "warning");
+
+    let recipe = r.into_output().unwrap();
+    let tags = recipe.metadata.tags().unwrap().to_vec();
+    assert_eq!(tags, ["quick", "easy"]);
+    assert_eq!(recipe.metadata.get("source"), Some("Grandma"));
+    assert_eq!(recipe.metadata.all("source"), ["Mom", "Grandma"]);
+    assert_eq!(recipe.metadata.all("tags"), ["quick", "easy"]);
+    assert_eq!(recipe.metadata.all("note"), ["one", "two"]);
+}
+
+#[test]
+fn canonical_units() {
+    let input = "@flour{100%Gram} @salt{1%pinch} @water{1}";
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+
+    let options = ParseOptions {
+        canonical_units: true,
+        ..Default::default()
+    };
+    let r = parser.parse_with_options(input, options).unwrap_output();
+    let flour = r.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(flour.unit_text(), Some("g"));
+    assert_eq!(flour.unit_raw(), Some("Gram"));
+    let salt = r.ingredients[1].quantity.as_ref().unwrap();
+    assert_eq!(salt.unit_text(), Some("pinch"));
+    assert_eq!(salt.unit_raw(), Some("pinch"));
+    let water = r.ingredients[2].quantity.as_ref().unwrap();
+    assert_eq!(water.unit_raw(), None);
+
+    // default
+    let r = parser.parse(input).unwrap_output();
+    let flour = r.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(flour.unit_text(), Some("Gram"));
+    assert_eq!(flour.unit_raw(), None);
+
+    // scaling keeps the raw unit. the flour has no `*`, so it's fixed
+    let input = format!(">> servings: 1\n{input}");
+    let options = ParseOptions {
+        canonical_units: true,
+        ..Default::default()
+    };
+    let r = parser.parse_with_options(&input, options).unwrap_output();
+    let r = r.scale(2, parser.converter());
+    let flour = r.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(flour.to_string(), "100 g");
+    assert_eq!(flour.unit_raw(), Some("Gram"));
+}
+
+#[test]
+fn select_variant() {
+    let input = indoc! {r#"
+        Mix @flour{200%g} with @[vegan]margarine{100%g} or @[classic]butter{100%g}.
+
+        Add @&butter{10%g} and @&[vegan]margarine{10%g} on top with @salt{}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let recipe = parser.parse(input).unwrap_output();
+    assert_eq!(recipe.ingredients.len(), 6);
+    assert_eq!(recipe.ingredients[3].variant.as_deref(), Some("classic")); // inherited
+
+    let names = |r: &cooklang::ScalableRecipe| {
+        r.ingredients
+            .iter()
+            .map(|i| i.name.clone())
+            .collect::<Vec<_>>()
+    };
+    let step_names = |r: &cooklang::ScalableRecipe, step: usize| {
+        r.sections[0].content[step]
+            .unwrap_step()
+            .items
+            .iter()
+            .filter_map(|i| match i {
+                Item::Ingredient { index } => Some(r.ingredients[*index].name.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let vegan = recipe.clone().select_variant("vegan");
+    assert_eq!(names(&vegan), ["flour", "margarine", "margarine", "salt"]);
+    assert_eq!(step_names(&vegan, 0), ["flour", "margarine"]);
+    assert_eq!(step_names(&vegan, 1), ["margarine", "salt"]);
+    assert_eq!(vegan.ingredients[1].relation.referenced_from(), [2]);
+    assert_eq!(
+        vegan.ingredients[2].relation.references_to().map(|r| r.0),
+        Some(1)
+    );
+
+    let classic = recipe.clone().select_variant("classic");
+    assert_eq!(names(&classic), ["flour", "butter", "butter", "salt"]);
+    assert_eq!(classic.ingredients[1].relation.referenced_from(), [2]);
+
+    // no variant selected
+    let default = recipe.select_variant("other");
+    assert_eq!(names(&default), ["flour", "salt"]);
+    assert_eq!(step_names(&default, 1), ["salt"]);
+}
+
+#[test_case("@water{2}" => 1; "bare number")]
+#[test_case("@water{1-2}" => 1; "bare range")]
+#[test_case("@water{2%ml}" => 0; "with unit")]
+#[test_case("@water{2 ml}" => 0; "with unit no separator")]
+#[test_case("@water{a splash}" => 0; "text")]
+#[test_case("@eggs{2%}" => 0; "count")]
+#[test_case("@eggs{2 % }" => 0; "count with spaces")]
+#[test_case("@water" => 0; "no quantity")]
+#[test_case("#pot{2}" => 0; "cookware")]
+fn require_units(input: &str) -> usize {
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let options = ParseOptions {
+        require_units: true,
+        ..Default::default()
+    };
+    let r = parser.parse_with_options(input, options);
+    // never an error without the option
+    assert!(!parser.parse(input).report().has_errors());
+    r.report().errors().count()
+}
+
+#[test]
+fn require_units_count() {
+    let input = "@eggs{2%}";
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let options = ParseOptions {
+        require_units: true,
+        ..Default::default()
+    };
+    let r = parser.parse_with_options(input, options);
+    assert!(r.report().is_empty());
+    let q = r.output().unwrap().ingredients[0]
+        .quantity
+        .as_ref()
+        .unwrap();
+    assert!(q.is_count());
+
+    // the empty unit is still a warning without the option
+    assert_eq!(parser.parse(input).report().warnings().count(), 1);
+}
+
+#[test]
+fn group_by_category() {
+    let input = indoc! {r#"
+        Mix @flour{200%g}[baking] with @milk{100%ml} and @salt{}.
+        Add @&flour{10%g} and @butter{}[dairy].
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let recipe = parser.parse(input).unwrap_output();
+    assert_eq!(recipe.ingredients[0].category.as_deref(), Some("baking"));
+    assert_eq!(recipe.ingredients[1].category, None);
+
+    let categories = HashMap::from([("milk", "dairy"), ("flour", "other aisle")]);
+    let grouped = recipe.group_by_category(&categories);
+    let names = |cat: &str| {
+        grouped[cat]
+            .iter()
+            .map(|i| i.name.as_str())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        grouped.keys().map(String::as_str).collect::<Vec<_>>(),
+        ["baking", "dairy", "other"]
+    );
+    // inline category first and only definitions
+    assert_eq!(names("baking"), ["flour"]);
+    assert_eq!(names("dairy"), ["milk", "butter"]);
+    assert_eq!(names("other"), ["salt"]);
+
+    // not enabled by default, the brackets are text
+    let recipe = CooklangParser::default()
+        .parse("Mix @flour{}[baking].")
+        .unwrap_output();
+    assert_eq!(recipe.ingredients[0].category, None);
+    let step = recipe.sections[0].content[0].unwrap_step();
+    assert_eq!(step.text(), "Mix [baking].");
+}
+
+#[test]
+fn scale_yield() {
+    use cooklang::scale::ScaleOptions;
+
+    let input = indoc! {r#"
+        >> servings: 4
+        >> yield: 12 muffins
+        Mix @flour{200%g}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input).unwrap_output();
+    let y = r.metadata.yield_().unwrap();
+    assert_eq!(y.amount, Value::from(12.0));
+    assert_eq!(y.unit.as_deref(), Some("muffins"));
+
+    let scaled = r.clone().scale(8, parser.converter());
+    assert_eq!(scaled.metadata.yield_().unwrap().amount, Value::from(12.0));
+
+    let options = ScaleOptions {
+        scale_yield: true,
+        ..Default::default()
+    };
+    let scaled = r.scale_with_options(8, parser.converter(), options);
+    assert_eq!(scaled.metadata.yield_().unwrap().amount, Value::from(24.0));
+    assert_eq!(scaled.metadata.map["yield"], "12 muffins");
+}
+
+#[test]
+fn per_unit_quantities_with_slash() {
+    let input = "Add @water{200 ml/serving}, @flour{1 cup/120 g} and @milk{1/2%cup}.";
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input).unwrap_output().default_scale();
+
+    let water = r.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(water.unit_text(), Some("ml"));
+    assert_eq!(water.per(), Some("serving"));
+    assert_eq!(water.to_string(), "200 ml/serving");
+    assert!(r.ingredients[0].alternative_quantities.is_empty());
+
+    let flour = &r.ingredients[1];
+    assert_eq!(flour.quantity.as_ref().unwrap().to_string(), "1 cup");
+    assert_eq!(flour.alternative_quantities[0].to_string(), "120 g");
+    assert!(flour.quantity.as_ref().unwrap().per().is_none());
+
+    let milk = r.ingredients[2].quantity.as_ref().unwrap();
+    assert_eq!(milk.value, Value::from(0.5));
+    assert!(milk.per().is_none());
+
+    // a quantity per serving cannot be added to a total
+    let total = water.try_add(milk, parser.converter());
+    assert!(total.is_err());
+
+    // converting and scaling keep what the quantity is for each of
+    let mut water = water.clone();
+    water.convert("l", parser.converter()).unwrap();
+    assert_eq!(water.unit_text(), Some("l"));
+    assert_eq!(water.per(), Some("serving"));
+    let r = parser
+        .parse(&format!(">> servings: 1\n{input}"))
+        .unwrap_output()
+        .scale(2, parser.converter());
+    let water = r.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(water.per(), Some("serving"));
+
+    let parser = CooklangParser::new(
+        Extensions::all() ^ Extensions::ADVANCED_UNITS,
+        Default::default(),
+    );
+    let r = parser.parse("Add @water{200%ml/serving}.").unwrap_output();
+    let water = r.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(water.unit_text(), Some("ml/serving"));
+    assert!(water.per().is_none());
+}
+
+#[test]
+fn metadata_setters() {
+    use cooklang::metadata::RecipeTime;
+
+    let input = indoc! {r#"
+        >> servings: 4
+        >> prep time: 10 min
+        Mix @flour{200*%g}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let mut r = parser.parse(input).unwrap_output();
+    r.metadata_mut().set_servings(&[2]).unwrap();
+    r.metadata_mut().set_time(RecipeTime::Total(45)).unwrap();
+    assert_eq!(r.metadata.map["servings"], "2");
+    assert_eq!(r.metadata.map["time"], "45 min");
+    assert!(!r.metadata.map.contains_key("prep time"));
+    assert_eq!(r.metadata.time(), Some(&RecipeTime::Total(45)));
+
+    // scaling uses the new servings
+    let scaled = r.scale(4, parser.converter());
+    assert_eq!(
+        scaled.ingredients[0].quantity.as_ref().unwrap().to_string(),
+        "400 g"
+    );
+}
+
+#[test]
+fn scale_to_ingredient() {
+    use cooklang::{scale::ScaleError, Converter, Quantity};
+
+    let input = indoc! {r#"
+        >> servings: 4
+        Mix @flour{1*%kg}, @eggs{2*}, @water{600*%ml} and @salt{a pinch}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Converter::bundled());
+    let recipe = parser.parse(input).unwrap_output();
+    let q =
+        |value: f64, unit: Option<&str>| Quantity::new(Value::from(value), unit.map(String::from));
+
+    let scaled = recipe
+        .clone()
+        .scale_to_ingredient("flour", q(500.0, Some("g")), parser.converter())
+        .unwrap();
+    let values = scaled
+        .ingredients
+        .iter()
+        .map(|i| i.quantity.as_ref().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(values, ["500 g", "1", "300 ml", "a pinch"]);
+
+    let scaled = recipe
+        .clone()
+        .scale_to_ingredient("eggs", q(3.0, None), parser.converter())
+        .unwrap();
+    assert_eq!(
+        scaled.ingredients[0].quantity.as_ref().unwrap().to_string(),
+        "1.5 kg"
+    );
+
+    let err = |name: &str, target| {
+        recipe
+            .clone()
+            .scale_to_ingredient(name, target, parser.converter())
+            .unwrap_err()
+    };
+    assert!(matches!(
+        err("salt", q(1.0, None)),
+        ScaleError::InvalidIngredientTarget { .. }
+    ));
+    assert!(matches!(
+        err(
+            "eggs",
+            Quantity::new(Value::from("a few".to_string()), None)
+        ),
+        ScaleError::TextValueError(_)
+    ));
+    assert!(matches!(
+        err("sugar", q(1.0, None)),
+        ScaleError::IngredientNotFound { .. }
+    ));
+    assert!(matches!(
+        err("flour", q(1.0, Some("ml"))),
+        ScaleError::ConvertError(_)
+    ));
+    assert!(matches!(
+        err("flour", q(1.0, None)),
+        ScaleError::InvalidIngredientTarget { .. }
+    ));
+}
+
+#[test]
+fn inverted_ranges() {
+    let input = "@water{5-2%l}";
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+
+    let r = parser.parse(input);
+    assert_eq!(r.report().warnings().count(), 1);
+    let r = r.unwrap_output().default_scale();
+    assert_eq!(
+        r.ingredients[0].quantity.as_ref().unwrap().to_string(),
+        "5-2 l"
+    );
+
+    let options = ParseOptions {
+        swap_inverted_ranges: true,
+        ..Default::default()
+    };
+    let r = parser.parse_with_options(input, options);
+    assert_eq!(r.report().warnings().count(), 1);
+    let r = r.unwrap_output().default_scale();
+    assert_eq!(
+        r.ingredients[0].quantity.as_ref().unwrap().to_string(),
+        "2-5 l"
+    );
+}
+
+#[test]
+fn keep_decimals() {
+    let input = "@flour{10.00%g} @milk{1.50-2.0%l} @salt{1/2%tsp} @eggs{2} @water{0.5*%l}";
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+
+    let r = parser.parse(input).unwrap_output().default_scale();
+    let q = |i: usize| r.ingredients[i].quantity.as_ref().unwrap();
+    assert_eq!(q(0).to_string(), "10 g");
+    assert_eq!(q(1).to_string(), "1.5-2 l");
+    assert!((0..5).all(|i| q(i).decimals().is_none()));
+
+    let options = ParseOptions {
+        keep_decimals: true,
+        ..Default::default()
+    };
+    let recipe = parser.parse_with_options(input, options).unwrap_output();
+    let r = recipe.clone().default_scale();
+    let q = |i: usize| r.ingredients[i].quantity.as_ref().unwrap();
+    let decimals = (0..5).map(|i| q(i).decimals()).collect::<Vec<_>>();
+    assert_eq!(decimals, [Some(2), Some(2), None, None, Some(1)]);
+    assert_eq!(q(0).to_string(), "10.00 g");
+    assert_eq!(q(1).to_string(), "1.50-2.00 l");
+    assert_eq!(q(2).to_string(), "1/2 tsp");
+    assert_eq!(q(3).to_string(), "2");
+    assert_eq!(q(4).to_string(), "0.5 l");
+
+    let converter = parser.converter();
+    let mut r = recipe.scale(2, converter);
+    let q = |i: usize| r.ingredients[i].quantity.as_ref().unwrap().to_string();
+    assert_eq!(q(0), "10.00 g");
+    assert_eq!(q(4), "1.0 l");
+
+    let flour = r.ingredients[0].quantity.as_mut().unwrap();
+    flour.convert("kg", converter).unwrap();
+    assert_eq!(flour.decimals(), None);
+    assert_eq!(flour.to_string(), "0.01 kg");
+}
+
+#[test]
+fn substitutes() {
+    let input = indoc! {r#"
+        Melt @butter{1%cup}(or margarine, ghee) with @sugar{}(brown or white).
+        Add @&butter{}(or oil).
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input);
+    assert_eq!(r.report().errors().count(), 1);
+    let recipe = r.into_output().unwrap();
+    assert_eq!(recipe.ingredients[0].substitutes, ["margarine", "ghee"]);
+    assert_eq!(recipe.ingredients[0].note, None);
+    assert!(recipe.ingredients[1].substitutes.is_empty());
+    assert_eq!(
+        recipe.ingredients[1].note.as_deref(),
+        Some("brown or white")
+    );
+
+    // not enabled by default, so a regular note can start with `or`
+    let recipe = CooklangParser::default()
+        .parse("Add @salt{}(or to taste).")
+        .unwrap_output();
+    assert!(recipe.ingredients[0].substitutes.is_empty());
+    assert_eq!(recipe.ingredients[0].note.as_deref(), Some("or to taste"));
+}
+
+#[test]
+fn section_name_reference() {
+    let input = indoc! {r#"
+        = Dough
+        Mix @flour{500%g} and @water{300%ml}.
+
+        = Sauce
+        Cook @tomato{2}.
+
+        = Pizza
+        Spread the @&(=Dough)dough{} and add the @&(=Sauce)sauce{}.
+        Add the @&(=Toppings)toppings{}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input);
+    let errors = r.report().errors().collect::<Vec<_>>();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].message.contains("unknown section 'Toppings'"));
+    let recipe = r.into_output().unwrap();
+    let target = |name: &str| {
+        recipe
+            .ingredients
+            .iter()
+            .find(|i| i.name == name)
+            .and_then(|i| i.relation.references_to())
+    };
+    let section = cooklang::IngredientReferenceTarget::Section;
+    assert_eq!(target("dough"), Some((0, section)));
+    assert_eq!(target("sauce"), Some((1, section)));
+}
+
+#[test]
+fn leading_units() {
+    let input = "Buy @bread{$5} and @cheese{3.5€} and @milk{2%l}.";
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input).unwrap_output().default_scale();
+    let q = |i: usize| r.ingredients[i].quantity.as_ref().unwrap();
+    assert_eq!(q(0).unit_text(), Some("$"));
+    assert_eq!(q(0).value, 5.0.into());
+    assert_eq!(q(1).unit_text(), Some("€"));
+    assert_eq!(q(1).value, 3.5.into());
+    assert_eq!(q(2).to_string(), "2 l");
+
+    // not enabled by default, the value is text
+    let r = CooklangParser::default()
+        .parse("Buy @bread{$5}.")
+        .unwrap_output();
+    let q = r.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(q.unit(), None);
+    assert_eq!(q.value.to_string(), "$5");
+}
+
+#[test]
+fn parse_partial() {
+    let input = indoc! {r#"
+        >> servings: 2
+        Mix @flour{200%g} with @[]water{}.
+
+        = Bake
+        Bake in the #oven{} with @salt{}[] for ~{20%min}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input);
+    assert_eq!(r.report().errors().count(), 2);
+    assert!(r.output().is_none());
+
+    let (recipe, report) = parser.parse_partial(input);
+    assert_eq!(report.errors().count(), 2);
+    assert_eq!(recipe.metadata.servings(), Some([2].as_slice()));
+    assert_eq!(recipe.sections.len(), 2);
+    assert_eq!(recipe.sections[1].name.as_deref(), Some("Bake"));
+    let names = recipe
+        .ingredients
+        .iter()
+        .map(|i| i.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["flour", "water", "salt"]);
+    assert_eq!(recipe.cookware.len(), 1);
+    assert_eq!(recipe.timers.len(), 1);
+
+    // valid input is the same as parse
+    let input = "Mix @flour{200%g}.";
+    let (recipe, report) = parser.parse_partial(input);
+    assert!(report.is_empty());
+    assert_eq!(recipe, parser.parse(input).unwrap_output());
+}
+
+#[test_case(10000.0, "cm2", "m2" => "1 m2" ; "area")]
+#[test_case(1.0, "m^2", "cm^2" => "10000 cm2" ; "area caret")]
+#[test_case(2.0, "in²", "cm2" => "12.903 cm2" ; "area imperial")]
+#[test_case(1000.0, "cm3", "l" => "1 l" ; "volume")]
+#[test_case(1.0, "l", "cm^3" => "1000 cm3" ; "to volume")]
+fn unit_exponents(value: f64, from: &str, to: &str) -> String {
+    let converter = cooklang::Converter::bundled();
+    let mut q = cooklang::Quantity::new(Value::from(value), Some(from.to_string()));
+    q.convert(to, &converter).unwrap();
+    q.to_string()
+}
+
+#[test_case(2_500_000.0, "µg", "g" => "2.5 g" ; "micro")]
+#[test_case(2_500_000.0, "ug", "g" => "2.5 g" ; "micro ascii")]
+#[test_case(500.0, "micrograms", "mg" => "0.5 mg" ; "micro name")]
+#[test_case(2.0, "kg", "g" => "2000 g" ; "kilo")]
+#[test_case(1.0, "Mg", "kg" => "1000 kg" ; "mega")]
+#[test_case(1.0, "µl", "ml" => "0.001 ml" ; "micro volume")]
+fn si_prefixes(value: f64, from: &str, to: &str) -> String {
+    let converter = cooklang::Converter::bundled();
+    let mut q = cooklang::Quantity::new(Value::from(value), Some(from.to_string()));
+    q.convert(to, &converter).unwrap();
+    q.to_string()
+}
+
+#[test]
+fn si_prefixes_generated() {
+    let converter = cooklang::Converter::minimal();
+    let kg = converter.scale_unit_prefix("kg").unwrap();
+    assert_eq!(kg.ratio, 1000.0);
+    let ug = converter.find_unit("µg").unwrap();
+    assert_eq!(ug.symbol(), "µg");
+    assert_eq!(ug.ratio, 1e-6);
+    assert_eq!(converter.find_unit("μg").unwrap().ratio, 1e-6);
+
+    // only linear metric units, and the prefix has to match the key kind
+    assert!(converter.scale_unit_prefix("kC").is_none());
+    assert!(converter.scale_unit_prefix("kmin").is_none());
+    assert!(converter.scale_unit_prefix("kgram").is_none());
+    assert!(converter.scale_unit_prefix("kilog").is_none());
+    assert!(cooklang::Converter::bundled()
+        .scale_unit_prefix("kcup")
+        .is_none());
+}
+
+#[test]
+fn unit_exponents_errors() {
+    let converter = cooklang::Converter::bundled();
+    let mut q = cooklang::Quantity::new(Value::from(1.0), Some("cm2".to_string()));
+    assert!(q.convert("cm", &converter).is_err());
+    assert!(q.convert("g2", &converter).is_err());
+
+    // areas have no best units, so fitting keeps them
+    let mut q = cooklang::Quantity::new(Value::from(20000.0), Some("cm2".to_string()));
+    q.fit(&converter).unwrap();
+    assert_eq!(q.to_string(), "20000 cm2");
+
+    let parser = CooklangParser::new(Extensions::all(), converter);
+    let r = parser.parse("Roll to @dough{600 cm2}.").unwrap_output();
+    let q = r.ingredients[0].quantity.as_ref().unwrap();
+    assert!(matches!(
+        q.unit().unwrap().unit_info_or_parse(parser.converter()),
+        cooklang::quantity::UnitInfo::Known(_)
+    ));
+}
+
+#[test]
+fn minimal_converter() {
+    let converter = cooklang::Converter::minimal();
+    let mut q = cooklang::Quantity::new(Value::from(1500.0), Some("g".to_string()));
+    q.convert("kg", &converter).unwrap();
+    assert_eq!(q.to_string(), "1.5 kg");
+    q.convert("g", &converter).unwrap();
+    assert_eq!(q.to_string(), "1500 g");
+
+    let mut q = cooklang::Quantity::new(Value::from(1.0), Some("cup".to_string()));
+    assert!(converter.find_unit("cup").is_none());
+    assert!(q.convert("ml", &converter).is_err());
+}
+
+#[test]
+fn bundled_subset_converter() {
+    use cooklang::convert::PhysicalQuantity;
+
+    let converter = cooklang::Converter::bundled_subset(&[PhysicalQuantity::Mass]);
+    assert!(converter.find_unit("kg").is_some());
+    assert!(converter.find_unit("oz").is_some());
+    assert!(converter.find_unit("cup").is_none());
+    assert!(converter.find_unit("min").is_none());
+    let mut q = cooklang::Quantity::new(Value::from(1.0), Some("kg".to_string()));
+    q.convert("g", &converter).unwrap();
+    assert_eq!(q.to_string(), "1000 g");
+}
+
+#[test]
+fn quantity_or_default() {
+    use cooklang::{MissingQuantityPolicy, Quantity};
+
+    let recipe = cooklang::parse("Add @salt{} and @flour{200%g}.")
+        .unwrap_output()
+        .default_scale();
+    let salt = &recipe.ingredients[0];
+    let flour = &recipe.ingredients[1];
+    assert!(salt.quantity.is_none());
+
+    let policy = MissingQuantityPolicy::default();
+    assert_eq!(
+        *salt.quantity_or_default(&policy),
+        Quantity::new(Value::from(1.0), None)
+    );
+    assert_eq!(
+        flour.quantity_or_default(&policy).as_ref(),
+        flour.quantity.as_ref().unwrap()
+    );
+
+    let policy = MissingQuantityPolicy::Text("to taste".to_string());
+    assert_eq!(salt.quantity_or_default(&policy).to_string(), "to taste");
+    assert_eq!(flour.quantity_or_default(&policy).to_string(), "200 g");
+
+    let pinch = Quantity::new(Value::from(1.0), Some("pinch".to_string()));
+    let policy = MissingQuantityPolicy::Quantity(pinch.clone());
+    assert_eq!(*salt.quantity_or_default(&policy), pinch);
+}
+
+#[test]
+fn text_value_whitespace() {
+    let input = "Add @salt{ a  pinch } and @pepper{to taste}.";
+    let text = |options: ParseOptions| {
+        let parser = CooklangParser::default();
+        let r = parser
+            .parse_with_options(input, options)
+            .unwrap_output()
+            .default_scale();
+        r.ingredients
+            .iter()
+            .map(|i| i.quantity.as_ref().unwrap().value.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(text(ParseOptions::default()), ["a  pinch", "to taste"]);
+    assert_eq!(
+        text(ParseOptions {
+            keep_text_whitespace: true,
+            ..Default::default()
+        }),
+        [" a  pinch ", "to taste"]
+    );
+    assert_eq!(
+        text(ParseOptions {
+            collapse_text_whitespace: true,
+            ..Default::default()
+        }),
+        ["a pinch", "to taste"]
+    );
+}
+
+#[test]
+fn comment_notes() {
+    let input = indoc! {"
+        -- Family recipe, from grandma
+        >> servings: 2
+        Mix @flour{200%g}.
+        [- rest overnight -]
+
+        Bake it. -- not a note
+    "};
+    let contents = |options: ParseOptions| {
+        let parser = CooklangParser::default();
+        let r = parser.parse_with_options(input, options).unwrap_output();
+        r.sections[0]
+            .content
+            .iter()
+            .map(|c| match c {
+                Content::Step(s) => format!("step {}", s.number),
+                Content::Text(t) => t.clone(),
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(contents(ParseOptions::default()), ["step 1", "step 2"]);
+    assert_eq!(
+        contents(ParseOptions {
+            comment_notes: true,
+            ..Default::default()
+        }),
+        [
+            "Family recipe, from grandma",
+            "step 1",
+            "rest overnight",
+            "step 2"
+        ]
+    );
+}
+
+#[test]
+fn zero_quantity_warnings() {
+    let input = "Add @salt{0%g} and @sugar{0.5%g}.";
+    let warnings = |options: ParseOptions| {
+        let parser = CooklangParser::default();
+        let r = parser.parse_with_options(input, options);
+        r.report()
+            .warnings()
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert!(warnings(ParseOptions::default()).is_empty());
+    assert_eq!(
+        warnings(ParseOptions {
+            warn_zero_quantities: true,
+            ..Default::default()
+        }),
+        ["Quantity value is zero"]
+    );
+}
+
+#[test]
+fn negative_quantities() {
+    let input = "Use @salt{-5%g} less.";
+    let parse = |options: ParseOptions| {
+        let parser = CooklangParser::default();
+        let r = parser.parse_with_options(input, options);
+        let warnings = r.report().warnings().count();
+        let r = r.unwrap_output().default_scale();
+        (
+            r.ingredients[0].quantity.as_ref().unwrap().to_string(),
+            warnings,
+        )
+    };
+    assert_eq!(parse(ParseOptions::default()), ("-5 g".to_string(), 1));
+    assert_eq!(
+        parse(ParseOptions {
+            delta_quantities: true,
+            ..Default::default()
+        }),
+        ("-5 g".to_string(), 0)
+    );
+}
+
+#[test]
+fn reference_fragments() {
+    let input = "Add @@./sauce#tomato{100%g}, @@./sauce{} and @@sauces/pesto#basil{}.";
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let recipe = parser.parse(input).unwrap_output();
+    let refs = recipe
+        .ingredients
+        .iter()
+        .map(|i| (i.name.as_str(), i.reference_fragment.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        refs,
+        [
+            ("./sauce", Some("tomato")),
+            ("./sauce", None),
+            ("sauces/pesto", Some("basil"))
+        ]
+    );
+
+    let parser = CooklangParser::new(
+        Extensions::all() ^ Extensions::REFERENCE_FRAGMENTS,
+        Default::default(),
+    );
+    let recipe = parser.parse("Add @@./sauce{}.").unwrap_output();
+    assert_eq!(recipe.ingredients[0].name, "./sauce");
+    assert!(recipe.ingredients[0].reference_fragment.is_none());
+}
+
+#[test]
+fn preparations() {
+    use cooklang::ingredient_list::IngredientList;
+
+    let input =
+        "Add @onion, chopped{1}(from the garden), @onion, sliced{2} and @garlic{1}(minced).";
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let recipe = parser.parse(input).unwrap_output();
+    let igrs = recipe
+        .ingredients
+        .iter()
+        .map(|i| (i.name.as_str(), i.preparation.as_deref(), i.note.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        igrs,
+        [
+            ("onion", Some("chopped"), Some("from the garden")),
+            ("onion", Some("sliced"), None),
+            ("garlic", None, Some("minced")),
+        ]
+    );
+
+    let list = IngredientList::from_recipe(&recipe.default_scale(), parser.converter());
+    let totals = list
+        .iter()
+        .map(|(name, q)| (name.as_str(), q.to_string()))
+        .collect::<Vec<_>>();
+    let totals = totals
+        .iter()
+        .map(|(name, q)| (*name, q.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(totals, [("garlic", "1"), ("onion", "3")]);
+
+    // not enabled by default
+    let parser = CooklangParser::default();
+    let recipe = parser.parse("Add @onion, chopped{1}.").unwrap_output();
+    assert_eq!(recipe.ingredients[0].name, "onion, chopped");
+    assert!(recipe.ingredients[0].preparation.is_none());
+}
+
+#[test]
+fn referenced_recipes() {
+    let input = indoc! {r#"
+        Make the @@./Pizza Dough{1} and the @@sauces/Tomato Sauce{200%ml}.
+        Spread the @&@sauces/Tomato Sauce{} over the @dough{}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let recipe = parser.parse(input).unwrap_output();
+    let refs = recipe.referenced_recipes().collect::<Vec<_>>();
+    assert_eq!(refs, ["./Pizza Dough", "sauces/Tomato Sauce"]);
+}
+
+#[test]
+fn mise_en_place() {
+    let input = indoc! {r#"
+        Mix @flour{200%g} with @water{100%ml} and more @&flour{10%g}.
+
+        > Some text
+
+        Cover with a #towel and wait.
+
+        Knead with the @&water{}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let recipe = parser.parse(input).unwrap_output();
+    let mep = recipe.mise_en_place();
+    let steps = mep
+        .iter()
+        .map(|(step, igrs)| {
+            let names = igrs.iter().map(|i| i.name.as_str()).collect::<Vec<_>>();
+            (step.content, names)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        steps,
+        [(0, vec!["flour", "water"]), (2, vec![]), (3, vec!["water"])]
+    );
+    // the reference resolves to the definition
+    assert!(std::ptr::eq(mep[2].1[0], &recipe.ingredients[1]));
+}
+
+#[test]
+fn metadata_comments() {
+    let input = indoc! {r#"
+        >> servings: 4 -- two big pizzas
+        >> source: Grandma
+        >> tags: quick --
+        Bake.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+
+    let r = parser.parse(input).unwrap_output();
+    let entry = r.metadata.entry("servings").unwrap();
+    assert_eq!(entry.value, "4");
+    assert_eq!(entry.comment, None);
+
+    let options = ParseOptions {
+        metadata_comments: true,
+        ..Default::default()
+    };
+    let r = parser.parse_with_options(input, options).unwrap_output();
+    let comments = r
+        .metadata
+        .entries()
+        .map(|(key, entry)| (key, entry.value, entry.comment))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        comments,
+        [
+            ("servings", "4", Some("two big pizzas")),
+            ("source", "Grandma", None),
+            ("tags", "quick", None),
+        ]
+    );
+    assert_eq!(r.metadata.servings(), Some([4].as_slice()));
+}
+
+#[test]
+fn tokenizer() {
+    use cooklang::{tokens, TokenKind as K};
+
+    let input = ">> tags: café\nAdd @sal{1½%g} -- ok";
+    let tokens = tokens(input)
+        .map(|t| (t.kind, &input[t.span.range()]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tokens,
+        [
+            (K::MetadataStart, ">>"),
+            (K::Whitespace, " "),
+            (K::Word, "tags"),
+            (K::Colon, ":"),
+            (K::Whitespace, " "),
+            (K::Word, "café"),
+            (K::Newline, "\n"),
+            (K::Word, "Add"),
+            (K::Whitespace, " "),
+            (K::At, "@"),
+            (K::Word, "sal"),
+            (K::OpenBrace, "{"),
+            (K::Int, "1"),
+            (K::Word, "½"),
+            (K::Percent, "%"),
+            (K::Word, "g"),
+            (K::CloseBrace, "}"),
+            (K::Whitespace, " "),
+            (K::LineComment, "-- ok"),
+        ]
+    );
+
+    // spans cover the whole input
+    let mut end = 0;
+    for token in cooklang::tokens(input) {
+        assert_eq!(token.span.start(), end);
+        end = token.span.end();
+    }
+    assert_eq!(end, input.len());
+}
+
+#[test]
+fn conversion_cache() {
+    use cooklang::convert::ConversionCache;
+
+    let units = [
+        "g", "kg", "oz", "lb", "ml", "l", "cup", "tsp", "tbsp", "pinch",
+    ];
+    let mut input = String::from("Add @flour{1%cup} @milk{200%ml} @salt{1%pinch} @eggs{2}.\n");
+    for i in 0..200 {
+        let unit = units[i % units.len()];
+        let igr = ["flour", "milk", "salt"][i % 3];
+        input.push_str(&format!("Add @&{igr}{{{}.5%{unit}}}.\n", i % 7));
+        if i % 10 == 0 {
+            input.push_str("More @&eggs{1/3}.\n");
+        }
+    }
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let converter = parser.converter();
+    let recipe = parser.parse(&input).unwrap_output().default_scale();
+
+    let mut cache = ConversionCache::new();
+    for _ in 0..2 {
+        for igr in recipe
+            .ingredients
+            .iter()
+            .filter(|i| i.relation.is_definition())
+        {
+            let plain = igr.group_quantities(&recipe.ingredients, converter);
+            let cached = igr.group_quantities_cached(&recipe.ingredients, converter, &mut cache);
+            assert_eq!(plain.to_string(), cached.to_string());
+            assert_eq!(plain.into_vec(), cached.into_vec());
+        }
+    }
+}
+
+#[test]
+fn per_unit_quantities() {
+    let input = indoc! {r#"
+        >> servings: 2
+        Add @eggs{2 x 50*%g} and @tomatoes{1-2 x 200*%g / 7%oz}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input).unwrap_output();
+
+    let default = r.clone().default_scale();
+    let eggs = &default.ingredients[0];
+    assert_eq!(eggs.quantity.as_ref().unwrap().to_string(), "100 g");
+    let per_unit = eggs.per_unit.as_ref().unwrap();
+    assert_eq!(per_unit.count, Value::from(2.0));
+    assert_eq!(per_unit.per_unit.to_string(), "50 g");
+    assert_eq!(per_unit.total().unwrap().to_string(), "100 g");
+    let tomatoes = &default.ingredients[1];
+    assert_eq!(tomatoes.quantity.as_ref().unwrap().to_string(), "200-400 g");
+    assert_eq!(tomatoes.alternative_quantities[0].to_string(), "7-14 oz");
+
+    // the count scales, each unit stays the same
+    let scaled = r.scale(4, parser.converter());
+    let eggs = &scaled.ingredients[0];
+    let per_unit = eggs.per_unit.as_ref().unwrap();
+    assert_eq!(per_unit.to_string(), "4 x 50 g");
+    assert_eq!(eggs.quantity.as_ref().unwrap().to_string(), "200 g");
+
+    let tomatoes = &scaled.ingredients[1];
+    assert_eq!(
+        tomatoes.per_unit.as_ref().unwrap().to_string(),
+        "2-4 x 200 g"
+    );
+    assert_eq!(tomatoes.quantity.as_ref().unwrap().to_string(), "400-800 g");
+
+    // without the extension it's just text
+    let parser = CooklangParser::new(
+        Extensions::all() ^ Extensions::PER_UNIT_QUANTITIES,
+        Default::default(),
+    );
+    let r = parser.parse("@eggs{2 x 50%g}").unwrap_output();
+    assert!(r.ingredients[0].per_unit.is_none());
+    assert!(matches!(
+        r.ingredients[0].quantity.as_ref().unwrap().value,
+        cooklang::ScalableValue::Fixed(Value::Text(_))
+    ));
+}
+
+#[test]
+fn merge_recipes() {
+    use cooklang::{metadata::MetadataConflict, model::IngredientReferenceTarget, ScalableValue};
+
+    let dinner = indoc! {r#"
+        >> servings: 2
+        >> title: Dinner
+        = Pasta
+        Boil @pasta{200*%g} in a #pot for ~{10%min}.
+
+        Drain the @&pasta{} in the #&pot{}.
+    "#};
+    let dessert = indoc! {r#"
+        >> servings: 4
+        >> title: Dessert
+        >> source: grandma
+        = Cream
+        Whip @cream{200*%ml} in a #bowl{} for ~{2%min}.
+        = Serve
+        Top with the @&(=Cream)whipped cream{} and @sugar{1%tbsp}.
+
+        Add more @&cream{}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let dinner = parser.parse(dinner).unwrap_output();
+    let dessert = parser.parse(dessert).unwrap_output();
+
+    let mut merged = dinner.clone();
+    merged
+        .merge(dessert.clone(), MetadataConflict::Keep)
+        .unwrap();
+    assert!(merged.validate(parser.converter()).is_empty());
+
+    let s = dinner.sections.len();
+    let names = |s: usize, c: usize| {
+        merged.sections[s].content[c]
+            .unwrap_step()
+            .ingredient_indices()
+            .map(|i| merged.ingredients[i].name.as_str())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(merged.sections.len(), s + dessert.sections.len());
+    assert_eq!(names(s - 1, 1), ["pasta"]);
+    assert_eq!(names(s, 0), ["cream"]);
+    assert_eq!(names(s + 1, 0), ["whipped cream", "sugar"]);
+    assert_eq!(names(s + 1, 1), ["cream"]);
+
+    let cream = &merged.ingredients[2];
+    assert_eq!(cream.relation.referenced_from(), [5]);
+    assert_eq!(
+        merged.ingredients[5].relation.references_to(),
+        Some((2, IngredientReferenceTarget::Ingredient))
+    );
+    let (section, target) = dessert.ingredients[1].relation.references_to().unwrap();
+    assert_eq!(target, IngredientReferenceTarget::Section);
+    assert_eq!(
+        merged.ingredients[3].relation.references_to(),
+        Some((s + section, IngredientReferenceTarget::Section))
+    );
+
+    let step = merged.sections[s].content[0].unwrap_step();
+    let bowl = step.cookware_indices().next().unwrap();
+    assert_eq!(merged.cookware[bowl].name, "bowl");
+    let timer = step.timer_indices().next().unwrap();
+    assert_eq!(timer, 1);
+    assert_eq!(merged.cookware[0].relation.referenced_from(), [1]);
+
+    assert_eq!(merged.metadata.map["title"], "Dinner");
+    assert_eq!(merged.metadata.map["source"], "grandma");
+    assert_eq!(merged.metadata.servings(), Some([2].as_slice()));
+
+    // the dessert was for 4 servings, now it's for 2
+    let cream = merged.ingredients[2].quantity.as_ref().unwrap();
+    assert_eq!(cream.value, ScalableValue::Linear(Value::from(100.0)));
+    let pasta = merged.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(pasta.value, ScalableValue::Linear(Value::from(200.0)));
+
+    let mut merged = dinner.clone();
+    merged
+        .merge(dessert.clone(), MetadataConflict::Replace)
+        .unwrap();
+    assert_eq!(merged.metadata.map["title"], "Dessert");
+    assert_eq!(merged.metadata.servings(), Some([4].as_slice()));
+    let pasta = merged.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(pasta.value, ScalableValue::Linear(Value::from(400.0)));
+    let keys = merged.metadata.map.keys().collect::<Vec<_>>();
+    assert_eq!(keys, ["servings", "title", "source"]);
+
+    // values by servings need the merged servings
+    let pizza = parser
+        .parse(">> servings: 2|4\nKnead @flour{200|400%g}.")
+        .unwrap_output();
+    let mut merged = dinner.clone();
+    merged.merge(pizza.clone(), MetadataConflict::Keep).unwrap();
+    let flour = merged
+        .ingredients
+        .last()
+        .unwrap()
+        .quantity
+        .as_ref()
+        .unwrap();
+    assert_eq!(
+        flour.value,
+        ScalableValue::ByServings(vec![Value::from(200.0)])
+    );
+    let mut merged = parser.parse(">> servings: 3\nServe.").unwrap_output();
+    let before = merged.clone();
+    assert!(merged.merge(pizza, MetadataConflict::Keep).is_err());
+    assert_eq!(merged, before);
+}
+
+#[test]
+fn count_quantities() {
+    let input = indoc! {r#"
+        Beat @eggs{3} with @sugar{100%g}.
+        Add @&eggs{1} and @&eggs{50%g} more, then @&sugar{2}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input).unwrap_output().default_scale();
+
+    let eggs = &r.ingredients[0];
+    assert!(eggs.quantity.as_ref().unwrap().is_count());
+    let grouped = eggs.group_quantities(&r.ingredients, parser.converter());
+    assert_eq!(grouped.count().unwrap().value, Value::from(4.0));
+    let measured = grouped
+        .measured()
+        .map(|q| q.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(measured, ["50 g"]);
+
+    let sugar = &r.ingredients[1];
+    assert!(!sugar.quantity.as_ref().unwrap().is_count());
+    let grouped = sugar.group_quantities(&r.ingredients, parser.converter());
+    assert_eq!(grouped.count().unwrap().to_string(), "2");
+    let measured = grouped
+        .measured()
+        .map(|q| q.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(measured, ["100 g"]);
+    assert_eq!(grouped.len(), 2);
+}
+
+#[test]
+fn unique_ingredients() {
+    let input = indoc! {r#"
+        Mix @flour{100%g}, @sugar{50%g} and @eggs{2}.
+
+        Add @&sugar{} then @butter{}, @&flour{20%g} and @salt{}.
+
+        Rest the @&(~1)dough{}, then top with @&butter{} and @&eggs{}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input).unwrap_output();
+    assert_eq!(r.ingredients.len(), 10);
+
+    let unique = r.unique_ingredients();
+    let names = unique.iter().map(|i| i.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["flour", "sugar", "eggs", "butter", "salt"]);
+    assert!(unique.iter().all(|i| i.relation.is_definition()));
+}
+
+#[test]
+fn temperature_scales() {
+    use cooklang::convert::{
+        units_file::{UnitEntry, UnitPoints},
+        ConvertTo, ConvertValue, ConverterBuilder, ConverterBuilderError, System,
+    };
+
+    let entry = |name: &str, symbol: &str, points| UnitEntry {
+        names: vec![name.into()],
+        symbols: vec![symbol.into()],
+        aliases: vec![],
+        ratio: 1.0,
+        difference: 0.0,
+        points,
+        expand_si: false,
+    };
+    let gas_mark = |values: &[(f64, f64)]| UnitPoints {
+        unit: "C".into(),
+        values: values.to_vec(),
+    };
+    let marks = [
+        (1.0, 140.0),
+        (2.0, 150.0),
+        (3.0, 170.0),
+        (4.0, 180.0),
+        (5.0, 190.0),
+        (6.0, 200.0),
+        (7.0, 220.0),
+        (8.0, 230.0),
+        (9.0, 240.0),
+    ];
+    let converter = ConverterBuilder::new()
+        .with_bundled_units()
+        .unwrap()
+        .with_temperature_scale(entry("kelvin", "K", None), Some(System::Metric))
+        .unwrap()
+        .with_temperature_scale(entry("gas mark", "gas", Some(gas_mark(&marks))), None)
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    let convert = |value: f64, from: &str, to: &str| {
+        let (value, unit) = converter
+            .convert(
+                ConvertValue::Number(value),
+                from.into(),
+                ConvertTo::from(to),
+            )
+            .unwrap();
+        // the bundled fahrenheit symbol is `°F`, `F` is an alias
+        assert_eq!(unit, converter.find_unit(to).unwrap());
+        let ConvertValue::Number(n) = value else {
+            panic!("not a number");
+        };
+        n
+    };
+    let assert_close = |a: f64, b: f64| assert!((a - b).abs() < 1e-6, "{a} != {b}");
+
+    assert_close(convert(100.0, "C", "F"), 212.0);
+    assert_close(convert(0.0, "C", "K"), 273.15);
+    assert_close(convert(212.0, "F", "K"), 373.15);
+    assert_close(convert(300.0, "K", "C"), 26.85);
+    assert_close(convert(4.0, "gas", "C"), 180.0);
+    assert_close(convert(2.5, "gas", "C"), 160.0);
+    assert_close(convert(170.0, "C", "gas"), 3.0);
+    assert_close(convert(356.0, "F", "gas"), 4.0);
+    // outside the table the first and last steps are extended
+    assert_close(convert(0.5, "gas", "C"), 135.0);
+    assert_close(convert(10.0, "gas", "C"), 250.0);
+
+    let build = |points| {
+        ConverterBuilder::new()
+            .with_bundled_units()
+            .unwrap()
+            .with_temperature_scale(entry("gas mark", "gas", Some(points)), None)
+            .unwrap()
+            .finish()
+    };
+    assert!(matches!(
+        build(gas_mark(&[(1.0, 140.0), (2.0, 130.0)])),
+        Err(ConverterBuilderError::InvalidPoints { .. })
+    ));
+    assert!(matches!(
+        build(gas_mark(&[(1.0, 140.0)])),
+        Err(ConverterBuilderError::InvalidPoints { .. })
+    ));
+    let unknown = UnitPoints {
+        unit: "R".into(),
+        values: marks.to_vec(),
+    };
+    assert!(matches!(
+        build(unknown),
+        Err(ConverterBuilderError::UnknownUnit(_))
+    ));
+}
+
+#[test]
+fn section_metadata() {
+    let input = indoc! {r#"
+        >> servings: 4
+        >> time: 1 hour
+        = Dough
+        >> time: 20 min
+        >> difficulty: easy
+        Knead the @flour{500%g}.
+        = Sauce
+        >> difficulty: hard
+        >> difficulty: very hard
+        >> time: 10 min
+        >> time: 15 min
+        Stir the @tomatoes{400%g}.
+        = Serve
+        Plate it.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let options = ParseOptions {
+        section_metadata: true,
+        ..Default::default()
+    };
+    let r = parser.parse_with_options(input, options);
+    // only special keys warn, and only when repeated in the same section
+    assert_eq!(r.report().warnings().count(), 1);
+    let r = r.unwrap_output();
+
+    assert_eq!(r.metadata.map.len(), 2);
+    assert_eq!(r.metadata.map["time"], "1 hour");
+    assert_eq!(r.metadata.servings(), Some([4].as_slice()));
+
+    let dough = &r.sections[0].metadata;
+    assert_eq!(dough.map["difficulty"], "easy");
+    assert_eq!(dough.time().unwrap().total(), 20);
+    assert_eq!(r.section_metadata(0, "time"), Some("20 min"));
+    assert_eq!(r.section_metadata(1, "time"), Some("15 min"));
+    assert_eq!(r.section_metadata(1, "difficulty"), Some("very hard"));
+    assert_eq!(r.section_metadata(2, "time"), Some("1 hour"));
+    assert_eq!(r.section_metadata(2, "difficulty"), None);
+    assert!(r.sections[2].metadata.map.is_empty());
+
+    // without the option everything is recipe metadata
+    let r = parser.parse(input).unwrap_output();
+    assert_eq!(r.metadata.map["time"], "15 min");
+    assert_eq!(r.metadata.map["difficulty"], "very hard");
+    assert!(r.sections.iter().all(|s| s.metadata.map.is_empty()));
+}
+
+#[test]
+fn parse_many() {
+    let input = indoc! {r#"
+        >> title: Toast
+        Toast the @bread{2}.
+        ===
+        Mix @flour{200%g} and @&(?)water{}.
+        ===
+
+        ===
+        >> title: Tea
+        Boil @water{} and add @tea{1%bag}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let results = parser.parse_many(input, "===").collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+
+    let toast = results[0].valid_output().unwrap();
+    assert_eq!(toast.metadata.map["title"], "Toast");
+    assert_eq!(toast.ingredients.len(), 1);
+
+    // errors only in its own recipe, located in the whole input
+    assert!(results[1].report().has_errors());
+    let err = results[1].report().errors().next().unwrap();
+    let second_start = input.find("Mix").unwrap();
+    let second_end = input[second_start..].find("===").unwrap() + second_start;
+    assert!((second_start..second_end).contains(&err.labels[0].0.start()));
+
+    let tea = results[2].valid_output().unwrap();
+    assert_eq!(tea.metadata.map["title"], "Tea");
+    let names = tea.ingredients.iter().map(|i| &i.name).collect::<Vec<_>>();
+    assert_eq!(names, ["water", "tea"]);
+    #[cfg(feature = "spans")]
+    assert_eq!(&input[tea.ingredients[1].span.range()], "@tea{1%bag}");
+}
+
+#[test]
+fn inline_unit_aliases() {
+    use cooklang::quantity::UnitInfo;
+
+    let input = indoc! {r#"
+        >> units: T=tbsp, grs=g
+        Add @sugar{2%T} and @oil{1-2%T}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input);
+    assert!(r.report().is_empty());
+    let r = r.unwrap_output();
+    assert_eq!(r.metadata.unit_aliases().unwrap().len(), 2);
+
+    for igr in &r.ingredients {
+        let unit = igr.quantity.as_ref().unwrap().unit().unwrap();
+        assert_eq!(unit.text(), "T");
+        let Some(UnitInfo::Known(known)) = unit.unit_info() else {
+            panic!("alias not resolved");
+        };
+        assert_eq!(known.symbol(), "tbsp");
+    }
+
+    // the alias is only for that recipe
+    let r = parser.parse("Add @sugar{2%T}.").unwrap_output();
+    let unit = r.ingredients[0].quantity.as_ref().unwrap().unit().unwrap();
+    assert!(matches!(
+        unit.unit_info_or_parse(parser.converter()),
+        UnitInfo::Unknown
+    ));
+
+    // other values are regular entries
+    let r = parser.parse(">> units: metric\nAdd @sugar{2%g}.");
+    assert!(r.report().is_empty());
+    let r = r.unwrap_output();
+    assert_eq!(r.metadata.get("units"), Some("metric"));
+    assert!(r.metadata.unit_aliases().is_none());
+}
+
+#[test]
+fn skip_empty_steps() {
+    // the middle step is only an escaped space
+    let input = "First @step{}.\n\n\n\\ \n\nSecond step.\n";
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+
+    let numbers = |options: ParseOptions| {
+        let r = parser.parse_with_options(input, options).unwrap_output();
+        r.sections[0]
+            .content
+            .iter()
+            .map(|c| {
+                let step = c.unwrap_step();
+                (step.number, step.is_empty())
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        numbers(ParseOptions::default()),
+        [(1, false), (2, true), (3, false)]
+    );
+    let options = ParseOptions {
+        skip_empty_steps: true,
+        ..Default::default()
+    };
+    assert_eq!(numbers(options), [(1, false), (2, false)]);
+}
+
+#[test_case(false => (None, false, "300 g".to_string()) ; "additive")]
+#[test_case(true => (Some("200 g".to_string()), true, "300 g".to_string()) ; "inherit")]
+fn inherit_reference_quantities(inherit: bool) -> (Option<String>, bool, String) {
+    let input = "Add @flour{200%g}, then @&flour{100%g} more. Sift the @&flour{}.";
+    let parser = CooklangParser::new(Extensions::all(), cooklang::Converter::bundled());
+    let options = ParseOptions {
+        inherit_reference_quantities: inherit,
+        ..Default::default()
+    };
+    let r = parser
+        .parse_with_options(input, options)
+        .unwrap_output()
+        .default_scale();
+    assert_eq!(r.ingredients[0].all_quantities(&r.ingredients).count(), 2);
+    let sift = &r.ingredients[2];
+    let total = r.ingredients[0].group_quantities(&r.ingredients, parser.converter());
+    (
+        sift.quantity.as_ref().map(|q| q.to_string()),
+        sift.quantity_inherited,
+        total.to_string(),
+    )
+}
+
+#[test]
+fn named_amounts() {
+    use cooklang::convert::{
+        units_file::NamedAmount, ConverterBuilder, ConverterBuilderError, UnitsFile,
+    };
+    use cooklang::quantity::UnitInfo;
+
+    let converter = cooklang::Converter::bundled();
+    let pinch = converter.named_amount("pinch").unwrap();
+    assert_eq!(pinch.value, Value::from(0.5));
+    assert!(matches!(
+        pinch.unit().unwrap().unit_info(),
+        Some(UnitInfo::Known(u)) if u.symbol() == "g"
+    ));
+    assert_eq!(
+        converter.named_amount("  A Dash ").unwrap().to_string(),
+        "0.6 ml"
+    );
+    assert!(converter.named_amount("to taste").is_none());
+
+    // the text value is kept
+    let r = CooklangParser::new(Extensions::all(), converter.clone())
+        .parse("Add @salt{a pinch}.")
+        .unwrap_output();
+    let q = r.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(q.value.to_string(), "a pinch");
+    assert!(converter.named_amount(&q.value.to_string()).is_some());
+
+    let mut units = UnitsFile::bundled();
+    units.named_amounts.insert(
+        "pinch".into(),
+        NamedAmount {
+            value: 1.0,
+            unit: "tsp".into(),
+        },
+    );
+    let custom = ConverterBuilder::new()
+        .with_units_file(units.clone())
+        .unwrap()
+        .finish()
+        .unwrap();
+    assert_eq!(custom.named_amount("pinch").unwrap().to_string(), "1 tsp");
+
+    units.named_amounts.insert(
+        "handful".into(),
+        NamedAmount {
+            value: 1.0,
+            unit: "hand".into(),
+        },
+    );
+    let err = ConverterBuilder::new()
+        .with_units_file(units)
+        .unwrap()
+        .finish()
+        .unwrap_err();
+    assert!(matches!(err, ConverterBuilderError::UnknownUnit(_)));
+}
+
+#[test]
+fn optional_section() {
+    use cooklang::ingredient_list::IngredientList;
+
+    let input = indoc! {r#"
+        = Pasta
+        Boil @pasta{200%g} in @water{2%l} with @salt{10%g}.
+        = ?Garnish
+        Top with @parsley{5%g} and @&salt{2%g}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input).unwrap_output();
+    assert!(!r.sections[0].optional);
+    assert!(r.sections[1].optional);
+    assert_eq!(r.sections[1].name.as_deref(), Some("Garnish"));
+
+    let r = r.default_scale();
+    let list = IngredientList::from_recipe(&r, parser.converter());
+    let totals = list
+        .iter()
+        .map(|(name, q)| (name.as_str(), q.to_string()))
+        .collect::<Vec<_>>();
+    let totals = totals
+        .iter()
+        .map(|(name, q)| (*name, q.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        totals,
+        [("pasta", "200 g"), ("salt", "10 g"), ("water", "2 l")]
+    );
+}
+
+#[test]
+fn timers_total_time() {
+    use cooklang::model::TimerDurationError;
+    use std::time::Duration;
+
+    let parser = CooklangParser::default();
+    let r = parser
+        .parse("Bake ~{20%min}, rest ~{1-2%h} and stir ~{30%s}.")
+        .unwrap_output()
+        .default_scale();
+    let expected = Duration::from_secs(20 * 60 + 2 * 3600 + 30);
+    assert_eq!(
+        r.timers_duration(parser.converter(), false).unwrap(),
+        expected
+    );
+    assert_eq!(r.total_time(parser.converter()), Some(expected));
+
+    // time metadata is added
+    let r = parser
+        .parse(">> time: 10 min\nBake ~{20%min}.")
+        .unwrap_output()
+        .default_scale();
+    assert_eq!(
+        r.total_time(parser.converter()),
+        Some(Duration::from_secs(30 * 60))
+    );
+
+    let r = parser.parse("No timers.").unwrap_output().default_scale();
+    assert_eq!(r.total_time(parser.converter()), None);
+
+    // timers without time units are only allowed without this extension
+    let parser = CooklangParser::new(
+        Extensions::all() - Extensions::ADVANCED_UNITS,
+        Default::default(),
+    );
+    let r = parser
+        .parse("Wait ~{2%cups}, then ~{10%min} and ~{a bit%min}.")
+        .unwrap_output()
+        .default_scale();
+    assert!(matches!(
+        r.timers_duration(parser.converter(), false),
+        Err(TimerDurationError::InvalidUnit { index: 0, .. })
+    ));
+    assert_eq!(
+        r.timers_duration(parser.converter(), true).unwrap(),
+        Duration::from_secs(10 * 60)
+    );
+    assert_eq!(
+        r.total_time(parser.converter()),
+        Some(Duration::from_secs(10 * 60))
+    );
+
+    // durations that don't fit are invalid, not a panic
+    let r = CooklangParser::default()
+        .parse("Wait ~{99999999999999999999999999%h} and ~{10%min}.")
+        .unwrap_output()
+        .default_scale();
+    assert!(matches!(
+        r.timers_duration(parser.converter(), false),
+        Err(TimerDurationError::InvalidDuration { index: 0, .. })
+    ));
+    assert_eq!(
+        r.total_time(parser.converter()),
+        Some(Duration::from_secs(10 * 60))
+    );
+}
+
+#[test]
+fn recipe_view() {
+    use cooklang::RecipeRef;
+
+    let input = indoc! {r#"
+        = Dough
+        Mix @flour{500%g} and @water{300%ml} in a #bowl{}.
+        > Let it rest.
+        = Bake
+        Bake for ~{30%min}.
+    "#};
+    let recipe = CooklangParser::default().parse(input).unwrap_output();
+
+    // the view only borrows the recipe
+    let view = recipe.view();
+    let same = RecipeRef::from(&recipe);
+    assert!(std::ptr::eq(
+        view.ingredients,
+        recipe.ingredients.as_slice()
+    ));
+    assert!(std::ptr::eq(same.sections, view.sections));
+
+    let names = view
+        .ingredients
+        .iter()
+        .map(|i| i.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["flour", "water"]);
+    assert_eq!(view.cookware[0].name, "bowl");
+    assert_eq!(view.timers.len(), 1);
+
+    let steps = view
+        .steps()
+        .map(|(at, step)| (at.section, at.content, step.number))
+        .collect::<Vec<_>>();
+    assert_eq!(steps, [(0, 0, 1), (1, 0, 1)]);
+}
+
+#[test]
+fn iso_duration_timers() {
+    let input = "Rest ~{PT30M}, bake ~{PT1H30M} and cool ~{PT1M30S}.";
+    let r = CooklangParser::default().parse(input).unwrap_output();
+    let timers = r
+        .timers
+        .iter()
+        .map(|t| t.quantity.as_ref().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(timers, ["30 min", "90 min", "90 s"]);
+
+    let r = CooklangParser::new(
+        Extensions::all() ^ Extensions::ISO_DURATIONS,
+        Default::default(),
+    )
+    .parse(input);
+    assert!(r.report().has_errors());
+}
+
+#[test]
+fn convert_recipe_partially() {
+    use cooklang::convert::System;
+
+    let input = "Add @flour{1%lb}, @salt{1%pinch}, @eggs{2} and @milk{1%cup}. Bake ~{1%hour}.";
+    let parser = CooklangParser::default();
+    let r = parser.parse(input).unwrap_output().default_scale();
+    let (r, warnings) = r.convert(System::Metric, parser.converter());
+
+    let units = r
+        .ingredients
+        .iter()
+        .map(|i| i.quantity.as_ref().unwrap().unit_text())
+        .collect::<Vec<_>>();
+    assert_eq!(units, [Some("g"), Some("pinch"), None, Some("ml")]);
+
+    let messages = warnings
+        .iter()
+        .map(|w| w.message.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "Cannot convert ingredient 'salt': Unknown unit: 'pinch'",
+            "Cannot convert ingredient 'eggs': Tried to convert a value with no unit",
+        ]
+    );
+    assert!(warnings.iter().all(|w| w.is_warning()));
+}
+
+#[test]
+fn metadata_unit_system() {
+    use cooklang::convert::System;
+
+    let parser = CooklangParser::default();
+    let converted_unit = |input: &str, to: Option<System>| {
+        let r = parser.parse(input).unwrap_output().default_scale();
+        let (r, warnings) = r.convert(to, parser.converter());
+        assert!(warnings.is_empty());
+        let q = r.ingredients[0].quantity.as_ref().unwrap();
+        q.unit_text().unwrap().to_string()
+    };
+
+    let imperial = ">> system: imperial\nAdd @flour{1%kg}.";
+    assert_eq!(
+        parser.parse(imperial).unwrap_output().metadata.system(),
+        Some(System::Imperial)
+    );
+    assert_eq!(converted_unit(imperial, None), "oz");
+    // an explicit system is used over the metadata
+    assert_eq!(converted_unit(imperial, Some(System::Metric)), "kg");
+
+    let metric = ">> system: metric\nAdd @milk{2%cups}.";
+    assert_eq!(converted_unit(metric, None), "ml");
+    assert_eq!(converted_unit(metric, Some(System::Imperial)), "c");
+}
+
+#[test]
+fn structurally_eq() {
+    use cooklang::model::ComponentRelation;
+
+    let input = "Fill a #pot{} with @water{}.\n\nBoil the #&pot{}.\n\nEmpty the #&pot{}.";
+    let parser = CooklangParser::default();
+    let a = parser.parse(input).unwrap_output();
+    let mut b = a.clone();
+    let ComponentRelation::Definition {
+        referenced_from, ..
+    } = &mut b.cookware[0].relation
+    else {
+        panic!("not a definition");
+    };
+    assert_eq!(referenced_from, &[1, 2]);
+    referenced_from.reverse();
+
+    assert_ne!(a, b);
+    assert!(a.structurally_eq(&b));
+    assert!(b.structurally_eq(&a));
+
+    let other = parser
+        .parse(&input.replace("water", "milk"))
+        .unwrap_output();
+    assert!(!a.structurally_eq(&other));
+}
+
+#[test]
+fn nutrition() {
+    let input = "Add @sugar{10%g}{cal=40, carbs=10} and @salt{}.";
+    let r = CooklangParser::default().parse(input).unwrap_output();
+    let sugar = r.ingredients[0].nutrition.as_ref().unwrap();
+    assert_eq!(sugar.len(), 2);
+    assert_eq!(sugar["cal"], Value::from(40.0));
+    assert_eq!(sugar["carbs"], Value::from(10.0));
+    assert!(r.ingredients[1].nutrition.is_none());
+
+    // it's kept after scaling
+    let r = r.default_scale();
+    assert_eq!(
+        r.ingredients[0].nutrition.as_ref().unwrap()["cal"],
+        Value::from(40.0)
+    );
+
+    // malformed facts are errors
+    let r = CooklangParser::default().parse("Add @sugar{10%g}{cal=a lot}.");
+    assert!(r.report().has_errors());
+
+    // without the extension it's just text
+    let r = CooklangParser::new(
+        Extensions::all() ^ Extensions::NUTRITION,
+        Default::default(),
+    )
+    .parse(input)
+    .unwrap_output();
+    assert!(r.ingredients[0].nutrition.is_none());
+}
+
+#[test]
+fn compatible_units() {
+    let converter = cooklang::Converter::bundled();
+    let units = converter.compatible_units("ml");
+    for unit in ["l", "tsp", "tbsp", "c", "gal"] {
+        assert!(units.contains(&unit), "missing {unit}");
+    }
+    assert!(!units.contains(&"ml"));
+    assert!(units.iter().all(|u| {
+        converter.find_unit(u).unwrap().physical_quantity
+            == cooklang::convert::PhysicalQuantity::Volume
+    }));
+
+    assert!(converter.compatible_units("g").contains(&"lb"));
+    assert!(converter.compatible_units("unknown").is_empty());
+}
+
+#[test]
+fn step_groups() {
+    let input = indoc! {"
+        Preheat the #oven{}.
+
+        Make the sauce:
+
+        Melt @butter{20%g}.
+
+        Add @flour{1%tbsp}.
+
+        Finish:
+
+        Serve.
+    "};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let r = parser.parse(input).unwrap_output();
+    let section = &r.sections[0];
+    assert_eq!(section.content.len(), 4);
+    assert_eq!(section.step_groups.len(), 2);
+    assert_eq!(section.step_groups[0].title, "Make the sauce");
+    assert_eq!(section.step_groups[0].steps, 1..3);
+    assert_eq!(section.step_groups[1].title, "Finish");
+    assert_eq!(section.step_groups[1].steps, 3..4);
+    assert!(section.step_group_of(0).is_none());
+    assert_eq!(section.step_group_of(2).unwrap().title, "Make the sauce");
+
+    // groups don't change the step numbers
+    let numbers = section
+        .content
+        .iter()
+        .filter_map(|c| match c {
+            Content::Step(s) => Some(s.number),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(numbers, [1, 2, 3, 4]);
+
+    // groups don't cross sections
+    let r = parser
+        .parse("Dough:\n\nKnead.\n\n= Topping\n\nSpread.")
+        .unwrap_output();
+    assert_eq!(r.sections[0].step_groups[0].steps, 0..1);
+    assert!(r.sections[1].step_groups.is_empty());
+
+    // without the extension the title is a regular step, it's opt-in
+    let r = CooklangParser::default().parse(input).unwrap_output();
+    assert!(r.sections[0].step_groups.is_empty());
+    assert_eq!(r.sections[0].content.len(), 6);
+}
+
+#[test]
+fn aggregate_ingredients() {
+    use cooklang::ingredient_list::aggregate_ingredients;
+
+    let parser = CooklangParser::default();
+    let bread = parser
+        .parse("Mix @flour{500%g} with @water{300%ml}.")
+        .unwrap_output()
+        .default_scale();
+    let pizza = parser
+        .parse("Knead @Flour{1%kg}, @water{600%ml} and @salt{10%g}.")
+        .unwrap_output()
+        .default_scale();
+
+    let totals = |case_sensitive| {
+        aggregate_ingredients(&[&bread, &pizza], parser.converter(), case_sensitive)
+            .into_iter()
+            .map(|(name, q)| (name, q.to_string()))
+            .collect::<Vec<_>>()
+    };
+    let s = |a: &str, b: &str| (a.to_string(), b.to_string());
+
+    assert_eq!(
+        totals(false),
+        [
+            s("flour", "1.5 kg"),
+            s("salt", "10 g"),
+            s("water", "900 ml")
+        ]
+    );
+    assert_eq!(
+        totals(true),
+        [
+            s("Flour", "1 kg"),
+            s("flour", "500 g"),
+            s("salt", "10 g"),
+            s("water", "900 ml")
+        ]
+    );
+}
+
+#[test]
+fn parser_config_flags() {
+    use cooklang::ParserConfig;
+
+    let input = "Make the sauce:\n\nMelt @butter{20%g}.";
+    let parse = |config: ParserConfig| {
+        CooklangParser::default()
+            .with_config(config)
+            .parse(input)
+            .unwrap_output()
+    };
+
+    let r = parse(ParserConfig::new());
+    assert!(r.sections[0].step_groups.is_empty());
+    assert_eq!(r.sections[0].content.len(), 2);
+
+    let r = parse(ParserConfig::new().with_flag("STEP_GROUPS", true));
+    assert_eq!(r.sections[0].step_groups.len(), 1);
+    assert_eq!(r.sections[0].content.len(), 1);
+
+    // unknown flags are kept but don't change anything
+    let config = ParserConfig::new().with_flag("my_plugin_syntax", true);
+    assert_eq!(config.flag("my_plugin_syntax"), Some(true));
+    assert_eq!(config.flag("other"), None);
+    assert_eq!(config.apply(Extensions::empty()), Extensions::empty());
+    assert_eq!(
+        parse(config),
+        CooklangParser::default().parse(input).unwrap_output()
+    );
+
+    // also for the pull parser
+    let config = ParserConfig::new().with_flag("sections", false);
+    let events = cooklang::parser::PullParser::new("= Section", Extensions::all())
+        .with_config(&config)
+        .collect::<Vec<_>>();
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, cooklang::parser::Event::Section { .. })));
+}
+
+#[test]
+fn canonicalize_names() {
+    use cooklang::{ingredient_list::IngredientList, model::normalize_name};
+
+    let input = indoc! {"
+        Cut @Tomatoes{2} and @tomato{1}.
+
+        Add @cherries{100%g}, @cherry{50%g} and @&Tomatoes{1}.
+    "};
+    let parser = CooklangParser::default();
+    let mut r = parser.parse(input).unwrap_output();
+    r.canonicalize_names(normalize_name);
+    let names = r
+        .ingredients
+        .iter()
+        .map(|i| i.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["tomato", "tomato", "cherry", "cherry", "tomato"]);
+    assert_eq!(r.ingredients[4].original_name.as_deref(), Some("Tomatoes"));
+
+    let list = IngredientList::from_recipe(&r.default_scale(), parser.converter());
+    let totals = list
+        .iter()
+        .map(|(name, q)| (name.as_str(), q.to_string()))
+        .collect::<Vec<_>>();
+    let totals = totals
+        .iter()
+        .map(|(name, q)| (*name, q.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(totals, [("cherry", "150 g"), ("tomato", "4")]);
+
+    // with a custom normalizer
+    let mut r = parser.parse(input).unwrap_output();
+    r.canonicalize_names(|name| name.to_uppercase());
+    assert_eq!(r.ingredients[2].name, "CHERRIES");
+    assert_eq!(r.ingredients[2].original_name.as_deref(), Some("cherries"));
+}
+
+#[test]
+fn renumber_steps() {
+    let input = indoc! {"
+        First.
+
+        > Some text.
+
+        Second.
+    "};
+    let mut r = CooklangParser::default().parse(input).unwrap_output();
+    let section = &mut r.sections[0];
+    let numbers = |content: &[Content]| {
+        content
+            .iter()
+            .filter_map(|c| match c {
+                Content::Step(s) => Some(s.number),
+                Content::Text(_) => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(numbers(&section.content), [1, 2]);
+
+    let mut new_step = section.content[0].unwrap_step().clone();
+    new_step.number = 0;
+    section.content.insert(0, Content::Step(new_step));
+    section.renumber_steps();
+    assert_eq!(numbers(&section.content), [1, 2, 3]);
+    assert!(section.content[2].is_text());
+
+    section.content.remove(1);
+    section.renumber_steps();
+    assert_eq!(numbers(&section.content), [1, 2]);
+
+    // empty section
+    let mut section = cooklang::model::Section::default();
+    section.renumber_steps();
+    assert!(section.content.is_empty());
+}
+
+#[test]
+fn tolerances() {
+    let input = indoc! {"
+        >> servings: 2
+        Add @flour{100±5*%g} and @water{50+-2.5%ml}.
+    "};
+    let parser = CooklangParser::default();
+    let r = parser.parse(input).unwrap_output();
+
+    let default = r.clone().default_scale();
+    let q = |r: &cooklang::ScaledRecipe, i: usize| {
+        r.ingredients[i].quantity.as_ref().unwrap().to_string()
+    };
+    assert_eq!(q(&default, 0), "100±5 g");
+    assert_eq!(q(&default, 1), "50±2.5 ml");
+
+    // scaling changes the center and the tolerance
+    let scaled = r.scale(4, parser.converter());
+    assert_eq!(q(&scaled, 0), "200±10 g");
+    assert_eq!(q(&scaled, 1), "50±2.5 ml");
+
+    // without the extension it's text
+    let r = CooklangParser::new(
+        Extensions::all() ^ Extensions::TOLERANCES,
+        Default::default(),
+    )
+    .parse("Add @flour{100±5%g}.")
+    .unwrap_output();
+    assert!(matches!(
+        &r.ingredients[0].quantity.as_ref().unwrap().value,
+        cooklang::ScalableValue::Fixed(Value::Text(t)) if t == "100±5"
+    ));
+}
+
+#[test]
+fn variables() {
+    let input = indoc! {"
+        >> servings: 2
+        Add @flour{$flour*%g}, @water{$water*%ml} and @salt{$salt%g}.
+        Rest for ~{$rest%min}.
+    "};
+    let parser = CooklangParser::default();
+    let r = parser.parse(input).unwrap_output();
+    let q = |r: &cooklang::ScaledRecipe, i: usize| {
+        r.ingredients[i].quantity.as_ref().unwrap().to_string()
+    };
+
+    // scaling leaves them as they are
+    let scaled = r.clone().scale(4, parser.converter());
+    assert_eq!(q(&scaled, 0), "$flour g");
+    assert_eq!(q(&scaled, 1), "$water ml");
+    assert!(scaled.timers_duration(parser.converter(), false).is_err());
+
+    // bound before scaling, the values are scaled
+    let mut bound = r.clone();
+    let vars = HashMap::from([
+        ("flour".to_string(), 200.0),
+        ("water".to_string(), 100.0),
+        ("rest".to_string(), 10.0),
+    ]);
+    let err = bound.bind(&vars).unwrap_err();
+    assert_eq!(err.unbound, ["salt"]);
+    assert_eq!(err.to_string(), "Unbound variables: salt");
+    let scaled = bound.scale(4, parser.converter());
+    assert_eq!(q(&scaled, 0), "400 g");
+    assert_eq!(q(&scaled, 1), "200 ml");
+    assert_eq!(q(&scaled, 2), "$salt g");
+    assert_eq!(
+        scaled.timers[0].quantity.as_ref().unwrap().to_string(),
+        "10 min"
+    );
+
+    // also after scaling
+    let mut scaled = r.default_scale();
+    let mut vars = vars;
+    vars.insert("salt".to_string(), 5.0);
+    scaled.bind(&vars).unwrap();
+    assert_eq!(q(&scaled, 0), "200 g");
+    assert_eq!(q(&scaled, 2), "5 g");
+}
+
+#[test]
+fn units_used() {
+    let input = indoc! {"
+        Mix @flour{1 cup / 120 g}, @water{1-2%l} and @eggs{2 x 50%g}.
+        Add @salt{} and @sugar{2%tbsp}, then bake at 180 ºC for ~{25%min}.
+    "};
+    let r = CooklangParser::default().parse(input).unwrap_output();
+    let mut units = r.units_used().into_iter().collect::<Vec<_>>();
+    units.sort();
+    assert_eq!(units, ["cup", "g", "l", "min", "tbsp", "ºC"]);
+}
+
+#[test]
+fn purchase_quantities() {
+    use cooklang::ingredient_list::IngredientList;
+
+    let input = indoc! {"
+        Add @milk{500%ml; 1%l} and @butter{100%g}.
+        Later add @&milk{250%ml; 1%l}.
+    "};
+    let parser = CooklangParser::default();
+    let r = parser.parse(input).unwrap_output().default_scale();
+    let milk = &r.ingredients[0];
+    assert_eq!(milk.quantity.as_ref().unwrap().to_string(), "500 ml");
+    assert_eq!(milk.purchase_quantity.as_ref().unwrap().to_string(), "1 l");
+    let butter = &r.ingredients[1];
+    assert!(butter.purchase_quantity.is_none());
+    assert_eq!(butter.shopping_quantity().unwrap().to_string(), "100 g");
+
+    // the recipe shows the cooking quantities
+    let grouped = r.group_ingredients(parser.converter());
+    assert_eq!(grouped[0].quantity.to_string(), "750 ml");
+
+    // and the shopping list the ones to buy
+    let list = IngredientList::from_recipe(&r, parser.converter());
+    let totals = list
+        .iter()
+        .map(|(name, q)| (name.as_str(), q.to_string()))
+        .collect::<Vec<_>>();
+    let totals = totals
+        .iter()
+        .map(|(name, q)| (*name, q.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(totals, [("butter", "100 g"), ("milk", "2 l")]);
+
+    let parser = CooklangParser::new(
+        Extensions::all() ^ Extensions::PURCHASE_QUANTITIES,
+        Default::default(),
+    );
+    let r = parser.parse("Add @milk{2 cups; 1 l}.").unwrap_output();
+    assert!(r.ingredients[0].purchase_quantity.is_none());
+}
+
+#[test_case("Add @salt{a pinch} and @pepper{to taste}." => false; "text only")]
+#[test_case("Add @salt{a pinch} and @flour{100*%g}." => true; "numeric")]
+#[test_case("Add @flour{100%g} for ~{10%min}." => false; "fixed")]
+#[test_case(">> [auto scale]: true\nAdd @flour{100%g}." => true; "auto scale")]
+#[test_case(">> servings: 2|4\nAdd @eggs{2|4}." => true; "by servings")]
+#[test_case("Add @salt." => false; "no quantities")]
+fn is_scalable(input: &str) -> bool {
+    CooklangParser::default()
+        .parse(input)
+        .unwrap_output()
+        .is_scalable()
+}
+
+#[test]
+fn cookware_scaling() {
+    use cooklang::{quantity::ScalableValue, Converter};
+
+    let input = indoc! {"
+        >> servings: 2|4
+        Use #pan{2}, #*bowls{2} and #&bowls{1}.
+        Then #tray{1|2}.
+    "};
+    let parser = CooklangParser::default();
+    let r = parser.parse(input).unwrap_output();
+    let amounts = r
+        .cookware
+        .iter()
+        .map(|cw| cw.quantity.clone().unwrap())
+        .collect::<Vec<_>>();
+    assert!(matches!(amounts[0], ScalableValue::Fixed(_)));
+    assert!(matches!(amounts[1], ScalableValue::Linear(_)));
+    // the reference inherits the modifier
+    assert!(matches!(amounts[2], ScalableValue::Linear(_)));
+    assert!(r.cookware[2].modifiers().is_scaled());
+
+    let r = r.scale(4, &Converter::empty());
+    let amounts = r
+        .cookware
+        .iter()
+        .map(|cw| cw.quantity.as_ref().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(amounts, ["2", "4", "2", "2"]);
+
+    let r = parser.parse("Use #*pan{big}.");
+    assert!(r.report().has_errors());
+
+    let r = parser.parse("Use #pan{2*}.");
+    assert!(r.report().has_errors());
+}